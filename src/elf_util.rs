@@ -0,0 +1,31 @@
+mod bytes;
+mod headers;
+pub mod address;
+pub mod address_layout;
+pub mod binding;
+pub mod compression;
+pub mod debug_file;
+pub mod dynamic_object;
+pub mod dwarf;
+pub mod file_fields;
+pub mod file_layout;
+pub mod hash_table;
+pub mod ifunc;
+pub mod init_fini;
+pub mod ld_cache;
+pub mod plt_got;
+pub mod printable_strings;
+pub mod reconstruct;
+pub mod recovered_function;
+pub mod relocations;
+pub mod run_path;
+pub mod section_group;
+pub mod string_table;
+pub mod struct_layout;
+pub mod tls;
+pub mod versions;
+
+pub use bytes::*;
+pub use headers::*;
+pub use dynamic_object::DynamicSymbol;
+pub use versions::VersionEntry;
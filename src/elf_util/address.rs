@@ -0,0 +1,130 @@
+//! 仮想アドレスから, それを含むセクション/セグメント/シンボルを引く.
+
+use crate::elf_util::ifunc;
+use elf_utilities::{
+    file,
+    section::{self, Contents64},
+    symbol,
+};
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_TLS: u64 = 0x400;
+
+pub struct AddressInfo {
+    /// (セクションのインデックス, セクション先頭からのずれ)
+    pub section: Option<(usize, u64)>,
+    /// (セグメントのインデックス, ファイルオフセット)
+    /// .bss のようにファイル上に無い部分ならファイルオフセットは None
+    pub segment: Option<(usize, Option<u64>)>,
+    /// (シンボル名, シンボル先頭からのずれ)
+    pub symbol: Option<(String, u64)>,
+}
+
+/// 16 進数のアドレスを読む. addr2line と同様に 0x は省略できる.
+pub fn parse_address(query: &str) -> Option<u64> {
+    let query = query.trim();
+    let digits = query
+        .strip_prefix("0x")
+        .or_else(|| query.strip_prefix("0X"))
+        .unwrap_or(query);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// symbol_table は strip されたファイルでは .dynsym や分離デバッグファイルのものを渡す.
+pub fn lookup(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    addr: u64,
+) -> AddressInfo {
+    let section = elf_file
+        .sections
+        .iter()
+        .position(|sct| {
+            // .tbss は TLS のテンプレートにしか無く, 後続のセクションとアドレスが重なる
+            let tbss = sct.header.sh_flags & SHF_TLS != 0
+                && sct.header.get_type() == section::Type::NoBits;
+            sct.header.sh_flags & SHF_ALLOC != 0
+                && !tbss
+                && sct.header.sh_addr <= addr
+                && addr < sct.header.sh_addr.saturating_add(sct.header.sh_size)
+        })
+        .map(|idx| (idx, addr - elf_file.sections[idx].header.sh_addr));
+
+    let segment = elf_file
+        .segments
+        .iter()
+        .position(|seg| {
+            seg.header.get_type() == elf_utilities::segment::Type::Load
+                && seg.header.p_vaddr <= addr
+                && addr < seg.header.p_vaddr.saturating_add(seg.header.p_memsz)
+        })
+        .map(|idx| {
+            let seg = &elf_file.segments[idx].header;
+            let delta = addr - seg.p_vaddr;
+            (
+                idx,
+                seg.p_offset
+                    .checked_add(delta)
+                    .filter(|_| delta < seg.p_filesz),
+            )
+        });
+
+    AddressInfo {
+        section,
+        segment,
+        symbol: symbol_table.and_then(|symbol_table| nearest_symbol(symbol_table, addr)),
+    }
+}
+
+/// addr を含む関数/変数のシンボル. サイズが 0 のシンボルは先頭が一致するときだけ選ぶ.
+pub fn nearest_symbol(symbol_table: &section::Section64, addr: u64) -> Option<(String, u64)> {
+    let symbols = match &symbol_table.contents {
+        Contents64::Symbols(symbols) => symbols,
+        _ => return None,
+    };
+
+    symbols
+        .iter()
+        .filter(|sym| {
+            (matches!(
+                sym.get_type(),
+                symbol::Type::Func | symbol::Type::Object | symbol::Type::NoType
+            ) || sym.st_info & 0x0f == ifunc::STT_GNU_IFUNC)
+                && sym.st_shndx != section::SHN_UNDEF
+                && !sym.symbol_name.is_empty()
+                && sym.st_value <= addr
+                && (addr < sym.st_value.saturating_add(sym.st_size) || addr == sym.st_value)
+        })
+        .max_by_key(|sym| (sym.st_value, sym.st_size))
+        .map(|sym| (sym.symbol_name.clone(), addr - sym.st_value))
+}
+
+/// ET_REL で, セクション内オフセットに置かれた関数のシンボル
+pub fn section_function(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    sct_idx: usize,
+    offset: u64,
+) -> Option<String> {
+    let symbols = match &symbol_table
+        .or_else(|| {
+            elf_file
+                .sections
+                .iter()
+                .find(|sct| sct.header.get_type() == section::Type::SymTab)
+        })?
+        .contents
+    {
+        Contents64::Symbols(symbols) => symbols,
+        _ => return None,
+    };
+
+    symbols
+        .iter()
+        .find(|sym| {
+            sym.get_type() == symbol::Type::Func
+                && sym.st_shndx as usize == sct_idx
+                && sym.st_value == offset
+        })
+        .map(|sym| sym.symbol_name.clone())
+}
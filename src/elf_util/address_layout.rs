@@ -0,0 +1,170 @@
+//! 仮想アドレス空間の配置. PT_LOAD セグメントを並べ, その間の隙間,
+//! 含まれるセクション, PT_GNU_RELRO の範囲を行として持つ.
+
+use elf_utilities::{file, section, segment};
+
+pub const PF_X: u32 = 0x1;
+pub const PF_W: u32 = 0x2;
+pub const PF_R: u32 = 0x4;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_TLS: u64 = 0x400;
+/// ld.so が mprotect するときの単位
+pub const PAGE_SIZE: u64 = 0x1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// プログラムヘッダのインデックス
+    Segment(usize),
+    Section(usize),
+    /// PT_GNU_RELRO のプログラムヘッダのインデックス
+    Relro(usize),
+    Gap,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub kind: RegionKind,
+    pub start: u64,
+    pub end: u64,
+    /// ファイルから読み込まれる部分の終わり. ここから end までは 0 埋めされる
+    pub file_end: u64,
+    /// 含まれる (セクション/RELRO なら, それを含む) セグメントの p_flags
+    pub flags: u32,
+}
+
+impl Region {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+pub struct AddressLayout {
+    pub regions: Vec<Region>,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl AddressLayout {
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// 範囲のうち RELRO で読み込み後に読み取り専用になる部分
+    pub fn relro_overlap(&self, start: u64, end: u64) -> Option<(u64, u64)> {
+        self.regions
+            .iter()
+            .filter(|region| matches!(region.kind, RegionKind::Relro(_)))
+            .map(|region| (region.start.max(start), region.end.min(end)))
+            .find(|(start, end)| start < end)
+    }
+
+    /// 範囲と重なるセクションの行
+    pub fn sections_in(&self, start: u64, end: u64) -> Vec<&Region> {
+        self.regions
+            .iter()
+            .filter(|region| {
+                matches!(region.kind, RegionKind::Section(_))
+                    && region.start < end
+                    && start < region.end.max(region.start.saturating_add(1))
+            })
+            .collect()
+    }
+
+    /// addr を含むセグメントの行
+    pub fn segment_of(&self, addr: u64) -> Option<&Region> {
+        self.regions.iter().find(|region| {
+            matches!(region.kind, RegionKind::Segment(_))
+                && region.start <= addr
+                && addr < region.end.max(region.start.saturating_add(1))
+        })
+    }
+}
+
+/// 仮想アドレス順に, 隙間, セグメント, その RELRO, そのセクションの順で並べる.
+pub fn address_layout(elf_file: &file::ELF64) -> AddressLayout {
+    let mut loads: Vec<(usize, &segment::Segment64)> = elf_file
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(_, seg)| seg.header.get_type() == segment::Type::Load)
+        .collect();
+    loads.sort_by_key(|(_, seg)| seg.header.p_vaddr);
+
+    let mut regions = Vec::new();
+    let mut previous_end = None;
+    for (seg_idx, seg) in loads.iter() {
+        let phdr = &seg.header;
+        let start = phdr.p_vaddr;
+        let end = start.saturating_add(phdr.p_memsz);
+        if let Some(previous_end) = previous_end.filter(|previous_end| *previous_end < start) {
+            regions.push(Region {
+                kind: RegionKind::Gap,
+                start: previous_end,
+                end: start,
+                file_end: start,
+                flags: 0,
+            });
+        }
+        previous_end = Some(previous_end.map_or(end, |previous_end: u64| previous_end.max(end)));
+
+        regions.push(Region {
+            kind: RegionKind::Segment(*seg_idx),
+            start,
+            end,
+            file_end: start.saturating_add(phdr.p_filesz.min(phdr.p_memsz)),
+            flags: phdr.p_flags,
+        });
+
+        for (relro_idx, relro) in elf_file.segments.iter().enumerate().filter(|(_, relro)| {
+            relro.header.get_type() == segment::Type::GNURelRO
+                && start <= relro.header.p_vaddr
+                && relro.header.p_vaddr < end
+        }) {
+            let relro_end = relro.header.p_vaddr.saturating_add(relro.header.p_memsz);
+            regions.push(Region {
+                kind: RegionKind::Relro(relro_idx),
+                start: relro.header.p_vaddr,
+                end: relro_end,
+                file_end: relro_end,
+                flags: phdr.p_flags,
+            });
+        }
+
+        let mut sections: Vec<(usize, &section::Section64)> = elf_file
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, sct)| {
+                // .tbss は TLS のテンプレートにしか無く, 後続のセクションと重なる
+                let tbss = sct.header.sh_flags & SHF_TLS != 0
+                    && sct.header.get_type() == section::Type::NoBits;
+                sct.header.sh_flags & SHF_ALLOC != 0
+                    && !tbss
+                    && start <= sct.header.sh_addr
+                    && sct.header.sh_addr < end.max(start.saturating_add(1))
+            })
+            .collect();
+        sections.sort_by_key(|(_, sct)| sct.header.sh_addr);
+        for (sct_idx, sct) in sections {
+            let sct_start = sct.header.sh_addr;
+            let sct_end = sct_start.saturating_add(sct.header.sh_size);
+            regions.push(Region {
+                kind: RegionKind::Section(sct_idx),
+                start: sct_start,
+                end: sct_end,
+                file_end: if sct.header.get_type() == section::Type::NoBits {
+                    sct_start
+                } else {
+                    sct_end
+                },
+                flags: phdr.p_flags,
+            });
+        }
+    }
+
+    AddressLayout {
+        start: loads.first().map_or(0, |(_, seg)| seg.header.p_vaddr),
+        end: previous_end.unwrap_or(0),
+        regions,
+    }
+}
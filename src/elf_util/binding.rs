@@ -0,0 +1,366 @@
+//! 未定義の動的シンボルを, 依存ライブラリのどれが提供するかを解決する.
+//! ld.so と同様に, 依存ライブラリを幅優先で読み込んだ順序で探索し,
+//! シンボルバージョンの要求も考慮する.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::elf_util::{
+    self, dynamic_object::DynamicObject, ld_cache, ld_cache::LdCache, run_path, DynamicSymbol,
+    VersionEntry,
+};
+
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const STB_GNU_UNIQUE: u8 = 10;
+
+const EM_SPARCV9: u16 = 43;
+const EM_PPC64: u16 = 21;
+const EM_S390: u16 = 22;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+const EM_RISCV: u16 = 243;
+const EM_LOONGARCH: u16 = 258;
+const ELFDATA2LSB: u8 = 1;
+
+/// ld.so が最後に探索するディレクトリのうち, アーキテクチャによらないもの
+const DEFAULT_LIBRARY_DIRS: [&str; 4] = ["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// 依存ライブラリの探索結果
+pub struct Dependency {
+    pub name: String,
+    /// 見つからなかった場合は None
+    pub path: Option<PathBuf>,
+    /// DT_NEEDED に名前を記述していたオブジェクト
+    pub needed_by: String,
+    object: Option<DynamicObject>,
+}
+
+pub enum BindingResult {
+    Bound {
+        /// 提供元ライブラリ(dependencies のインデックス)
+        library: usize,
+        version: Option<String>,
+    },
+    Unresolved,
+}
+
+/// 未定義シンボル1つ分の解決結果
+pub struct Binding {
+    pub symbol: DynamicSymbol,
+    /// .gnu.version_r で要求しているバージョン
+    pub required_version: Option<VersionEntry>,
+    pub result: BindingResult,
+    /// 探索順で後ろにあるため, 使われなかった定義を持つライブラリ
+    pub shadowed: Vec<usize>,
+}
+
+impl Binding {
+    pub fn is_weak(&self) -> bool {
+        self.symbol.st_bind() == STB_WEAK
+    }
+}
+
+#[derive(Default)]
+pub struct BindingReport {
+    pub dependencies: Vec<Dependency>,
+    pub bindings: Vec<Binding>,
+}
+
+impl BindingReport {
+    pub fn unresolved(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(|b| matches!(b.result, BindingResult::Unresolved))
+    }
+
+    pub fn missing_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(|dep| dep.path.is_none())
+    }
+
+    /// 依存ライブラリの表示名(SONAME があれば SONAME)
+    pub fn library_name(&self, idx: usize) -> &str {
+        let dep = &self.dependencies[idx];
+        dep.object
+            .as_ref()
+            .and_then(|obj| obj.soname.as_deref())
+            .unwrap_or(&dep.name)
+    }
+
+    /// 提供元ライブラリが, リンク時に要求したライブラリ(vn_file)と異なるか
+    pub fn is_mismatched(&self, binding: &Binding) -> bool {
+        match (&binding.result, &binding.required_version) {
+            (
+                BindingResult::Bound { library, .. },
+                Some(VersionEntry {
+                    file: Some(file), ..
+                }),
+            ) => self.library_name(*library) != file,
+            _ => false,
+        }
+    }
+}
+
+/// 解決すべき未定義の動的シンボルを持つか. 依存ライブラリは読まない.
+pub fn has_imports(bytes: &[u8]) -> bool {
+    DynamicObject::parse(bytes).is_some_and(|obj| {
+        obj.symbols
+            .iter()
+            .skip(1)
+            .any(|sym| !sym.is_defined() && !sym.name.is_empty())
+    })
+}
+
+/// file_path の未定義動的シンボルを解決する.
+/// ld_cache が与えられた場合, 既定のディレクトリより先にキャッシュを参照する.
+pub fn resolve(file_path: &str, bytes: &[u8], ld_cache: Option<&LdCache>) -> BindingReport {
+    let executable = match DynamicObject::parse(bytes) {
+        Some(obj) => obj,
+        None => return Default::default(),
+    };
+    let machine = elf_util::read_u16(bytes, 0x12).unwrap_or(0);
+    let cache_lookup =
+        |name: &str| ld_cache?.lookup(name, ld_cache::required_flag_for_machine(machine));
+    let triplet = multiarch_triplet(machine, bytes.get(5).copied().unwrap_or(0));
+    let default_dirs = default_library_dirs(triplet);
+    let dependencies = load_dependencies(
+        file_path,
+        &executable,
+        machine,
+        triplet,
+        cache_lookup,
+        &default_dirs,
+    );
+
+    // 各ライブラリのシンボルを名前で引けるようにしておく
+    let symbol_maps: Vec<HashMap<&str, Vec<&DynamicSymbol>>> = dependencies
+        .iter()
+        .map(|dep| {
+            let mut map: HashMap<&str, Vec<&DynamicSymbol>> = HashMap::new();
+            if let Some(obj) = &dep.object {
+                for sym in obj.symbols.iter().filter(|sym| is_exported(sym)) {
+                    map.entry(sym.name.as_str()).or_default().push(sym);
+                }
+            }
+            map
+        })
+        .collect();
+
+    let mut bindings = Vec::new();
+    for sym in executable.symbols.iter().skip(1) {
+        if sym.is_defined() || sym.name.is_empty() {
+            continue;
+        }
+        let required_version = executable.symbol_version(sym).cloned();
+
+        let mut providers = dependencies.iter().enumerate().filter_map(|(idx, dep)| {
+            let obj = dep.object.as_ref()?;
+            let candidates = symbol_maps[idx].get(sym.name.as_str())?;
+            candidates
+                .iter()
+                .find(|candidate| version_matches(obj, candidate, &required_version))
+                .map(|candidate| (idx, obj.symbol_version(candidate)))
+        });
+
+        let result = match providers.next() {
+            Some((library, version)) => BindingResult::Bound {
+                library,
+                version: version.map(|v| v.name.clone()),
+            },
+            None => BindingResult::Unresolved,
+        };
+        let shadowed = providers.map(|(idx, _)| idx).collect();
+
+        bindings.push(Binding {
+            symbol: sym.clone(),
+            required_version,
+            result,
+            shadowed,
+        });
+    }
+
+    BindingReport {
+        dependencies,
+        bindings,
+    }
+}
+
+fn is_exported(sym: &DynamicSymbol) -> bool {
+    sym.is_defined() && matches!(sym.st_bind(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+}
+
+/// ld.so の check_match() 相当のバージョン照合
+fn version_matches(
+    provider: &DynamicObject,
+    candidate: &DynamicSymbol,
+    required: &Option<VersionEntry>,
+) -> bool {
+    match required {
+        // バージョン情報を持たないライブラリは要求に関わらず受け入れる
+        Some(_) if provider.verdefs.is_empty() => true,
+        Some(required) => provider
+            .symbol_version(candidate)
+            .is_some_and(|v| v.name == required.name),
+        // バージョン指定が無ければ, デフォルトバージョンのみ対象とする
+        None => !candidate.is_hidden(),
+    }
+}
+
+/// DT_NEEDED を幅優先にたどり, ld.so と同じ順序で依存ライブラリを並べる.
+fn load_dependencies<F>(
+    file_path: &str,
+    executable: &DynamicObject,
+    machine: u16,
+    triplet: Option<&str>,
+    cache_lookup: F,
+    default_dirs: &[PathBuf],
+) -> Vec<Dependency>
+where
+    F: Fn(&str) -> Option<PathBuf>,
+{
+    let exe_origin = origin_of(Path::new(file_path));
+    let mut dependencies: Vec<Dependency> = Vec::new();
+    let mut queue: VecDeque<(String, Option<usize>)> = executable
+        .needed
+        .iter()
+        .map(|name| (name.clone(), None))
+        .collect();
+
+    while let Some((name, loader)) = queue.pop_front() {
+        let already_loaded = dependencies.iter().any(|dep| {
+            dep.name == name
+                || dep.object.as_ref().and_then(|obj| obj.soname.as_deref()) == Some(&name)
+        });
+        if already_loaded {
+            continue;
+        }
+
+        let (loader_obj, loader_origin, needed_by) = match loader {
+            Some(idx) => {
+                let dep = &dependencies[idx];
+                (
+                    dep.object.as_ref().unwrap(),
+                    dep.path.as_deref().map(origin_of).unwrap_or_default(),
+                    dep.name.clone(),
+                )
+            }
+            None => (executable, exe_origin.clone(), file_path.to_string()),
+        };
+
+        let mut search_dirs = Vec::new();
+        // DT_RUNPATH を持たない場合のみ, 読み込み元と実行ファイルの DT_RPATH を使う
+        if loader_obj.runpath.is_none() {
+            search_dirs.extend(split_path_list(
+                loader_obj.rpath.as_deref(),
+                &loader_origin,
+                triplet,
+            ));
+            if loader.is_some() && executable.runpath.is_none() {
+                search_dirs.extend(split_path_list(
+                    executable.rpath.as_deref(),
+                    &exe_origin,
+                    triplet,
+                ));
+            }
+        }
+        let ld_library_path = std::env::var("LD_LIBRARY_PATH").ok();
+        search_dirs.extend(split_path_list(
+            ld_library_path.as_deref(),
+            &exe_origin,
+            triplet,
+        ));
+        search_dirs.extend(split_path_list(
+            loader_obj.runpath.as_deref(),
+            &loader_origin,
+            triplet,
+        ));
+
+        let mut candidates: Vec<PathBuf> = search_dirs.iter().map(|dir| dir.join(&name)).collect();
+        candidates.extend(cache_lookup(&name));
+        candidates.extend(default_dirs.iter().map(|dir| dir.join(&name)));
+
+        let (path, object) = match find_library(&name, machine, candidates) {
+            Some((path, bytes)) => (Some(path), DynamicObject::parse(&bytes)),
+            None => (None, None),
+        };
+
+        if let Some(obj) = &object {
+            let idx = dependencies.len();
+            queue.extend(obj.needed.iter().map(|n| (n.clone(), Some(idx))));
+        }
+        dependencies.push(Dependency {
+            name,
+            path,
+            needed_by,
+            object,
+        });
+    }
+
+    dependencies
+}
+
+/// Debian 系の multiarch ディレクトリを e_machine から決め, 共通のディレクトリの前に置く.
+/// Debian 系の multiarch ディレクトリ名に使われる, アーキテクチャの三つ組.
+/// 分からないアーキテクチャでは None を返す.
+pub fn multiarch_triplet(machine: u16, data: u8) -> Option<&'static str> {
+    match machine {
+        EM_X86_64 => Some("x86_64-linux-gnu"),
+        EM_AARCH64 => Some("aarch64-linux-gnu"),
+        EM_RISCV => Some("riscv64-linux-gnu"),
+        EM_PPC64 if data == ELFDATA2LSB => Some("powerpc64le-linux-gnu"),
+        EM_PPC64 => Some("powerpc64-linux-gnu"),
+        EM_S390 => Some("s390x-linux-gnu"),
+        EM_SPARCV9 => Some("sparc64-linux-gnu"),
+        EM_LOONGARCH => Some("loongarch64-linux-gnu"),
+        _ => None,
+    }
+}
+
+fn default_library_dirs(triplet: Option<&str>) -> Vec<PathBuf> {
+    triplet
+        .into_iter()
+        .flat_map(|triplet| {
+            ["/lib", "/usr/lib"]
+                .iter()
+                .map(move |dir| Path::new(dir).join(triplet))
+        })
+        .chain(DEFAULT_LIBRARY_DIRS.iter().map(PathBuf::from))
+        .collect()
+}
+
+fn find_library(name: &str, machine: u16, candidates: Vec<PathBuf>) -> Option<(PathBuf, Vec<u8>)> {
+    if name.contains('/') {
+        return load_file(PathBuf::from(name), machine);
+    }
+
+    candidates
+        .into_iter()
+        .find_map(|path| load_file(path, machine))
+}
+
+/// ld.so と同じく, 異なるクラスやアーキテクチャのファイルは読み飛ばす
+fn load_file(path: PathBuf, machine: u16) -> Option<(PathBuf, Vec<u8>)> {
+    let bytes = std::fs::read(&path).ok()?;
+    if elf_util::is_elf64(&bytes) && elf_util::read_u16(&bytes, 0x12) == Some(machine) {
+        Some((path, bytes))
+    } else {
+        None
+    }
+}
+
+fn origin_of(path: &Path) -> PathBuf {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+fn split_path_list(list: Option<&str>, origin: &Path, triplet: Option<&str>) -> Vec<PathBuf> {
+    list.map(|list| {
+        run_path::expand_run_path(list, origin, triplet)
+            .into_iter()
+            .filter_map(|entry| entry.expanded)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    })
+    .unwrap_or_default()
+}
@@ -0,0 +1,127 @@
+//! ファイルの生バイト列から値を読み出すためのヘルパ.
+//! elf_utilities が解析しないセクションを読む際に用いる.
+//! 範囲外アクセスは None を返すので, 壊れたバイナリでもパニックしない.
+
+pub fn read_u8(bytes: &[u8], offset: usize) -> Option<u8> {
+    bytes.get(offset).copied()
+}
+
+pub fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let b = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+pub fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let b = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+pub fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    let b = bytes.get(offset..offset.checked_add(8)?)?;
+    let mut buf = [0; 8];
+    buf.copy_from_slice(b);
+    Some(u64::from_le_bytes(buf))
+}
+
+pub fn read_i64(bytes: &[u8], offset: usize) -> Option<i64> {
+    read_u64(bytes, offset).map(|v| v as i64)
+}
+
+/// offset から NUL までを文字列として読み出す.
+pub fn read_cstr(bytes: &[u8], offset: usize) -> Option<String> {
+    let tail = bytes.get(offset..)?;
+    let len = tail.iter().position(|b| *b == 0x00)?;
+    Some(String::from_utf8_lossy(&tail[..len]).to_string())
+}
+
+/// ULEB128 を読み出し, 値と消費したバイト数を返す.
+pub fn read_uleb128(bytes: &[u8], offset: usize) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    for (len, byte) in bytes.get(offset..)?.iter().enumerate() {
+        if shift < 64 {
+            value |= u64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some((value, len + 1));
+        }
+    }
+    None
+}
+
+/// SLEB128 を読み出し, 値と消費したバイト数を返す.
+pub fn read_sleb128(bytes: &[u8], offset: usize) -> Option<(i64, usize)> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    for (len, byte) in bytes.get(offset..)?.iter().enumerate() {
+        if shift < 64 {
+            value |= i64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return Some((value, len + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uleb128_known_values() {
+        // DWARF 5 仕様書 7.6 節の例
+        let cases: &[(&[u8], u64)] = &[
+            (&[0x02], 2),
+            (&[0x7f], 127),
+            (&[0x80, 0x01], 128),
+            (&[0x81, 0x01], 129),
+            (&[0x82, 0x01], 130),
+            (&[0xb9, 0x64], 12857),
+            (&[0xe5, 0x8e, 0x26], 624485),
+        ];
+        for (bytes, value) in cases {
+            assert_eq!(read_uleb128(bytes, 0), Some((*value, bytes.len())));
+        }
+    }
+
+    #[test]
+    fn sleb128_known_values() {
+        let cases: &[(&[u8], i64)] = &[
+            (&[0x02], 2),
+            (&[0x7e], -2),
+            (&[0xff, 0x00], 127),
+            (&[0x81, 0x7f], -127),
+            (&[0x80, 0x01], 128),
+            (&[0x80, 0x7f], -128),
+            (&[0x81, 0x01], 129),
+            (&[0xff, 0x7e], -129),
+            (&[0xc0, 0xbb, 0x78], -123456),
+        ];
+        for (bytes, value) in cases {
+            assert_eq!(read_sleb128(bytes, 0), Some((*value, bytes.len())));
+        }
+    }
+
+    #[test]
+    fn leb128_extremes_and_truncation() {
+        let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(read_uleb128(&max, 0), Some((u64::MAX, 10)));
+        let min = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f];
+        assert_eq!(read_sleb128(&min, 0), Some((i64::MIN, 10)));
+
+        // offset から読み始め, 継続ビットが立ったまま終われば None
+        assert_eq!(
+            read_uleb128(&[0x00, 0xe5, 0x8e, 0x26], 1),
+            Some((624485, 3))
+        );
+        assert_eq!(read_uleb128(&[0x80, 0x80], 0), None);
+        assert_eq!(read_sleb128(&[0xff], 0), None);
+        assert_eq!(read_uleb128(&[0x01], 2), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! 圧縮されたセクション (SHF_COMPRESSED と, 古い形式の .zdebug_*) を展開する.
+//! 他の解析はここで展開した中身を読むので, 圧縮の有無を意識しなくてよい.
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use crate::elf_util;
+use elf_utilities::section;
+
+pub const SHF_COMPRESSED: u64 = 0x800;
+
+const ELFCOMPRESS_ZLIB: u32 = 1;
+const ELFCOMPRESS_ZSTD: u32 = 2;
+/// Elf64_Chdr の大きさ
+const CHDR_SIZE: usize = 24;
+/// .zdebug_* の先頭に置かれる "ZLIB" と, ビッグエンディアンの展開後サイズ
+const ZDEBUG_HEADER_SIZE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Zlib,
+    Zstd,
+    Unknown(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub format: Format,
+    /// Elf64_Chdr ではなく .zdebug_* のヘッダ
+    pub legacy: bool,
+    pub uncompressed_size: u64,
+    /// 展開後のアラインメント. .zdebug_* には無い
+    pub uncompressed_align: Option<u64>,
+}
+
+/// セクションが圧縮されていれば, そのヘッダを読む.
+pub fn compression(sct: &section::Section64, bytes: &[u8]) -> Option<Compression> {
+    let data = raw_data(sct, bytes)?;
+
+    if sct.header.sh_flags & SHF_COMPRESSED != 0 {
+        let ch_type = elf_util::read_u32(data, 0)?;
+        return Some(Compression {
+            format: match ch_type {
+                ELFCOMPRESS_ZLIB => Format::Zlib,
+                ELFCOMPRESS_ZSTD => Format::Zstd,
+                _ => Format::Unknown(ch_type),
+            },
+            legacy: false,
+            uncompressed_size: elf_util::read_u64(data, 8)?,
+            uncompressed_align: Some(elf_util::read_u64(data, 16)?),
+        });
+    }
+
+    if sct.name.starts_with(".zdebug") && data.starts_with(b"ZLIB") {
+        let size = data.get(4..ZDEBUG_HEADER_SIZE)?;
+        let mut be = [0; 8];
+        be.copy_from_slice(size);
+        return Some(Compression {
+            format: Format::Zlib,
+            legacy: true,
+            uncompressed_size: u64::from_be_bytes(be),
+            uncompressed_align: None,
+        });
+    }
+
+    None
+}
+
+/// セクションの中身. 圧縮されていれば展開して返し, 展開できなければ None.
+pub fn section_data<'a>(sct: &section::Section64, bytes: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+    let data = raw_data(sct, bytes)?;
+    let compression = match compression(sct, bytes) {
+        Some(compression) => compression,
+        None => return Some(Cow::Borrowed(data)),
+    };
+    let header_size = if compression.legacy {
+        ZDEBUG_HEADER_SIZE
+    } else {
+        CHDR_SIZE
+    };
+    let compressed = data.get(header_size..)?;
+    // 壊れたヘッダで巨大な領域を確保しないよう, 展開後のサイズで打ち切る
+    let limit = compression.uncompressed_size as usize;
+
+    let uncompressed = match compression.format {
+        Format::Zlib => {
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(compressed, limit).ok()?
+        }
+        Format::Zstd => {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(compressed).ok()?;
+            let mut uncompressed = Vec::new();
+            decoder
+                .take(limit as u64)
+                .read_to_end(&mut uncompressed)
+                .ok()?;
+            uncompressed
+        }
+        Format::Unknown(_) => return None,
+    };
+    if uncompressed.len() != limit {
+        return None;
+    }
+
+    Some(Cow::Owned(uncompressed))
+}
+
+/// .zdebug_info なら .debug_info のように, 圧縮前の名前を返す.
+pub fn uncompressed_name(name: &str) -> Cow<'_, str> {
+    match name.strip_prefix(".zdebug") {
+        Some(rest) => Cow::Owned(format!(".debug{}", rest)),
+        None => Cow::Borrowed(name),
+    }
+}
+
+fn raw_data<'a>(sct: &section::Section64, bytes: &'a [u8]) -> Option<&'a [u8]> {
+    if sct.header.get_type() == section::Type::NoBits {
+        return None;
+    }
+    let start = sct.header.sh_offset as usize;
+    bytes.get(start..start.checked_add(sct.header.sh_size as usize)?)
+}
@@ -0,0 +1,273 @@
+//! strip されたファイルに対応する分離デバッグファイルを探す.
+//! gdb と同様に, NT_GNU_BUILD_ID のノートと .gnu_debuglink (ファイル名と CRC32) を手がかりにする.
+
+use std::path::{Path, PathBuf};
+
+use crate::elf_util::{self, reconstruct};
+use elf_utilities::{file, section, segment};
+
+/// --debug-dir の指定に関わらず探すディレクトリ
+pub const DEFAULT_DEBUG_DIR: &str = "/usr/lib/debug";
+
+const NT_GNU_BUILD_ID: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    BuildId,
+    DebugLink,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    NotFound,
+    Matched,
+    BuildIdMismatch,
+    /// 実際の CRC32
+    CrcMismatch(u32),
+    /// ELF として読めない
+    Unreadable,
+}
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub method: Method,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DebugFileReport {
+    pub build_id: Option<Vec<u8>>,
+    /// (ファイル名, CRC32)
+    pub debuglink: Option<(String, u32)>,
+    pub candidates: Vec<Candidate>,
+}
+
+impl DebugFileReport {
+    /// 最初に検証を通った候補
+    pub fn found(&self) -> Option<&Candidate> {
+        self.candidates
+            .iter()
+            .find(|candidate| candidate.status == Status::Matched)
+    }
+}
+
+/// gdb と同じ順に候補を並べ, 存在するものは build-id か CRC で検証する.
+/// debug_dirs は DEFAULT_DEBUG_DIR より先に探す.
+pub fn locate(
+    file_path: &str,
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    debug_dirs: &[String],
+) -> DebugFileReport {
+    let mut report = DebugFileReport {
+        build_id: build_id(elf_file, bytes),
+        debuglink: debuglink(elf_file, bytes),
+        candidates: Vec::new(),
+    };
+    if report.build_id.is_none() && report.debuglink.is_none() {
+        return report;
+    }
+
+    let mut dirs: Vec<PathBuf> = debug_dirs.iter().map(PathBuf::from).collect();
+    dirs.push(PathBuf::from(DEFAULT_DEBUG_DIR));
+    let file_path = Path::new(file_path);
+    let file_dir = file_path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    let mut paths = Vec::new();
+    if let Some(id) = report.build_id.as_ref().filter(|id| id.len() >= 2) {
+        let hex: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+        for dir in dirs.iter() {
+            paths.push((
+                dir.join(".build-id")
+                    .join(&hex[..2])
+                    .join(format!("{}.debug", &hex[2..])),
+                Method::BuildId,
+            ));
+        }
+        // ファイルと並べて置いた <ファイル名>.debug
+        let mut alongside = file_path.as_os_str().to_os_string();
+        alongside.push(".debug");
+        paths.push((PathBuf::from(alongside), Method::BuildId));
+    }
+    if let Some((name, _)) = &report.debuglink {
+        paths.push((file_dir.join(name), Method::DebugLink));
+        paths.push((file_dir.join(".debug").join(name), Method::DebugLink));
+        for dir in dirs.iter() {
+            let relative = file_dir.strip_prefix("/").unwrap_or(&file_dir);
+            paths.push((dir.join(relative).join(name), Method::DebugLink));
+        }
+    }
+
+    let itself = file_path.canonicalize().ok();
+    for (path, method) in paths {
+        if path.canonicalize().ok().is_some_and(|p| Some(p) == itself) {
+            continue;
+        }
+        let status = verify(&path, method, &report);
+        report.candidates.push(Candidate {
+            path,
+            method,
+            status,
+        });
+    }
+
+    report
+}
+
+/// 候補のファイルを読み込む. 検証に通ったものだけを渡すこと.
+pub fn load(path: &Path) -> Option<(file::ELF64, Vec<u8>)> {
+    let bytes = std::fs::read(path).ok()?;
+    if !reconstruct::has_section_headers(&bytes) {
+        return None;
+    }
+    let elf_file = elf_util::elf_from_section_headers(path.to_str()?, &bytes).ok()?;
+    Some((elf_file, bytes))
+}
+
+fn verify(path: &Path, method: Method, report: &DebugFileReport) -> Status {
+    if !path.is_file() {
+        return Status::NotFound;
+    }
+    match method {
+        Method::BuildId => match load(path) {
+            Some((elf_file, bytes)) if build_id(&elf_file, &bytes) == report.build_id => {
+                Status::Matched
+            }
+            Some(_) => Status::BuildIdMismatch,
+            None => Status::Unreadable,
+        },
+        Method::DebugLink => {
+            let expected = report.debuglink.as_ref().map(|(_, crc)| *crc);
+            match std::fs::read(path) {
+                Ok(bytes) => match crc32(&bytes) {
+                    actual if Some(actual) == expected => Status::Matched,
+                    actual => Status::CrcMismatch(actual),
+                },
+                Err(_) => Status::Unreadable,
+            }
+        }
+    }
+}
+
+/// NT_GNU_BUILD_ID ノートの中身. セクションヘッダが無ければ PT_NOTE から探す.
+pub fn build_id(elf_file: &file::ELF64, bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut notes: Vec<(u64, u64)> = elf_file
+        .sections
+        .iter()
+        .filter(|sct| sct.header.get_type() == section::Type::Note)
+        .map(|sct| (sct.header.sh_offset, sct.header.sh_size))
+        .collect();
+    if elf_file.ehdr.e_shnum == 0 {
+        notes.extend(
+            elf_file
+                .segments
+                .iter()
+                .filter(|seg| seg.header.get_type() == segment::Type::Note)
+                .map(|seg| (seg.header.p_offset, seg.header.p_filesz)),
+        );
+    }
+
+    notes.into_iter().find_map(|(offset, size)| {
+        let data = bytes.get(offset as usize..offset.checked_add(size)? as usize)?;
+        find_note(data, NT_GNU_BUILD_ID)
+    })
+}
+
+/// ノートを順に読み, 名前が "GNU" で種類が note_type のものの中身を返す.
+fn find_note(data: &[u8], note_type: u32) -> Option<Vec<u8>> {
+    let align = |n: usize| (n + 3) & !3;
+    let mut pos = 0;
+    while pos + 12 <= data.len() {
+        let namesz = elf_util::read_u32(data, pos)? as usize;
+        let descsz = elf_util::read_u32(data, pos + 4)? as usize;
+        let n_type = elf_util::read_u32(data, pos + 8)?;
+        let name_start = pos + 12;
+        let desc_start = name_start.checked_add(align(namesz))?;
+        let desc = data.get(desc_start..desc_start.checked_add(descsz)?)?;
+        if n_type == note_type && data.get(name_start..name_start + namesz) == Some(b"GNU\0") {
+            return Some(desc.to_vec());
+        }
+        pos = desc_start + align(descsz);
+    }
+    None
+}
+
+/// .gnu_debuglink のファイル名と, 4 バイト境界に揃えて続く CRC32
+pub fn debuglink(elf_file: &file::ELF64, bytes: &[u8]) -> Option<(String, u32)> {
+    let sct = elf_file
+        .sections
+        .iter()
+        .find(|sct| sct.name == ".gnu_debuglink")?;
+    let offset = sct.header.sh_offset as usize;
+    let data = bytes.get(offset..offset.checked_add(sct.header.sh_size as usize)?)?;
+    let name = elf_util::read_cstr(data, 0)?;
+    let crc = elf_util::read_u32(data, (name.len() + 1 + 3) & !3)?;
+    Some((name, crc))
+}
+
+/// gdb の gnu_debuglink_crc32 と同じ CRC-32 (多項式 0xedb88320)
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+
+    !bytes.iter().fold(!0u32, |crc, b| {
+        table[((crc ^ u32::from(*b)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(name: &[u8], n_type: u32, desc: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend((name.len() as u32).to_le_bytes());
+        data.extend((desc.len() as u32).to_le_bytes());
+        data.extend(n_type.to_le_bytes());
+        for field in [name, desc] {
+            data.extend(field);
+            data.resize((data.len() + 3) & !3, 0);
+        }
+        data
+    }
+
+    #[test]
+    fn crc32_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn find_note_skips_other_owners_and_types() {
+        let build_id: Vec<u8> = (0..20).collect();
+        let mut data = note(b"GNU\0", 1, &[0; 16]);
+        data.extend(note(b"Go\0", NT_GNU_BUILD_ID, b"not a build-id"));
+        data.extend(note(b"GNU\0", NT_GNU_BUILD_ID, &build_id));
+
+        assert_eq!(find_note(&data, NT_GNU_BUILD_ID), Some(build_id));
+        assert_eq!(find_note(&data, 2), None);
+    }
+
+    #[test]
+    fn find_note_rejects_truncated_desc() {
+        let mut data = note(b"GNU\0", NT_GNU_BUILD_ID, &[0xaa; 20]);
+        data.truncate(data.len() - 4);
+
+        assert_eq!(find_note(&data, NT_GNU_BUILD_ID), None);
+    }
+}
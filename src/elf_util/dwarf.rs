@@ -0,0 +1,1310 @@
+//! DWARF のデバッグ情報(.debug_info 等)を解析する.
+//! DWARF 2〜5 のユニットを読み, 各 DIE を深さ付きで行きがけ順に並べて保持する.
+//! 再配置可能ファイルでは, デバッグセクションに対する再配置を適用してから読む.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::elf_util::{
+    compression, read_sleb128, read_u16, read_u32, read_u64, read_u8, read_uleb128,
+    relocations::{self, Relocation},
+};
+use elf_utilities::{file, header, section::Contents64};
+
+pub mod eh_frame;
+pub mod line;
+
+pub const DW_TAG_ARRAY_TYPE: u64 = 0x01;
+pub const DW_TAG_CLASS_TYPE: u64 = 0x02;
+pub const DW_TAG_ENUMERATION_TYPE: u64 = 0x04;
+pub const DW_TAG_FORMAL_PARAMETER: u64 = 0x05;
+pub const DW_TAG_POINTER_TYPE: u64 = 0x0f;
+pub const DW_TAG_REFERENCE_TYPE: u64 = 0x10;
+pub const DW_TAG_STRUCTURE_TYPE: u64 = 0x13;
+pub const DW_TAG_SUBROUTINE_TYPE: u64 = 0x15;
+pub const DW_TAG_UNION_TYPE: u64 = 0x17;
+pub const DW_TAG_TYPEDEF: u64 = 0x16;
+pub const DW_TAG_UNSPECIFIED_PARAMETERS: u64 = 0x18;
+pub const DW_TAG_PTR_TO_MEMBER_TYPE: u64 = 0x1f;
+pub const DW_TAG_SUBRANGE_TYPE: u64 = 0x21;
+pub const DW_TAG_CONST_TYPE: u64 = 0x26;
+pub const DW_TAG_VOLATILE_TYPE: u64 = 0x35;
+pub const DW_TAG_RESTRICT_TYPE: u64 = 0x37;
+pub const DW_TAG_RVALUE_REFERENCE_TYPE: u64 = 0x42;
+pub const DW_TAG_ATOMIC_TYPE: u64 = 0x47;
+
+pub const DW_AT_LOCATION: u64 = 0x02;
+pub const DW_AT_NAME: u64 = 0x03;
+pub const DW_AT_BYTE_SIZE: u64 = 0x0b;
+pub const DW_AT_LOW_PC: u64 = 0x11;
+pub const DW_AT_HIGH_PC: u64 = 0x12;
+pub const DW_AT_LOWER_BOUND: u64 = 0x22;
+pub const DW_AT_UPPER_BOUND: u64 = 0x2f;
+pub const DW_AT_ABSTRACT_ORIGIN: u64 = 0x31;
+pub const DW_AT_COUNT: u64 = 0x37;
+pub const DW_AT_DATA_MEMBER_LOCATION: u64 = 0x38;
+pub const DW_AT_FRAME_BASE: u64 = 0x40;
+pub const DW_AT_SPECIFICATION: u64 = 0x47;
+pub const DW_AT_TYPE: u64 = 0x49;
+pub const DW_AT_RANGES: u64 = 0x55;
+pub const DW_AT_STR_OFFSETS_BASE: u64 = 0x72;
+pub const DW_AT_ADDR_BASE: u64 = 0x73;
+pub const DW_AT_RNGLISTS_BASE: u64 = 0x74;
+pub const DW_AT_LOCLISTS_BASE: u64 = 0x8c;
+pub const DW_AT_GNU_RANGES_BASE: u64 = 0x2132;
+pub const DW_AT_GNU_ADDR_BASE: u64 = 0x2133;
+
+const DW_FORM_ADDR: u64 = 0x01;
+const DW_FORM_BLOCK2: u64 = 0x03;
+const DW_FORM_BLOCK4: u64 = 0x04;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_DATA8: u64 = 0x07;
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_BLOCK: u64 = 0x09;
+const DW_FORM_BLOCK1: u64 = 0x0a;
+const DW_FORM_DATA1: u64 = 0x0b;
+const DW_FORM_FLAG: u64 = 0x0c;
+const DW_FORM_SDATA: u64 = 0x0d;
+const DW_FORM_STRP: u64 = 0x0e;
+const DW_FORM_UDATA: u64 = 0x0f;
+const DW_FORM_REF_ADDR: u64 = 0x10;
+const DW_FORM_REF1: u64 = 0x11;
+const DW_FORM_REF2: u64 = 0x12;
+const DW_FORM_REF4: u64 = 0x13;
+const DW_FORM_REF8: u64 = 0x14;
+const DW_FORM_REF_UDATA: u64 = 0x15;
+const DW_FORM_INDIRECT: u64 = 0x16;
+const DW_FORM_SEC_OFFSET: u64 = 0x17;
+const DW_FORM_EXPRLOC: u64 = 0x18;
+const DW_FORM_FLAG_PRESENT: u64 = 0x19;
+const DW_FORM_STRX: u64 = 0x1a;
+const DW_FORM_ADDRX: u64 = 0x1b;
+const DW_FORM_REF_SUP4: u64 = 0x1c;
+const DW_FORM_STRP_SUP: u64 = 0x1d;
+const DW_FORM_DATA16: u64 = 0x1e;
+const DW_FORM_LINE_STRP: u64 = 0x1f;
+const DW_FORM_REF_SIG8: u64 = 0x20;
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+const DW_FORM_LOCLISTX: u64 = 0x22;
+const DW_FORM_RNGLISTX: u64 = 0x23;
+const DW_FORM_REF_SUP8: u64 = 0x24;
+const DW_FORM_STRX1: u64 = 0x25;
+const DW_FORM_STRX2: u64 = 0x26;
+const DW_FORM_STRX3: u64 = 0x27;
+const DW_FORM_STRX4: u64 = 0x28;
+const DW_FORM_ADDRX1: u64 = 0x29;
+const DW_FORM_ADDRX2: u64 = 0x2a;
+const DW_FORM_ADDRX3: u64 = 0x2b;
+const DW_FORM_ADDRX4: u64 = 0x2c;
+const DW_FORM_GNU_ADDR_INDEX: u64 = 0x1f01;
+const DW_FORM_GNU_STR_INDEX: u64 = 0x1f02;
+const DW_FORM_GNU_REF_ALT: u64 = 0x1f20;
+const DW_FORM_GNU_STRP_ALT: u64 = 0x1f21;
+
+pub const DW_UT_COMPILE: u8 = 0x01;
+pub const DW_UT_TYPE: u8 = 0x02;
+pub const DW_UT_PARTIAL: u8 = 0x03;
+pub const DW_UT_SKELETON: u8 = 0x04;
+pub const DW_UT_SPLIT_COMPILE: u8 = 0x05;
+pub const DW_UT_SPLIT_TYPE: u8 = 0x06;
+
+/// 解析対象のデバッグセクション
+const DEBUG_SECTIONS: [&str; 11] = [
+    ".debug_info",
+    ".debug_abbrev",
+    ".debug_str",
+    ".debug_line_str",
+    ".debug_str_offsets",
+    ".debug_addr",
+    ".debug_ranges",
+    ".debug_rnglists",
+    ".debug_loc",
+    ".debug_loclists",
+    ".debug_line",
+];
+
+const R_X86_64_64: u32 = 1;
+const R_X86_64_32: u32 = 10;
+const R_AARCH64_ABS64: u32 = 257;
+const R_AARCH64_ABS32: u32 = 258;
+
+/// 型名を組み立てる際にたどる参照の上限. 壊れた循環参照で止まらないようにする.
+const TYPE_NAME_DEPTH: usize = 16;
+
+/// デバッグセクションの中身. 再配置の適用などで書き換えるため, コピーを持つ.
+pub struct DebugSections {
+    sections: HashMap<&'static str, Vec<u8>>,
+    /// 再配置を適用できないアーキテクチャの ET_REL で, オフセットが未解決のまま残っている
+    pub unapplied_relocations: bool,
+}
+
+impl DebugSections {
+    pub fn new(elf_file: &file::ELF64, bytes: &[u8]) -> Self {
+        let relocs = if elf_file.ehdr.get_type() == header::Type::Rel {
+            relocations::relocations(elf_file)
+        } else {
+            Vec::new()
+        };
+        let mut sections = HashMap::new();
+        let mut unapplied_relocations = false;
+
+        for name in DEBUG_SECTIONS.iter() {
+            let (idx, sct) = match elf_file
+                .sections
+                .iter()
+                .enumerate()
+                // 分割 DWARF の .dwo ファイルでは名前に .dwo が付き,
+                // 古い形式の圧縮セクションは .zdebug_* になる
+                .find(|(_, sct)| {
+                    let sct_name = compression::uncompressed_name(&sct.name);
+                    sct_name.strip_suffix(".dwo").unwrap_or(&sct_name) == *name
+                }) {
+                Some(found) => found,
+                None => continue,
+            };
+            let mut data = match compression::section_data(sct, bytes) {
+                Some(data) => data.into_owned(),
+                None => continue,
+            };
+            unapplied_relocations |= !apply_relocations(elf_file, &relocs, idx, &mut data);
+            sections.insert(*name, data);
+        }
+
+        Self {
+            sections,
+            unapplied_relocations,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> &[u8] {
+        self.sections
+            .get(name)
+            .map(|data| data.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// ET_REL のデバッグセクションでは, 他セクションへのオフセットが再配置で埋められる.
+/// 対応していないアーキテクチャの再配置があれば false を返す.
+fn apply_relocations(
+    elf_file: &file::ELF64,
+    relocs: &[Relocation],
+    target: usize,
+    data: &mut [u8],
+) -> bool {
+    let machine = elf_file.ehdr.e_machine;
+    let mut applied = true;
+
+    for reloc in relocs {
+        let reloc_sct = &elf_file.sections[reloc.section];
+        if reloc_sct.header.sh_info as usize != target {
+            continue;
+        }
+        let symbol_value = match elf_file
+            .sections
+            .get(reloc_sct.header.sh_link as usize)
+            .map(|sct| &sct.contents)
+        {
+            Some(Contents64::Symbols(symbols)) => symbols
+                .get(reloc.r_sym as usize)
+                .map(|sym| sym.st_value)
+                .unwrap_or(0),
+            _ => 0,
+        };
+        let value = symbol_value.wrapping_add(reloc.r_addend.unwrap_or(0) as u64);
+        let offset = reloc.r_offset as usize;
+
+        let written = match (machine, reloc.r_type) {
+            (relocations::EM_X86_64, R_X86_64_64) | (relocations::EM_AARCH64, R_AARCH64_ABS64) => {
+                value.to_le_bytes().to_vec()
+            }
+            (relocations::EM_X86_64, R_X86_64_32) | (relocations::EM_AARCH64, R_AARCH64_ABS32) => {
+                (value as u32).to_le_bytes().to_vec()
+            }
+            (relocations::EM_X86_64, _) | (relocations::EM_AARCH64, _) => continue,
+            _ => {
+                applied = false;
+                continue;
+            }
+        };
+        if let Some(dest) = offset
+            .checked_add(written.len())
+            .and_then(|end| data.get_mut(offset..end))
+        {
+            dest.copy_from_slice(&written);
+        }
+    }
+
+    applied
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    Address(u64),
+    /// .debug_addr のインデックス (DW_FORM_addrx 等)
+    AddressIndex(u64),
+    Block(Vec<u8>),
+    Exprloc(Vec<u8>),
+    Udata(u64),
+    Sdata(i64),
+    Flag(bool),
+    String(String),
+    /// .debug_str へのオフセット
+    StrOffset(u64),
+    /// .debug_line_str へのオフセット
+    LineStrOffset(u64),
+    /// .debug_str_offsets のインデックス (DW_FORM_strx 等)
+    StrIndex(u64),
+    /// .debug_info 先頭からのオフセットに直した参照
+    Ref(u64),
+    RefSig8(u64),
+    /// 補助オブジェクトファイル(.debug_sup, .gnu_debugaltlink)への参照
+    Supplementary(u64),
+    SecOffset(u64),
+    LocListIndex(u64),
+    RngListIndex(u64),
+}
+
+impl AttrValue {
+    /// 定数として読める値
+    pub fn constant(&self) -> Option<i64> {
+        match self {
+            AttrValue::Udata(value) => Some(*value as i64),
+            AttrValue::Sdata(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: u64,
+    pub value: AttrValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct Die {
+    /// .debug_info 先頭からのオフセット
+    pub offset: u64,
+    pub depth: usize,
+    pub tag: u64,
+    pub attributes: Vec<Attribute>,
+}
+
+impl Die {
+    pub fn attribute(&self, name: u64) -> Option<&AttrValue> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| &attr.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Unit {
+    pub offset: u64,
+    pub version: u16,
+    pub unit_type: u8,
+    pub address_size: u8,
+    pub dwarf64: bool,
+    pub abbrev_offset: u64,
+    /// 型ユニットの型シグネチャ, スケルトン/分割ユニットの DWO ID
+    pub signature: Option<u64>,
+    pub dies: Vec<Die>,
+    pub str_offsets_base: Option<u64>,
+    pub addr_base: Option<u64>,
+    pub rnglists_base: Option<u64>,
+    pub loclists_base: Option<u64>,
+    /// 範囲リストや位置リストの基準アドレス(ユニットの DW_AT_low_pc)
+    pub base_address: u64,
+    pub line_program: Option<line::LineProgram>,
+}
+
+impl Unit {
+    pub fn offset_size(&self) -> usize {
+        if self.dwarf64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// idx 番目の DIE の子のインデックス
+    pub fn children(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let depth = self.dies[idx].depth;
+        self.dies[idx + 1..]
+            .iter()
+            .take_while(move |die| die.depth > depth)
+            .enumerate()
+            .filter(move |(_, die)| die.depth == depth + 1)
+            .map(move |(child, _)| idx + 1 + child)
+    }
+}
+
+/// 位置リストの1エントリ. 範囲が無いものは既定の位置 (DW_LLE_default_location)
+#[derive(Debug, Clone)]
+pub struct LocationEntry {
+    pub range: Option<(u64, u64)>,
+    pub expression: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Operand {
+    Unsigned(u64),
+    Signed(i64),
+    Block(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub opcode: u8,
+    pub operands: Vec<Operand>,
+}
+
+pub struct Dwarf {
+    pub sections: DebugSections,
+    pub units: Vec<Unit>,
+    /// unit_for_address 用の索引
+    unit_ranges: line::RangeIndex,
+}
+
+#[derive(Debug, Clone)]
+struct Abbrev {
+    tag: u64,
+    has_children: bool,
+    /// (属性名, 形式, DW_FORM_implicit_const の値)
+    specs: Vec<(u64, u64, Option<i64>)>,
+}
+
+/// ユニット内を読み進めるためのカーソル
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Self { bytes, pos }
+    }
+
+    fn advance<T>(&mut self, value: Option<T>, len: usize) -> Option<T> {
+        let value = value?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        let value = read_u8(self.bytes, self.pos);
+        self.advance(value, 1)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let value = read_u16(self.bytes, self.pos);
+        self.advance(value, 2)
+    }
+
+    fn u24(&mut self) -> Option<u32> {
+        let low = u32::from(self.u16()?);
+        Some(low | u32::from(self.u8()?) << 16)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let value = read_u32(self.bytes, self.pos);
+        self.advance(value, 4)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let value = read_u64(self.bytes, self.pos);
+        self.advance(value, 8)
+    }
+
+    fn uleb(&mut self) -> Option<u64> {
+        let (value, len) = read_uleb128(self.bytes, self.pos)?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn sleb(&mut self) -> Option<i64> {
+        let (value, len) = read_sleb128(self.bytes, self.pos)?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn sized(&mut self, size: usize) -> Option<u64> {
+        match size {
+            1 => self.u8().map(u64::from),
+            2 => self.u16().map(u64::from),
+            4 => self.u32().map(u64::from),
+            8 => self.u64(),
+            _ => None,
+        }
+    }
+
+    fn offset(&mut self, dwarf64: bool) -> Option<u64> {
+        self.sized(if dwarf64 { 8 } else { 4 })
+    }
+
+    fn block(&mut self, len: u64) -> Option<Vec<u8>> {
+        let end = self.pos.checked_add(len as usize)?;
+        let block = self.bytes.get(self.pos..end)?.to_vec();
+        self.pos = end;
+        Some(block)
+    }
+
+    fn cstr(&mut self) -> Option<String> {
+        let tail = self.bytes.get(self.pos..)?;
+        let len = tail.iter().position(|b| *b == 0)?;
+        self.pos += len + 1;
+        Some(String::from_utf8_lossy(&tail[..len]).to_string())
+    }
+
+    /// ユニット長を読み, (64bit DWARF か, ユニットの終端) を返す.
+    fn initial_length(&mut self) -> Option<(bool, usize)> {
+        match self.u32()? {
+            0xffff_ffff => {
+                let len = self.u64()? as usize;
+                Some((true, self.pos.checked_add(len)?))
+            }
+            len => Some((false, self.pos.checked_add(len as usize)?)),
+        }
+    }
+}
+
+impl Dwarf {
+    /// .debug_info が無ければ None
+    pub fn parse(elf_file: &file::ELF64, bytes: &[u8]) -> Option<Self> {
+        Self::from_sections(DebugSections::new(elf_file, bytes))
+    }
+
+    pub fn from_sections(sections: DebugSections) -> Option<Self> {
+        if sections.get(".debug_info").is_empty() {
+            return None;
+        }
+
+        let mut units = Vec::new();
+        let mut abbrev_tables = HashMap::new();
+        let info = sections.get(".debug_info");
+        let mut offset = 0;
+        while offset < info.len() {
+            match parse_unit(&sections, &mut abbrev_tables, offset) {
+                Some((unit, next)) => {
+                    units.push(unit);
+                    offset = next;
+                }
+                None => break,
+            }
+        }
+
+        let unit_ranges = line::unit_ranges(&units);
+        Some(Self {
+            sections,
+            units,
+            unit_ranges,
+        })
+    }
+
+    /// offset の DIE を含む (ユニット, DIE) のインデックス
+    pub fn find_die(&self, offset: u64) -> Option<(usize, usize)> {
+        let unit_idx = self
+            .units
+            .partition_point(|unit| unit.offset <= offset)
+            .checked_sub(1)?;
+        let dies = &self.units[unit_idx].dies;
+        let die_idx = dies.binary_search_by_key(&offset, |die| die.offset).ok()?;
+        Some((unit_idx, die_idx))
+    }
+
+    pub fn die(&self, offset: u64) -> Option<(&Unit, &Die)> {
+        let (unit_idx, die_idx) = self.find_die(offset)?;
+        let unit = &self.units[unit_idx];
+        Some((unit, &unit.dies[die_idx]))
+    }
+
+    /// 文字列を表す属性値を解決する.
+    pub fn string(&self, unit: &Unit, value: &AttrValue) -> Option<String> {
+        string(&self.sections, unit, value)
+    }
+
+    /// アドレスを表す属性値を解決する.
+    pub fn address(&self, unit: &Unit, value: &AttrValue) -> Option<u64> {
+        match value {
+            AttrValue::Address(addr) => Some(*addr),
+            AttrValue::AddressIndex(idx) => self.indexed_address(unit, *idx),
+            _ => None,
+        }
+    }
+
+    fn indexed_address(&self, unit: &Unit, idx: u64) -> Option<u64> {
+        indexed_address(&self.sections, unit, idx)
+    }
+
+    /// DIE の名前. 宣言や抽象インスタンスを指していればそちらの名前を使う.
+    pub fn die_name(&self, unit: &Unit, die: &Die) -> Option<String> {
+        self.die_name_at_depth(unit, die, 0)
+    }
+
+    fn die_name_at_depth(&self, unit: &Unit, die: &Die, depth: usize) -> Option<String> {
+        if let Some(name) = die.attribute(DW_AT_NAME) {
+            return self.string(unit, name);
+        }
+        if depth >= TYPE_NAME_DEPTH {
+            return None;
+        }
+        [DW_AT_SPECIFICATION, DW_AT_ABSTRACT_ORIGIN]
+            .iter()
+            .filter_map(|at| match die.attribute(*at) {
+                Some(AttrValue::Ref(offset)) => self.die(*offset),
+                _ => None,
+            })
+            .find_map(|(unit, die)| self.die_name_at_depth(unit, die, depth + 1))
+    }
+
+    /// DW_AT_ranges の値から範囲リストを読む.
+    pub fn ranges(&self, unit: &Unit, value: &AttrValue) -> Vec<(u64, u64)> {
+        let offset = match value {
+            AttrValue::SecOffset(offset) if unit.version < 5 => {
+                return self.ranges_v4(unit, *offset as usize);
+            }
+            // 分割 DWARF 4 の DW_AT_GNU_ranges_base も rnglists_base に入れている
+            AttrValue::SecOffset(offset) => *offset as usize,
+            AttrValue::RngListIndex(idx) => {
+                match self.list_offset(unit, ".debug_rnglists", unit.rnglists_base, *idx) {
+                    Some(offset) => offset,
+                    None => return Vec::new(),
+                }
+            }
+            _ => return Vec::new(),
+        };
+        self.rnglists(unit, offset)
+    }
+
+    fn ranges_v4(&self, unit: &Unit, offset: usize) -> Vec<(u64, u64)> {
+        let size = unit.address_size as usize;
+        let max = if size == 4 { 0xffff_ffff } else { u64::MAX };
+        let mut reader = Reader::new(self.sections.get(".debug_ranges"), offset);
+        let mut base = unit.base_address;
+        let mut ranges = Vec::new();
+
+        while let (Some(begin), Some(end)) = (reader.sized(size), reader.sized(size)) {
+            match (begin, end) {
+                (0, 0) => break,
+                (begin, end) if begin == max => base = end,
+                (begin, end) => ranges.push((base.wrapping_add(begin), base.wrapping_add(end))),
+            }
+        }
+
+        ranges
+    }
+
+    fn rnglists(&self, unit: &Unit, offset: usize) -> Vec<(u64, u64)> {
+        let size = unit.address_size as usize;
+        let mut reader = Reader::new(self.sections.get(".debug_rnglists"), offset);
+        let mut base = unit.base_address;
+        let mut ranges = Vec::new();
+
+        loop {
+            let range = match reader.u8() {
+                // DW_RLE_base_addressx
+                Some(0x01) => {
+                    base = reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx))
+                        .unwrap_or(0);
+                    continue;
+                }
+                // DW_RLE_startx_endx
+                Some(0x02) => (
+                    reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx)),
+                    reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx)),
+                ),
+                // DW_RLE_startx_length
+                Some(0x03) => {
+                    let begin = reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx));
+                    let len = reader.uleb();
+                    (begin, begin.zip(len).map(|(b, l)| b.wrapping_add(l)))
+                }
+                // DW_RLE_offset_pair
+                Some(0x04) => (
+                    reader.uleb().map(|b| base.wrapping_add(b)),
+                    reader.uleb().map(|e| base.wrapping_add(e)),
+                ),
+                // DW_RLE_base_address
+                Some(0x05) => {
+                    base = reader.sized(size).unwrap_or(0);
+                    continue;
+                }
+                // DW_RLE_start_end
+                Some(0x06) => (reader.sized(size), reader.sized(size)),
+                // DW_RLE_start_length
+                Some(0x07) => {
+                    let begin = reader.sized(size);
+                    let len = reader.uleb();
+                    (begin, begin.zip(len).map(|(b, l)| b.wrapping_add(l)))
+                }
+                // DW_RLE_end_of_list, または壊れている
+                _ => break,
+            };
+            match range {
+                (Some(begin), Some(end)) => ranges.push((begin, end)),
+                _ => break,
+            }
+        }
+
+        ranges
+    }
+
+    /// DW_AT_location などが位置リストを指していれば読む.
+    pub fn location_list(&self, unit: &Unit, value: &AttrValue) -> Vec<LocationEntry> {
+        match value {
+            AttrValue::SecOffset(offset) if unit.version < 5 => self.loc_v4(unit, *offset as usize),
+            AttrValue::SecOffset(offset) => self.loclists(unit, *offset as usize),
+            AttrValue::LocListIndex(idx) => self
+                .list_offset(unit, ".debug_loclists", unit.loclists_base, *idx)
+                .map(|offset| self.loclists(unit, offset))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn loc_v4(&self, unit: &Unit, offset: usize) -> Vec<LocationEntry> {
+        let size = unit.address_size as usize;
+        let max = if size == 4 { 0xffff_ffff } else { u64::MAX };
+        let mut reader = Reader::new(self.sections.get(".debug_loc"), offset);
+        let mut base = unit.base_address;
+        let mut entries = Vec::new();
+
+        while let (Some(begin), Some(end)) = (reader.sized(size), reader.sized(size)) {
+            match (begin, end) {
+                (0, 0) => break,
+                (begin, end) if begin == max => base = end,
+                (begin, end) => {
+                    let expression = match reader.u16().and_then(|len| reader.block(len.into())) {
+                        Some(expression) => expression,
+                        None => break,
+                    };
+                    entries.push(LocationEntry {
+                        range: Some((base.wrapping_add(begin), base.wrapping_add(end))),
+                        expression,
+                    });
+                }
+            }
+        }
+
+        entries
+    }
+
+    fn loclists(&self, unit: &Unit, offset: usize) -> Vec<LocationEntry> {
+        let size = unit.address_size as usize;
+        let mut reader = Reader::new(self.sections.get(".debug_loclists"), offset);
+        let mut base = unit.base_address;
+        let mut entries = Vec::new();
+
+        loop {
+            let range = match reader.u8() {
+                // DW_LLE_base_addressx
+                Some(0x01) => {
+                    base = reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx))
+                        .unwrap_or(0);
+                    continue;
+                }
+                // DW_LLE_startx_endx
+                Some(0x02) => Some((
+                    reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx)),
+                    reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx)),
+                )),
+                // DW_LLE_startx_length
+                Some(0x03) => {
+                    let begin = reader
+                        .uleb()
+                        .and_then(|idx| self.indexed_address(unit, idx));
+                    let len = reader.uleb();
+                    Some((begin, begin.zip(len).map(|(b, l)| b.wrapping_add(l))))
+                }
+                // DW_LLE_offset_pair
+                Some(0x04) => Some((
+                    reader.uleb().map(|b| base.wrapping_add(b)),
+                    reader.uleb().map(|e| base.wrapping_add(e)),
+                )),
+                // DW_LLE_default_location
+                Some(0x05) => None,
+                // DW_LLE_base_address
+                Some(0x06) => {
+                    base = reader.sized(size).unwrap_or(0);
+                    continue;
+                }
+                // DW_LLE_start_end
+                Some(0x07) => Some((reader.sized(size), reader.sized(size))),
+                // DW_LLE_start_length
+                Some(0x08) => {
+                    let begin = reader.sized(size);
+                    let len = reader.uleb();
+                    Some((begin, begin.zip(len).map(|(b, l)| b.wrapping_add(l))))
+                }
+                // DW_LLE_end_of_list, または壊れている
+                _ => break,
+            };
+            let range = match range {
+                Some((Some(begin), Some(end))) => Some((begin, end)),
+                Some(_) => break,
+                None => None,
+            };
+            let expression = match reader.uleb().and_then(|len| reader.block(len)) {
+                Some(expression) => expression,
+                None => break,
+            };
+            entries.push(LocationEntry { range, expression });
+        }
+
+        entries
+    }
+
+    /// DW_FORM_rnglistx/loclistx のインデックスを, オフセット表を引いてセクション内オフセットにする.
+    fn list_offset(
+        &self,
+        unit: &Unit,
+        section: &str,
+        base: Option<u64>,
+        idx: u64,
+    ) -> Option<usize> {
+        // 基準が無ければ, 先頭のリストヘッダ(12 or 20バイト)の直後とみなす
+        let base = base.unwrap_or(if unit.dwarf64 { 20 } else { 12 }) as usize;
+        let entry = base.checked_add((idx as usize).checked_mul(unit.offset_size())?)?;
+        let offset = Reader::new(self.sections.get(section), entry).offset(unit.dwarf64)?;
+        base.checked_add(offset as usize)
+    }
+
+    /// 型 DIE (offset) を C 風の型名にする.
+    pub fn type_name(&self, offset: u64) -> String {
+        self.type_name_at_depth(Some(offset), 0)
+    }
+
+    fn type_name_at_depth(&self, offset: Option<u64>, depth: usize) -> String {
+        let (unit, die) = match offset.and_then(|offset| self.die(offset)) {
+            Some(found) => found,
+            None => return "void".to_string(),
+        };
+        if depth >= TYPE_NAME_DEPTH {
+            return "...".to_string();
+        }
+
+        let target = match die.attribute(DW_AT_TYPE) {
+            Some(AttrValue::Ref(target)) => Some(*target),
+            _ => None,
+        };
+        let name = self.die_name(unit, die);
+        let inner = || self.type_name_at_depth(target, depth + 1);
+
+        match die.tag {
+            DW_TAG_POINTER_TYPE => match target.and_then(|t| self.die(t)) {
+                Some((_, target_die)) if target_die.tag == DW_TAG_SUBROUTINE_TYPE => {
+                    self.subroutine_type_name(target.unwrap(), "(*)", depth)
+                }
+                _ => {
+                    let inner = inner();
+                    if inner.ends_with('*') {
+                        format!("{}*", inner)
+                    } else {
+                        format!("{} *", inner)
+                    }
+                }
+            },
+            DW_TAG_REFERENCE_TYPE => format!("{} &", inner()),
+            DW_TAG_RVALUE_REFERENCE_TYPE => format!("{} &&", inner()),
+            DW_TAG_CONST_TYPE => format!("const {}", inner()),
+            DW_TAG_VOLATILE_TYPE => format!("volatile {}", inner()),
+            DW_TAG_RESTRICT_TYPE => format!("{} restrict", inner()),
+            DW_TAG_ATOMIC_TYPE => format!("_Atomic {}", inner()),
+            DW_TAG_STRUCTURE_TYPE => format!("struct {}", name.unwrap_or_default()),
+            DW_TAG_UNION_TYPE => format!("union {}", name.unwrap_or_default()),
+            DW_TAG_CLASS_TYPE => format!("class {}", name.unwrap_or_default()),
+            DW_TAG_ENUMERATION_TYPE => format!("enum {}", name.unwrap_or_default()),
+            DW_TAG_ARRAY_TYPE => {
+                let (unit_idx, die_idx) = self.find_die(die.offset).unwrap();
+                let bounds: String = unit
+                    .children(die_idx)
+                    .map(|child| &self.units[unit_idx].dies[child])
+                    .filter(|child| child.tag == DW_TAG_SUBRANGE_TYPE)
+                    .map(|child| match subrange_count(child) {
+                        Some(count) => format!("[{}]", count),
+                        None => "[]".to_string(),
+                    })
+                    .collect();
+                format!("{}{}", inner(), bounds)
+            }
+            DW_TAG_SUBROUTINE_TYPE => self.subroutine_type_name(die.offset, "", depth),
+            DW_TAG_PTR_TO_MEMBER_TYPE => format!("{} ::*", inner()),
+            _ => name.unwrap_or_else(|| "?".to_string()),
+        }
+    }
+
+    fn subroutine_type_name(&self, offset: u64, declarator: &str, depth: usize) -> String {
+        let (unit_idx, die_idx) = match self.find_die(offset) {
+            Some(found) => found,
+            None => return "?".to_string(),
+        };
+        let unit = &self.units[unit_idx];
+        let die = &unit.dies[die_idx];
+
+        let ret = match die.attribute(DW_AT_TYPE) {
+            Some(AttrValue::Ref(target)) => self.type_name_at_depth(Some(*target), depth + 1),
+            _ => "void".to_string(),
+        };
+        let params: Vec<String> = unit
+            .children(die_idx)
+            .map(|child| &unit.dies[child])
+            .filter_map(|child| match child.tag {
+                DW_TAG_FORMAL_PARAMETER => Some(match child.attribute(DW_AT_TYPE) {
+                    Some(AttrValue::Ref(target)) => {
+                        self.type_name_at_depth(Some(*target), depth + 1)
+                    }
+                    _ => "?".to_string(),
+                }),
+                DW_TAG_UNSPECIFIED_PARAMETERS => Some("...".to_string()),
+                _ => None,
+            })
+            .collect();
+
+        format!("{} {}({})", ret, declarator, params.join(", "))
+    }
+
+    /// 型のバイト数. typedef や修飾子はたどり, 配列は要素数を掛ける.
+    pub fn type_size(&self, offset: u64) -> Option<u64> {
+        self.type_size_at_depth(offset, 0)
+    }
+
+    fn type_size_at_depth(&self, offset: u64, depth: usize) -> Option<u64> {
+        let (unit_idx, die_idx) = self.find_die(offset)?;
+        let unit = &self.units[unit_idx];
+        let die = &unit.dies[die_idx];
+        if let Some(size) = die.attribute(DW_AT_BYTE_SIZE).and_then(AttrValue::constant) {
+            return Some(size as u64);
+        }
+        if depth >= TYPE_NAME_DEPTH {
+            return None;
+        }
+
+        let target = match die.attribute(DW_AT_TYPE) {
+            Some(AttrValue::Ref(target)) => Some(*target),
+            _ => None,
+        };
+        match die.tag {
+            DW_TAG_POINTER_TYPE
+            | DW_TAG_REFERENCE_TYPE
+            | DW_TAG_RVALUE_REFERENCE_TYPE
+            | DW_TAG_PTR_TO_MEMBER_TYPE => Some(unit.address_size.into()),
+            DW_TAG_TYPEDEF | DW_TAG_CONST_TYPE | DW_TAG_VOLATILE_TYPE | DW_TAG_RESTRICT_TYPE
+            | DW_TAG_ATOMIC_TYPE => self.type_size_at_depth(target?, depth + 1),
+            DW_TAG_ARRAY_TYPE => {
+                let element = self.type_size_at_depth(target?, depth + 1)?;
+                unit.children(die_idx)
+                    .map(|child| &unit.dies[child])
+                    .filter(|child| child.tag == DW_TAG_SUBRANGE_TYPE)
+                    .try_fold(element, |size, child| {
+                        size.checked_mul(subrange_count(child)?)
+                    })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn string(sections: &DebugSections, unit: &Unit, value: &AttrValue) -> Option<String> {
+    match value {
+        AttrValue::String(s) => Some(s.clone()),
+        AttrValue::StrOffset(offset) => {
+            crate::elf_util::read_cstr(sections.get(".debug_str"), *offset as usize)
+        }
+        AttrValue::LineStrOffset(offset) => {
+            crate::elf_util::read_cstr(sections.get(".debug_line_str"), *offset as usize)
+        }
+        AttrValue::StrIndex(idx) => {
+            // DW_AT_str_offsets_base が無ければヘッダ直後を指すとみなす.
+            // DWARF 4 の GNU 拡張(分割 DWARF)ではヘッダが無い
+            let header_size = if unit.version >= 5 { 8 } else { 0 };
+            let base = unit.str_offsets_base.unwrap_or(header_size) as usize;
+            let entry = base.checked_add((*idx as usize).checked_mul(unit.offset_size())?)?;
+            let mut reader = Reader::new(sections.get(".debug_str_offsets"), entry);
+            let offset = reader.offset(unit.dwarf64)?;
+            crate::elf_util::read_cstr(sections.get(".debug_str"), offset as usize)
+        }
+        _ => None,
+    }
+}
+
+/// .debug_addr の idx 番目のアドレス. DW_AT_addr_base が無ければヘッダ直後から数える.
+fn indexed_address(sections: &DebugSections, unit: &Unit, idx: u64) -> Option<u64> {
+    let base = unit.addr_base.unwrap_or(8) as usize;
+    let entry = base.checked_add((idx as usize).checked_mul(unit.address_size as usize)?)?;
+    Reader::new(sections.get(".debug_addr"), entry).sized(unit.address_size as usize)
+}
+
+/// 配列の次元の要素数. DW_AT_count か DW_AT_upper_bound から求める.
+pub fn subrange_count(subrange: &Die) -> Option<u64> {
+    if let Some(count) = subrange
+        .attribute(DW_AT_COUNT)
+        .and_then(AttrValue::constant)
+    {
+        return Some(count as u64);
+    }
+    let upper = subrange
+        .attribute(DW_AT_UPPER_BOUND)
+        .and_then(AttrValue::constant)?;
+    let lower = subrange
+        .attribute(DW_AT_LOWER_BOUND)
+        .and_then(AttrValue::constant)
+        .unwrap_or(0);
+    upper
+        .checked_sub(lower)?
+        .checked_add(1)
+        .filter(|count| *count >= 0)
+        .map(|count| count as u64)
+}
+
+/// DWARF 式をオペランド付きの命令列に分解する. 解釈できない命令があればそこで止める.
+pub fn operations(expression: &[u8], unit: &Unit) -> Vec<Operation> {
+    let mut reader = Reader::new(expression, 0);
+    let mut ops = Vec::new();
+
+    while reader.pos < expression.len() {
+        let opcode = match reader.u8() {
+            Some(opcode) => opcode,
+            None => break,
+        };
+        let operands = operation_operands(&mut reader, opcode, unit);
+        let complete = operands.is_some();
+        ops.push(Operation {
+            opcode,
+            operands: operands.unwrap_or_default(),
+        });
+        if !complete {
+            break;
+        }
+    }
+
+    ops
+}
+
+fn operation_operands(reader: &mut Reader, opcode: u8, unit: &Unit) -> Option<Vec<Operand>> {
+    use Operand::{Block, Signed, Unsigned};
+
+    let operands = match opcode {
+        // DW_OP_addr
+        0x03 => vec![Unsigned(reader.sized(unit.address_size as usize)?)],
+        // DW_OP_const1u, const1s, const2u, const2s, const4u, const4s, const8u, const8s
+        0x08 => vec![Unsigned(reader.u8()?.into())],
+        0x09 => vec![Signed((reader.u8()? as i8).into())],
+        0x0a => vec![Unsigned(reader.u16()?.into())],
+        0x0b => vec![Signed((reader.u16()? as i16).into())],
+        0x0c => vec![Unsigned(reader.u32()?.into())],
+        0x0d => vec![Signed((reader.u32()? as i32).into())],
+        0x0e => vec![Unsigned(reader.u64()?)],
+        0x0f => vec![Signed(reader.u64()? as i64)],
+        // DW_OP_constu, plus_uconst, regx, piece, addrx, constx, convert, reinterpret,
+        // GNU_addr_index, GNU_const_index, GNU_convert, GNU_reinterpret
+        0x10 | 0x23 | 0x90 | 0x93 | 0xa1 | 0xa2 | 0xa8 | 0xa9 | 0xfb | 0xfc | 0xf7 | 0xf9 => {
+            vec![Unsigned(reader.uleb()?)]
+        }
+        // DW_OP_consts, breg0..31, fbreg
+        0x11 | 0x70..=0x8f | 0x91 => vec![Signed(reader.sleb()?)],
+        // DW_OP_pick, deref_size, xderef_size
+        0x15 | 0x94 | 0x95 => vec![Unsigned(reader.u8()?.into())],
+        // DW_OP_skip, bra
+        0x2f | 0x28 => vec![Signed((reader.u16()? as i16).into())],
+        // DW_OP_bregx
+        0x92 => vec![Unsigned(reader.uleb()?), Signed(reader.sleb()?)],
+        // DW_OP_call2, call4, GNU_parameter_ref
+        0x98 => vec![Unsigned(reader.u16()?.into())],
+        0x99 | 0xfa => vec![Unsigned(reader.u32()?.into())],
+        // DW_OP_call_ref, GNU_variable_value
+        0x9a | 0xfd => vec![Unsigned(reader.offset(unit.dwarf64)?)],
+        // DW_OP_bit_piece, regval_type, GNU_regval_type
+        0x9d | 0xa5 | 0xf5 => vec![Unsigned(reader.uleb()?), Unsigned(reader.uleb()?)],
+        // DW_OP_implicit_value, entry_value, GNU_entry_value
+        0x9e | 0xa3 | 0xf3 => {
+            let len = reader.uleb()?;
+            vec![Block(reader.block(len)?)]
+        }
+        // DW_OP_implicit_pointer, GNU_implicit_pointer
+        0xa0 | 0xf2 => vec![
+            Unsigned(reader.offset(unit.dwarf64)?),
+            Signed(reader.sleb()?),
+        ],
+        // DW_OP_const_type, GNU_const_type
+        0xa4 | 0xf4 => {
+            let ty = reader.uleb()?;
+            let len = reader.u8()?;
+            vec![Unsigned(ty), Block(reader.block(len.into())?)]
+        }
+        // DW_OP_deref_type, xderef_type, GNU_deref_type
+        0xa6 | 0xa7 | 0xf6 => vec![Unsigned(reader.u8()?.into()), Unsigned(reader.uleb()?)],
+        // オペランドを持たない命令
+        0x06
+        | 0x12..=0x14
+        | 0x16..=0x22
+        | 0x24..=0x27
+        | 0x29..=0x2e
+        | 0x30..=0x6f
+        | 0x96
+        | 0x97
+        | 0x9b
+        | 0x9c
+        | 0x9f
+        | 0xe0
+        | 0xf0 => Vec::new(),
+        _ => return None,
+    };
+    Some(operands)
+}
+
+/// offset から始まるユニットを読み, 次のユニットの位置と共に返す.
+fn parse_unit(
+    sections: &DebugSections,
+    abbrev_tables: &mut HashMap<u64, BTreeMap<u64, Abbrev>>,
+    offset: usize,
+) -> Option<(Unit, usize)> {
+    let info = sections.get(".debug_info");
+    let mut reader = Reader::new(info, offset);
+    let (dwarf64, end) = reader.initial_length()?;
+    let version = reader.u16()?;
+
+    let (unit_type, address_size, abbrev_offset) = if version >= 5 {
+        let unit_type = reader.u8()?;
+        let address_size = reader.u8()?;
+        (unit_type, address_size, reader.offset(dwarf64)?)
+    } else {
+        let abbrev_offset = reader.offset(dwarf64)?;
+        (DW_UT_COMPILE, reader.u8()?, abbrev_offset)
+    };
+    let signature = match unit_type {
+        DW_UT_SKELETON | DW_UT_SPLIT_COMPILE => Some(reader.u64()?),
+        DW_UT_TYPE | DW_UT_SPLIT_TYPE => {
+            let signature = reader.u64()?;
+            reader.offset(dwarf64)?;
+            Some(signature)
+        }
+        _ => None,
+    };
+
+    let abbrevs = abbrev_tables
+        .entry(abbrev_offset)
+        .or_insert_with(|| parse_abbrevs(sections.get(".debug_abbrev"), abbrev_offset as usize));
+
+    let mut unit = Unit {
+        offset: offset as u64,
+        version,
+        unit_type,
+        address_size,
+        dwarf64,
+        abbrev_offset,
+        signature,
+        dies: Vec::new(),
+        str_offsets_base: None,
+        addr_base: None,
+        rnglists_base: None,
+        loclists_base: None,
+        base_address: 0,
+        line_program: None,
+    };
+
+    // 壊れたユニットでも, 読めたところまでは見せる
+    let mut reader = Reader::new(info.get(..end.min(info.len()))?, reader.pos);
+    let mut parents: Vec<usize> = Vec::new();
+    while reader.pos < reader.bytes.len() {
+        let die_offset = reader.pos as u64;
+        let code = match reader.uleb() {
+            Some(code) => code,
+            None => break,
+        };
+        if code == 0 {
+            parents.pop();
+            if parents.is_empty() {
+                // ルート DIE の子が尽きた. 残りはパディング
+                break;
+            }
+            continue;
+        }
+        let abbrev = match abbrevs.get(&code) {
+            Some(abbrev) => abbrev,
+            None => break,
+        };
+
+        let mut attributes = Vec::new();
+        for (name, form, implicit) in abbrev.specs.iter() {
+            match parse_attr_value(&mut reader, &unit, *form, *implicit) {
+                Some(value) => attributes.push(Attribute { name: *name, value }),
+                None => break,
+            }
+        }
+        if attributes.len() != abbrev.specs.len() {
+            break;
+        }
+
+        unit.dies.push(Die {
+            offset: die_offset,
+            depth: parents.len(),
+            tag: abbrev.tag,
+            attributes,
+        });
+        if abbrev.has_children {
+            parents.push(unit.dies.len() - 1);
+        } else if parents.is_empty() {
+            break;
+        }
+    }
+
+    if let Some(root) = unit.dies.first().cloned() {
+        let section_offset = |at| match root.attribute(at) {
+            Some(AttrValue::SecOffset(offset)) | Some(AttrValue::Udata(offset)) => Some(*offset),
+            _ => None,
+        };
+        unit.str_offsets_base = section_offset(DW_AT_STR_OFFSETS_BASE);
+        unit.addr_base = section_offset(DW_AT_ADDR_BASE).or(section_offset(DW_AT_GNU_ADDR_BASE));
+        unit.rnglists_base =
+            section_offset(DW_AT_RNGLISTS_BASE).or(section_offset(DW_AT_GNU_RANGES_BASE));
+        unit.loclists_base = section_offset(DW_AT_LOCLISTS_BASE);
+    }
+    // 範囲リストなどの基準アドレス
+    unit.base_address = match unit
+        .dies
+        .first()
+        .and_then(|root| root.attribute(DW_AT_LOW_PC))
+    {
+        Some(AttrValue::Address(addr)) => *addr,
+        Some(AttrValue::AddressIndex(idx)) => indexed_address(sections, &unit, *idx).unwrap_or(0),
+        _ => 0,
+    };
+    unit.line_program = line::parse(sections, &unit);
+
+    Some((unit, end))
+}
+
+fn parse_attr_value(
+    reader: &mut Reader,
+    unit: &Unit,
+    form: u64,
+    implicit: Option<i64>,
+) -> Option<AttrValue> {
+    let offset_size = unit.offset_size();
+    let value = match form {
+        DW_FORM_ADDR => AttrValue::Address(reader.sized(unit.address_size as usize)?),
+        DW_FORM_BLOCK1 => {
+            let len = reader.u8()?;
+            AttrValue::Block(reader.block(len.into())?)
+        }
+        DW_FORM_BLOCK2 => {
+            let len = reader.u16()?;
+            AttrValue::Block(reader.block(len.into())?)
+        }
+        DW_FORM_BLOCK4 => {
+            let len = reader.u32()?;
+            AttrValue::Block(reader.block(len.into())?)
+        }
+        DW_FORM_BLOCK => {
+            let len = reader.uleb()?;
+            AttrValue::Block(reader.block(len)?)
+        }
+        DW_FORM_DATA16 => AttrValue::Block(reader.block(16)?),
+        DW_FORM_EXPRLOC => {
+            let len = reader.uleb()?;
+            AttrValue::Exprloc(reader.block(len)?)
+        }
+        DW_FORM_DATA1 => AttrValue::Udata(reader.u8()?.into()),
+        DW_FORM_DATA2 => AttrValue::Udata(reader.u16()?.into()),
+        DW_FORM_DATA4 => AttrValue::Udata(reader.u32()?.into()),
+        DW_FORM_DATA8 => AttrValue::Udata(reader.u64()?),
+        DW_FORM_UDATA => AttrValue::Udata(reader.uleb()?),
+        DW_FORM_SDATA => AttrValue::Sdata(reader.sleb()?),
+        DW_FORM_IMPLICIT_CONST => AttrValue::Sdata(implicit?),
+        DW_FORM_FLAG => AttrValue::Flag(reader.u8()? != 0),
+        DW_FORM_FLAG_PRESENT => AttrValue::Flag(true),
+        DW_FORM_STRING => AttrValue::String(reader.cstr()?),
+        DW_FORM_STRP => AttrValue::StrOffset(reader.offset(unit.dwarf64)?),
+        DW_FORM_LINE_STRP => AttrValue::LineStrOffset(reader.offset(unit.dwarf64)?),
+        DW_FORM_STRX | DW_FORM_GNU_STR_INDEX => AttrValue::StrIndex(reader.uleb()?),
+        DW_FORM_STRX1 => AttrValue::StrIndex(reader.u8()?.into()),
+        DW_FORM_STRX2 => AttrValue::StrIndex(reader.u16()?.into()),
+        DW_FORM_STRX3 => AttrValue::StrIndex(reader.u24()?.into()),
+        DW_FORM_STRX4 => AttrValue::StrIndex(reader.u32()?.into()),
+        DW_FORM_ADDRX | DW_FORM_GNU_ADDR_INDEX => AttrValue::AddressIndex(reader.uleb()?),
+        DW_FORM_ADDRX1 => AttrValue::AddressIndex(reader.u8()?.into()),
+        DW_FORM_ADDRX2 => AttrValue::AddressIndex(reader.u16()?.into()),
+        DW_FORM_ADDRX3 => AttrValue::AddressIndex(reader.u24()?.into()),
+        DW_FORM_ADDRX4 => AttrValue::AddressIndex(reader.u32()?.into()),
+        DW_FORM_REF1 => AttrValue::Ref(unit.offset + u64::from(reader.u8()?)),
+        DW_FORM_REF2 => AttrValue::Ref(unit.offset + u64::from(reader.u16()?)),
+        DW_FORM_REF4 => AttrValue::Ref(unit.offset + u64::from(reader.u32()?)),
+        DW_FORM_REF8 => AttrValue::Ref(unit.offset + reader.u64()?),
+        DW_FORM_REF_UDATA => AttrValue::Ref(unit.offset + reader.uleb()?),
+        // DWARF 2 ではアドレスの大きさ
+        DW_FORM_REF_ADDR if unit.version <= 2 => {
+            AttrValue::Ref(reader.sized(unit.address_size as usize)?)
+        }
+        DW_FORM_REF_ADDR => AttrValue::Ref(reader.offset(unit.dwarf64)?),
+        DW_FORM_REF_SIG8 => AttrValue::RefSig8(reader.u64()?),
+        DW_FORM_REF_SUP4 => AttrValue::Supplementary(reader.u32()?.into()),
+        DW_FORM_REF_SUP8 => AttrValue::Supplementary(reader.u64()?),
+        DW_FORM_STRP_SUP | DW_FORM_GNU_REF_ALT | DW_FORM_GNU_STRP_ALT => {
+            AttrValue::Supplementary(reader.sized(offset_size)?)
+        }
+        DW_FORM_SEC_OFFSET => AttrValue::SecOffset(reader.offset(unit.dwarf64)?),
+        DW_FORM_LOCLISTX => AttrValue::LocListIndex(reader.uleb()?),
+        DW_FORM_RNGLISTX => AttrValue::RngListIndex(reader.uleb()?),
+        DW_FORM_INDIRECT => {
+            let form = reader.uleb()?;
+            return parse_attr_value(reader, unit, form, implicit);
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn parse_abbrevs(bytes: &[u8], offset: usize) -> BTreeMap<u64, Abbrev> {
+    let mut abbrevs = BTreeMap::new();
+    let mut reader = Reader::new(bytes, offset);
+
+    while let Some(code) = reader.uleb() {
+        if code == 0 {
+            break;
+        }
+        let (tag, has_children) = match (reader.uleb(), reader.u8()) {
+            (Some(tag), Some(children)) => (tag, children != 0),
+            _ => break,
+        };
+
+        let mut specs = Vec::new();
+        while let (Some(name), Some(form)) = (reader.uleb(), reader.uleb()) {
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit = if form == DW_FORM_IMPLICIT_CONST {
+                reader.sleb()
+            } else {
+                None
+            };
+            specs.push((name, form, implicit));
+        }
+
+        abbrevs.insert(
+            code,
+            Abbrev {
+                tag,
+                has_children,
+                specs,
+            },
+        );
+    }
+
+    abbrevs
+}
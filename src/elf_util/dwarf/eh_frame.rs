@@ -0,0 +1,749 @@
+//! .eh_frame の CIE/FDE と .eh_frame_hdr の二分探索表を読み,
+//! 呼び出しフレーム命令を実行してアドレスごとの CFA と各レジスタの復元規則を求める.
+//! 書式は .debug_frame とほぼ同じだが, CIE ID が 0 で, ポインタが DW_EH_PE_* で符号化される.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::Reader;
+use crate::elf_util::relocations;
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+    segment,
+};
+
+pub const DW_EH_PE_OMIT: u8 = 0xff;
+pub const DW_EH_PE_INDIRECT: u8 = 0x80;
+
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+const DW_EH_PE_SLEB128: u8 = 0x09;
+const DW_EH_PE_SDATA2: u8 = 0x0a;
+const DW_EH_PE_SDATA4: u8 = 0x0b;
+const DW_EH_PE_SDATA8: u8 = 0x0c;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+const DW_EH_PE_ALIGNED: u8 = 0x50;
+
+#[derive(Debug, Clone)]
+pub struct Cie {
+    /// .eh_frame 先頭からのオフセット
+    pub offset: u64,
+    pub version: u8,
+    pub augmentation: String,
+    pub code_align: u64,
+    pub data_align: i64,
+    pub return_register: u64,
+    pub fde_encoding: u8,
+    pub lsda_encoding: u8,
+    /// (符号化, パーソナリティルーチンのアドレス)
+    pub personality: Option<(u8, u64)>,
+    /// 'S': シグナルハンドラのフレーム
+    pub signal_frame: bool,
+    /// 命令列の .eh_frame 先頭からのオフセット. DW_CFA_set_loc の PC 相対の基準になる
+    pub instructions_offset: u64,
+    pub instructions: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fde {
+    pub offset: u64,
+    /// cies の添字
+    pub cie: usize,
+    pub pc_begin: u64,
+    pub pc_range: u64,
+    /// ET_REL では pc_begin は再配置先セクション内のオフセットになる
+    pub pc_section: Option<usize>,
+    pub lsda: Option<u64>,
+    pub instructions_offset: u64,
+    pub instructions: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EhFrameHdr {
+    pub address: u64,
+    pub version: u8,
+    pub eh_frame_ptr_encoding: u8,
+    pub fde_count_encoding: u8,
+    pub table_encoding: u8,
+    pub eh_frame_ptr: Option<u64>,
+    /// (関数の先頭アドレス, FDE のアドレス)
+    pub table: Vec<(u64, u64)>,
+}
+
+pub struct EhFrame {
+    /// .eh_frame のセクション. セクションヘッダが無ければ .eh_frame_hdr からたどる
+    pub section: Option<usize>,
+    pub address: u64,
+    pub cies: Vec<Cie>,
+    pub fdes: Vec<Fde>,
+    pub hdr: Option<EhFrameHdr>,
+}
+
+/// 一覧に並べる項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameEntry {
+    Header,
+    Cie(usize),
+    Fde(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfaRule {
+    /// レジスタ + オフセット
+    RegisterOffset(u64, i64),
+    Expression(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterRule {
+    Undefined,
+    SameValue,
+    /// CFA + n に保存されている
+    Offset(i64),
+    /// 値が CFA + n
+    ValOffset(i64),
+    Register(u64),
+    Expression(Vec<u8>),
+    ValExpression(Vec<u8>),
+}
+
+/// ある番地から次の行の番地までの規則
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CfaRow {
+    pub address: u64,
+    pub cfa: CfaRule,
+    pub registers: BTreeMap<u64, RegisterRule>,
+}
+
+/// 符号化されたポインタを読むときの基準
+struct Bases {
+    /// 読んでいるデータ先頭のアドレス (DW_EH_PE_pcrel)
+    address: u64,
+    /// DW_EH_PE_datarel の基準. .eh_frame_hdr では自身の先頭
+    data: u64,
+}
+
+impl EhFrame {
+    pub fn parse(elf_file: &file::ELF64, bytes: &[u8]) -> Option<Self> {
+        let hdr = hdr_region(elf_file, bytes).and_then(|(address, data)| parse_hdr(address, data));
+
+        let eh_frame = elf_file.sections.iter().enumerate().find(|(_, sct)| {
+            sct.name == ".eh_frame" && sct.header.get_type() != section::Type::NoBits
+        });
+        let (section, address, data) = match eh_frame {
+            Some((idx, sct)) => {
+                let start = sct.header.sh_offset as usize;
+                let data = bytes.get(start..start.checked_add(sct.header.sh_size as usize)?)?;
+                (Some(idx), sct.header.sh_addr, data)
+            }
+            None => {
+                let address = hdr.as_ref()?.eh_frame_ptr?;
+                (None, address, segment_data(elf_file, bytes, address)?)
+            }
+        };
+
+        // ET_REL では pc_begin などが PC 相対の再配置 (S + A - P) で埋まる
+        let relocated: HashMap<u64, (usize, u64)> = match (section, elf_file.ehdr.get_type()) {
+            (Some(idx), header::Type::Rel) => relocations::relocations(elf_file)
+                .into_iter()
+                .filter(|reloc| elf_file.sections[reloc.section].header.sh_info as usize == idx)
+                .filter_map(|reloc| {
+                    let symtab = elf_file
+                        .sections
+                        .get(elf_file.sections[reloc.section].header.sh_link as usize)?;
+                    let sym = match &symtab.contents {
+                        Contents64::Symbols(symbols) => symbols.get(reloc.r_sym as usize)?,
+                        _ => return None,
+                    };
+                    let value = sym
+                        .st_value
+                        .wrapping_add(reloc.r_addend.unwrap_or(0) as u64);
+                    Some((reloc.r_offset, (sym.st_shndx as usize, value)))
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        let mut eh_frame = Self {
+            section,
+            address,
+            cies: Vec::new(),
+            fdes: Vec::new(),
+            hdr,
+        };
+        let bases = Bases { address, data: 0 };
+
+        // FDE は手前の CIE を指すので, 先に CIE を全て読んでおく
+        let records = records(data);
+        let mut cie_offsets = HashMap::new();
+        for (offset, id_pos, dwarf64, end) in records.iter().copied() {
+            let mut reader = Reader::new(&data[..end], id_pos);
+            if reader.offset(dwarf64)? != 0 {
+                continue;
+            }
+            if let Some(cie) = parse_cie(&mut reader, offset as u64, &bases) {
+                cie_offsets.insert(offset as u64, eh_frame.cies.len());
+                eh_frame.cies.push(cie);
+            }
+        }
+        for (offset, id_pos, dwarf64, end) in records.iter().copied() {
+            let mut reader = Reader::new(&data[..end], id_pos);
+            let id = reader.offset(dwarf64)?;
+            if id == 0 {
+                continue;
+            }
+            let cie = match (id_pos as u64)
+                .checked_sub(id)
+                .and_then(|cie_offset| cie_offsets.get(&cie_offset))
+            {
+                Some(cie) => *cie,
+                None => continue,
+            };
+            if let Some(fde) = parse_fde(
+                &mut reader,
+                offset as u64,
+                cie,
+                &eh_frame.cies[cie],
+                &bases,
+                &relocated,
+            ) {
+                eh_frame.fdes.push(fde);
+            }
+        }
+
+        if eh_frame.cies.is_empty() && eh_frame.hdr.is_none() {
+            return None;
+        }
+        Some(eh_frame)
+    }
+
+    /// .eh_frame_hdr, CIE, FDE を .eh_frame 上の順に並べる.
+    pub fn entries(&self) -> Vec<FrameEntry> {
+        let mut entries: Vec<(u64, FrameEntry)> = self
+            .cies
+            .iter()
+            .enumerate()
+            .map(|(idx, cie)| (cie.offset, FrameEntry::Cie(idx)))
+            .chain(
+                self.fdes
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, fde)| (fde.offset, FrameEntry::Fde(idx))),
+            )
+            .collect();
+        entries.sort_by_key(|(offset, _)| *offset);
+
+        let header = self.hdr.as_ref().map(|_| FrameEntry::Header);
+        header
+            .into_iter()
+            .chain(entries.into_iter().map(|(_, entry)| entry))
+            .collect()
+    }
+
+    /// .eh_frame_hdr の表でこの FDE を指す項目の添字
+    pub fn hdr_index(&self, fde: &Fde) -> Option<usize> {
+        let address = self.address + fde.offset;
+        self.hdr
+            .as_ref()?
+            .table
+            .iter()
+            .position(|(_, fde_address)| *fde_address == address)
+    }
+
+    /// CIE の初期命令のあとに FDE の命令を実行し, アドレスごとの規則を並べる.
+    pub fn cfa_rows(&self, fde: &Fde) -> Vec<CfaRow> {
+        let cie = &self.cies[fde.cie];
+        let initial = execute(
+            cie,
+            &cie.instructions,
+            self.bases(cie.instructions_offset),
+            fde.pc_begin,
+            None,
+        );
+        let initial = match initial.last() {
+            Some(row) => row.clone(),
+            None => return Vec::new(),
+        };
+
+        execute(
+            cie,
+            &fde.instructions,
+            self.bases(fde.instructions_offset),
+            fde.pc_begin,
+            Some(&initial),
+        )
+        .into_iter()
+        .filter(|row| row.address < fde.pc_begin.wrapping_add(fde.pc_range))
+        .collect()
+    }
+
+    /// CIE の初期命令だけを実行した規則
+    pub fn initial_row(&self, cie: &Cie) -> Option<CfaRow> {
+        execute(
+            cie,
+            &cie.instructions,
+            self.bases(cie.instructions_offset),
+            0,
+            None,
+        )
+        .pop()
+    }
+
+    /// .eh_frame 先頭から offset の位置を基準にする
+    fn bases(&self, offset: u64) -> Bases {
+        Bases {
+            address: self.address.wrapping_add(offset),
+            data: 0,
+        }
+    }
+}
+
+/// (レコード先頭, CIE ID の位置, 64bit か, レコードの終端) を並べる. 長さ 0 は終端.
+fn records(data: &[u8]) -> Vec<(usize, usize, bool, usize)> {
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos + 4 <= data.len() {
+        let mut reader = Reader::new(data, pos);
+        let (dwarf64, end) = match reader.initial_length() {
+            Some(length) => length,
+            None => break,
+        };
+        if end == reader.pos || end > data.len() {
+            break;
+        }
+        records.push((pos, reader.pos, dwarf64, end));
+        pos = end;
+    }
+    records
+}
+
+fn parse_cie(reader: &mut Reader, offset: u64, bases: &Bases) -> Option<Cie> {
+    let version = reader.u8()?;
+    let augmentation = reader.cstr()?;
+    if augmentation.contains("eh") {
+        reader.u64()?;
+    }
+    if version >= 4 {
+        // address_size, segment_selector_size
+        reader.u8()?;
+        reader.u8()?;
+    }
+    let code_align = reader.uleb()?;
+    let data_align = reader.sleb()?;
+    let return_register = if version == 1 {
+        u64::from(reader.u8()?)
+    } else {
+        reader.uleb()?
+    };
+
+    let mut cie = Cie {
+        offset,
+        version,
+        augmentation: augmentation.clone(),
+        code_align,
+        data_align,
+        return_register,
+        fde_encoding: DW_EH_PE_ABSPTR,
+        lsda_encoding: DW_EH_PE_OMIT,
+        personality: None,
+        signal_frame: false,
+        instructions_offset: 0,
+        instructions: Vec::new(),
+    };
+    if augmentation.starts_with('z') {
+        let len = reader.uleb()?;
+        let end = reader.pos.checked_add(len as usize)?;
+        for c in augmentation.chars().skip(1) {
+            match c {
+                'L' => cie.lsda_encoding = reader.u8()?,
+                'P' => {
+                    let encoding = reader.u8()?;
+                    cie.personality = Some((encoding, read_pointer(reader, encoding, bases)?));
+                }
+                'R' => cie.fde_encoding = reader.u8()?,
+                'S' => cie.signal_frame = true,
+                'B' => {}
+                _ => break,
+            }
+        }
+        reader.pos = end;
+    }
+    cie.instructions_offset = reader.pos as u64;
+    cie.instructions = reader.bytes.get(reader.pos..)?.to_vec();
+
+    Some(cie)
+}
+
+fn parse_fde(
+    reader: &mut Reader,
+    offset: u64,
+    cie_idx: usize,
+    cie: &Cie,
+    bases: &Bases,
+    relocated: &HashMap<u64, (usize, u64)>,
+) -> Option<Fde> {
+    let pc_pos = reader.pos as u64;
+    let mut pc_begin = read_pointer(reader, cie.fde_encoding, bases)?;
+    let mut pc_section = None;
+    if let Some((sct, value)) = relocated.get(&pc_pos) {
+        pc_begin = *value;
+        pc_section = Some(*sct);
+    }
+    // pc_range は値の形式だけを使い, 相対指定は無視する
+    let pc_range = read_pointer(reader, cie.fde_encoding & 0x0f, bases)?;
+
+    let mut lsda = None;
+    if cie.augmentation.starts_with('z') {
+        let len = reader.uleb()?;
+        let end = reader.pos.checked_add(len as usize)?;
+        if cie.lsda_encoding != DW_EH_PE_OMIT && len != 0 {
+            let lsda_pos = reader.pos as u64;
+            lsda = read_pointer(reader, cie.lsda_encoding, bases)
+                .map(|lsda| relocated.get(&lsda_pos).map_or(lsda, |(_, value)| *value))
+                .filter(|lsda| *lsda != 0);
+        }
+        reader.pos = end;
+    }
+
+    Some(Fde {
+        offset,
+        cie: cie_idx,
+        pc_begin,
+        pc_range,
+        pc_section,
+        lsda,
+        instructions_offset: reader.pos as u64,
+        instructions: reader.bytes.get(reader.pos..)?.to_vec(),
+    })
+}
+
+fn read_pointer(reader: &mut Reader, encoding: u8, bases: &Bases) -> Option<u64> {
+    if encoding == DW_EH_PE_OMIT {
+        return None;
+    }
+    if encoding & 0x70 == DW_EH_PE_ALIGNED {
+        reader.pos = (reader.pos + 7) & !7;
+    }
+    let field = bases.address.wrapping_add(reader.pos as u64);
+    let value = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 | DW_EH_PE_SDATA8 => reader.u64()?,
+        DW_EH_PE_ULEB128 => reader.uleb()?,
+        DW_EH_PE_UDATA2 => u64::from(reader.u16()?),
+        DW_EH_PE_UDATA4 => u64::from(reader.u32()?),
+        DW_EH_PE_SLEB128 => reader.sleb()? as u64,
+        DW_EH_PE_SDATA2 => reader.u16()? as i16 as u64,
+        DW_EH_PE_SDATA4 => reader.u32()? as i32 as u64,
+        _ => return None,
+    };
+
+    Some(match encoding & 0x70 {
+        DW_EH_PE_PCREL => field.wrapping_add(value),
+        DW_EH_PE_DATAREL => bases.data.wrapping_add(value),
+        _ => value,
+    })
+}
+
+/// .eh_frame_hdr の中身. セクションが無ければ PT_GNU_EH_FRAME から探す.
+fn hdr_region<'a>(elf_file: &file::ELF64, bytes: &'a [u8]) -> Option<(u64, &'a [u8])> {
+    if let Some(sct) = elf_file
+        .sections
+        .iter()
+        .find(|sct| sct.name == ".eh_frame_hdr")
+    {
+        let start = sct.header.sh_offset as usize;
+        let data = bytes.get(start..start.checked_add(sct.header.sh_size as usize)?)?;
+        return Some((sct.header.sh_addr, data));
+    }
+
+    let seg = elf_file
+        .segments
+        .iter()
+        .find(|seg| seg.header.get_type() == segment::Type::GNUEHFrame)?;
+    let start = seg.header.p_offset as usize;
+    let data = bytes.get(start..start.checked_add(seg.header.p_filesz as usize)?)?;
+    Some((seg.header.p_vaddr, data))
+}
+
+fn parse_hdr(address: u64, data: &[u8]) -> Option<EhFrameHdr> {
+    let mut reader = Reader::new(data, 0);
+    let bases = Bases {
+        address,
+        data: address,
+    };
+    let version = reader.u8()?;
+    let eh_frame_ptr_encoding = reader.u8()?;
+    let fde_count_encoding = reader.u8()?;
+    let table_encoding = reader.u8()?;
+    let eh_frame_ptr = read_pointer(&mut reader, eh_frame_ptr_encoding, &bases);
+    let fde_count = read_pointer(&mut reader, fde_count_encoding, &bases).unwrap_or(0);
+
+    let mut table = Vec::new();
+    if table_encoding != DW_EH_PE_OMIT {
+        for _ in 0..fde_count {
+            let initial_location = match read_pointer(&mut reader, table_encoding, &bases) {
+                Some(location) => location,
+                None => break,
+            };
+            match read_pointer(&mut reader, table_encoding, &bases) {
+                Some(fde_address) => table.push((initial_location, fde_address)),
+                None => break,
+            }
+        }
+    }
+
+    Some(EhFrameHdr {
+        address,
+        version,
+        eh_frame_ptr_encoding,
+        fde_count_encoding,
+        table_encoding,
+        eh_frame_ptr,
+        table,
+    })
+}
+
+/// address を含む LOAD セグメントの, address から末尾までの中身
+fn segment_data<'a>(elf_file: &file::ELF64, bytes: &'a [u8], address: u64) -> Option<&'a [u8]> {
+    let seg = elf_file.segments.iter().find(|seg| {
+        seg.header.get_type() == segment::Type::Load
+            && seg.header.p_vaddr <= address
+            && seg
+                .header
+                .p_vaddr
+                .checked_add(seg.header.p_filesz)
+                .is_some_and(|end| address < end)
+    })?;
+    let start = seg
+        .header
+        .p_offset
+        .checked_add(address - seg.header.p_vaddr)? as usize;
+    let end = seg.header.p_offset.checked_add(seg.header.p_filesz)? as usize;
+    bytes.get(start..end)
+}
+
+/// 呼び出しフレーム命令を実行する. 番地が進むたびにそれまでの規則を1行として出す.
+/// initial は DW_CFA_restore で戻す CIE の規則.
+fn execute(
+    cie: &Cie,
+    instructions: &[u8],
+    bases: Bases,
+    start: u64,
+    initial: Option<&CfaRow>,
+) -> Vec<CfaRow> {
+    let mut row = initial.cloned().unwrap_or(CfaRow {
+        address: start,
+        cfa: CfaRule::RegisterOffset(0, 0),
+        registers: BTreeMap::new(),
+    });
+    row.address = start;
+    let mut rows = Vec::new();
+    let mut stack = Vec::new();
+    let mut reader = Reader::new(instructions, 0);
+
+    while let Some(op) = reader.u8() {
+        // DW_CFA_set_loc のアドレスは FDE の pc_begin と同じ符号化で読む
+        if op == 0x01 {
+            match read_pointer(&mut reader, cie.fde_encoding, &bases) {
+                Some(address) => advance(&mut row, &mut rows, address),
+                None => break,
+            }
+            continue;
+        }
+        // 解釈できない命令があればそこで止める
+        if execute_instruction(
+            &mut reader,
+            op,
+            cie,
+            initial,
+            &mut row,
+            &mut rows,
+            &mut stack,
+        )
+        .is_none()
+        {
+            break;
+        }
+    }
+
+    rows.push(row);
+    rows
+}
+
+fn execute_instruction(
+    reader: &mut Reader,
+    op: u8,
+    cie: &Cie,
+    initial: Option<&CfaRow>,
+    row: &mut CfaRow,
+    rows: &mut Vec<CfaRow>,
+    stack: &mut Vec<CfaRow>,
+) -> Option<()> {
+    let data_align = |n: i64| n.wrapping_mul(cie.data_align);
+
+    match (op >> 6, op & 0x3f) {
+        // DW_CFA_advance_loc
+        (1, delta) => {
+            let address = row
+                .address
+                .wrapping_add(u64::from(delta).wrapping_mul(cie.code_align));
+            advance(row, rows, address);
+        }
+        // DW_CFA_offset
+        (2, reg) => {
+            let offset = data_align(reader.uleb()? as i64);
+            row.registers
+                .insert(u64::from(reg), RegisterRule::Offset(offset));
+        }
+        // DW_CFA_restore
+        (3, reg) => restore(row, initial, u64::from(reg)),
+        // DW_CFA_nop
+        (_, 0x00) => {}
+        // DW_CFA_advance_loc1, advance_loc2, advance_loc4
+        (_, 0x02) | (_, 0x03) | (_, 0x04) => {
+            let delta = match op {
+                0x02 => u64::from(reader.u8()?),
+                0x03 => u64::from(reader.u16()?),
+                _ => u64::from(reader.u32()?),
+            };
+            let address = row.address.wrapping_add(delta.wrapping_mul(cie.code_align));
+            advance(row, rows, address);
+        }
+        // DW_CFA_offset_extended
+        (_, 0x05) => {
+            let reg = reader.uleb()?;
+            let offset = data_align(reader.uleb()? as i64);
+            row.registers.insert(reg, RegisterRule::Offset(offset));
+        }
+        // DW_CFA_restore_extended
+        (_, 0x06) => {
+            let reg = reader.uleb()?;
+            restore(row, initial, reg);
+        }
+        // DW_CFA_undefined
+        (_, 0x07) => {
+            row.registers
+                .insert(reader.uleb()?, RegisterRule::Undefined);
+        }
+        // DW_CFA_same_value
+        (_, 0x08) => {
+            row.registers
+                .insert(reader.uleb()?, RegisterRule::SameValue);
+        }
+        // DW_CFA_register
+        (_, 0x09) => {
+            let reg = reader.uleb()?;
+            row.registers
+                .insert(reg, RegisterRule::Register(reader.uleb()?));
+        }
+        // DW_CFA_remember_state
+        (_, 0x0a) => stack.push(row.clone()),
+        // DW_CFA_restore_state
+        (_, 0x0b) => {
+            let address = row.address;
+            *row = stack.pop()?;
+            row.address = address;
+        }
+        // DW_CFA_def_cfa
+        (_, 0x0c) => {
+            let reg = reader.uleb()?;
+            row.cfa = CfaRule::RegisterOffset(reg, reader.uleb()? as i64);
+        }
+        // DW_CFA_def_cfa_register
+        (_, 0x0d) => {
+            let reg = reader.uleb()?;
+            row.cfa = match row.cfa {
+                CfaRule::RegisterOffset(_, offset) => CfaRule::RegisterOffset(reg, offset),
+                CfaRule::Expression(_) => CfaRule::RegisterOffset(reg, 0),
+            };
+        }
+        // DW_CFA_def_cfa_offset
+        (_, 0x0e) => {
+            let offset = reader.uleb()? as i64;
+            if let CfaRule::RegisterOffset(reg, _) = row.cfa {
+                row.cfa = CfaRule::RegisterOffset(reg, offset);
+            }
+        }
+        // DW_CFA_def_cfa_expression
+        (_, 0x0f) => {
+            let len = reader.uleb()?;
+            row.cfa = CfaRule::Expression(reader.block(len)?);
+        }
+        // DW_CFA_expression, val_expression
+        (_, 0x10) | (_, 0x16) => {
+            let reg = reader.uleb()?;
+            let len = reader.uleb()?;
+            let expression = reader.block(len)?;
+            row.registers.insert(
+                reg,
+                if op == 0x10 {
+                    RegisterRule::Expression(expression)
+                } else {
+                    RegisterRule::ValExpression(expression)
+                },
+            );
+        }
+        // DW_CFA_offset_extended_sf
+        (_, 0x11) => {
+            let reg = reader.uleb()?;
+            let offset = data_align(reader.sleb()?);
+            row.registers.insert(reg, RegisterRule::Offset(offset));
+        }
+        // DW_CFA_def_cfa_sf
+        (_, 0x12) => {
+            let reg = reader.uleb()?;
+            row.cfa = CfaRule::RegisterOffset(reg, data_align(reader.sleb()?));
+        }
+        // DW_CFA_def_cfa_offset_sf
+        (_, 0x13) => {
+            let offset = data_align(reader.sleb()?);
+            if let CfaRule::RegisterOffset(reg, _) = row.cfa {
+                row.cfa = CfaRule::RegisterOffset(reg, offset);
+            }
+        }
+        // DW_CFA_val_offset, val_offset_sf
+        (_, 0x14) | (_, 0x15) => {
+            let reg = reader.uleb()?;
+            let offset = if op == 0x14 {
+                data_align(reader.uleb()? as i64)
+            } else {
+                data_align(reader.sleb()?)
+            };
+            row.registers.insert(reg, RegisterRule::ValOffset(offset));
+        }
+        // DW_CFA_GNU_args_size
+        (_, 0x2e) => {
+            reader.uleb()?;
+        }
+        // DW_CFA_GNU_negative_offset_extended
+        (_, 0x2f) => {
+            let reg = reader.uleb()?;
+            let offset = data_align((reader.uleb()? as i64).checked_neg()?);
+            row.registers.insert(reg, RegisterRule::Offset(offset));
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+fn advance(row: &mut CfaRow, rows: &mut Vec<CfaRow>, address: u64) {
+    if address != row.address {
+        rows.push(row.clone());
+        row.address = address;
+    }
+}
+
+fn restore(row: &mut CfaRow, initial: Option<&CfaRow>, reg: u64) {
+    match initial.and_then(|initial| initial.registers.get(&reg)) {
+        Some(rule) => {
+            row.registers.insert(reg, rule.clone());
+        }
+        None => {
+            row.registers.remove(&reg);
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! .debug_line の行番号プログラムを実行し, アドレスとソース上の位置の対応表を作る.
+//! DW_TAG_inlined_subroutine をたどり, addr2line -i と同様にインライン展開された呼び出しも求める.
+
+use super::{
+    AttrValue, DebugSections, Die, Dwarf, Reader, Unit, DW_AT_HIGH_PC, DW_AT_LOW_PC, DW_AT_RANGES,
+    DW_FORM_BLOCK, DW_FORM_DATA1, DW_FORM_DATA16, DW_FORM_DATA2, DW_FORM_DATA4, DW_FORM_DATA8,
+    DW_FORM_LINE_STRP, DW_FORM_STRING, DW_FORM_STRP, DW_FORM_STRX, DW_FORM_STRX1, DW_FORM_STRX2,
+    DW_FORM_STRX3, DW_FORM_STRX4, DW_FORM_UDATA, DW_UT_SPLIT_COMPILE,
+};
+
+pub const DW_TAG_INLINED_SUBROUTINE: u64 = 0x1d;
+pub const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+
+pub const DW_AT_STMT_LIST: u64 = 0x10;
+pub const DW_AT_COMP_DIR: u64 = 0x1b;
+pub const DW_AT_DECL_FILE: u64 = 0x3a;
+pub const DW_AT_CALL_COLUMN: u64 = 0x57;
+pub const DW_AT_CALL_FILE: u64 = 0x58;
+pub const DW_AT_CALL_LINE: u64 = 0x59;
+
+const DW_LNCT_PATH: u64 = 0x1;
+const DW_LNCT_DIRECTORY_INDEX: u64 = 0x2;
+
+/// 行番号表の1行
+#[derive(Debug, Clone, Copy)]
+pub struct LineRow {
+    pub address: u64,
+    pub file: u64,
+    pub line: u64,
+    pub column: u64,
+    /// 直前の行までで連続したアドレス範囲(シーケンス)が終わる
+    pub end_sequence: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LineProgram {
+    pub version: u16,
+    /// ディレクトリを補ったファイル名. DWARF 4 以前は 1 番から数える
+    pub files: Vec<String>,
+    pub rows: Vec<LineRow>,
+    /// 各行が受け持つアドレス範囲と rows の添字
+    row_ranges: RangeIndex,
+}
+
+/// 重なりうるアドレス範囲 [start, end) を開始アドレス順に並べ, 二分探索で引く
+#[derive(Debug, Clone, Default)]
+pub struct RangeIndex {
+    /// (開始, 終わり, 添字, ここまでの終わりの最大値)
+    ranges: Vec<(u64, u64, usize, u64)>,
+}
+
+impl RangeIndex {
+    pub fn new(mut ranges: Vec<(u64, u64, usize)>) -> Self {
+        ranges.sort_by_key(|(start, _, idx)| (*start, *idx));
+        let mut max_end = 0;
+        Self {
+            ranges: ranges
+                .into_iter()
+                .map(|(start, end, idx)| {
+                    max_end = max_end.max(end);
+                    (start, end, idx, max_end)
+                })
+                .collect(),
+        }
+    }
+
+    /// addr を含む範囲のうち, 最も小さい添字
+    pub fn find(&self, addr: u64) -> Option<usize> {
+        let count = self.ranges.partition_point(|(start, ..)| *start <= addr);
+        // 手前の範囲がまだ addr に届いている間だけさかのぼる
+        self.ranges[..count]
+            .iter()
+            .rev()
+            .take_while(|(.., max_end)| addr < *max_end)
+            .filter(|(_, end, ..)| addr < *end)
+            .map(|(_, _, idx, _)| *idx)
+            .min()
+    }
+}
+
+impl LineProgram {
+    /// 行番号表や DW_AT_decl_file のファイル番号からファイル名を引く.
+    pub fn file(&self, idx: u64) -> Option<&str> {
+        let idx = if self.version >= 5 {
+            idx
+        } else {
+            idx.checked_sub(1)?
+        };
+        self.files.get(idx as usize).map(|file| file.as_str())
+    }
+
+    /// addr を含む行. シーケンスの終端を指す行は除く.
+    pub fn find_row(&self, addr: u64) -> Option<&LineRow> {
+        self.row_ranges.find(addr).map(|idx| &self.rows[idx])
+    }
+
+    /// 各シーケンスの [先頭の行のアドレス, 終端のアドレス)
+    fn sequences(&self) -> Vec<(u64, u64)> {
+        let mut sequences = Vec::new();
+        let mut start = None;
+        for row in self.rows.iter() {
+            if row.end_sequence {
+                if let Some(start) = start.take().filter(|start| *start < row.address) {
+                    sequences.push((start, row.address));
+                }
+            } else if start.is_none() {
+                start = Some(row.address);
+            }
+        }
+        sequences
+    }
+}
+
+/// 行番号表を持つユニットを, そのシーケンスのアドレス範囲から引く索引
+pub fn unit_ranges(units: &[Unit]) -> RangeIndex {
+    RangeIndex::new(
+        units
+            .iter()
+            .enumerate()
+            .filter_map(|(unit_idx, unit)| Some((unit_idx, unit.line_program.as_ref()?)))
+            .flat_map(|(unit_idx, program)| {
+                program
+                    .sequences()
+                    .into_iter()
+                    .map(move |(start, end)| (start, end, unit_idx))
+            })
+            .collect(),
+    )
+}
+
+fn row_ranges(rows: &[LineRow]) -> RangeIndex {
+    RangeIndex::new(
+        rows.windows(2)
+            .enumerate()
+            .filter(|(_, pair)| !pair[0].end_sequence && pair[0].address < pair[1].address)
+            .map(|(idx, pair)| (pair[0].address, pair[1].address, idx))
+            .collect(),
+    )
+}
+
+/// アドレスに対応するソース上の位置. インライン展開された関数ごとに1つ作る.
+#[derive(Debug, Clone)]
+pub struct SourceFrame {
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: u64,
+    pub column: u64,
+}
+
+impl Dwarf {
+    /// addr を含む行番号表を持つユニットのインデックス
+    pub fn unit_for_address(&self, addr: u64) -> Option<usize> {
+        self.unit_ranges.find(addr)
+    }
+
+    /// addr2line -i と同様に, 最も内側のインライン展開から順にソース上の位置を求める.
+    pub fn source_frames(&self, addr: u64) -> Vec<SourceFrame> {
+        let unit = match self.unit_for_address(addr) {
+            Some(unit_idx) => &self.units[unit_idx],
+            None => return Vec::new(),
+        };
+        let program = unit.line_program.as_ref().unwrap();
+        let row = program.find_row(addr).unwrap();
+        let chain = self.inline_chain(unit, addr);
+
+        let mut frames = Vec::new();
+        let (mut file, mut line, mut column) = (
+            program.file(row.file).map(String::from),
+            row.line,
+            row.column,
+        );
+        for die_idx in chain.iter().rev() {
+            let die = &unit.dies[*die_idx];
+            frames.push(SourceFrame {
+                function: self.die_name(unit, die),
+                file: file.clone(),
+                line,
+                column,
+            });
+            // 呼び出し元の位置は, インライン展開された DIE の DW_AT_call_* にある
+            let call = |at| die.attribute(at).and_then(AttrValue::constant).unwrap_or(0) as u64;
+            file = program.file(call(DW_AT_CALL_FILE)).map(String::from);
+            line = call(DW_AT_CALL_LINE);
+            column = call(DW_AT_CALL_COLUMN);
+        }
+        if frames.is_empty() {
+            frames.push(SourceFrame {
+                function: None,
+                file,
+                line,
+                column,
+            });
+        }
+
+        frames
+    }
+
+    /// [begin, end) の機械語に対応するファイルと行の範囲. 先頭アドレスの行と同じファイルの行だけを数える.
+    pub fn source_range(&self, begin: u64, end: u64) -> Option<(String, u64, u64)> {
+        let unit = &self.units[self.unit_for_address(begin)?];
+        let program = unit.line_program.as_ref()?;
+        let file = program.find_row(begin)?.file;
+
+        let lines = program
+            .rows
+            .iter()
+            .filter(|row| {
+                !row.end_sequence
+                    && row.file == file
+                    && row.line != 0
+                    && begin <= row.address
+                    && row.address < end.max(begin + 1)
+            })
+            .map(|row| row.line);
+        let (min, max) = lines.fold((u64::MAX, 0), |(min, max), line| {
+            (min.min(line), max.max(line))
+        });
+        if min > max {
+            return None;
+        }
+        Some((program.file(file)?.to_string(), min, max))
+    }
+
+    /// DW_AT_low_pc/high_pc か DW_AT_ranges が表すアドレス範囲
+    pub fn pc_ranges(&self, unit: &Unit, die: &Die) -> Vec<(u64, u64)> {
+        if let Some(ranges) = die.attribute(DW_AT_RANGES) {
+            return self.ranges(unit, ranges);
+        }
+        let low = match die
+            .attribute(DW_AT_LOW_PC)
+            .and_then(|low| self.address(unit, low))
+        {
+            Some(low) => low,
+            None => return Vec::new(),
+        };
+        // DWARF 4 以降の DW_AT_high_pc は定数なら low_pc からの長さ
+        match die.attribute(DW_AT_HIGH_PC) {
+            Some(AttrValue::Udata(len)) => vec![(low, low.wrapping_add(*len))],
+            Some(high) => self
+                .address(unit, high)
+                .map(|high| vec![(low, high)])
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    /// addr を含む関数と, その中で addr を含むインライン展開の DIE を外側から並べる.
+    fn inline_chain(&self, unit: &Unit, addr: u64) -> Vec<usize> {
+        let contains = |die: &Die| {
+            self.pc_ranges(unit, die)
+                .iter()
+                .any(|(begin, end)| *begin <= addr && addr < *end)
+        };
+
+        let subprogram = match unit
+            .dies
+            .iter()
+            .position(|die| die.tag == DW_TAG_SUBPROGRAM && contains(die))
+        {
+            Some(idx) => idx,
+            None => return Vec::new(),
+        };
+
+        // 行きがけ順なので, 内側のインライン展開ほど後に現れる
+        let depth = unit.dies[subprogram].depth;
+        let mut chain = vec![subprogram];
+        chain.extend(
+            unit.dies[subprogram + 1..]
+                .iter()
+                .enumerate()
+                .take_while(|(_, die)| die.depth > depth)
+                .filter(|(_, die)| die.tag == DW_TAG_INLINED_SUBROUTINE && contains(die))
+                .map(|(idx, _)| subprogram + 1 + idx),
+        );
+        chain
+    }
+}
+
+/// ユニットの DW_AT_stmt_list が指す行番号プログラムを読んで実行する.
+pub(super) fn parse(sections: &DebugSections, unit: &Unit) -> Option<LineProgram> {
+    let root = unit.dies.first()?;
+    let offset = match root.attribute(DW_AT_STMT_LIST) {
+        Some(AttrValue::SecOffset(offset)) | Some(AttrValue::Udata(offset)) => *offset,
+        // 分割ユニットはファイル名だけを持つ .debug_line.dwo の先頭を使う
+        _ if unit.unit_type == DW_UT_SPLIT_COMPILE => 0,
+        _ => return None,
+    };
+    let comp_dir = root
+        .attribute(DW_AT_COMP_DIR)
+        .and_then(|dir| super::string(sections, unit, dir));
+
+    let mut reader = Reader::new(sections.get(".debug_line"), offset as usize);
+    let (dwarf64, end) = reader.initial_length()?;
+    let version = reader.u16()?;
+    let mut address_size = unit.address_size;
+    if version >= 5 {
+        address_size = reader.u8()?;
+        // segment_selector_size
+        reader.u8()?;
+    }
+    let header_length = reader.offset(dwarf64)?;
+    let program_start = reader.pos.checked_add(header_length as usize)?;
+    let min_inst_length = reader.u8()?;
+    if version >= 4 {
+        // maximum_operations_per_instruction. VLIW 向けなので無視する
+        reader.u8()?;
+    }
+    // default_is_stmt
+    reader.u8()?;
+    let line_base = reader.u8()? as i8;
+    let line_range = reader.u8()?;
+    let opcode_base = reader.u8()?;
+    let mut standard_opcode_lengths = Vec::new();
+    for _ in 1..opcode_base {
+        standard_opcode_lengths.push(reader.u8()?);
+    }
+
+    let files = if version >= 5 {
+        let context = (sections, unit, dwarf64);
+        let directories = entries(&mut reader, context)?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        entries(&mut reader, context)?
+            .into_iter()
+            .map(|(path, dir)| join_path(directories.get(dir as usize), &path))
+            .collect()
+    } else {
+        let mut directories = vec![comp_dir.clone().unwrap_or_default()];
+        while let Some(dir) = reader.cstr().filter(|dir| !dir.is_empty()) {
+            directories.push(join_path(comp_dir.as_ref(), &dir));
+        }
+        let mut files = Vec::new();
+        while let Some(path) = reader.cstr().filter(|path| !path.is_empty()) {
+            let dir = reader.uleb()?;
+            // 更新時刻とファイルサイズ
+            reader.uleb()?;
+            reader.uleb()?;
+            files.push(join_path(directories.get(dir as usize), &path));
+        }
+        files
+    };
+
+    let mut program = LineProgram {
+        version,
+        files,
+        rows: Vec::new(),
+        row_ranges: RangeIndex::default(),
+    };
+    if line_range == 0 {
+        return Some(program);
+    }
+
+    let bytes = reader.bytes.get(..end.min(reader.bytes.len()))?;
+    let mut reader = Reader::new(bytes, program_start);
+    let initial = LineRow {
+        address: 0,
+        file: 1,
+        line: 1,
+        column: 0,
+        end_sequence: false,
+    };
+    let mut row = initial;
+    let min_inst_length = u64::from(min_inst_length);
+
+    while let Some(opcode) = reader.u8() {
+        if opcode >= opcode_base {
+            let adjusted = opcode - opcode_base;
+            row.address = row
+                .address
+                .wrapping_add(u64::from(adjusted / line_range) * min_inst_length);
+            row.line = row
+                .line
+                .wrapping_add((i64::from(line_base) + i64::from(adjusted % line_range)) as u64);
+            program.rows.push(row);
+            continue;
+        }
+
+        match opcode {
+            // 拡張命令
+            0 => {
+                let len = reader.uleb()?;
+                let next = reader.pos.checked_add(len as usize)?;
+                match reader.u8() {
+                    // DW_LNE_end_sequence
+                    Some(0x01) => {
+                        row.end_sequence = true;
+                        program.rows.push(row);
+                        row = initial;
+                    }
+                    // DW_LNE_set_address
+                    Some(0x02) => row.address = reader.sized(address_size as usize)?,
+                    // DW_LNE_define_file
+                    Some(0x03) if version < 5 => {
+                        let path = reader.cstr()?;
+                        program.files.push(join_path(comp_dir.as_ref(), &path));
+                    }
+                    _ => {}
+                }
+                reader.pos = next;
+            }
+            // DW_LNS_copy
+            0x01 => program.rows.push(row),
+            // DW_LNS_advance_pc
+            0x02 => {
+                row.address = row
+                    .address
+                    .wrapping_add(reader.uleb()?.wrapping_mul(min_inst_length))
+            }
+            // DW_LNS_advance_line
+            0x03 => row.line = row.line.wrapping_add(reader.sleb()? as u64),
+            // DW_LNS_set_file
+            0x04 => row.file = reader.uleb()?,
+            // DW_LNS_set_column
+            0x05 => row.column = reader.uleb()?,
+            // DW_LNS_const_add_pc
+            0x08 => {
+                let adjusted = 255 - opcode_base;
+                row.address = row
+                    .address
+                    .wrapping_add(u64::from(adjusted / line_range) * min_inst_length);
+            }
+            // DW_LNS_fixed_advance_pc
+            0x09 => row.address = row.address.wrapping_add(reader.u16()?.into()),
+            // 未知の標準命令は, ヘッダにあるオペランド数だけ読み飛ばす
+            _ => {
+                let operands = standard_opcode_lengths
+                    .get(opcode as usize - 1)
+                    .copied()
+                    .unwrap_or(0);
+                for _ in 0..operands {
+                    reader.uleb()?;
+                }
+            }
+        }
+    }
+    program.row_ranges = row_ranges(&program.rows);
+    Some(program)
+}
+
+/// DWARF 5 のディレクトリ/ファイル名の表を読み, (パス, ディレクトリ番号) を返す.
+fn entries(
+    reader: &mut Reader,
+    (sections, unit, dwarf64): (&DebugSections, &Unit, bool),
+) -> Option<Vec<(String, u64)>> {
+    let format_count = reader.u8()?;
+    let mut formats = Vec::new();
+    for _ in 0..format_count {
+        formats.push((reader.uleb()?, reader.uleb()?));
+    }
+
+    let count = reader.uleb()?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let mut path = String::new();
+        let mut dir = 0;
+        for (content_type, form) in formats.iter() {
+            let value = match *form {
+                DW_FORM_STRING => AttrValue::String(reader.cstr()?),
+                DW_FORM_LINE_STRP => AttrValue::LineStrOffset(reader.offset(dwarf64)?),
+                DW_FORM_STRP => AttrValue::StrOffset(reader.offset(dwarf64)?),
+                DW_FORM_STRX | DW_FORM_UDATA => AttrValue::Udata(reader.uleb()?),
+                DW_FORM_STRX1 | DW_FORM_DATA1 => AttrValue::Udata(reader.u8()?.into()),
+                DW_FORM_STRX2 | DW_FORM_DATA2 => AttrValue::Udata(reader.u16()?.into()),
+                DW_FORM_STRX3 => AttrValue::Udata(reader.u24()?.into()),
+                DW_FORM_STRX4 | DW_FORM_DATA4 => AttrValue::Udata(reader.u32()?.into()),
+                DW_FORM_DATA8 => AttrValue::Udata(reader.u64()?),
+                DW_FORM_DATA16 => AttrValue::Block(reader.block(16)?),
+                DW_FORM_BLOCK => {
+                    let len = reader.uleb()?;
+                    AttrValue::Block(reader.block(len)?)
+                }
+                _ => return None,
+            };
+            let value = match (*form, value) {
+                (
+                    DW_FORM_STRX | DW_FORM_STRX1 | DW_FORM_STRX2 | DW_FORM_STRX3 | DW_FORM_STRX4,
+                    AttrValue::Udata(idx),
+                ) => AttrValue::StrIndex(idx),
+                (_, value) => value,
+            };
+            match *content_type {
+                DW_LNCT_PATH => path = super::string(sections, unit, &value).unwrap_or_default(),
+                DW_LNCT_DIRECTORY_INDEX => dir = value.constant().unwrap_or(0) as u64,
+                _ => {}
+            }
+        }
+        entries.push((path, dir));
+    }
+
+    Some(entries)
+}
+
+fn join_path(dir: Option<&String>, path: &str) -> String {
+    match dir {
+        Some(dir) if !dir.is_empty() && !path.starts_with('/') => {
+            format!("{}/{}", dir.trim_end_matches('/'), path)
+        }
+        _ => path.to_string(),
+    }
+}
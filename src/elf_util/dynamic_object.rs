@@ -0,0 +1,213 @@
+//! 動的リンクに関わる情報(.dynamic, .dynsym, バージョン情報)を生バイト列から集める.
+//! 表示対象のファイルだけでなく, 依存ライブラリの解析にも用いる.
+
+use std::collections::BTreeMap;
+
+use crate::elf_util::{self, reconstruct, versions, VersionEntry};
+use elf_utilities::{section, symbol, Elf64Xword};
+
+pub const DT_NULL: i64 = 0;
+pub const DT_NEEDED: i64 = 1;
+pub const DT_SONAME: i64 = 14;
+pub const DT_RPATH: i64 = 15;
+pub const DT_RUNPATH: i64 = 29;
+
+#[derive(Debug, Clone)]
+pub struct DynamicSymbol {
+    pub name: String,
+    pub st_info: u8,
+    pub st_shndx: u16,
+    /// .gnu.version のエントリ(存在しなければ None)
+    pub versym: Option<u16>,
+}
+
+impl DynamicSymbol {
+    pub fn is_defined(&self) -> bool {
+        self.st_shndx != section::SHN_UNDEF
+    }
+
+    /// elf_utilities の `symbol::Bind` は STB_GNU_UNIQUE を区別できないので生の値を返す.
+    pub fn st_bind(&self) -> u8 {
+        self.st_info >> 4
+    }
+
+    /// VERSYM の hidden ビットが立っているか(= デフォルトバージョンでないか)
+    pub fn is_hidden(&self) -> bool {
+        self.versym
+            .is_some_and(|v| v & versions::VERSYM_HIDDEN != 0)
+    }
+
+    pub fn version_index(&self) -> Option<u16> {
+        self.versym.map(|v| v & !versions::VERSYM_HIDDEN)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DynamicObject {
+    pub soname: Option<String>,
+    pub needed: Vec<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    pub symbols: Vec<DynamicSymbol>,
+    /// バージョン定義(.gnu.version_d)
+    pub verdefs: BTreeMap<u16, VersionEntry>,
+    /// バージョン要求(.gnu.version_r)
+    pub verneeds: BTreeMap<u16, VersionEntry>,
+}
+
+impl DynamicObject {
+    /// セクションヘッダを頼りに動的リンク情報を集める.
+    /// セクションヘッダを持たなければ, プログラムヘッダから組み立て直したものを使う.
+    /// 動的シンボルテーブルを持たないファイルでは None を返す.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let shdrs = if reconstruct::has_section_headers(bytes) {
+            elf_util::section_headers(bytes)
+        } else {
+            reconstruct::section_headers(bytes)
+                .into_iter()
+                .map(|sct| sct.header)
+                .collect()
+        };
+        let dynsym = shdrs
+            .iter()
+            .find(|shdr| shdr.get_type() == section::Type::DynSym)?;
+        let dynstr = shdrs.get(dynsym.sh_link as usize)?;
+
+        let mut object = Self::default();
+
+        if let Some(dynamic) = shdrs
+            .iter()
+            .find(|shdr| shdr.get_type() == section::Type::Dynamic)
+        {
+            let strtab_offset = shdrs
+                .get(dynamic.sh_link as usize)
+                .map_or(dynstr.sh_offset, |shdr| shdr.sh_offset);
+            object.read_dynamic_entries(
+                bytes,
+                dynamic.sh_offset as usize,
+                dynamic.sh_size,
+                strtab_offset as usize,
+            );
+        }
+
+        let versym = shdrs
+            .iter()
+            .find(|shdr| shdr.sh_type == versions::SHT_GNU_VERSYM)
+            .map(|shdr| {
+                versions::parse_versym(bytes, shdr.sh_offset as usize, (shdr.sh_size / 2) as usize)
+            })
+            .unwrap_or_default();
+
+        if let Some(verdef) = shdrs
+            .iter()
+            .find(|shdr| shdr.sh_type == versions::SHT_GNU_VERDEF)
+        {
+            object.verdefs = versions::parse_verdef(
+                bytes,
+                verdef.sh_offset as usize,
+                verdef.sh_info as usize,
+                dynstr.sh_offset as usize,
+            );
+        }
+        if let Some(verneed) = shdrs
+            .iter()
+            .find(|shdr| shdr.sh_type == versions::SHT_GNU_VERNEED)
+        {
+            object.verneeds = versions::parse_verneed(
+                bytes,
+                verneed.sh_offset as usize,
+                verneed.sh_info as usize,
+                dynstr.sh_offset as usize,
+            );
+        }
+
+        let count = (dynsym.sh_size / symbol::Symbol64::SIZE as u64) as usize;
+        object.symbols = read_symbols(
+            bytes,
+            dynsym.sh_offset as usize,
+            count,
+            dynstr.sh_offset as usize,
+            &versym,
+        );
+
+        Some(object)
+    }
+
+    /// シンボルに付与されたバージョン名を引く.
+    /// 定義シンボルなら verdef, 未定義シンボルなら verneed を参照する.
+    pub fn symbol_version(&self, sym: &DynamicSymbol) -> Option<&VersionEntry> {
+        let idx = sym.version_index()?;
+        if idx == versions::VER_NDX_LOCAL || idx == versions::VER_NDX_GLOBAL {
+            return None;
+        }
+
+        if sym.is_defined() {
+            self.verdefs.get(&idx)
+        } else {
+            self.verneeds.get(&idx)
+        }
+    }
+
+    fn read_dynamic_entries(
+        &mut self,
+        bytes: &[u8],
+        offset: usize,
+        size: Elf64Xword,
+        strtab_offset: usize,
+    ) {
+        let read_str =
+            |value: u64| elf_util::read_cstr(bytes, strtab_offset.checked_add(value as usize)?);
+
+        for idx in 0..(size / 0x10) as usize {
+            let entry = match idx
+                .checked_mul(0x10)
+                .and_then(|rel| offset.checked_add(rel))
+            {
+                Some(entry) => entry,
+                None => break,
+            };
+            let (tag, value) = match (
+                elf_util::read_i64(bytes, entry),
+                entry
+                    .checked_add(8)
+                    .and_then(|pos| elf_util::read_u64(bytes, pos)),
+            ) {
+                (Some(tag), Some(value)) => (tag, value),
+                _ => break,
+            };
+
+            match tag {
+                DT_NULL => break,
+                DT_NEEDED => self.needed.extend(read_str(value)),
+                DT_SONAME => self.soname = read_str(value),
+                DT_RPATH => self.rpath = read_str(value),
+                DT_RUNPATH => self.runpath = read_str(value),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn read_symbols(
+    bytes: &[u8],
+    offset: usize,
+    count: usize,
+    strtab_offset: usize,
+    versym: &[u16],
+) -> Vec<DynamicSymbol> {
+    (0..count)
+        .map_while(|idx| {
+            let base = offset.checked_add(idx.checked_mul(symbol::Symbol64::SIZE)?)?;
+            let st_name = elf_util::read_u32(bytes, base)?;
+            Some(DynamicSymbol {
+                name: strtab_offset
+                    .checked_add(st_name as usize)
+                    .and_then(|pos| elf_util::read_cstr(bytes, pos))
+                    .unwrap_or_default(),
+                st_info: elf_util::read_u8(bytes, base.checked_add(4)?)?,
+                st_shndx: elf_util::read_u16(bytes, base.checked_add(6)?)?,
+                versym: versym.get(idx).copied(),
+            })
+        })
+        .collect()
+}
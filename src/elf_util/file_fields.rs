@@ -0,0 +1,298 @@
+//! ファイルの各バイトが属する構造体のフィールド. ELF ヘッダ (e_ident を含む),
+//! プログラムヘッダ, セクションヘッダの各エントリをフィールド単位で持ち,
+//! シンボルのフィールドはオフセットから都度求める. それ以外はセクションの中身として扱う.
+
+use elf_utilities::{file, section};
+
+/// (フィールド名, ヘッダ/エントリ先頭からのオフセット, 大きさ)
+pub const IDENT_FIELDS: [(&str, u64, u64); 7] = [
+    ("e_ident[EI_MAG0..3]", 0, 4),
+    ("e_ident[EI_CLASS]", 4, 1),
+    ("e_ident[EI_DATA]", 5, 1),
+    ("e_ident[EI_VERSION]", 6, 1),
+    ("e_ident[EI_OSABI]", 7, 1),
+    ("e_ident[EI_ABIVERSION]", 8, 1),
+    ("e_ident[EI_PAD]", 9, 7),
+];
+pub const EHDR_FIELDS: [(&str, u64, u64); 13] = [
+    ("e_type", 16, 2),
+    ("e_machine", 18, 2),
+    ("e_version", 20, 4),
+    ("e_entry", 24, 8),
+    ("e_phoff", 32, 8),
+    ("e_shoff", 40, 8),
+    ("e_flags", 48, 4),
+    ("e_ehsize", 52, 2),
+    ("e_phentsize", 54, 2),
+    ("e_phnum", 56, 2),
+    ("e_shentsize", 58, 2),
+    ("e_shnum", 60, 2),
+    ("e_shstrndx", 62, 2),
+];
+const PHDR_FIELDS: [(&str, u64, u64); 8] = [
+    ("p_type", 0, 4),
+    ("p_flags", 4, 4),
+    ("p_offset", 8, 8),
+    ("p_vaddr", 16, 8),
+    ("p_paddr", 24, 8),
+    ("p_filesz", 32, 8),
+    ("p_memsz", 40, 8),
+    ("p_align", 48, 8),
+];
+const SHDR_FIELDS: [(&str, u64, u64); 10] = [
+    ("sh_name", 0, 4),
+    ("sh_type", 4, 4),
+    ("sh_flags", 8, 8),
+    ("sh_addr", 16, 8),
+    ("sh_offset", 24, 8),
+    ("sh_size", 32, 8),
+    ("sh_link", 40, 4),
+    ("sh_info", 44, 4),
+    ("sh_addralign", 48, 8),
+    ("sh_entsize", 56, 8),
+];
+const SYM_FIELDS: [(&str, u64, u64); 6] = [
+    ("st_name", 0, 4),
+    ("st_info", 4, 1),
+    ("st_other", 5, 1),
+    ("st_shndx", 6, 2),
+    ("st_value", 8, 8),
+    ("st_size", 16, 8),
+];
+const SYM_SIZE: u64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Ident,
+    ElfHeader,
+    /// プログラムヘッダのインデックス
+    ProgramHeader(usize),
+    SectionHeader(usize),
+    /// シンボルテーブルのセクションのインデックスと, シンボルのインデックス
+    Symbol(usize, usize),
+}
+
+/// 名前は描画のときに組み立てる. シンボルの多いファイルで文字列を持ちすぎないため.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub kind: FieldKind,
+    pub member: &'static str,
+    /// 構造体の中で何番目のフィールドか. 隣り合うフィールドを見分けるのに使う
+    pub index: usize,
+    pub start: u64,
+    pub size: u64,
+}
+
+impl Field {
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.size)
+    }
+
+    /// "shdr[12].sh_offset" のような名前
+    pub fn name(&self, elf_file: &file::ELF64) -> String {
+        match self.kind {
+            FieldKind::Ident | FieldKind::ElfHeader => self.member.to_string(),
+            FieldKind::ProgramHeader(idx) => format!("phdr[{}].{}", idx, self.member),
+            FieldKind::SectionHeader(idx) => format!("shdr[{}].{}", idx, self.member),
+            FieldKind::Symbol(sct_idx, idx) => {
+                format!(
+                    "{}[{}].{}",
+                    elf_file.sections[sct_idx].name, idx, self.member
+                )
+            }
+        }
+    }
+}
+
+/// シンボルテーブルの中身. エントリ数に比例するフィールドを持たないよう, 範囲だけ覚えておく
+struct SymbolTable {
+    section: usize,
+    start: u64,
+    entsize: u64,
+    count: u64,
+}
+
+impl SymbolTable {
+    fn field_at(&self, offset: u64) -> Option<Field> {
+        let delta = offset.checked_sub(self.start)?;
+        let idx = delta / self.entsize;
+        if idx >= self.count {
+            return None;
+        }
+        let entry_start = self.start + idx * self.entsize;
+        SYM_FIELDS
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, field_offset, size))| field_offset + size <= self.entsize)
+            .map(|(index, (member, field_offset, size))| Field {
+                kind: FieldKind::Symbol(self.section, idx as usize),
+                member,
+                index,
+                start: entry_start + field_offset,
+                size: *size,
+            })
+            .find(|field| field.start <= offset && offset < field.end())
+    }
+}
+
+pub struct FileFields {
+    /// ヘッダのフィールド. 開始オフセット順
+    fields: Vec<Field>,
+    symbol_tables: Vec<SymbolTable>,
+    /// 中身を持つセクションの (インデックス, 開始, 終わり). 開始オフセット順
+    pub sections: Vec<(usize, u64, u64)>,
+}
+
+impl FileFields {
+    /// offset を含むフィールド. 重なっていれば後から始まるものを選ぶ
+    pub fn field_at(&self, offset: u64) -> Option<Field> {
+        let header = self
+            .fields
+            .partition_point(|field| field.start <= offset)
+            .checked_sub(1)
+            .map(|idx| self.fields[idx])
+            .filter(|field| offset < field.end());
+        let symbol = self
+            .symbol_tables
+            .iter()
+            .filter_map(|table| table.field_at(offset))
+            .max_by_key(|field| field.start);
+
+        match (header, symbol) {
+            (Some(header), Some(symbol)) if symbol.start > header.start => Some(symbol),
+            (Some(header), _) => Some(header),
+            (None, symbol) => symbol,
+        }
+    }
+
+    /// offset を含むセクションと, その先頭からのオフセット.
+    /// 重なっていれば後から始まるものを選ぶ
+    pub fn section_at(&self, offset: u64) -> Option<(usize, u64)> {
+        self.sections
+            .iter()
+            .rev()
+            .find(|(_, start, end)| *start <= offset && offset < *end)
+            .map(|(idx, start, _)| (*idx, offset - start))
+    }
+
+    /// ELF ヘッダのフィールドの開始オフセット
+    pub fn header_field_offset(member: &str) -> Option<u64> {
+        IDENT_FIELDS
+            .iter()
+            .chain(EHDR_FIELDS.iter())
+            .find(|(name, _, _)| *name == member)
+            .map(|(_, offset, _)| *offset)
+    }
+}
+
+pub fn file_fields(elf_file: &file::ELF64, reconstructed: bool) -> FileFields {
+    let ehdr = &elf_file.ehdr;
+    let mut fields = Vec::new();
+    push_entry(&mut fields, FieldKind::Ident, 0, &IDENT_FIELDS, u64::MAX);
+    push_entry(
+        &mut fields,
+        FieldKind::ElfHeader,
+        0,
+        &EHDR_FIELDS,
+        u64::from(ehdr.e_ehsize),
+    );
+
+    for idx in 0..usize::from(ehdr.e_phnum) {
+        let start = ehdr
+            .e_phoff
+            .saturating_add(idx as u64 * u64::from(ehdr.e_phentsize));
+        push_entry(
+            &mut fields,
+            FieldKind::ProgramHeader(idx),
+            start,
+            &PHDR_FIELDS,
+            u64::from(ehdr.e_phentsize),
+        );
+    }
+    // 組み立て直したセクションはファイル上にヘッダを持たない.
+    // 拡張形式では e_shnum が 0 なので, 読み込んだセクションの数を使う
+    let shnum = if reconstructed {
+        0
+    } else {
+        elf_file.sections.len()
+    };
+    for idx in 0..shnum {
+        let start = ehdr
+            .e_shoff
+            .saturating_add(idx as u64 * u64::from(ehdr.e_shentsize));
+        push_entry(
+            &mut fields,
+            FieldKind::SectionHeader(idx),
+            start,
+            &SHDR_FIELDS,
+            u64::from(ehdr.e_shentsize),
+        );
+    }
+
+    let mut symbol_tables = Vec::new();
+    let mut sections = Vec::new();
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        let shdr = &sct.header;
+        if sct_idx == 0 || shdr.get_type() == section::Type::NoBits || shdr.sh_size == 0 {
+            continue;
+        }
+        sections.push((
+            sct_idx,
+            shdr.sh_offset,
+            shdr.sh_offset.saturating_add(shdr.sh_size),
+        ));
+
+        if matches!(
+            shdr.get_type(),
+            section::Type::SymTab | section::Type::DynSym
+        ) {
+            let entsize = match shdr.sh_entsize {
+                0 => SYM_SIZE,
+                entsize => entsize,
+            };
+            // 末尾のエントリがアドレス空間を越えないよう, 収まる数に減らす
+            let count = (shdr.sh_size / entsize).min((u64::MAX - shdr.sh_offset) / entsize);
+            symbol_tables.push(SymbolTable {
+                section: sct_idx,
+                start: shdr.sh_offset,
+                entsize,
+                count,
+            });
+        }
+    }
+
+    fields.sort_by_key(|field| field.start);
+    sections.sort_by_key(|(_, start, _)| *start);
+    FileFields {
+        fields,
+        symbol_tables,
+        sections,
+    }
+}
+
+/// entry_size を超えるフィールドは, 壊れた/小さすぎる entsize とみなして含めない
+fn push_entry(
+    fields: &mut Vec<Field>,
+    kind: FieldKind,
+    start: u64,
+    members: &[(&'static str, u64, u64)],
+    entry_size: u64,
+) {
+    // e_ident と ELF ヘッダは一続きに番号を振る
+    let first_index = if kind == FieldKind::ElfHeader {
+        IDENT_FIELDS.len()
+    } else {
+        0
+    };
+    for (index, (member, offset, size)) in members.iter().enumerate() {
+        if offset + size <= entry_size {
+            fields.push(Field {
+                kind,
+                member,
+                index: first_index + index,
+                start: start.saturating_add(*offset),
+                size: *size,
+            });
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! ファイルオフセット空間の配置. ELF ヘッダ, プログラムヘッダテーブル, 各セクション,
+//! セクションヘッダテーブルを並べ, どれにも属さない隙間と, 互いに重なる範囲を行として持つ.
+
+use elf_utilities::{file, section, segment};
+
+/// ファイル上に中身を持つもの. 隙間と重なりはこれらだけで判定する
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    ElfHeader,
+    ProgramHeaders,
+    Section(usize),
+    SectionHeaders,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileRegionKind {
+    Part(Part),
+    /// PT_LOAD のプログラムヘッダのインデックス. どこが読み込まれるかを示すだけで,
+    /// 隙間や重なりの判定には使わない
+    Segment(usize),
+    Gap,
+    /// 先に始まるものと, それに重なるもの
+    Overlap(Part, Part),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileRegion {
+    pub kind: FileRegionKind,
+    pub start: u64,
+    pub end: u64,
+    /// 隙間のうち 0 でないバイトの数
+    pub nonzero_bytes: usize,
+}
+
+impl FileRegion {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+pub struct FileLayout {
+    pub regions: Vec<FileRegion>,
+    pub file_size: u64,
+}
+
+impl FileLayout {
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn gap_count(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|region| region.kind == FileRegionKind::Gap)
+            .count()
+    }
+
+    pub fn overlap_count(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|region| matches!(region.kind, FileRegionKind::Overlap(_, _)))
+            .count()
+    }
+
+    /// offset から始まるものの行
+    pub fn part_at(&self, offset: u64) -> Option<&FileRegion> {
+        self.regions
+            .iter()
+            .find(|region| matches!(region.kind, FileRegionKind::Part(_)) && region.start == offset)
+    }
+
+    /// offset を含む PT_LOAD の行
+    pub fn segment_of(&self, offset: u64) -> Option<&FileRegion> {
+        self.regions.iter().find(|region| {
+            matches!(region.kind, FileRegionKind::Segment(_))
+                && region.start <= offset
+                && offset < region.end
+        })
+    }
+
+    /// 範囲に含まれるものの行
+    pub fn parts_in(&self, start: u64, end: u64) -> Vec<&FileRegion> {
+        self.regions
+            .iter()
+            .filter(|region| {
+                matches!(region.kind, FileRegionKind::Part(_))
+                    && start <= region.start
+                    && region.start < end
+            })
+            .collect()
+    }
+}
+
+/// オフセット順に並べる. 同じオフセットでは PT_LOAD を先に, 重なりは重なった後ろ側の直後に置く.
+/// reconstructed にはセクションをプログラムヘッダから組み立て直したかどうかを与える.
+pub fn file_layout(elf_file: &file::ELF64, bytes: &[u8], reconstructed: bool) -> FileLayout {
+    let ehdr = &elf_file.ehdr;
+    let mut parts = vec![(Part::ElfHeader, 0, u64::from(ehdr.e_ehsize))];
+    if ehdr.e_phnum != 0 {
+        parts.push((
+            Part::ProgramHeaders,
+            ehdr.e_phoff,
+            ehdr.e_phoff + u64::from(ehdr.e_phnum) * u64::from(ehdr.e_phentsize),
+        ));
+    }
+    // 再構築したセクションの位置は推測なので, ファイルの配置には含めない
+    if !reconstructed {
+        for (idx, sct) in elf_file.sections.iter().enumerate() {
+            let shdr = &sct.header;
+            if idx == 0 || shdr.get_type() == section::Type::NoBits || shdr.sh_size == 0 {
+                continue;
+            }
+            parts.push((
+                Part::Section(idx),
+                shdr.sh_offset,
+                shdr.sh_offset.saturating_add(shdr.sh_size),
+            ));
+        }
+    }
+    // 拡張形式では e_shnum が 0 なので, 読み込んだセクションの数を使う
+    if !reconstructed && !elf_file.sections.is_empty() {
+        parts.push((
+            Part::SectionHeaders,
+            ehdr.e_shoff,
+            ehdr.e_shoff + elf_file.sections.len() as u64 * u64::from(ehdr.e_shentsize),
+        ));
+    }
+    parts.sort_by_key(|(_, start, end)| (*start, std::cmp::Reverse(*end)));
+
+    let mut regions = Vec::new();
+    for (seg_idx, seg) in elf_file.segments.iter().enumerate() {
+        let phdr = &seg.header;
+        if phdr.get_type() == segment::Type::Load && phdr.p_filesz != 0 {
+            regions.push(FileRegion {
+                kind: FileRegionKind::Segment(seg_idx),
+                start: phdr.p_offset,
+                end: phdr.p_offset.saturating_add(phdr.p_filesz),
+                nonzero_bytes: 0,
+            });
+        }
+    }
+
+    let file_size = bytes.len() as u64;
+    let gap = |start: u64, end: u64| FileRegion {
+        kind: FileRegionKind::Gap,
+        start,
+        end,
+        nonzero_bytes: bytes
+            .get(start as usize..end.min(file_size) as usize)
+            .map_or(0, |gap| gap.iter().filter(|b| **b != 0).count()),
+    };
+
+    // covering はそれまでで最も後ろまで届いているもの
+    let mut covering: Option<(Part, u64)> = None;
+    for (part, start, end) in parts {
+        match covering {
+            Some((_, covered_end)) if covered_end < start => regions.push(gap(covered_end, start)),
+            None if 0 < start => regions.push(gap(0, start)),
+            _ => {}
+        }
+        regions.push(FileRegion {
+            kind: FileRegionKind::Part(part),
+            start,
+            end,
+            nonzero_bytes: 0,
+        });
+        if let Some((previous, covered_end)) =
+            covering.filter(|(_, covered_end)| start < *covered_end)
+        {
+            regions.push(FileRegion {
+                kind: FileRegionKind::Overlap(previous, part),
+                start,
+                end: end.min(covered_end),
+                nonzero_bytes: 0,
+            });
+        }
+        if covering.is_none_or(|(_, covered_end)| covered_end < end) {
+            covering = Some((part, end));
+        }
+    }
+    if let Some((_, covered_end)) = covering.filter(|(_, covered_end)| *covered_end < file_size) {
+        regions.push(gap(covered_end, file_size));
+    }
+
+    // PT_LOAD は同じオフセットから始まるものより先に置く
+    regions.sort_by_key(|region| {
+        (
+            region.start,
+            !matches!(region.kind, FileRegionKind::Segment(_)),
+        )
+    });
+
+    FileLayout { regions, file_size }
+}
@@ -0,0 +1,443 @@
+//! .hash (SysV) と .gnu.hash のシンボルハッシュテーブルを解析し,
+//! ld.so と同じ手順での探索をなぞる.
+
+use std::collections::BTreeMap;
+
+use crate::elf_util::{read_u32, read_u64};
+use elf_utilities::{
+    file,
+    section::{self, Contents64},
+};
+
+pub const SHT_GNU_HASH: u32 = 0x6fff_fff6;
+
+const STB_LOCAL: u8 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    SysV,
+    Gnu,
+}
+
+/// ハッシュテーブルが参照するシンボル
+#[derive(Debug, Clone)]
+pub struct HashSymbol {
+    pub name: String,
+    /// 定義済みの非 LOCAL シンボル(= ハッシュテーブルから引けるべきもの)か
+    pub exported: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct HashTable {
+    pub kind: Kind,
+    pub section_name: String,
+    pub symbol_table_name: String,
+    pub buckets: Vec<u32>,
+    /// SysV ではチェーン配列, GNU では symoffset 以降のシンボルのハッシュ値
+    pub chains: Vec<u32>,
+    /// GNU のみ: ハッシュテーブルに含まれる最初のシンボル
+    pub symoffset: u32,
+    /// GNU のみ: ブルームフィルタ
+    pub bloom: Vec<u64>,
+    pub bloom_shift: u32,
+    pub symbols: Vec<HashSymbol>,
+    /// テーブルをたどっても辿り着けない公開シンボル
+    pub unreachable: Vec<usize>,
+}
+
+/// 名前で探索した結果
+pub struct Lookup {
+    pub hash: u32,
+    pub bucket: usize,
+    /// GNU のみ: ブルームフィルタを通過したか
+    pub bloom_passed: Option<bool>,
+    /// 比較したシンボルのインデックス順
+    pub probes: Vec<Probe>,
+    pub found: Option<usize>,
+}
+
+pub struct Probe {
+    pub index: usize,
+    /// GNU のみ: チェーンのハッシュ値が一致したか (SysV では常に true)
+    pub hash_matched: bool,
+    pub name_matched: bool,
+}
+
+/// ELF 標準の (SysV) ハッシュ関数
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    let mut h: u32 = 0;
+    for c in name {
+        h = (h << 4).wrapping_add(*c as u32);
+        let g = h & 0xf000_0000;
+        if g != 0 {
+            h ^= g >> 24;
+        }
+        h &= !g;
+    }
+    h
+}
+
+/// GNU ハッシュ関数 (DJB hash)
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .fold(5381u32, |h, c| h.wrapping_mul(33).wrapping_add(*c as u32))
+}
+
+/// ファイル中のハッシュテーブルセクションを全て解析する.
+pub fn hash_tables(elf_file: &file::ELF64) -> Vec<HashTable> {
+    elf_file
+        .sections
+        .iter()
+        .filter_map(|sct| HashTable::from_section(elf_file, sct))
+        .collect()
+}
+
+impl HashTable {
+    pub fn from_section(elf_file: &file::ELF64, sct: &section::Section64) -> Option<Self> {
+        let kind = match sct.header.sh_type {
+            SHT_GNU_HASH => Kind::Gnu,
+            _ if sct.header.get_type() == section::Type::Hash => Kind::SysV,
+            _ => return None,
+        };
+        let data = match &sct.contents {
+            Contents64::Raw(data) => data,
+            _ => return None,
+        };
+
+        let symtab = elf_file.sections.get(sct.header.sh_link as usize)?;
+        let symbols = match &symtab.contents {
+            Contents64::Symbols(symbols) => symbols
+                .iter()
+                .map(|sym| HashSymbol {
+                    name: sym.symbol_name.clone(),
+                    exported: sym.st_shndx != section::SHN_UNDEF
+                        && sym.st_info >> 4 != STB_LOCAL
+                        && !sym.symbol_name.is_empty(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let mut table = match kind {
+            Kind::SysV => Self::parse_sysv(data, symbols)?,
+            Kind::Gnu => Self::parse_gnu(data, symbols)?,
+        };
+        table.section_name = sct.name.clone();
+        table.symbol_table_name = symtab.name.clone();
+        table.unreachable = table.find_unreachable();
+
+        Some(table)
+    }
+
+    fn parse_sysv(data: &[u8], symbols: Vec<HashSymbol>) -> Option<Self> {
+        let nbucket = read_u32(data, 0)? as usize;
+        let nchain = read_u32(data, 4)? as usize;
+        let read_words = |start: usize, count: usize| -> Vec<u32> {
+            (0..count)
+                .map_while(|idx| read_u32(data, start + idx * 4))
+                .collect()
+        };
+
+        Some(Self {
+            kind: Kind::SysV,
+            section_name: String::new(),
+            symbol_table_name: String::new(),
+            buckets: read_words(8, nbucket),
+            chains: read_words(8 + nbucket * 4, nchain),
+            symoffset: 0,
+            bloom: Vec::new(),
+            bloom_shift: 0,
+            symbols,
+            unreachable: Vec::new(),
+        })
+    }
+
+    fn parse_gnu(data: &[u8], symbols: Vec<HashSymbol>) -> Option<Self> {
+        let nbuckets = read_u32(data, 0)? as usize;
+        let symoffset = read_u32(data, 4)?;
+        let bloom_size = read_u32(data, 8)? as usize;
+        let bloom_shift = read_u32(data, 12)?;
+
+        let bloom = (0..bloom_size)
+            .map_while(|idx| read_u64(data, 16 + idx * 8))
+            .collect();
+        let buckets_start = 16 + bloom_size * 8;
+        let buckets = (0..nbuckets)
+            .map_while(|idx| read_u32(data, buckets_start + idx * 4))
+            .collect();
+        // チェーンはセクションの末尾まで続く
+        let chains_start = buckets_start + nbuckets * 4;
+        let chains = (0..data.len().saturating_sub(chains_start) / 4)
+            .map_while(|idx| read_u32(data, chains_start + idx * 4))
+            .collect();
+
+        Some(Self {
+            kind: Kind::Gnu,
+            section_name: String::new(),
+            symbol_table_name: String::new(),
+            buckets,
+            chains,
+            symoffset,
+            bloom,
+            bloom_shift,
+            symbols,
+            unreachable: Vec::new(),
+        })
+    }
+
+    pub fn hash(&self, name: &str) -> u32 {
+        match self.kind {
+            Kind::SysV => sysv_hash(name.as_bytes()),
+            Kind::Gnu => gnu_hash(name.as_bytes()),
+        }
+    }
+
+    /// バケットに属するシンボルのインデックス列
+    pub fn chain(&self, bucket: usize) -> Vec<usize> {
+        let mut indices = Vec::new();
+        let start = match self.buckets.get(bucket) {
+            Some(start) => *start as usize,
+            None => return indices,
+        };
+
+        match self.kind {
+            Kind::SysV => {
+                let mut idx = start;
+                // 壊れたテーブルで無限ループしないよう, チェーン長で打ち切る
+                while idx != 0 && indices.len() < self.chains.len() {
+                    indices.push(idx);
+                    idx = self.chains.get(idx).copied().unwrap_or(0) as usize;
+                }
+            }
+            Kind::Gnu => {
+                if start < self.symoffset as usize {
+                    return indices;
+                }
+                let mut idx = start;
+                while let Some(hash) = self.chains.get(idx - self.symoffset as usize) {
+                    indices.push(idx);
+                    if hash & 1 != 0 {
+                        break;
+                    }
+                    idx += 1;
+                }
+            }
+        }
+
+        indices
+    }
+
+    /// 各バケットのチェーン長
+    pub fn chain_lengths(&self) -> Vec<usize> {
+        (0..self.buckets.len())
+            .map(|bucket| self.chain(bucket).len())
+            .collect()
+    }
+
+    /// チェーン長 -> バケット数
+    pub fn histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for len in self.chain_lengths() {
+            *histogram.entry(len).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// 空でないバケットの平均チェーン長
+    pub fn average_chain_length(&self) -> f64 {
+        let lengths: Vec<usize> = self
+            .chain_lengths()
+            .into_iter()
+            .filter(|len| *len != 0)
+            .collect();
+        if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+        }
+    }
+
+    /// GNU のみ: ブルームフィルタで立っているビットの割合
+    pub fn bloom_fill_ratio(&self) -> f64 {
+        if self.bloom.is_empty() {
+            return 0.0;
+        }
+        let set: u32 = self.bloom.iter().map(|word| word.count_ones()).sum();
+        set as f64 / (self.bloom.len() * 64) as f64
+    }
+
+    /// ld.so と同じ手順で名前を探索する.
+    pub fn lookup(&self, name: &str) -> Lookup {
+        let hash = self.hash(name);
+        let bucket = if self.buckets.is_empty() {
+            0
+        } else {
+            hash as usize % self.buckets.len()
+        };
+
+        let bloom_passed = match self.kind {
+            Kind::Gnu => Some(self.bloom_check(hash)),
+            Kind::SysV => None,
+        };
+
+        let mut probes = Vec::new();
+        let mut found = None;
+        if bloom_passed != Some(false) {
+            for index in self.chain(bucket) {
+                let hash_matched = match self.kind {
+                    Kind::SysV => true,
+                    Kind::Gnu => self.chains[index - self.symoffset as usize] | 1 == hash | 1,
+                };
+                let name_matched =
+                    hash_matched && self.symbols.get(index).is_some_and(|sym| sym.name == name);
+                probes.push(Probe {
+                    index,
+                    hash_matched,
+                    name_matched,
+                });
+                if name_matched {
+                    found = Some(index);
+                    break;
+                }
+            }
+        }
+
+        Lookup {
+            hash,
+            bucket,
+            bloom_passed,
+            probes,
+            found,
+        }
+    }
+
+    fn bloom_check(&self, hash: u32) -> bool {
+        if self.bloom.is_empty() {
+            return false;
+        }
+        let word = self.bloom[(hash as usize / 64) % self.bloom.len()];
+        // bloom_shift はヘッダの値そのままなので, 32 以上でも panic しないようにする
+        let shifted = hash.checked_shr(self.bloom_shift).unwrap_or(0);
+        let mask = (1u64 << (hash % 64)) | (1u64 << (shifted % 64));
+        word & mask == mask
+    }
+
+    /// 公開シンボルが全てテーブルから辿れるかを確かめる.
+    /// 同名のシンボル(別バージョン)があるので, 名前で引いた結果ではなく
+    /// 自身のハッシュ値のバケットのチェーン上にあるかで判定する.
+    fn find_unreachable(&self) -> Vec<usize> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(idx, sym)| sym.exported && !self.reaches(*idx, &sym.name))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn reaches(&self, idx: usize, name: &str) -> bool {
+        if self.buckets.is_empty() {
+            return false;
+        }
+        let hash = self.hash(name);
+
+        match self.kind {
+            Kind::SysV => self
+                .chain(hash as usize % self.buckets.len())
+                .contains(&idx),
+            Kind::Gnu => {
+                self.bloom_check(hash)
+                    && self
+                        .chain(hash as usize % self.buckets.len())
+                        .contains(&idx)
+                    && self.chains[idx - self.symoffset as usize] | 1 == hash | 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn symbols(names: &[&str]) -> Vec<HashSymbol> {
+        names
+            .iter()
+            .map(|name| HashSymbol {
+                name: name.to_string(),
+                exported: !name.is_empty(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gnu_hash_known_values() {
+        assert_eq!(gnu_hash(b""), 0x1505);
+        assert_eq!(gnu_hash(b"printf"), 0x156b_2bb8);
+        assert_eq!(gnu_hash(b"exit"), 0x7c96_7e3f);
+        assert_eq!(gnu_hash(b"syscall"), 0xbac2_12a0);
+    }
+
+    #[test]
+    fn sysv_hash_known_values() {
+        assert_eq!(sysv_hash(b""), 0);
+        assert_eq!(sysv_hash(b"printf"), 0x0779_05a6);
+        assert_eq!(sysv_hash(b"exit"), 0x0006_cf04);
+        assert_eq!(sysv_hash(b"syscall"), 0x0b09_985c);
+    }
+
+    #[test]
+    fn sysv_lookup_follows_chain() {
+        // バケット 0 -> シンボル 2 -> シンボル 1 -> 終端
+        let data = words(&[1, 3, 2, 0, 0, 1]);
+        let mut table = HashTable::parse_sysv(&data, symbols(&["", "printf", "exit"])).unwrap();
+        table.unreachable = table.find_unreachable();
+
+        let lookup = table.lookup("printf");
+        assert_eq!(lookup.found, Some(1));
+        assert_eq!(lookup.bloom_passed, None);
+        assert_eq!(
+            lookup.probes.iter().map(|p| p.index).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(table.unreachable.is_empty());
+    }
+
+    #[test]
+    fn gnu_bloom_filter_rejects_missing_names() {
+        let hash = gnu_hash(b"printf");
+        let shift = 6;
+        let bloom = (1u64 << (hash % 64)) | (1u64 << ((hash >> shift) % 64));
+
+        let mut data = words(&[1, 1, 1, shift]);
+        data.extend(bloom.to_le_bytes());
+        // バケット 0 はシンボル 1 から始まり, チェーンの最下位ビットが終端を示す
+        data.extend(words(&[1, hash | 1]));
+        let mut table = HashTable::parse_gnu(&data, symbols(&["", "printf"])).unwrap();
+        table.unreachable = table.find_unreachable();
+
+        let found = table.lookup("printf");
+        assert_eq!(found.bloom_passed, Some(true));
+        assert_eq!(found.found, Some(1));
+        assert!(table.unreachable.is_empty());
+
+        // "exit" のビットの片方 (hash % 64 == 63) はフィルタに立っていない
+        let missing = table.lookup("exit");
+        assert_eq!(missing.bloom_passed, Some(false));
+        assert!(missing.probes.is_empty());
+        assert_eq!(missing.found, None);
+    }
+
+    #[test]
+    fn gnu_bloom_shift_out_of_range() {
+        let mut data = words(&[1, 1, 1, 64]);
+        data.extend(u64::MAX.to_le_bytes());
+        data.extend(words(&[1, gnu_hash(b"printf") | 1]));
+        let table = HashTable::parse_gnu(&data, symbols(&["", "printf"])).unwrap();
+
+        assert_eq!(table.lookup("printf").found, Some(1));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::elf_util::{read_cstr, read_u16, read_u32, read_u64};
+use elf_utilities::{dynamic, file, header, relocation, section, segment, symbol};
+
+const DYN_SIZE: usize = 0x10;
+const RELA_SIZE: usize = 0x18;
+
+/// 生バイト列が解析可能な ELF64 (リトルエンディアン) かどうか.
+pub fn is_elf64(bytes: &[u8]) -> bool {
+    bytes.len() >= header::Ehdr64::SIZE as usize
+        && bytes[..4] == [0x7f, b'E', b'L', b'F']
+        && bytes[4] == 2
+        && bytes[5] == 1
+}
+
+/// elf_utilities を経由せずにセクションヘッダテーブルを読み出す.
+/// 依存ライブラリ等, TUI で表示しないファイルを軽量に扱う為に用いる.
+pub fn section_headers(bytes: &[u8]) -> Vec<section::Shdr64> {
+    if !is_elf64(bytes) {
+        return Vec::new();
+    }
+
+    let shoff = read_u64(bytes, 0x28).unwrap_or(0) as usize;
+    let shnum = section_count(bytes);
+
+    (0..shnum)
+        .map_while(|idx| {
+            let base = shoff.checked_add(idx * section::Shdr64::SIZE)?;
+            Some(section::Shdr64 {
+                sh_name: read_u32(bytes, base)?,
+                sh_type: read_u32(bytes, base + 0x4)?,
+                sh_flags: read_u64(bytes, base + 0x8)?,
+                sh_addr: read_u64(bytes, base + 0x10)?,
+                sh_offset: read_u64(bytes, base + 0x18)?,
+                sh_size: read_u64(bytes, base + 0x20)?,
+                sh_link: read_u32(bytes, base + 0x28)?,
+                sh_info: read_u32(bytes, base + 0x2c)?,
+                sh_addralign: read_u64(bytes, base + 0x30)?,
+                sh_entsize: read_u64(bytes, base + 0x38)?,
+            })
+        })
+        .collect()
+}
+
+/// セクションヘッダの個数. e_shnum が 0 でテーブルがある場合は,
+/// 個数を先頭エントリの sh_size に逃がす拡張形式として読む.
+pub fn section_count(bytes: &[u8]) -> usize {
+    let shoff = read_u64(bytes, 0x28).unwrap_or(0);
+    match read_u16(bytes, 0x3c).unwrap_or(0) {
+        0 if shoff != 0 => (shoff as usize)
+            .checked_add(0x20)
+            .and_then(|pos| read_u64(bytes, pos))
+            .unwrap_or(0) as usize,
+        shnum => usize::from(shnum),
+    }
+}
+
+/// セクションヘッダを持つファイルを読み込む.
+/// 拡張形式では parse_elf64 がパニックするので, 自前でセクションを読み出す.
+pub fn elf_from_section_headers(
+    file_path: &str,
+    bytes: &[u8],
+) -> Result<file::ELF64, Box<dyn std::error::Error>> {
+    if read_u16(bytes, 0x3c) != Some(0) {
+        return elf_utilities::parser::parse_elf64(file_path);
+    }
+    if !is_elf64(bytes) {
+        return Err("not a 64-bit little endian ELF file".into());
+    }
+
+    let ehdr = header::Ehdr64::deserialize(bytes, 0)?;
+    let shdrs = section_headers(bytes);
+    // e_shstrndx も溢れていれば先頭エントリの sh_link に入っている
+    let shstrndx = match ehdr.e_shstrndx {
+        section::SHN_XINDEX => shdrs.first().map_or(0, |shdr| shdr.sh_link as usize),
+        shstrndx => usize::from(shstrndx),
+    };
+    let shstrtab_offset = shdrs
+        .get(shstrndx)
+        .map_or(0, |shdr| shdr.sh_offset as usize);
+
+    let sections = shdrs
+        .iter()
+        .map(|shdr| section::Section64 {
+            name: shstrtab_offset
+                .checked_add(shdr.sh_name as usize)
+                .and_then(|pos| read_cstr(bytes, pos))
+                .unwrap_or_default(),
+            header: *shdr,
+            contents: section_contents(bytes, &shdrs, shdr),
+        })
+        .collect();
+    let segments = program_headers(bytes)
+        .into_iter()
+        .map(|header| segment::Segment64 { header })
+        .collect();
+
+    Ok(file::ELF64 {
+        ehdr,
+        sections,
+        segments,
+    })
+}
+
+/// parse_elf64 と同じ形でセクションの中身を読み出す.
+/// 文字列テーブルは分割せずに Raw のまま返す.
+pub fn section_contents(
+    bytes: &[u8],
+    shdrs: &[section::Shdr64],
+    shdr: &section::Shdr64,
+) -> section::Contents64 {
+    let start = shdr.sh_offset as usize;
+    let end = start
+        .checked_add(shdr.sh_size as usize)
+        .map_or(bytes.len(), |end| end.min(bytes.len()));
+    let raw = bytes.get(start..end).unwrap_or_default();
+
+    match shdr.get_type() {
+        section::Type::NoBits => section::Contents64::Raw(Vec::new()),
+        section::Type::SymTab | section::Type::DynSym => {
+            let strtab_offset = shdrs
+                .get(shdr.sh_link as usize)
+                .map_or(0, |strtab| strtab.sh_offset as usize);
+            let symbols = (0..raw.len() / symbol::Symbol64::SIZE)
+                .filter_map(|idx| {
+                    let mut sym =
+                        symbol::Symbol64::deserialize(raw, idx * symbol::Symbol64::SIZE).ok()?;
+                    sym.symbol_name = strtab_offset
+                        .checked_add(sym.st_name as usize)
+                        .and_then(|pos| read_cstr(bytes, pos))
+                        .unwrap_or_default();
+                    Some(sym)
+                })
+                .collect();
+            section::Contents64::Symbols(symbols)
+        }
+        section::Type::Rela => section::Contents64::RelaSymbols(
+            (0..raw.len() / RELA_SIZE)
+                .filter_map(|idx| relocation::Rela64::deserialize(raw, idx * RELA_SIZE).ok())
+                .collect(),
+        ),
+        section::Type::Dynamic => section::Contents64::Dynamics(
+            (0..raw.len() / DYN_SIZE)
+                .filter_map(|idx| dynamic::Dyn64::deserialize(raw, idx * DYN_SIZE).ok())
+                .collect(),
+        ),
+        _ => section::Contents64::Raw(raw.to_vec()),
+    }
+}
+
+/// elf_utilities を経由せずにプログラムヘッダテーブルを読み出す.
+pub fn program_headers(bytes: &[u8]) -> Vec<segment::Phdr64> {
+    if !is_elf64(bytes) {
+        return Vec::new();
+    }
+
+    let phoff = read_u64(bytes, 0x20).unwrap_or(0) as usize;
+    let phnum = read_u16(bytes, 0x38).unwrap_or(0) as usize;
+
+    (0..phnum)
+        .map_while(|idx| {
+            let base = phoff.checked_add(idx * segment::Phdr64::SIZE)?;
+            Some(segment::Phdr64 {
+                p_type: read_u32(bytes, base)?,
+                p_flags: read_u32(bytes, base + 0x4)?,
+                p_offset: read_u64(bytes, base + 0x8)?,
+                p_vaddr: read_u64(bytes, base + 0x10)?,
+                p_paddr: read_u64(bytes, base + 0x18)?,
+                p_filesz: read_u64(bytes, base + 0x20)?,
+                p_memsz: read_u64(bytes, base + 0x28)?,
+                p_align: read_u64(bytes, base + 0x30)?,
+            })
+        })
+        .collect()
+}
+
+/// 仮想アドレスを, それを含む PT_LOAD セグメントを通してファイルオフセットに変換する.
+/// ファイル上に実体を持たない(.bss 等の)アドレスでは None を返す.
+pub fn vaddr_to_offset(phdrs: &[segment::Phdr64], addr: u64) -> Option<u64> {
+    phdrs
+        .iter()
+        .filter(|phdr| phdr.get_type() == segment::Type::Load)
+        .find(|phdr| phdr.p_vaddr <= addr && addr - phdr.p_vaddr < phdr.p_filesz)
+        .map(|phdr| phdr.p_offset + (addr - phdr.p_vaddr))
+}
@@ -0,0 +1,227 @@
+//! GNU IFUNC (STT_GNU_IFUNC のシンボルと R_*_IRELATIVE の再配置) をリゾルバごとにまとめる.
+//! リゾルバが選ぶ関数は, リゾルバの機械語に埋め込まれた関数のアドレスから推定する.
+
+use crate::elf_util::relocations::{self, Relocation, EM_AARCH64, EM_X86_64};
+use crate::elf_util::{address, read_u32};
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+    segment,
+};
+use std::collections::BTreeMap;
+
+pub const STT_FUNC: u8 = 2;
+pub const STT_GNU_IFUNC: u8 = 10;
+const R_X86_64_IRELATIVE: u32 = 37;
+const R_AARCH64_IRELATIVE: u32 = 1032;
+/// リゾルバの大きさが分からないときに調べるバイト数
+const RESOLVER_SCAN_LIMIT: u64 = 256;
+
+#[derive(Debug, Clone)]
+pub struct Ifunc {
+    pub resolver: u64,
+    /// STT_GNU_IFUNC のシンボル名
+    pub names: Vec<String>,
+    /// リゾルバ自身の関数シンボル
+    pub resolver_name: Option<String>,
+    pub irelatives: Vec<Relocation>,
+    /// リゾルバが返しうる関数 (シンボル名, アドレス)
+    pub candidates: Vec<(String, u64)>,
+}
+
+impl Ifunc {
+    pub fn display_name(&self) -> String {
+        match (self.names.first(), &self.resolver_name) {
+            (Some(name), _) => name.clone(),
+            (None, Some(resolver)) => format!("*{}", resolver),
+            (None, None) => format!("*0x{:x}", self.resolver),
+        }
+    }
+}
+
+/// IRELATIVE を誰が処理するか, 関数ポインタがどう決まるかに注意が要る文脈
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// 静的リンク (static-pie を含む). libc の起動処理が IRELATIVE を適用する
+    Static,
+    /// 非 PIE の実行ファイル. アドレスを取られた IFUNC は PLT のエントリが正準アドレスになる
+    NonPie,
+}
+
+pub fn contexts(elf_file: &file::ELF64) -> Vec<Context> {
+    let has_interp = elf_file
+        .segments
+        .iter()
+        .any(|seg| seg.header.get_type() == segment::Type::Interp);
+    let is_executable = match elf_file.ehdr.get_type() {
+        header::Type::Exec => true,
+        header::Type::Dyn => is_pie(elf_file),
+        _ => false,
+    };
+
+    let mut contexts = Vec::new();
+    if is_executable && !has_interp {
+        contexts.push(Context::Static);
+    }
+    if elf_file.ehdr.get_type() == header::Type::Exec {
+        contexts.push(Context::NonPie);
+    }
+    contexts
+}
+
+/// ET_DYN のうち, 共有ライブラリでなく PIE なもの (DT_FLAGS_1 の DF_1_PIE)
+pub fn is_pie(elf_file: &file::ELF64) -> bool {
+    const DT_FLAGS_1: i64 = 0x6fff_fffb;
+    const DF_1_PIE: u64 = 0x0800_0000;
+
+    elf_file
+        .sections
+        .iter()
+        .filter_map(|sct| match &sct.contents {
+            Contents64::Dynamics(dynamics) => Some(dynamics),
+            _ => None,
+        })
+        .flatten()
+        .any(|dynamic| dynamic.d_tag == DT_FLAGS_1 && dynamic.d_un & DF_1_PIE != 0)
+}
+
+/// symbol_tables は .symtab と .dynsym (あれば両方)
+pub fn ifuncs(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    symbol_tables: &[&section::Section64],
+) -> Vec<Ifunc> {
+    let mut ifuncs: BTreeMap<u64, Ifunc> = BTreeMap::new();
+
+    for symbols in symbol_tables.iter().filter_map(|sct| match &sct.contents {
+        Contents64::Symbols(symbols) => Some(symbols),
+        _ => None,
+    }) {
+        for sym in symbols
+            .iter()
+            .filter(|sym| sym.st_info & 0x0f == STT_GNU_IFUNC && sym.st_shndx != section::SHN_UNDEF)
+        {
+            let ifunc = entry(&mut ifuncs, sym.st_value);
+            if !ifunc.names.contains(&sym.symbol_name) {
+                ifunc.names.push(sym.symbol_name.clone());
+            }
+        }
+    }
+
+    let irelative = match elf_file.ehdr.e_machine {
+        EM_X86_64 => Some(R_X86_64_IRELATIVE),
+        EM_AARCH64 => Some(R_AARCH64_IRELATIVE),
+        _ => None,
+    };
+    if let Some(irelative) = irelative {
+        for reloc in relocations::relocations(elf_file)
+            .into_iter()
+            .filter(|reloc| reloc.r_type == irelative)
+        {
+            // SHT_REL なら加数は再配置先に埋め込まれているが, どちらも RELA だけを考える
+            if let Some(addend) = reloc.r_addend {
+                entry(&mut ifuncs, addend as u64).irelatives.push(reloc);
+            }
+        }
+    }
+
+    let functions = function_symbols(symbol_tables);
+    for ifunc in ifuncs.values_mut() {
+        let resolver = functions.get(&ifunc.resolver);
+        ifunc.resolver_name = resolver.map(|(name, _)| name.clone());
+        // リゾルバの機械語を読めるのは x86-64 だけ
+        if elf_file.ehdr.e_machine != EM_X86_64 {
+            continue;
+        }
+        let size = resolver
+            .map(|(_, size)| *size)
+            .filter(|size| *size != 0)
+            .unwrap_or(RESOLVER_SCAN_LIMIT);
+        ifunc.candidates = code(elf_file, bytes, ifunc.resolver, size)
+            .map(|code| candidates(code, ifunc.resolver, &functions))
+            .unwrap_or_default();
+    }
+
+    ifuncs.into_values().collect()
+}
+
+fn entry(ifuncs: &mut BTreeMap<u64, Ifunc>, resolver: u64) -> &mut Ifunc {
+    ifuncs.entry(resolver).or_insert(Ifunc {
+        resolver,
+        names: Vec::new(),
+        resolver_name: None,
+        irelatives: Vec::new(),
+        candidates: Vec::new(),
+    })
+}
+
+/// 関数の先頭アドレス → (名前, 大きさ)
+fn function_symbols(symbol_tables: &[&section::Section64]) -> BTreeMap<u64, (String, u64)> {
+    let mut functions = BTreeMap::new();
+    for symbols in symbol_tables.iter().filter_map(|sct| match &sct.contents {
+        Contents64::Symbols(symbols) => Some(symbols),
+        _ => None,
+    }) {
+        for sym in symbols.iter().filter(|sym| {
+            sym.st_info & 0x0f == STT_FUNC
+                && sym.st_shndx != section::SHN_UNDEF
+                && !sym.symbol_name.is_empty()
+        }) {
+            functions
+                .entry(sym.st_value)
+                .or_insert((sym.symbol_name.clone(), sym.st_size));
+        }
+    }
+    functions
+}
+
+/// addr から size バイトの, ファイル上にある機械語
+fn code<'a>(elf_file: &file::ELF64, bytes: &'a [u8], addr: u64, size: u64) -> Option<&'a [u8]> {
+    let info = address::lookup(elf_file, None, addr);
+    let (seg_idx, offset) = info.segment?;
+    let offset = offset? as usize;
+    let seg = &elf_file.segments[seg_idx].header;
+    let available = seg
+        .p_offset
+        .checked_add(seg.p_filesz)?
+        .checked_sub(offset as u64)?;
+    bytes.get(offset..offset.checked_add(size.min(available) as usize)?)
+}
+
+/// `lea disp32(%rip), %reg` と `mov $imm32, %reg` が指す関数の先頭を集める.
+fn candidates(
+    code: &[u8],
+    resolver: u64,
+    functions: &BTreeMap<u64, (String, u64)>,
+) -> Vec<(String, u64)> {
+    let mut found: Vec<(String, u64)> = Vec::new();
+    let mut push = |target: u64| {
+        if target == resolver || found.iter().any(|(_, addr)| *addr == target) {
+            return;
+        }
+        if let Some((name, _)) = functions.get(&target) {
+            found.push((name.clone(), target));
+        }
+    };
+
+    for pos in 0..code.len() {
+        match code[pos..] {
+            // REX.W lea: 48/4c 8d, ModRM の mod=00 rm=101 が RIP 相対
+            [0x48 | 0x4c, 0x8d, modrm, ..] if modrm & 0xc7 == 0x05 => {
+                if let Some(disp) = read_u32(code, pos + 3) {
+                    let next = resolver + pos as u64 + 7;
+                    push(next.wrapping_add(disp as i32 as i64 as u64));
+                }
+            }
+            // mov $imm32, %r32 (b8+r)
+            [0xb8..=0xbf, ..] => {
+                if let Some(imm) = read_u32(code, pos + 1) {
+                    push(u64::from(imm));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    found
+}
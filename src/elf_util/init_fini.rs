@@ -0,0 +1,218 @@
+//! .preinit_array/.init_array/.fini_array と古い形式の .ctors/.dtors のポインタを読み,
+//! 動的リンカ(と crtstuff)が呼び出す順に並べる.
+//! PIE や再配置可能ファイルではスロットの中身が再配置で埋まるので, 再配置から求める.
+
+use std::collections::HashMap;
+
+use crate::elf_util::relocations::{self, EM_AARCH64, EM_X86_64};
+use crate::elf_util::{address, read_u64};
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+};
+
+const SLOT_SIZE: usize = 8;
+const R_X86_64_64: u32 = 1;
+const R_X86_64_RELATIVE: u32 = 8;
+const R_AARCH64_ABS64: u32 = 257;
+const R_AARCH64_RELATIVE: u32 = 1027;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayKind {
+    PreInit,
+    Init,
+    Fini,
+    Ctors,
+    Dtors,
+}
+
+impl ArrayKind {
+    /// 起動時に呼ばれるなら true, 終了時なら false
+    pub fn is_startup(&self) -> bool {
+        matches!(self, Self::PreInit | Self::Init | Self::Ctors)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// 呼び出すアドレスと, そこにある関数 (シンボル名, 先頭からのずれ)
+    Address(u64, Option<(String, u64)>),
+    /// ET_REL で, あるセクション内のオフセットを指すもの. 関数のシンボルがあれば添える.
+    SectionOffset(usize, u64, Option<String>),
+    /// 他のオブジェクトで定義されるシンボル
+    External(String),
+    /// 読み解けない再配置で埋まるスロット. ファイル上の値と再配置の種類
+    Unresolved(u64, u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct InitFiniEntry {
+    pub kind: ArrayKind,
+    pub section: usize,
+    /// 配列内の添字
+    pub index: usize,
+    /// スロットのアドレス. ET_REL ではセクション内のオフセット
+    pub slot: u64,
+    pub target: Target,
+}
+
+/// 呼び出される順に並べた全エントリ.
+/// 起動時は preinit_array → .ctors (後ろから) → init_array,
+/// 終了時は fini_array (後ろから) → .dtors の順になる.
+pub fn init_fini_entries(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    symbol_table: Option<&section::Section64>,
+) -> Vec<InitFiniEntry> {
+    // ET_REL の r_offset はセクションごとのオフセットなので, 対象のセクションと組にして引く
+    let is_rel = elf_file.ehdr.get_type() == header::Type::Rel;
+    let mut relocations = HashMap::new();
+    for reloc in relocations::relocations(elf_file) {
+        let target_sct = if is_rel {
+            elf_file.sections[reloc.section].header.sh_info as usize
+        } else {
+            0
+        };
+        relocations
+            .entry((target_sct, reloc.r_offset))
+            .or_insert(reloc);
+    }
+    let mut arrays: Vec<(ArrayKind, Vec<InitFiniEntry>)> = Vec::new();
+
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        let kind = match array_kind(sct) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let start = sct.header.sh_offset as usize;
+        let data = match start
+            .checked_add(sct.header.sh_size as usize)
+            .and_then(|end| bytes.get(start..end))
+        {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let entries = (0..data.len() / SLOT_SIZE)
+            .filter_map(|index| {
+                let offset = (index * SLOT_SIZE) as u64;
+                let (slot, reloc) = if is_rel {
+                    (offset, relocations.get(&(sct_idx, offset)))
+                } else {
+                    let slot = sct.header.sh_addr.checked_add(offset)?;
+                    (slot, relocations.get(&(0, slot)))
+                };
+                let value = read_u64(data, index * SLOT_SIZE)?;
+                // .ctors/.dtors の先頭(-1)と末尾(0)は番兵
+                if matches!(kind, ArrayKind::Ctors | ArrayKind::Dtors)
+                    && reloc.is_none()
+                    && (value == 0 || value == u64::MAX)
+                {
+                    return None;
+                }
+
+                let target = match reloc {
+                    Some(reloc) => relocated_target(elf_file, symbol_table, reloc, value)
+                        .unwrap_or(Target::Unresolved(value, reloc.r_type)),
+                    None => Target::Address(value, function_symbol(symbol_table, value)),
+                };
+                Some(InitFiniEntry {
+                    kind,
+                    section: sct_idx,
+                    index,
+                    slot,
+                    target,
+                })
+            })
+            .collect();
+        arrays.push((kind, entries));
+    }
+
+    // ET_REL ではリンカが .init_array.NNNNN のような優先度付きの名前で並べ替える.
+    // 優先度の無いものは, 配列では後ろ, .ctors/.dtors では前に置かれる.
+    arrays.sort_by_key(|(kind, entries)| {
+        let priority = entries
+            .first()
+            .and_then(|entry| elf_file.sections[entry.section].name.rsplit('.').next())
+            .and_then(|suffix| suffix.parse::<u32>().ok());
+        match kind {
+            ArrayKind::Ctors | ArrayKind::Dtors => priority.unwrap_or(0),
+            _ => priority.unwrap_or(u32::MAX),
+        }
+    });
+
+    let mut ordered = Vec::new();
+    for kind in [
+        ArrayKind::PreInit,
+        ArrayKind::Ctors,
+        ArrayKind::Init,
+        ArrayKind::Fini,
+        ArrayKind::Dtors,
+    ] {
+        for (_, entries) in arrays.iter().filter(|(k, _)| *k == kind) {
+            match kind {
+                ArrayKind::Ctors | ArrayKind::Fini => ordered.extend(entries.iter().rev().cloned()),
+                _ => ordered.extend(entries.iter().cloned()),
+            }
+        }
+    }
+
+    ordered
+}
+
+fn array_kind(sct: &section::Section64) -> Option<ArrayKind> {
+    match sct.header.get_type() {
+        section::Type::PreInitArray => Some(ArrayKind::PreInit),
+        section::Type::InitArray => Some(ArrayKind::Init),
+        section::Type::FiniArray => Some(ArrayKind::Fini),
+        section::Type::ProgBits if sct.name.starts_with(".ctors") => Some(ArrayKind::Ctors),
+        section::Type::ProgBits if sct.name.starts_with(".dtors") => Some(ArrayKind::Dtors),
+        _ => None,
+    }
+}
+
+fn relocated_target(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    reloc: &relocations::Relocation,
+    value: u64,
+) -> Option<Target> {
+    // SHT_REL/SHT_RELR ではスロットの値が加数になる
+    let addend = reloc.r_addend.unwrap_or(value as i64) as u64;
+    match (elf_file.ehdr.e_machine, reloc.r_type) {
+        (EM_X86_64, R_X86_64_RELATIVE) | (EM_AARCH64, R_AARCH64_RELATIVE) => {
+            let addr = addend;
+            return Some(Target::Address(addr, function_symbol(symbol_table, addr)));
+        }
+        (EM_X86_64, R_X86_64_64) | (EM_AARCH64, R_AARCH64_ABS64) => {}
+        _ => return None,
+    }
+
+    let sym = match &elf_file
+        .sections
+        .get(elf_file.sections[reloc.section].header.sh_link as usize)?
+        .contents
+    {
+        Contents64::Symbols(symbols) => symbols.get(reloc.r_sym as usize)?,
+        _ => return None,
+    };
+    if sym.st_shndx == section::SHN_UNDEF {
+        return Some(Target::External(sym.symbol_name.clone()));
+    }
+    let value = sym.st_value.wrapping_add(addend);
+    match elf_file.ehdr.get_type() {
+        header::Type::Rel => {
+            let sct_idx = sym.st_shndx as usize;
+            Some(Target::SectionOffset(
+                sct_idx,
+                value,
+                address::section_function(elf_file, symbol_table, sct_idx, value),
+            ))
+        }
+        _ => Some(Target::Address(value, function_symbol(symbol_table, value))),
+    }
+}
+
+fn function_symbol(symbol_table: Option<&section::Section64>, addr: u64) -> Option<(String, u64)> {
+    address::nearest_symbol(symbol_table?, addr)
+}
@@ -0,0 +1,377 @@
+//! ld.so が参照するライブラリキャッシュ(/etc/ld.so.cache)の解析.
+//! libc5 時代の旧形式(ld.so-1.7.0)と glibc の新形式(glibc-ld.so.cache1.1),
+//! およびその両方を連結した形式に対応する.
+
+use std::path::PathBuf;
+
+use crate::elf_util::{read_cstr, read_u32, read_u64};
+
+pub const DEFAULT_LD_CACHE_PATH: &str = "/etc/ld.so.cache";
+
+const OLD_MAGIC: &[u8] = b"ld.so-1.7.0";
+const NEW_MAGIC: &[u8] = b"glibc-ld.so.cache";
+const NEW_VERSION: &[u8] = b"1.1";
+
+const OLD_HEADER_SIZE: usize = 16;
+const OLD_ENTRY_SIZE: usize = 12;
+const NEW_HEADER_SIZE: usize = 48;
+const NEW_ENTRY_SIZE: usize = 24;
+
+const EXTENSION_MAGIC: u32 = 0xeaa4_2174;
+const EXTENSION_TAG_GENERATOR: u32 = 0;
+const EXTENSION_TAG_GLIBC_HWCAPS: u32 = 1;
+/// hwcap の下位32bitが glibc-hwcaps サブディレクトリのインデックスであることを示す
+const HWCAP_EXTENSION: u64 = 1 << 62;
+
+pub const FLAG_TYPE_MASK: i32 = 0x00ff;
+pub const FLAG_REQUIRED_MASK: i32 = 0xff00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Old,
+    New,
+    /// 旧形式の後ろに新形式が続く互換形式
+    Compat,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub name: String,
+    pub flags: i32,
+    pub osversion: u32,
+    pub hwcap: u64,
+    pub path: String,
+    /// glibc-hwcaps サブディレクトリ名 (例: x86-64-v3)
+    pub hwcaps_subdir: Option<String>,
+}
+
+impl CacheEntry {
+    /// ライブラリの種類 (FLAG_TYPE_MASK 部分)
+    pub fn type_string<'a>(&self) -> &'a str {
+        match self.flags & FLAG_TYPE_MASK {
+            0 => "libc4",
+            1 => "ELF",
+            2 => "libc5",
+            3 => "libc6",
+            _ => "unknown",
+        }
+    }
+
+    /// 対象アーキテクチャ (FLAG_REQUIRED_MASK 部分)
+    pub fn arch_string<'a>(&self) -> &'a str {
+        match self.flags & FLAG_REQUIRED_MASK {
+            0x0000 => "",
+            0x0100 => "SPARC 64bit",
+            0x0200 => "IA-64",
+            0x0300 => "x86-64",
+            0x0400 => "s390 64bit",
+            0x0500 => "PowerPC 64bit",
+            0x0600 => "MIPS N32",
+            0x0700 => "MIPS 64bit",
+            0x0800 => "x32",
+            0x0900 => "ARM hard-float",
+            0x0a00 => "AArch64",
+            0x0b00 => "ARM soft-float",
+            0x0c00 => "MIPS nan2008",
+            0x0d00 => "MIPS N32 nan2008",
+            0x0e00 => "MIPS 64bit nan2008",
+            0x0f00 => "RISC-V soft-float",
+            0x1000 => "RISC-V double-float",
+            0x1100 => "LoongArch soft-float",
+            0x1200 => "LoongArch double-float",
+            _ => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LdCache {
+    pub path: String,
+    pub format: Format,
+    pub entries: Vec<CacheEntry>,
+    /// ldconfig が書き込んだ生成元の情報
+    pub generator: Option<String>,
+}
+
+impl LdCache {
+    pub fn read(path: &str) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        Self::parse(path, &bytes)
+    }
+
+    pub fn parse(path: &str, bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(NEW_MAGIC) {
+            let (entries, generator) = parse_new(bytes, 0)?;
+            return Some(Self {
+                path: path.to_string(),
+                format: Format::New,
+                entries,
+                generator,
+            });
+        }
+
+        if !bytes.starts_with(OLD_MAGIC) {
+            return None;
+        }
+
+        let nlibs = read_u32(bytes, 12)? as usize;
+        let old_end = OLD_HEADER_SIZE + nlibs * OLD_ENTRY_SIZE;
+
+        // 新形式は旧形式のエントリ列の直後, 8byte境界に置かれる
+        let new_start = (old_end + 7) & !7;
+        if bytes
+            .get(new_start..)
+            .is_some_and(|b| b.starts_with(NEW_MAGIC))
+        {
+            if let Some((entries, generator)) = parse_new(bytes, new_start) {
+                return Some(Self {
+                    path: path.to_string(),
+                    format: Format::Compat,
+                    entries,
+                    generator,
+                });
+            }
+        }
+
+        // 旧形式の文字列オフセットはエントリ列の直後からの相対値
+        let entries = (0..nlibs)
+            .map_while(|idx| {
+                let base = OLD_HEADER_SIZE + idx * OLD_ENTRY_SIZE;
+                Some(CacheEntry {
+                    flags: read_u32(bytes, base)? as i32,
+                    name: read_cstr(bytes, old_end + read_u32(bytes, base + 4)? as usize)?,
+                    path: read_cstr(bytes, old_end + read_u32(bytes, base + 8)? as usize)?,
+                    osversion: 0,
+                    hwcap: 0,
+                    hwcaps_subdir: None,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            path: path.to_string(),
+            format: Format::Old,
+            entries,
+            generator: None,
+        })
+    }
+
+    /// ld.so と同様に, 先頭から順に名前とアーキテクチャが一致するエントリを探す.
+    pub fn lookup(&self, name: &str, required_flag: Option<i32>) -> Option<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.name == name)
+            .find(|entry| required_flag.is_none_or(|flag| entry.flags & FLAG_REQUIRED_MASK == flag))
+            .map(|entry| PathBuf::from(&entry.path))
+    }
+}
+
+/// e_machine から, キャッシュエントリに要求されるアーキテクチャフラグを求める.
+pub fn required_flag_for_machine(e_machine: u16) -> Option<i32> {
+    match e_machine {
+        // EM_X86_64
+        62 => Some(0x0300),
+        // EM_AARCH64
+        183 => Some(0x0a00),
+        _ => None,
+    }
+}
+
+/// 新形式では文字列オフセットが新形式ヘッダ先頭からの相対値になっている.
+fn parse_new(bytes: &[u8], start: usize) -> Option<(Vec<CacheEntry>, Option<String>)> {
+    let version_start = start + NEW_MAGIC.len();
+    if bytes.get(version_start..version_start + NEW_VERSION.len())? != NEW_VERSION {
+        return None;
+    }
+
+    let nlibs = read_u32(bytes, start + 20)? as usize;
+    let extension_offset = read_u32(bytes, start + 32)? as usize;
+    let (hwcaps, generator) = if extension_offset != 0 {
+        parse_extensions(bytes, start, start + extension_offset)
+    } else {
+        (Vec::new(), None)
+    };
+
+    let entries = (0..nlibs)
+        .map_while(|idx| {
+            let base = start + NEW_HEADER_SIZE + idx * NEW_ENTRY_SIZE;
+            let hwcap = read_u64(bytes, base + 16)?;
+            let hwcaps_subdir = if hwcap & HWCAP_EXTENSION != 0 {
+                hwcaps.get((hwcap & 0xffff_ffff) as usize).cloned()
+            } else {
+                None
+            };
+
+            Some(CacheEntry {
+                flags: read_u32(bytes, base)? as i32,
+                name: read_cstr(bytes, start + read_u32(bytes, base + 4)? as usize)?,
+                path: read_cstr(bytes, start + read_u32(bytes, base + 8)? as usize)?,
+                osversion: read_u32(bytes, base + 12)?,
+                hwcap,
+                hwcaps_subdir,
+            })
+        })
+        .collect();
+
+    Some((entries, generator))
+}
+
+/// 拡張セクションから glibc-hwcaps サブディレクトリ名と生成元文字列を読み出す.
+fn parse_extensions(bytes: &[u8], start: usize, offset: usize) -> (Vec<String>, Option<String>) {
+    let mut hwcaps = Vec::new();
+    let mut generator = None;
+
+    if read_u32(bytes, offset) != Some(EXTENSION_MAGIC) {
+        return (hwcaps, generator);
+    }
+
+    let count = read_u32(bytes, offset + 4).unwrap_or(0) as usize;
+    for idx in 0..count {
+        let base = offset + 8 + idx * 16;
+        let (tag, sct_offset, size) = match (
+            read_u32(bytes, base),
+            read_u32(bytes, base + 8),
+            read_u32(bytes, base + 12),
+        ) {
+            (Some(tag), Some(o), Some(s)) => (tag, start + o as usize, s as usize),
+            _ => break,
+        };
+
+        match tag {
+            EXTENSION_TAG_GENERATOR => {
+                generator = bytes
+                    .get(sct_offset..sct_offset + size)
+                    .map(|b| String::from_utf8_lossy(b).to_string());
+            }
+            EXTENSION_TAG_GLIBC_HWCAPS => {
+                hwcaps = (0..size / 4)
+                    .filter_map(|i| {
+                        let name = read_u32(bytes, sct_offset + i * 4)?;
+                        read_cstr(bytes, start + name as usize)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    (hwcaps, generator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (flags, name, path) から旧形式のキャッシュを作る
+    fn old_cache(entries: &[(i32, &str, &str)]) -> Vec<u8> {
+        let mut bytes = OLD_MAGIC.to_vec();
+        bytes.resize(12, 0);
+        bytes.extend((entries.len() as u32).to_le_bytes());
+
+        let mut strings = Vec::new();
+        for (flags, name, path) in entries {
+            bytes.extend(flags.to_le_bytes());
+            for s in [name, path] {
+                bytes.extend((strings.len() as u32).to_le_bytes());
+                strings.extend(s.as_bytes());
+                strings.push(0);
+            }
+        }
+        bytes.extend(strings);
+        bytes
+    }
+
+    /// (flags, name, path, hwcap) から新形式のキャッシュを作る
+    fn new_cache(entries: &[(i32, &str, &str, u64)]) -> Vec<u8> {
+        let mut bytes = NEW_MAGIC.to_vec();
+        bytes.extend(NEW_VERSION);
+        bytes.extend((entries.len() as u32).to_le_bytes());
+        bytes.resize(NEW_HEADER_SIZE, 0);
+
+        // 文字列オフセットは新形式ヘッダ先頭から数える
+        let mut offset = NEW_HEADER_SIZE + entries.len() * NEW_ENTRY_SIZE;
+        let mut strings = Vec::new();
+        for (flags, name, path, hwcap) in entries {
+            bytes.extend(flags.to_le_bytes());
+            for s in [name, path] {
+                bytes.extend((offset as u32).to_le_bytes());
+                strings.extend(s.as_bytes());
+                strings.push(0);
+                offset += s.len() + 1;
+            }
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend(hwcap.to_le_bytes());
+        }
+        bytes.extend(strings);
+        bytes
+    }
+
+    #[test]
+    fn old_format() {
+        let bytes = old_cache(&[
+            (0x0303, "libc.so.6", "/lib/libc.so.6"),
+            (0x0003, "libm.so.6", "/lib/libm.so.6"),
+        ]);
+        let cache = LdCache::parse("test", &bytes).unwrap();
+
+        assert_eq!(cache.format, Format::Old);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.entries[1].name, "libm.so.6");
+        assert_eq!(cache.entries[1].path, "/lib/libm.so.6");
+        assert_eq!(cache.entries[0].arch_string(), "x86-64");
+        assert_eq!(cache.entries[0].type_string(), "libc6");
+    }
+
+    #[test]
+    fn new_format() {
+        let bytes = new_cache(&[
+            (0x0a03, "libc.so.6", "/lib/aarch64-linux-gnu/libc.so.6", 0),
+            (0x0303, "libc.so.6", "/lib/x86_64-linux-gnu/libc.so.6", 0),
+        ]);
+        let cache = LdCache::parse("test", &bytes).unwrap();
+
+        assert_eq!(cache.format, Format::New);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.entries[1].path, "/lib/x86_64-linux-gnu/libc.so.6");
+        assert_eq!(cache.generator, None);
+        // アーキテクチャが一致する最初のエントリを選ぶ
+        assert_eq!(
+            cache.lookup("libc.so.6", required_flag_for_machine(62)),
+            Some(PathBuf::from("/lib/x86_64-linux-gnu/libc.so.6"))
+        );
+        assert_eq!(cache.lookup("libm.so.6", None), None);
+    }
+
+    #[test]
+    fn compat_format_uses_new_entries() {
+        // 旧形式のエントリは, 後ろに続く新形式の文字列を共有する
+        let new = new_cache(&[(0x0303, "libc.so.6", "/lib/libc.so.6", 0)]);
+        let old_end = OLD_HEADER_SIZE + OLD_ENTRY_SIZE;
+        let new_start = (old_end + 7) & !7;
+        let name = (new_start + NEW_HEADER_SIZE + NEW_ENTRY_SIZE - old_end) as u32;
+
+        let mut bytes = OLD_MAGIC.to_vec();
+        bytes.resize(12, 0);
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(0x0303i32.to_le_bytes());
+        bytes.extend(name.to_le_bytes());
+        bytes.extend((name + "libc.so.6\0".len() as u32).to_le_bytes());
+        bytes.resize(new_start, 0);
+        bytes.extend(new);
+
+        let cache = LdCache::parse("test", &bytes).unwrap();
+        assert_eq!(cache.format, Format::Compat);
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.entries[0].name, "libc.so.6");
+        assert_eq!(cache.entries[0].path, "/lib/libc.so.6");
+    }
+
+    #[test]
+    fn rejects_unknown_magic_and_version() {
+        assert!(LdCache::parse("test", b"not a cache").is_none());
+
+        let mut bytes = new_cache(&[]);
+        bytes[NEW_MAGIC.len()..NEW_MAGIC.len() + 3].copy_from_slice(b"1.0");
+        assert!(LdCache::parse("test", &bytes).is_none());
+    }
+}
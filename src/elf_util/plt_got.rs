@@ -0,0 +1,233 @@
+//! .plt/.plt.sec/.plt.got のスタブと .got/.got.plt のスロットを並べ,
+//! JUMP_SLOT/GLOB_DAT などの再配置と対応付ける.
+//! スタブが使うスロットは `jmp *disp32(%rip)` の飛び先から, 遅延束縛のスタブは push する番号から求める.
+
+use std::collections::HashMap;
+
+use crate::elf_util::relocations::{self, Relocation, EM_X86_64};
+use crate::elf_util::{read_u32, read_u64};
+use elf_utilities::{file, header, section};
+
+const GOT_ENTRY_SIZE: u64 = 8;
+const PLT_ENTRY_SIZE: u64 = 16;
+
+#[derive(Debug, Clone)]
+pub struct PltStub {
+    pub section: usize,
+    /// セクション内のエントリ番号
+    pub index: usize,
+    pub address: u64,
+    pub size: u64,
+    /// 間接ジャンプで参照する GOT スロットのアドレス
+    pub slot: Option<u64>,
+    /// 遅延束縛で push する .rela.plt の番号
+    pub push_index: Option<u32>,
+    pub reloc: Option<Relocation>,
+    pub symbol: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GotSlot {
+    pub section: usize,
+    pub index: usize,
+    pub address: u64,
+    /// ファイル上の初期値
+    pub value: Option<u64>,
+    pub reloc: Option<Relocation>,
+    pub symbol: Option<String>,
+}
+
+pub struct PltGot {
+    pub stubs: Vec<PltStub>,
+    pub slots: Vec<GotSlot>,
+}
+
+impl PltStub {
+    /// .plt の先頭は _dl_runtime_resolve を呼ぶ PLT0
+    pub fn is_resolver(&self, elf_file: &file::ELF64) -> bool {
+        self.index == 0 && elf_file.sections[self.section].name == ".plt"
+    }
+}
+
+impl GotSlot {
+    /// .got.plt の先頭3つは動的リンカが使う
+    pub fn reserved<'a>(&self, elf_file: &file::ELF64) -> Option<&'a str> {
+        if elf_file.sections[self.section].name != ".got.plt" {
+            return None;
+        }
+        match self.index {
+            0 => Some("GOT[0]: address of _DYNAMIC"),
+            1 => Some("GOT[1]: link_map, set by ld.so"),
+            2 => Some("GOT[2]: _dl_runtime_resolve, set by ld.so"),
+            _ => None,
+        }
+    }
+}
+
+impl PltGot {
+    pub fn is_empty(&self) -> bool {
+        self.stubs.is_empty() && self.slots.is_empty()
+    }
+
+    pub fn slot(&self, address: u64) -> Option<&GotSlot> {
+        self.slots.iter().find(|slot| slot.address == address)
+    }
+
+    /// スロットを通してジャンプする, または遅延束縛でスロットを埋めるスタブ
+    pub fn stubs_of(&self, slot: &GotSlot) -> Vec<&PltStub> {
+        self.stubs
+            .iter()
+            .filter(|stub| {
+                stub.slot == Some(slot.address)
+                    || (stub.slot.is_none()
+                        && stub.reloc.map(|reloc| reloc.r_offset) == Some(slot.address))
+            })
+            .collect()
+    }
+}
+
+/// 実行ファイルと共有ライブラリの PLT/GOT. 再配置可能ファイルにはまだ無い.
+pub fn plt_got(elf_file: &file::ELF64, bytes: &[u8]) -> PltGot {
+    if elf_file.ehdr.get_type() == header::Type::Rel {
+        return PltGot {
+            stubs: Vec::new(),
+            slots: Vec::new(),
+        };
+    }
+
+    let relocations = relocations::relocations(elf_file);
+    // 同じアドレスへの再配置が複数あれば最初のものを使う
+    let mut by_offset: HashMap<u64, Relocation> = HashMap::new();
+    for reloc in relocations.iter() {
+        by_offset.entry(reloc.r_offset).or_insert(*reloc);
+    }
+    let rela_plt = elf_file
+        .sections
+        .iter()
+        .position(|sct| sct.name == ".rela.plt" || sct.name == ".rel.plt");
+    let plt_relocations: Vec<&Relocation> = relocations
+        .iter()
+        .filter(|reloc| Some(reloc.section) == rela_plt)
+        .collect();
+
+    let mut slots = Vec::new();
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        if sct.name != ".got" && sct.name != ".got.plt" {
+            continue;
+        }
+        let data = section_data(sct, bytes);
+        for index in 0..(sct.header.sh_size / GOT_ENTRY_SIZE) as usize {
+            let address = match sct
+                .header
+                .sh_addr
+                .checked_add(index as u64 * GOT_ENTRY_SIZE)
+            {
+                Some(address) => address,
+                None => break,
+            };
+            let reloc = by_offset.get(&address).copied();
+            slots.push(GotSlot {
+                section: sct_idx,
+                index,
+                address,
+                value: data.and_then(|data| read_u64(data, index * GOT_ENTRY_SIZE as usize)),
+                reloc,
+                symbol: reloc.and_then(|reloc| reloc_symbol(elf_file, &reloc)),
+            });
+        }
+    }
+
+    // スタブの命令を読むのは x86-64 だけ
+    let mut stubs = Vec::new();
+    if elf_file.ehdr.e_machine != EM_X86_64 {
+        return PltGot { stubs, slots };
+    }
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        if !matches!(sct.name.as_str(), ".plt" | ".plt.sec" | ".plt.got") {
+            continue;
+        }
+        let data = match section_data(sct, bytes) {
+            Some(data) => data,
+            None => continue,
+        };
+        let size = match sct.header.sh_entsize {
+            0 => PLT_ENTRY_SIZE,
+            entsize => entsize,
+        };
+        for index in 0..(sct.header.sh_size / size) as usize {
+            let start = index * size as usize;
+            let entry = match data.get(start..start + size as usize) {
+                Some(entry) => entry,
+                None => break,
+            };
+            let address = match sct.header.sh_addr.checked_add(start as u64) {
+                Some(address) => address,
+                None => break,
+            };
+            let slot = jump_slot(entry, address);
+            let push_index = push_index(entry);
+            let reloc = match (slot, push_index) {
+                (Some(slot), _) => by_offset.get(&slot).copied(),
+                (None, Some(push_index)) => plt_relocations
+                    .get(push_index as usize)
+                    .filter(|reloc| reloc.index == push_index as usize)
+                    .map(|reloc| **reloc),
+                _ => None,
+            };
+            stubs.push(PltStub {
+                section: sct_idx,
+                index,
+                address,
+                size,
+                slot,
+                push_index,
+                reloc,
+                symbol: reloc.and_then(|reloc| reloc_symbol(elf_file, &reloc)),
+            });
+        }
+    }
+
+    PltGot { stubs, slots }
+}
+
+/// `jmp *disp32(%rip)` (ff 25, bnd 付きなら f2 ff 25) の参照先.
+/// PLT0 の `push GOT[1]` (ff 35) は読み飛ばす.
+fn jump_slot(entry: &[u8], address: u64) -> Option<u64> {
+    let pos = entry.windows(2).position(|op| op == [0xff, 0x25])?;
+    let disp = read_u32(entry, pos + 2)? as i32 as i64;
+    Some(
+        address
+            .wrapping_add(pos as u64 + 6)
+            .wrapping_add(disp as u64),
+    )
+}
+
+/// 遅延束縛のスタブの `push imm32` (68)
+fn push_index(entry: &[u8]) -> Option<u32> {
+    // IBT 有効時は endbr64 (f3 0f 1e fa) のあとに置かれる
+    let pos = match entry {
+        [0xf3, 0x0f, 0x1e, 0xfa, 0x68, ..] => 4,
+        [0xff, 0x25, _, _, _, _, 0x68, ..] => 6,
+        _ => return None,
+    };
+    read_u32(entry, pos + 1)
+}
+
+fn section_data<'a>(sct: &section::Section64, bytes: &'a [u8]) -> Option<&'a [u8]> {
+    if sct.header.get_type() == section::Type::NoBits {
+        return None;
+    }
+    let start = sct.header.sh_offset as usize;
+    bytes.get(start..start.checked_add(sct.header.sh_size as usize)?)
+}
+
+/// 再配置が参照するシンボル名. IRELATIVE などシンボルが無ければ加数を使う.
+fn reloc_symbol(elf_file: &file::ELF64, reloc: &Relocation) -> Option<String> {
+    if reloc.r_sym == 0 {
+        return reloc
+            .r_addend
+            .filter(|addend| *addend != 0)
+            .map(|addend| format!("*ABS*+0x{:x}", addend));
+    }
+    relocations::symbol_name(elf_file, reloc)
+}
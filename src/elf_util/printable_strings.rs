@@ -0,0 +1,287 @@
+//! strings(1) と同様に, セクションから表示可能な文字列を抜き出す.
+//! 圧縮されたデバッグ情報は展開してから探す.
+//! ASCII/UTF-8 に加えて UTF-16LE も探し, 文字列のアドレスを指すシンボルや再配置を引けるようにする.
+
+use std::collections::BTreeMap;
+
+use crate::elf_util::compression;
+use crate::elf_util::relocations::{self, Relocation};
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+    symbol,
+};
+
+pub const DEFAULT_MIN_LENGTH: usize = 4;
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Ascii,
+    Utf8,
+    Utf16Le,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrintableString {
+    pub section: usize,
+    /// 圧縮されたセクションでは展開後の中身にあるので, ファイル上の位置は無い
+    pub offset: Option<u64>,
+    /// 再配置可能ファイルとロードされないセクションではセクション内のオフセット
+    pub addr: u64,
+    /// SHF_ALLOC なセクションにある
+    pub loaded: bool,
+    /// バイト数
+    pub size: u64,
+    pub encoding: Encoding,
+    pub value: String,
+}
+
+/// 文字列のアドレスを参照しているもの
+#[derive(Debug, Clone)]
+pub enum Xref {
+    Symbol {
+        symbol_table: usize,
+        index: usize,
+        name: String,
+    },
+    Relocation(Relocation),
+}
+
+/// アドレスから参照元を引くための索引.
+/// 再配置可能ファイルではアドレスがセクションごとに 0 から始まるので, セクション番号も鍵にする.
+pub struct Xrefs {
+    relocatable: bool,
+    map: BTreeMap<(usize, u64), Vec<Xref>>,
+}
+
+impl Xrefs {
+    pub fn new(elf_file: &file::ELF64, bytes: &[u8]) -> Self {
+        let relocatable = elf_file.ehdr.get_type() == header::Type::Rel;
+        let mut xrefs = Self {
+            relocatable,
+            map: BTreeMap::new(),
+        };
+
+        for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+            let symbols = match &sct.contents {
+                Contents64::Symbols(symbols) => symbols,
+                _ => continue,
+            };
+            for (idx, sym) in symbols.iter().enumerate() {
+                let is_data = matches!(
+                    sym.get_type(),
+                    symbol::Type::Object | symbol::Type::NoType | symbol::Type::Func
+                );
+                if sym.st_shndx == section::SHN_UNDEF || !is_data || sym.symbol_name.is_empty() {
+                    continue;
+                }
+                xrefs.insert(
+                    sym.st_shndx as usize,
+                    sym.st_value,
+                    Xref::Symbol {
+                        symbol_table: sct_idx,
+                        index: idx,
+                        name: sym.symbol_name.clone(),
+                    },
+                );
+            }
+        }
+
+        for reloc in relocations::relocations(elf_file) {
+            // 埋め込みの幅が分からない SHT_REL の加数は 0 とみなし, シンボルそのものを指すとする
+            let addend = relocations::addend(elf_file, bytes, &reloc).unwrap_or(0);
+            let reloc_sct = &elf_file.sections[reloc.section];
+            let sym = match elf_file
+                .sections
+                .get(reloc_sct.header.sh_link as usize)
+                .map(|sct| &sct.contents)
+            {
+                Some(Contents64::Symbols(symbols)) => symbols.get(reloc.r_sym as usize),
+                _ => None,
+            };
+            // r_sym == 0 (RELATIVE 等) なら加数がそのままアドレスになる
+            let (shndx, value) = match sym {
+                Some(sym) if reloc.r_sym != 0 => (sym.st_shndx as usize, sym.st_value),
+                _ => (0, 0),
+            };
+            let bias = relocations::pc_relative_field_size(elf_file.ehdr.e_machine, reloc.r_type)
+                .unwrap_or(0);
+            xrefs.insert(
+                shndx,
+                value.wrapping_add(addend as u64).wrapping_add(bias),
+                Xref::Relocation(reloc),
+            );
+        }
+
+        xrefs
+    }
+
+    fn insert(&mut self, section: usize, addr: u64, xref: Xref) {
+        let key = (if self.relocatable { section } else { 0 }, addr);
+        self.map.entry(key).or_default().push(xref);
+    }
+
+    /// 文字列の範囲内を指す参照元と, 文字列先頭からのずれ
+    pub fn find(&self, string: &PrintableString) -> Vec<(u64, &Xref)> {
+        // ロードされないセクションにはアドレスが無いので, 引けるのは再配置可能ファイルだけ
+        if !self.relocatable && !string.loaded {
+            return Vec::new();
+        }
+        let section = if self.relocatable { string.section } else { 0 };
+        self.map
+            .range(
+                (section, string.addr)..(section, string.addr.saturating_add(string.size.max(1))),
+            )
+            .flat_map(|((_, addr), xrefs)| xrefs.iter().map(move |x| (addr - string.addr, x)))
+            .collect()
+    }
+}
+
+/// SHF_ALLOC なセクションから min_length 文字以上の文字列を抜き出す.
+/// 機械語は偶然文字列に見えるバイト列だらけなので, 実行可能なセクションは対象にしない.
+/// include_unloaded なら, ロードされない .comment や .debug_* のような PROGBITS も対象にする.
+pub fn extract(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    min_length: usize,
+    include_unloaded: bool,
+) -> Vec<PrintableString> {
+    let mut strings = Vec::new();
+
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        let loaded = sct.header.sh_flags & SHF_ALLOC != 0;
+        let target = if loaded {
+            sct.header.sh_flags & SHF_EXECINSTR == 0
+                && sct.header.get_type() != section::Type::NoBits
+        } else {
+            include_unloaded && sct.header.get_type() == section::Type::ProgBits
+        };
+        if !target {
+            continue;
+        }
+        let compressed = compression::compression(sct, bytes).is_some();
+        let data = match compression::section_data(sct, bytes) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let mut found = scan_utf8(&data, min_length);
+        found.extend(scan_utf16le(&data, min_length));
+        found.sort_by_key(|(pos, ..)| *pos);
+
+        let base = if loaded { sct.header.sh_addr } else { 0 };
+        strings.extend(
+            found
+                .into_iter()
+                .map(|(pos, size, encoding, value)| PrintableString {
+                    section: sct_idx,
+                    offset: if compressed {
+                        None
+                    } else {
+                        Some(sct.header.sh_offset.saturating_add(pos as u64))
+                    },
+                    addr: base.wrapping_add(pos as u64),
+                    loaded,
+                    size: size as u64,
+                    encoding,
+                    value,
+                }),
+        );
+    }
+
+    strings
+}
+
+type Found = (usize, usize, Encoding, String);
+
+fn scan_utf8(data: &[u8], min_length: usize) -> Vec<Found> {
+    let mut found = Vec::new();
+    let mut run_start = 0;
+    let mut run = String::new();
+    let mut pos = 0;
+
+    let mut flush = |run: &mut String, run_start: usize, end: usize| {
+        // 非 ASCII の並びは偶然できやすいので, NUL 終端された C 文字列に限る
+        let encoding = if run.is_ascii() {
+            Some(Encoding::Ascii)
+        } else if data.get(end) == Some(&0) {
+            Some(Encoding::Utf8)
+        } else {
+            None
+        };
+        if let Some(encoding) = encoding.filter(|_| run.chars().count() >= min_length) {
+            found.push((run_start, end - run_start, encoding, run.clone()));
+        }
+        run.clear();
+    };
+
+    while pos < data.len() {
+        match decode_utf8(&data[pos..]) {
+            Some((c, len)) if is_printable(c) => {
+                if run.is_empty() {
+                    run_start = pos;
+                }
+                run.push(c);
+                pos += len;
+            }
+            _ => {
+                flush(&mut run, run_start, pos);
+                pos += 1;
+            }
+        }
+    }
+    flush(&mut run, run_start, pos);
+
+    found
+}
+
+/// ASCII 文字列や数値の表を誤検出しないよう, UTF-16 では Latin-1 の範囲の文字のみ認める.
+fn scan_utf16le(data: &[u8], min_length: usize) -> Vec<Found> {
+    let mut found = Vec::new();
+    let mut run_start = 0;
+    let mut run = String::new();
+
+    for pos in (0..data.len() / 2 * 2).step_by(2) {
+        let unit = u16::from_le_bytes([data[pos], data[pos + 1]]) as u32;
+        match char::from_u32(unit) {
+            Some(c) if c.is_ascii() && is_printable(c) || c.is_alphabetic() && unit < 0x100 => {
+                if run.is_empty() {
+                    run_start = pos;
+                }
+                run.push(c);
+            }
+            _ => {
+                // NUL 終端されていて, 半分以上が英字であるものに限る
+                let letters = run.chars().filter(|c| c.is_ascii_alphabetic()).count();
+                if unit == 0
+                    && run.chars().count() >= min_length
+                    && letters * 2 >= run.chars().count()
+                {
+                    found.push((run_start, pos - run_start, Encoding::Utf16Le, run.clone()));
+                }
+                run.clear();
+            }
+        }
+    }
+
+    found
+}
+
+fn decode_utf8(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match bytes.first()? {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return None,
+    };
+    let s = std::str::from_utf8(bytes.get(..len)?).ok()?;
+    s.chars().next().map(|c| (c, len))
+}
+
+fn is_printable(c: char) -> bool {
+    c == '\t' || !c.is_control()
+}
@@ -0,0 +1,506 @@
+//! セクションヘッダテーブルを持たない(sstrip 等で削られた)ファイルについて,
+//! PT_DYNAMIC と DT_* エントリが指すアドレスから動的リンク関連のセクションを組み立て直す.
+//! アドレスは PT_LOAD を通してファイルオフセットに変換する.
+
+use std::collections::HashMap;
+
+use crate::elf_util::{self, versions};
+use elf_utilities::{file, header, section, segment, symbol};
+
+const DT_NULL: i64 = 0;
+const DT_PLTRELSZ: i64 = 2;
+const DT_HASH: i64 = 4;
+const DT_STRTAB: i64 = 5;
+const DT_SYMTAB: i64 = 6;
+const DT_RELA: i64 = 7;
+const DT_RELASZ: i64 = 8;
+const DT_STRSZ: i64 = 10;
+const DT_REL: i64 = 17;
+const DT_RELSZ: i64 = 18;
+const DT_PLTREL: i64 = 20;
+const DT_JMPREL: i64 = 23;
+const DT_RELRSZ: i64 = 35;
+const DT_RELR: i64 = 36;
+const DT_ANDROID_REL: i64 = 0x6000_000f;
+const DT_ANDROID_RELSZ: i64 = 0x6000_0010;
+const DT_ANDROID_RELA: i64 = 0x6000_0011;
+const DT_ANDROID_RELASZ: i64 = 0x6000_0012;
+const DT_GNU_HASH: i64 = 0x6fff_fef5;
+const DT_VERSYM: i64 = 0x6fff_fff0;
+const DT_VERDEF: i64 = 0x6fff_fffc;
+const DT_VERDEFNUM: i64 = 0x6fff_fffd;
+const DT_VERNEED: i64 = 0x6fff_fffe;
+const DT_VERNEEDNUM: i64 = 0x6fff_ffff;
+
+const SHT_RELR: u32 = 19;
+const SHT_ANDROID_REL: u32 = 0x6000_0001;
+const SHT_ANDROID_RELA: u32 = 0x6000_0002;
+const SHT_GNU_HASH: u32 = 0x6fff_fff6;
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+
+const DYN_SIZE: u64 = 0x10;
+const REL_SIZE: u64 = 0x10;
+const RELA_SIZE: u64 = 0x18;
+const RELR_SIZE: u64 = 0x8;
+/// APS2 は可変長なので, lld にならって 1 とする
+const ANDROID_RELOCATION_SIZE: u64 = 0x1;
+
+/// DT_* から見つけたセクションの候補.
+/// (名前, 種類, アドレス, サイズ, エントリサイズ, sh_link の対象, sh_info)
+struct Candidate(&'static str, u32, u64, u64, u64, Option<&'static str>, u32);
+
+/// 組み立て直したセクションヘッダ
+pub struct ReconstructedSection {
+    pub name: &'static str,
+    pub header: section::Shdr64,
+}
+
+/// セクションヘッダテーブルを持ち, それがファイルに収まっているかどうか.
+/// e_shnum が 0 で個数を sh_size に逃がす拡張形式も, テーブルがあるものとして扱う.
+pub fn has_section_headers(bytes: &[u8]) -> bool {
+    let e_shoff = elf_util::read_u64(bytes, 0x28).unwrap_or(0);
+    let e_shentsize = u64::from(elf_util::read_u16(bytes, 0x3a).unwrap_or(0));
+    let shnum = elf_util::section_count(bytes) as u64;
+    if e_shoff == 0 || shnum == 0 {
+        return false;
+    }
+    shnum
+        .checked_mul(e_shentsize)
+        .and_then(|size| e_shoff.checked_add(size))
+        .is_some_and(|end| end <= bytes.len() as u64)
+}
+
+/// セクションヘッダを持たないファイルを, parse_elf64 の代わりに読み込む.
+/// 動的リンク情報を持たなければ sections は空になる.
+/// セクションを組み立て直したかどうかも合わせて返す.
+pub fn elf_from_program_headers(
+    bytes: &[u8],
+) -> Result<(file::ELF64, bool), Box<dyn std::error::Error>> {
+    if !elf_util::is_elf64(bytes) {
+        return Err("not a 64-bit little endian ELF file".into());
+    }
+
+    let ehdr = header::Ehdr64::deserialize(bytes, 0)?;
+    let segments = elf_util::program_headers(bytes)
+        .into_iter()
+        .map(|header| segment::Segment64 { header })
+        .collect();
+
+    let shdrs = section_headers(bytes);
+    let headers: Vec<section::Shdr64> = shdrs.iter().map(|sct| sct.header).collect();
+    let sections: Vec<section::Section64> = shdrs
+        .iter()
+        .map(|sct| section::Section64 {
+            name: sct.name.to_string(),
+            header: sct.header,
+            contents: elf_util::section_contents(bytes, &headers, &sct.header),
+        })
+        .collect();
+    let reconstructed = !sections.is_empty();
+
+    Ok((
+        file::ELF64 {
+            ehdr,
+            sections,
+            segments,
+        },
+        reconstructed,
+    ))
+}
+
+/// PT_DYNAMIC から動的リンク関連のセクションヘッダを組み立てる.
+/// 先頭には NULL セクションを置き, アドレス順に並べて sh_link を張り直す.
+pub fn section_headers(bytes: &[u8]) -> Vec<ReconstructedSection> {
+    let phdrs = elf_util::program_headers(bytes);
+    let dynamic_phdr = match phdrs
+        .iter()
+        .find(|phdr| phdr.get_type() == segment::Type::Dynamic)
+    {
+        Some(phdr) => phdr,
+        None => return Vec::new(),
+    };
+
+    let entries = read_dynamic_entries(bytes, dynamic_phdr);
+    let tags: HashMap<i64, u64> = entries.iter().copied().collect();
+    let offset_of = |addr: u64| elf_util::vaddr_to_offset(&phdrs, addr);
+
+    let mut found = vec![Candidate(
+        ".dynamic",
+        section::Type::Dynamic.into(),
+        dynamic_phdr.p_vaddr,
+        (entries.len() as u64) * DYN_SIZE,
+        DYN_SIZE,
+        Some(".dynstr"),
+        0,
+    )];
+
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_STRTAB), tags.get(&DT_STRSZ)) {
+        found.push(Candidate(
+            ".dynstr",
+            section::Type::StrTab.into(),
+            addr,
+            size,
+            0,
+            None,
+            0,
+        ));
+    }
+
+    let symbol_count = symbol_count(bytes, &tags, offset_of);
+    if let Some(&addr) = tags.get(&DT_SYMTAB) {
+        let first_global = offset_of(addr)
+            .map(|offset| first_global_symbol(bytes, offset as usize, symbol_count))
+            .unwrap_or(0);
+        found.push(Candidate(
+            ".dynsym",
+            section::Type::DynSym.into(),
+            addr,
+            symbol_count as u64 * symbol::Symbol64::SIZE as u64,
+            symbol::Symbol64::SIZE as u64,
+            Some(".dynstr"),
+            first_global,
+        ));
+    }
+
+    if let Some(&addr) = tags.get(&DT_HASH) {
+        let size = offset_of(addr)
+            .and_then(|offset| sysv_hash_size(bytes, offset as usize))
+            .unwrap_or(0);
+        found.push(Candidate(
+            ".hash",
+            section::Type::Hash.into(),
+            addr,
+            size,
+            4,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let Some(&addr) = tags.get(&DT_GNU_HASH) {
+        let size = offset_of(addr)
+            .and_then(|offset| gnu_hash_size(bytes, offset as usize))
+            .unwrap_or(0);
+        found.push(Candidate(
+            ".gnu.hash",
+            SHT_GNU_HASH,
+            addr,
+            size,
+            0,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+
+    if let Some(&addr) = tags.get(&DT_VERSYM) {
+        found.push(Candidate(
+            ".gnu.version",
+            versions::SHT_GNU_VERSYM,
+            addr,
+            symbol_count as u64 * 2,
+            2,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&count)) = (tags.get(&DT_VERDEF), tags.get(&DT_VERDEFNUM)) {
+        let size = offset_of(addr)
+            .map(|offset| version_table_size(bytes, offset as usize, count, VERDEF_LAYOUT))
+            .unwrap_or(0);
+        found.push(Candidate(
+            ".gnu.version_d",
+            versions::SHT_GNU_VERDEF,
+            addr,
+            size,
+            0,
+            Some(".dynstr"),
+            count as u32,
+        ));
+    }
+    if let (Some(&addr), Some(&count)) = (tags.get(&DT_VERNEED), tags.get(&DT_VERNEEDNUM)) {
+        let size = offset_of(addr)
+            .map(|offset| version_table_size(bytes, offset as usize, count, VERNEED_LAYOUT))
+            .unwrap_or(0);
+        found.push(Candidate(
+            ".gnu.version_r",
+            versions::SHT_GNU_VERNEED,
+            addr,
+            size,
+            0,
+            Some(".dynstr"),
+            count as u32,
+        ));
+    }
+
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_RELA), tags.get(&DT_RELASZ)) {
+        found.push(Candidate(
+            ".rela.dyn",
+            section::Type::Rela.into(),
+            addr,
+            size,
+            RELA_SIZE,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_REL), tags.get(&DT_RELSZ)) {
+        found.push(Candidate(
+            ".rel.dyn",
+            section::Type::Rel.into(),
+            addr,
+            size,
+            REL_SIZE,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_JMPREL), tags.get(&DT_PLTRELSZ)) {
+        let (name, sh_type, entsize) = if tags.get(&DT_PLTREL) == Some(&(DT_REL as u64)) {
+            (".rel.plt", section::Type::Rel.into(), REL_SIZE)
+        } else {
+            (".rela.plt", section::Type::Rela.into(), RELA_SIZE)
+        };
+        found.push(Candidate(
+            name,
+            sh_type,
+            addr,
+            size,
+            entsize,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_RELR), tags.get(&DT_RELRSZ)) {
+        found.push(Candidate(
+            ".relr.dyn",
+            SHT_RELR,
+            addr,
+            size,
+            RELR_SIZE,
+            None,
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_ANDROID_RELA), tags.get(&DT_ANDROID_RELASZ)) {
+        found.push(Candidate(
+            ".rela.dyn",
+            SHT_ANDROID_RELA,
+            addr,
+            size,
+            ANDROID_RELOCATION_SIZE,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+    if let (Some(&addr), Some(&size)) = (tags.get(&DT_ANDROID_REL), tags.get(&DT_ANDROID_RELSZ)) {
+        found.push(Candidate(
+            ".rel.dyn",
+            SHT_ANDROID_REL,
+            addr,
+            size,
+            ANDROID_RELOCATION_SIZE,
+            Some(".dynsym"),
+            0,
+        ));
+    }
+
+    // ファイル上に実体が無いものは捨てる
+    found.retain(|candidate| offset_of(candidate.2).is_some());
+    found.sort_by_key(|candidate| candidate.2);
+
+    let index_of = |name: &str| {
+        found
+            .iter()
+            .position(|candidate| candidate.0 == name)
+            .map_or(0, |idx| idx as u32 + 1)
+    };
+
+    let mut sections = vec![ReconstructedSection {
+        name: "",
+        header: section::Shdr64::default(),
+    }];
+    for Candidate(name, sh_type, addr, size, entsize, link, info) in found.iter() {
+        let flags = if *sh_type == section::Type::Dynamic.into() {
+            SHF_WRITE | SHF_ALLOC
+        } else {
+            SHF_ALLOC
+        };
+        sections.push(ReconstructedSection {
+            name,
+            header: section::Shdr64 {
+                sh_name: 0,
+                sh_type: *sh_type,
+                sh_flags: flags,
+                sh_addr: *addr,
+                sh_offset: offset_of(*addr).unwrap_or(0),
+                sh_size: *size,
+                sh_link: link.map_or(0, index_of),
+                sh_info: *info,
+                sh_addralign: 8,
+                sh_entsize: *entsize,
+            },
+        });
+    }
+
+    sections
+}
+
+/// DT_NULL までの (d_tag, d_un) を読み出す. DT_NULL 自身も含める.
+fn read_dynamic_entries(bytes: &[u8], phdr: &segment::Phdr64) -> Vec<(i64, u64)> {
+    let mut entries = Vec::new();
+    for idx in 0..phdr.p_filesz / DYN_SIZE {
+        let base = match phdr.p_offset.checked_add(idx * DYN_SIZE) {
+            Some(base) => base as usize,
+            None => break,
+        };
+        let (tag, value) = match (
+            elf_util::read_i64(bytes, base),
+            elf_util::read_u64(bytes, base.wrapping_add(8)),
+        ) {
+            (Some(tag), Some(value)) => (tag, value),
+            _ => break,
+        };
+        entries.push((tag, value));
+        if tag == DT_NULL {
+            break;
+        }
+    }
+    entries
+}
+
+/// 動的シンボルの個数はどこにも記録されていないので, ハッシュテーブルから求める.
+/// どちらも無ければ, .dynsym の直後に .dynstr が置かれているとみなす.
+fn symbol_count<F>(bytes: &[u8], tags: &HashMap<i64, u64>, offset_of: F) -> usize
+where
+    F: Fn(u64) -> Option<u64>,
+{
+    let from_sysv = tags
+        .get(&DT_HASH)
+        .and_then(|addr| offset_of(*addr))
+        .and_then(|offset| elf_util::read_u32(bytes, offset as usize + 4));
+    if let Some(nchain) = from_sysv {
+        return nchain as usize;
+    }
+
+    let from_gnu = tags
+        .get(&DT_GNU_HASH)
+        .and_then(|addr| offset_of(*addr))
+        .and_then(|offset| gnu_hash_symbol_count(bytes, offset as usize));
+    if let Some(count) = from_gnu {
+        return count;
+    }
+
+    match (tags.get(&DT_SYMTAB), tags.get(&DT_STRTAB)) {
+        (Some(symtab), Some(strtab)) if symtab < strtab => {
+            ((strtab - symtab) / symbol::Symbol64::SIZE as u64) as usize
+        }
+        _ => 0,
+    }
+}
+
+/// sh_info に入れる, 最初の非 LOCAL シンボルのインデックス
+fn first_global_symbol(bytes: &[u8], offset: usize, count: usize) -> u32 {
+    (0..count)
+        .find(|idx| {
+            elf_util::read_u8(bytes, offset + idx * symbol::Symbol64::SIZE + 4)
+                .is_some_and(|st_info| st_info >> 4 != 0)
+        })
+        .unwrap_or(0) as u32
+}
+
+fn sysv_hash_size(bytes: &[u8], offset: usize) -> Option<u64> {
+    let nbucket = elf_util::read_u32(bytes, offset)? as u64;
+    let nchain = elf_util::read_u32(bytes, offset + 4)? as u64;
+    Some((2 + nbucket + nchain) * 4)
+}
+
+/// GNU ハッシュの各バケットのチェーンを末尾までたどり, シンボル数を求める.
+fn gnu_hash_symbol_count(bytes: &[u8], offset: usize) -> Option<usize> {
+    let nbuckets = elf_util::read_u32(bytes, offset)? as usize;
+    let symoffset = elf_util::read_u32(bytes, offset + 4)? as usize;
+    let bloom_size = elf_util::read_u32(bytes, offset + 8)? as usize;
+    let buckets = offset + 16 + bloom_size * 8;
+    let chains = buckets + nbuckets * 4;
+
+    let last_bucket = (0..nbuckets)
+        .filter_map(|idx| elf_util::read_u32(bytes, buckets + idx * 4))
+        .max()
+        .unwrap_or(0) as usize;
+    if last_bucket < symoffset {
+        return Some(symoffset);
+    }
+
+    let mut idx = last_bucket;
+    loop {
+        let hash = elf_util::read_u32(bytes, chains + (idx - symoffset) * 4)?;
+        if hash & 1 != 0 {
+            return Some(idx + 1);
+        }
+        idx += 1;
+    }
+}
+
+fn gnu_hash_size(bytes: &[u8], offset: usize) -> Option<u64> {
+    let nbuckets = elf_util::read_u32(bytes, offset)? as u64;
+    let symoffset = elf_util::read_u32(bytes, offset + 4)? as u64;
+    let bloom_size = elf_util::read_u32(bytes, offset + 8)? as u64;
+    let count = gnu_hash_symbol_count(bytes, offset)? as u64;
+    Some(16 + bloom_size * 8 + nbuckets * 4 + count.saturating_sub(symoffset) * 4)
+}
+
+/// verdef/verneed の各フィールドの位置
+#[derive(Clone, Copy)]
+struct VersionLayout {
+    entry_size: usize,
+    aux_count: usize,
+    aux: usize,
+    next: usize,
+    aux_size: usize,
+    aux_next: usize,
+}
+
+const VERDEF_LAYOUT: VersionLayout = VersionLayout {
+    entry_size: 20,
+    aux_count: 6,
+    aux: 12,
+    next: 16,
+    aux_size: 8,
+    aux_next: 4,
+};
+
+const VERNEED_LAYOUT: VersionLayout = VersionLayout {
+    entry_size: 16,
+    aux_count: 2,
+    aux: 8,
+    next: 12,
+    aux_size: 16,
+    aux_next: 12,
+};
+
+/// エントリと補助エントリをたどり, 最も後ろにあるものの終端までをテーブルの大きさとする.
+fn version_table_size(bytes: &[u8], offset: usize, count: u64, layout: VersionLayout) -> u64 {
+    let mut end = offset;
+    let mut entry = offset;
+
+    for _ in 0..count {
+        end = end.max(entry + layout.entry_size);
+
+        let aux_count = elf_util::read_u16(bytes, entry + layout.aux_count).unwrap_or(0);
+        let mut aux = entry + elf_util::read_u32(bytes, entry + layout.aux).unwrap_or(0) as usize;
+        for _ in 0..aux_count {
+            end = end.max(aux + layout.aux_size);
+            match elf_util::read_u32(bytes, aux + layout.aux_next) {
+                Some(0) | None => break,
+                Some(next) => aux += next as usize,
+            }
+        }
+
+        match elf_util::read_u32(bytes, entry + layout.next) {
+            Some(0) | None => break,
+            Some(next) => entry += next as usize,
+        }
+    }
+
+    (end - offset) as u64
+}
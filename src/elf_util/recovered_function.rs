@@ -0,0 +1,126 @@
+//! .symtab の無い (strip された) ファイルで, 関数の先頭を他の情報から集める.
+//! .eh_frame の FDE, .dynsym のエクスポート, GOT を通してジャンプする PLT スタブ,
+//! init/fini 配列, エントリポイントをアドレスごとにまとめる.
+
+use crate::elf_util::dwarf::eh_frame::EhFrame;
+use crate::elf_util::ifunc::{STT_FUNC, STT_GNU_IFUNC};
+use crate::elf_util::init_fini::{ArrayKind, InitFiniEntry, Target};
+use crate::elf_util::plt_got::PltGot;
+use elf_utilities::{
+    file,
+    section::{self, Contents64},
+};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Fde,
+    DynamicSymbol,
+    PltStub,
+    InitFini(ArrayKind),
+    EntryPoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecoveredFunction {
+    pub address: u64,
+    pub size: Option<u64>,
+    pub name: Option<String>,
+    /// 先頭だと判断した根拠
+    pub sources: Vec<Source>,
+}
+
+impl RecoveredFunction {
+    /// 名前が分からなければ sub_<アドレス> とする
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("sub_{:x}", self.address),
+        }
+    }
+}
+
+/// アドレス順に並べた関数
+pub fn recover(
+    elf_file: &file::ELF64,
+    eh_frame: Option<&EhFrame>,
+    dynsym_sct: Option<&section::Section64>,
+    plt_got: &PltGot,
+    init_fini: &[InitFiniEntry],
+) -> Vec<RecoveredFunction> {
+    let mut functions: BTreeMap<u64, RecoveredFunction> = BTreeMap::new();
+    let mut add = |address: u64, size: Option<u64>, name: Option<String>, source: Source| {
+        let function = functions.entry(address).or_insert(RecoveredFunction {
+            address,
+            size: None,
+            name: None,
+            sources: Vec::new(),
+        });
+        // FDE の範囲が最も確かなので, 先に入れた大きさを優先する
+        function.size = function.size.or(size).filter(|size| *size != 0);
+        function.name = function.name.take().or(name);
+        if !function.sources.contains(&source) {
+            function.sources.push(source);
+        }
+    };
+
+    // ET_REL の FDE はセクション内のオフセットなので扱わない
+    for fde in eh_frame
+        .iter()
+        .flat_map(|eh_frame| eh_frame.fdes.iter())
+        .filter(|fde| fde.pc_section.is_none() && fde.pc_begin != 0)
+    {
+        add(fde.pc_begin, Some(fde.pc_range), None, Source::Fde);
+    }
+
+    if let Some(Contents64::Symbols(symbols)) = dynsym_sct.map(|sct| &sct.contents) {
+        for sym in symbols.iter().filter(|sym| {
+            matches!(sym.st_info & 0x0f, STT_FUNC | STT_GNU_IFUNC)
+                && sym.st_shndx != section::SHN_UNDEF
+                && sym.st_value != 0
+        }) {
+            add(
+                sym.st_value,
+                Some(sym.st_size),
+                Some(sym.symbol_name.clone()),
+                Source::DynamicSymbol,
+            );
+        }
+    }
+
+    // 遅延束縛のスタブ (PLT0 や IBT 有効時の .plt) は呼び出し先にならないので除く
+    for stub in plt_got.stubs.iter().filter(|stub| stub.slot.is_some()) {
+        if let Some(symbol) = &stub.symbol {
+            add(
+                stub.address,
+                Some(stub.size),
+                Some(format!("{}@plt", symbol)),
+                Source::PltStub,
+            );
+        }
+    }
+
+    for entry in init_fini.iter() {
+        if let Target::Address(address, _) = entry.target {
+            add(address, None, None, Source::InitFini(entry.kind));
+        }
+    }
+
+    if elf_file.ehdr.e_entry != 0 {
+        add(elf_file.ehdr.e_entry, None, None, Source::EntryPoint);
+    }
+
+    functions.into_values().collect()
+}
+
+/// addr を含む関数 (関数名, 先頭からのずれ). 大きさが分からなければ先頭が一致するときだけ.
+pub fn containing(functions: &[RecoveredFunction], addr: u64) -> Option<(String, u64)> {
+    let idx = functions.partition_point(|function| function.address <= addr);
+    let function = functions.get(idx.checked_sub(1)?)?;
+    let delta = addr - function.address;
+    match function.size {
+        Some(size) if delta < size => Some((function.display_name(), delta)),
+        _ if delta == 0 => Some((function.display_name(), 0)),
+        _ => None,
+    }
+}
@@ -0,0 +1,538 @@
+//! SHT_RELA/SHT_REL セクションの再配置エントリを, 種類によらず同じ形で扱う.
+//! elf_utilities は SHT_REL を解析しないので, そちらは生バイト列から読む.
+//! SHT_RELR と Android の packed relocation (APS2) も, 1エントリずつに展開する.
+
+use crate::elf_util::{address, read_sleb128, read_u32, read_u64};
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+};
+
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+
+const REL_SIZE: usize = 0x10;
+const RELR_ENTRY_SIZE: u64 = 8;
+
+const SHT_RELR: u32 = 19;
+const SHT_ANDROID_REL: u32 = 0x6000_0001;
+const SHT_ANDROID_RELA: u32 = 0x6000_0002;
+/// SHT_RELR が標準化される前に Android で使われていた番号
+const SHT_ANDROID_RELR: u32 = 0x6fff_ff00;
+
+const R_X86_64_64: u32 = 1;
+const R_X86_64_PC32: u32 = 2;
+const R_X86_64_PLT32: u32 = 4;
+const R_X86_64_RELATIVE: u32 = 8;
+const R_X86_64_32: u32 = 10;
+const R_X86_64_32S: u32 = 11;
+const R_X86_64_PC64: u32 = 24;
+const R_X86_64_IRELATIVE: u32 = 37;
+const R_AARCH64_ABS64: u32 = 257;
+const R_AARCH64_ABS32: u32 = 258;
+const R_AARCH64_PREL64: u32 = 260;
+const R_AARCH64_PREL32: u32 = 261;
+const R_AARCH64_RELATIVE: u32 = 1027;
+const R_AARCH64_IRELATIVE: u32 = 1032;
+
+const RELOCATION_GROUPED_BY_INFO_FLAG: i64 = 1;
+const RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG: i64 = 2;
+const RELOCATION_GROUPED_BY_ADDEND_FLAG: i64 = 4;
+const RELOCATION_GROUP_HAS_ADDEND_FLAG: i64 = 8;
+
+/// 再配置セクションの符号化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rel,
+    Rela,
+    /// アドレスとビットマップで RELATIVE を詰めたもの
+    Relr,
+    /// "APS2" に続く SLEB128 のグループ
+    AndroidRel,
+    AndroidRela,
+}
+
+impl Format {
+    pub fn is_packed(&self) -> bool {
+        matches!(
+            self,
+            Format::Relr | Format::AndroidRel | Format::AndroidRela
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Relocation {
+    /// 再配置セクションのインデックス
+    pub section: usize,
+    /// セクション内でのエントリ番号
+    pub index: usize,
+    pub r_offset: u64,
+    pub r_sym: u32,
+    pub r_type: u32,
+    /// SHT_REL/SHT_RELR では None (加数は再配置先に埋め込まれている)
+    pub r_addend: Option<i64>,
+    pub format: Format,
+}
+
+/// ファイル中の全ての再配置エントリ
+pub fn relocations(elf_file: &file::ELF64) -> Vec<Relocation> {
+    let mut relocations = Vec::new();
+
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        match (&sct.contents, sct.header.get_type()) {
+            (Contents64::RelaSymbols(entries), _) => {
+                relocations.extend(entries.iter().enumerate().map(|(idx, rela)| Relocation {
+                    section: sct_idx,
+                    index: idx,
+                    r_offset: rela.get_offset(),
+                    r_sym: rela.get_sym() as u32,
+                    r_type: rela.get_type() as u32,
+                    r_addend: Some(rela.get_addend()),
+                    format: Format::Rela,
+                }))
+            }
+            (Contents64::Raw(data), section::Type::Rel) => {
+                relocations.extend((0..data.len() / REL_SIZE).filter_map(|idx| {
+                    let r_info = read_u64(data, idx * REL_SIZE + 8)?;
+                    Some(Relocation {
+                        section: sct_idx,
+                        index: idx,
+                        r_offset: read_u64(data, idx * REL_SIZE)?,
+                        r_sym: (r_info >> 32) as u32,
+                        r_type: (r_info & 0xffff_ffff) as u32,
+                        r_addend: None,
+                        format: Format::Rel,
+                    })
+                }))
+            }
+            (Contents64::Raw(data), _) => match sct.header.sh_type {
+                SHT_RELR | SHT_ANDROID_RELR => relocations.extend(
+                    relr_offsets(elf_file.ehdr.e_machine, data)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, (r_offset, r_type))| Relocation {
+                            section: sct_idx,
+                            index: idx,
+                            r_offset,
+                            r_sym: 0,
+                            r_type,
+                            r_addend: None,
+                            format: Format::Relr,
+                        }),
+                ),
+                SHT_ANDROID_REL => relocations.extend(android_relocations(sct_idx, data, false)),
+                SHT_ANDROID_RELA => relocations.extend(android_relocations(sct_idx, data, true)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    relocations
+}
+
+/// 加数. r_addend が無ければ, 再配置先に埋め込まれた値を読む.
+/// 埋め込みの幅が分かるのは, 8 バイトか 4 バイトのデータをそのまま書き換える種類だけ.
+pub fn addend(elf_file: &file::ELF64, bytes: &[u8], reloc: &Relocation) -> Option<i64> {
+    if let Some(addend) = reloc.r_addend {
+        return Some(addend);
+    }
+    let field_size = if reloc.format == Format::Relr {
+        8
+    } else {
+        match (elf_file.ehdr.e_machine, reloc.r_type) {
+            (EM_X86_64, R_X86_64_64 | R_X86_64_RELATIVE | R_X86_64_PC64 | R_X86_64_IRELATIVE) => 8,
+            (EM_X86_64, R_X86_64_PC32 | R_X86_64_PLT32 | R_X86_64_32 | R_X86_64_32S) => 4,
+            (
+                EM_AARCH64,
+                R_AARCH64_ABS64 | R_AARCH64_RELATIVE | R_AARCH64_PREL64 | R_AARCH64_IRELATIVE,
+            ) => 8,
+            (EM_AARCH64, R_AARCH64_ABS32 | R_AARCH64_PREL32) => 4,
+            _ => return None,
+        }
+    };
+
+    let offset = match elf_file.ehdr.get_type() {
+        // ET_REL の r_offset は sh_info が指すセクション内のオフセット
+        header::Type::Rel => {
+            let target = elf_file
+                .sections
+                .get(elf_file.sections[reloc.section].header.sh_info as usize)?;
+            if target.header.get_type() == section::Type::NoBits {
+                return None;
+            }
+            target.header.sh_offset.checked_add(reloc.r_offset)?
+        }
+        _ => address::lookup(elf_file, None, reloc.r_offset).segment?.1?,
+    };
+    if field_size == 4 {
+        // 加数は符号付きなので, 4 バイトの埋め込みは符号拡張する
+        return read_u32(bytes, offset as usize).map(|value| i64::from(value as i32));
+    }
+    read_u64(bytes, offset as usize).map(|value| value as i64)
+}
+
+/// 再配置が参照するシンボルの名前. r_sym が 0 なら None.
+pub fn symbol_name(elf_file: &file::ELF64, reloc: &Relocation) -> Option<String> {
+    if reloc.r_sym == 0 {
+        return None;
+    }
+    let symtab = elf_file
+        .sections
+        .get(elf_file.sections[reloc.section].header.sh_link as usize)?;
+    match &symtab.contents {
+        Contents64::Symbols(symbols) => symbols
+            .get(reloc.r_sym as usize)
+            .map(|sym| sym.symbol_name.clone()),
+        _ => None,
+    }
+}
+
+/// SHT_RELR を展開する. 偶数のワードは再配置先のアドレスそのもの,
+/// 奇数のワードは直前のアドレスに続く 63 ワード分のビットマップになる.
+fn relr_offsets(e_machine: u16, data: &[u8]) -> Vec<(u64, u32)> {
+    let r_type = match e_machine {
+        EM_X86_64 => R_X86_64_RELATIVE,
+        EM_AARCH64 => R_AARCH64_RELATIVE,
+        _ => 0,
+    };
+    let bits = RELR_ENTRY_SIZE * 8 - 1;
+
+    let mut offsets = Vec::new();
+    let mut base = 0;
+    for idx in 0..data.len() / RELR_ENTRY_SIZE as usize {
+        let word = match read_u64(data, idx * RELR_ENTRY_SIZE as usize) {
+            Some(word) => word,
+            None => break,
+        };
+        if word & 1 == 0 {
+            offsets.push((word, r_type));
+            // アドレスはファイルの値なので, 壊れていても桁あふれで止まらないよう wrapping で進める
+            base = word.wrapping_add(RELR_ENTRY_SIZE);
+            continue;
+        }
+        let mut bitmap = word >> 1;
+        let mut offset = base;
+        while bitmap != 0 {
+            if bitmap & 1 != 0 {
+                offsets.push((offset, r_type));
+            }
+            bitmap >>= 1;
+            offset = offset.wrapping_add(RELR_ENTRY_SIZE);
+        }
+        base = base.wrapping_add(bits * RELR_ENTRY_SIZE);
+    }
+    offsets
+}
+
+/// Android の packed relocation (APS2) を展開する.
+/// 個数と最初の r_offset のあとに, 同じ r_info/オフセットの差/加数を共有するグループが続く.
+fn android_relocations(sct_idx: usize, data: &[u8], has_addend: bool) -> Vec<Relocation> {
+    let mut relocations = Vec::new();
+    if data.get(..4) != Some(b"APS2") {
+        return relocations;
+    }
+    let mut pos = 4;
+    let mut next = || {
+        let (value, len) = read_sleb128(data, pos)?;
+        pos += len;
+        Some(value)
+    };
+
+    let (count, mut r_offset) = match (next(), next()) {
+        (Some(count), Some(offset)) => (count, offset as u64),
+        _ => return relocations,
+    };
+    let mut r_info = 0;
+    let mut r_addend = 0i64;
+    while (relocations.len() as i64) < count {
+        let (group_size, group_flags) = match (next(), next()) {
+            (Some(size), Some(flags)) => (size, flags),
+            _ => break,
+        };
+        // グループの大きさはファイルの値なので, 残りの個数を超えて展開しない
+        if group_size <= 0 {
+            break;
+        }
+        let group_size = group_size.min(count - relocations.len() as i64);
+        let offset_delta = match group_flags & RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG {
+            0 => None,
+            _ => next(),
+        };
+        if group_flags & RELOCATION_GROUPED_BY_INFO_FLAG != 0 {
+            r_info = next().unwrap_or_default() as u64;
+        }
+        let group_has_addend = group_flags & RELOCATION_GROUP_HAS_ADDEND_FLAG != 0;
+        let grouped_by_addend = group_flags & RELOCATION_GROUPED_BY_ADDEND_FLAG != 0;
+        if group_has_addend && grouped_by_addend {
+            r_addend = r_addend.wrapping_add(next().unwrap_or_default());
+        } else if !group_has_addend {
+            r_addend = 0;
+        }
+
+        for _ in 0..group_size {
+            let delta = match offset_delta {
+                Some(delta) => delta,
+                None => match next() {
+                    Some(delta) => delta,
+                    None => return relocations,
+                },
+            };
+            r_offset = r_offset.wrapping_add(delta as u64);
+            if group_flags & RELOCATION_GROUPED_BY_INFO_FLAG == 0 {
+                r_info = next().unwrap_or_default() as u64;
+            }
+            if group_has_addend && !grouped_by_addend {
+                r_addend = r_addend.wrapping_add(next().unwrap_or_default());
+            }
+            relocations.push(Relocation {
+                section: sct_idx,
+                index: relocations.len(),
+                r_offset,
+                r_sym: (r_info >> 32) as u32,
+                r_type: (r_info & 0xffff_ffff) as u32,
+                r_addend: has_addend.then_some(r_addend),
+                format: if has_addend {
+                    Format::AndroidRela
+                } else {
+                    Format::AndroidRel
+                },
+            });
+        }
+    }
+    relocations
+}
+
+pub fn type_string(e_machine: u16, r_type: u32) -> String {
+    let name = match e_machine {
+        EM_X86_64 => x86_64_type_string(r_type),
+        EM_AARCH64 => aarch64_type_string(r_type),
+        _ => None,
+    };
+    name.map(|name| name.to_string())
+        .unwrap_or_else(|| format!("R_{}", r_type))
+}
+
+/// PC 相対の再配置で書き換えるフィールドの大きさ.
+/// 加数には命令末尾までの距離(通常はフィールド長)が負値で含まれるので, 参照先を求めるときに戻す.
+pub fn pc_relative_field_size(e_machine: u16, r_type: u32) -> Option<u64> {
+    match (e_machine, r_type) {
+        (EM_X86_64, 2 | 4 | 9 | 41 | 42) => Some(4),
+        (EM_X86_64, 13) => Some(2),
+        (EM_X86_64, 15) => Some(1),
+        (EM_X86_64, 24) => Some(8),
+        _ => None,
+    }
+}
+
+fn x86_64_type_string<'a>(r_type: u32) -> Option<&'a str> {
+    let name = match r_type {
+        0 => "R_X86_64_NONE",
+        1 => "R_X86_64_64",
+        2 => "R_X86_64_PC32",
+        3 => "R_X86_64_GOT32",
+        4 => "R_X86_64_PLT32",
+        5 => "R_X86_64_COPY",
+        6 => "R_X86_64_GLOB_DAT",
+        7 => "R_X86_64_JUMP_SLOT",
+        8 => "R_X86_64_RELATIVE",
+        9 => "R_X86_64_GOTPCREL",
+        10 => "R_X86_64_32",
+        11 => "R_X86_64_32S",
+        12 => "R_X86_64_16",
+        13 => "R_X86_64_PC16",
+        14 => "R_X86_64_8",
+        15 => "R_X86_64_PC8",
+        16 => "R_X86_64_DTPMOD64",
+        17 => "R_X86_64_DTPOFF64",
+        18 => "R_X86_64_TPOFF64",
+        19 => "R_X86_64_TLSGD",
+        20 => "R_X86_64_TLSLD",
+        21 => "R_X86_64_DTPOFF32",
+        22 => "R_X86_64_GOTTPOFF",
+        23 => "R_X86_64_TPOFF32",
+        24 => "R_X86_64_PC64",
+        25 => "R_X86_64_GOTOFF64",
+        26 => "R_X86_64_GOTPC32",
+        27 => "R_X86_64_GOT64",
+        28 => "R_X86_64_GOTPCREL64",
+        29 => "R_X86_64_GOTPC64",
+        30 => "R_X86_64_GOTPLT64",
+        31 => "R_X86_64_PLTOFF64",
+        32 => "R_X86_64_SIZE32",
+        33 => "R_X86_64_SIZE64",
+        34 => "R_X86_64_GOTPC32_TLSDESC",
+        35 => "R_X86_64_TLSDESC_CALL",
+        36 => "R_X86_64_TLSDESC",
+        37 => "R_X86_64_IRELATIVE",
+        38 => "R_X86_64_RELATIVE64",
+        41 => "R_X86_64_GOTPCRELX",
+        42 => "R_X86_64_REX_GOTPCRELX",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn aarch64_type_string(r_type: u32) -> Option<&'static str> {
+    let name = match r_type {
+        0 => "R_AARCH64_NONE",
+        257 => "R_AARCH64_ABS64",
+        258 => "R_AARCH64_ABS32",
+        259 => "R_AARCH64_ABS16",
+        260 => "R_AARCH64_PREL64",
+        261 => "R_AARCH64_PREL32",
+        262 => "R_AARCH64_PREL16",
+        263 => "R_AARCH64_MOVW_UABS_G0",
+        264 => "R_AARCH64_MOVW_UABS_G0_NC",
+        265 => "R_AARCH64_MOVW_UABS_G1",
+        266 => "R_AARCH64_MOVW_UABS_G1_NC",
+        267 => "R_AARCH64_MOVW_UABS_G2",
+        268 => "R_AARCH64_MOVW_UABS_G2_NC",
+        269 => "R_AARCH64_MOVW_UABS_G3",
+        273 => "R_AARCH64_LD_PREL_LO19",
+        274 => "R_AARCH64_ADR_PREL_LO21",
+        275 => "R_AARCH64_ADR_PREL_PG_HI21",
+        276 => "R_AARCH64_ADR_PREL_PG_HI21_NC",
+        277 => "R_AARCH64_ADD_ABS_LO12_NC",
+        278 => "R_AARCH64_LDST8_ABS_LO12_NC",
+        279 => "R_AARCH64_TSTBR14",
+        280 => "R_AARCH64_CONDBR19",
+        282 => "R_AARCH64_JUMP26",
+        283 => "R_AARCH64_CALL26",
+        284 => "R_AARCH64_LDST16_ABS_LO12_NC",
+        285 => "R_AARCH64_LDST32_ABS_LO12_NC",
+        286 => "R_AARCH64_LDST64_ABS_LO12_NC",
+        299 => "R_AARCH64_LDST128_ABS_LO12_NC",
+        311 => "R_AARCH64_ADR_GOT_PAGE",
+        312 => "R_AARCH64_LD64_GOT_LO12_NC",
+        1024 => "R_AARCH64_COPY",
+        1025 => "R_AARCH64_GLOB_DAT",
+        1026 => "R_AARCH64_JUMP_SLOT",
+        1027 => "R_AARCH64_RELATIVE",
+        1028 => "R_AARCH64_TLS_DTPMOD",
+        1029 => "R_AARCH64_TLS_DTPREL",
+        1030 => "R_AARCH64_TLS_TPREL",
+        1031 => "R_AARCH64_TLSDESC",
+        1032 => "R_AARCH64_IRELATIVE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[u64]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn sleb(values: &[i64]) -> Vec<u8> {
+        let mut data = b"APS2".to_vec();
+        for value in values {
+            let mut value = *value;
+            loop {
+                let byte = (value & 0x7f) as u8;
+                value >>= 7;
+                let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+                data.push(if done { byte } else { byte | 0x80 });
+                if done {
+                    break;
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn relr_address_and_bitmap() {
+        // 0x1000 自身と, ビット 0, 2 が立ったビットマップで 0x1008, 0x1018
+        let data = words(&[0x1000, (0b101 << 1) | 1]);
+        assert_eq!(
+            relr_offsets(EM_X86_64, &data),
+            vec![
+                (0x1000, R_X86_64_RELATIVE),
+                (0x1008, R_X86_64_RELATIVE),
+                (0x1018, R_X86_64_RELATIVE)
+            ]
+        );
+    }
+
+    #[test]
+    fn relr_consecutive_bitmaps() {
+        // 2つ目のビットマップは 1つ目から 63 ワード後ろを指す
+        let data = words(&[0x2000, 0b11, 0b11]);
+        assert_eq!(
+            relr_offsets(EM_AARCH64, &data),
+            vec![
+                (0x2000, R_AARCH64_RELATIVE),
+                (0x2008, R_AARCH64_RELATIVE),
+                (0x2008 + 63 * 8, R_AARCH64_RELATIVE)
+            ]
+        );
+    }
+
+    #[test]
+    fn relr_wraps_at_the_end_of_the_address_space() {
+        let data = words(&[u64::MAX - 7, u64::MAX]);
+        assert_eq!(relr_offsets(EM_X86_64, &data).len(), 64);
+    }
+
+    #[test]
+    fn android_grouped_by_info_and_delta() {
+        // 3個, 先頭 0x1000. 8 バイトおきの RELATIVE が 3個のグループ
+        let flags = RELOCATION_GROUPED_BY_INFO_FLAG | RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG;
+        let data = sleb(&[3, 0x1000, 3, flags, 8, R_AARCH64_RELATIVE as i64]);
+        let relocations = android_relocations(1, &data, false);
+        assert_eq!(
+            relocations
+                .iter()
+                .map(|reloc| (reloc.r_offset, reloc.r_type, reloc.r_addend))
+                .collect::<Vec<_>>(),
+            vec![
+                (0x1008, R_AARCH64_RELATIVE, None),
+                (0x1010, R_AARCH64_RELATIVE, None),
+                (0x1018, R_AARCH64_RELATIVE, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn android_ungrouped_with_addend() {
+        // 2個. オフセットの差, r_info, 加数の差をエントリごとに持つ
+        let r_info = (5 << 32) | i64::from(R_AARCH64_ABS64);
+        let data = sleb(&[
+            2,
+            0,
+            2,
+            RELOCATION_GROUP_HAS_ADDEND_FLAG,
+            0x100,
+            r_info,
+            16,
+            0x10,
+            r_info,
+            -4,
+        ]);
+        let relocations = android_relocations(1, &data, true);
+        assert_eq!(
+            relocations
+                .iter()
+                .map(|reloc| (reloc.r_offset, reloc.r_sym, reloc.r_addend))
+                .collect::<Vec<_>>(),
+            vec![(0x100, 5, Some(16)), (0x110, 5, Some(12))]
+        );
+    }
+
+    #[test]
+    fn android_group_larger_than_count() {
+        let flags = RELOCATION_GROUPED_BY_INFO_FLAG | RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG;
+        let data = sleb(&[2, 0, i64::MAX, flags, 8, R_AARCH64_RELATIVE as i64]);
+        assert_eq!(android_relocations(1, &data, false).len(), 2);
+    }
+
+    #[test]
+    fn android_without_magic() {
+        assert!(android_relocations(1, b"APS1\x01", false).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! DT_RPATH/DT_RUNPATH 等の検索パスに含まれるトークン($ORIGIN, $LIB, $PLATFORM)を展開する.
+
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct RunPathEntry {
+    /// 展開前の文字列
+    pub raw: String,
+    /// 展開後の文字列(不明なトークンを含む場合は None)
+    pub expanded: Option<String>,
+    /// ld.so の挙動に影響する問題点
+    pub problem: Option<String>,
+}
+
+/// ':' 区切りの検索パスを分割し, 各エントリのトークンを展開する.
+/// origin には検索パスを持つオブジェクトのディレクトリを, triplet にはオブジェクトの
+/// アーキテクチャに対応する multiarch の三つ組(`binding::multiarch_triplet`)を与える.
+pub fn expand_run_path(list: &str, origin: &Path, triplet: Option<&str>) -> Vec<RunPathEntry> {
+    list.split(':')
+        .map(|raw| {
+            let (expanded, problem) = match expand_tokens(raw, origin, triplet) {
+                Ok(expanded) => {
+                    let problem = validate(&expanded);
+                    (Some(expanded), problem)
+                }
+                Err(e) => (None, Some(e)),
+            };
+
+            RunPathEntry {
+                raw: raw.to_string(),
+                expanded,
+                problem,
+            }
+        })
+        .collect()
+}
+
+fn expand_tokens(raw: &str, origin: &Path, triplet: Option<&str>) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = raw;
+
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        let (token, len) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => return Err("unterminated `${`".to_string()),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+
+        match token {
+            "ORIGIN" => expanded.push_str(&origin.to_string_lossy()),
+            "LIB" | "PLATFORM" => match triplet {
+                // multiarch 構成の ld.so を想定し, 三つ組から値を決める
+                Some(triplet) if token == "LIB" => expanded.push_str(&format!("lib/{}", triplet)),
+                Some(triplet) => expanded.push_str(triplet.split('-').next().unwrap_or(triplet)),
+                None => return Err(format!("`${}` is unknown for this machine", token)),
+            },
+            _ => return Err(format!("unknown token `${}`", token)),
+        }
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+fn validate(expanded: &str) -> Option<String> {
+    if expanded.is_empty() {
+        Some("empty entry, searches the current directory".to_string())
+    } else if !expanded.starts_with('/') {
+        Some("relative path, resolved against the current directory".to_string())
+    } else if !Path::new(expanded).is_dir() {
+        Some("directory not found".to_string())
+    } else {
+        None
+    }
+}
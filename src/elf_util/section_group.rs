@@ -0,0 +1,68 @@
+//! SHT_GROUP セクション (C++ の COMDAT グループ等) の中身を読む.
+//! 先頭のワードがフラグで, 残りのワードがメンバのセクションインデックスになる.
+
+use crate::elf_util::read_u32;
+use elf_utilities::{
+    file,
+    section::{self, Contents64},
+    symbol,
+};
+
+pub const GRP_COMDAT: u32 = 0x1;
+
+#[derive(Debug, Clone)]
+pub struct SectionGroup {
+    /// SHT_GROUP セクションのインデックス
+    pub section: usize,
+    pub flags: u32,
+    pub members: Vec<usize>,
+}
+
+impl SectionGroup {
+    pub fn is_comdat(&self) -> bool {
+        self.flags & GRP_COMDAT != 0
+    }
+
+    /// sh_link のシンボルテーブルの sh_info 番目がグループのシグネチャ.
+    /// STT_SECTION のシンボルなら, そのセクション名を使う.
+    pub fn signature(&self, elf_file: &file::ELF64) -> Option<String> {
+        let sct = &elf_file.sections[self.section];
+        let symbols = match &elf_file.sections.get(sct.header.sh_link as usize)?.contents {
+            Contents64::Symbols(symbols) => symbols,
+            _ => return None,
+        };
+        let sym = symbols.get(sct.header.sh_info as usize)?;
+        if sym.get_type() == symbol::Type::Section {
+            return elf_file
+                .sections
+                .get(sym.st_shndx as usize)
+                .map(|sct| sct.name.clone());
+        }
+
+        Some(sym.symbol_name.clone())
+    }
+}
+
+pub fn section_group(elf_file: &file::ELF64, idx: usize) -> Option<SectionGroup> {
+    let sct = elf_file.sections.get(idx)?;
+    let data = match (&sct.contents, sct.header.get_type()) {
+        (Contents64::Raw(data), section::Type::Group) => data,
+        _ => return None,
+    };
+
+    Some(SectionGroup {
+        section: idx,
+        flags: read_u32(data, 0)?,
+        members: (1..data.len() / 4)
+            .filter_map(|word| read_u32(data, word * 4))
+            .map(|member| member as usize)
+            .collect(),
+    })
+}
+
+/// セクションが属するグループ
+pub fn group_of(elf_file: &file::ELF64, member: usize) -> Option<SectionGroup> {
+    (0..elf_file.sections.len())
+        .filter_map(|idx| section_group(elf_file, idx))
+        .find(|group| group.members.contains(&member))
+}
@@ -0,0 +1,339 @@
+//! 文字列テーブル(.strtab, .dynstr, .shstrtab 等)を NUL 区切りの文字列に分解し,
+//! 各文字列をどこから参照しているかを集める.
+
+use crate::elf_util::versions::{self, read_field};
+use crate::elf_util::{compression, read_u16, read_u32};
+use elf_utilities::{
+    file,
+    section::{self, Contents64},
+};
+
+// 値が文字列テーブルのオフセットである動的タグ
+const DT_NEEDED: i64 = 1;
+const DT_SONAME: i64 = 14;
+const DT_RPATH: i64 = 15;
+const DT_RUNPATH: i64 = 29;
+const DT_CONFIG: i64 = 0x6fff_fefa;
+const DT_DEPAUDIT: i64 = 0x6fff_fefb;
+const DT_AUDIT: i64 = 0x6fff_fefc;
+const DT_AUXILIARY: i64 = 0x7fff_fffd;
+const DT_FILTER: i64 = 0x7fff_ffff;
+
+/// 文字列を参照しているもの
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringUser {
+    /// セクションヘッダの sh_name
+    SectionName { section: usize },
+    /// シンボルテーブル(section)の index 番目のシンボルの st_name
+    Symbol { section: usize, index: usize },
+    /// .dynamic(section)の index 番目のエントリ
+    Dynamic {
+        section: usize,
+        index: usize,
+        tag: i64,
+    },
+    /// バージョン定義/要求(section)中の名前
+    Version { section: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct StringReference {
+    pub user: StringUser,
+    /// 参照しているオフセット. 文字列の先頭でなければ末尾共有(tail-merge)による参照
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct StringEntry {
+    pub offset: usize,
+    /// NUL 終端を含まない生のバイト列
+    pub bytes: Vec<u8>,
+    /// 表示用の文字列(不正な UTF-8 は置き換える)
+    pub value: String,
+    pub references: Vec<StringReference>,
+}
+
+impl StringEntry {
+    /// 先頭から参照されているか
+    pub fn is_referenced_directly(&self) -> bool {
+        self.references.iter().any(|r| r.offset == self.offset)
+    }
+
+    /// 末尾部分だけが他の名前として参照されている(= 末尾共有されている)参照
+    pub fn suffix_references(&self) -> impl Iterator<Item = &StringReference> {
+        self.references
+            .iter()
+            .filter(move |r| r.offset != self.offset)
+    }
+
+    pub fn is_unreferenced(&self) -> bool {
+        self.references.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StringTable {
+    pub section: usize,
+    pub name: String,
+    pub entries: Vec<StringEntry>,
+    /// どの文字列にも当たらないオフセットを指す参照
+    pub dangling: Vec<StringReference>,
+}
+
+/// 全ての SHT_STRTAB セクションを解析する.
+pub fn string_tables(elf_file: &file::ELF64, bytes: &[u8]) -> Vec<StringTable> {
+    elf_file
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, sct)| sct.header.get_type() == section::Type::StrTab)
+        .filter_map(|(idx, sct)| {
+            let data = compression::section_data(sct, bytes)?;
+            if data.is_empty() {
+                return None;
+            }
+
+            let mut table = StringTable {
+                section: idx,
+                name: sct.name.clone(),
+                entries: split_strings(&data),
+                dangling: Vec::new(),
+            };
+            for reference in collect_references(elf_file, bytes, idx) {
+                table.attach(reference);
+            }
+            Some(table)
+        })
+        .collect()
+}
+
+impl StringTable {
+    fn attach(&mut self, reference: StringReference) {
+        // offset を含む(NUL 終端も含めた)文字列を探す
+        let pos = self
+            .entries
+            .partition_point(|e| e.offset <= reference.offset);
+        match pos.checked_sub(1).map(|i| &mut self.entries[i]) {
+            Some(entry) if reference.offset <= entry.offset + entry.bytes.len() => {
+                entry.references.push(reference)
+            }
+            _ => self.dangling.push(reference),
+        }
+    }
+}
+
+/// 先頭の空文字列を除き, 連続する NUL(パディング)は文字列として扱わない.
+fn split_strings(data: &[u8]) -> Vec<StringEntry> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let len = data[offset..]
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(data.len() - offset);
+        if len != 0 || offset == 0 {
+            let bytes = data[offset..offset + len].to_vec();
+            entries.push(StringEntry {
+                offset,
+                value: String::from_utf8_lossy(&bytes).to_string(),
+                bytes,
+                references: Vec::new(),
+            });
+        }
+        offset += len + 1;
+    }
+
+    entries
+}
+
+/// strtab を参照先とする全ての名前を集める.
+fn collect_references(elf_file: &file::ELF64, bytes: &[u8], strtab: usize) -> Vec<StringReference> {
+    let mut references = Vec::new();
+
+    // 拡張形式では, 実際のインデックスは先頭エントリの sh_link に入っている
+    let shstrndx = match elf_file.ehdr.e_shstrndx {
+        section::SHN_XINDEX => elf_file
+            .sections
+            .first()
+            .map(|sct| sct.header.sh_link as usize),
+        _ if elf_file.ehdr.e_shnum == 0 => None,
+        shstrndx => Some(usize::from(shstrndx)),
+    };
+    if shstrndx == Some(strtab) {
+        references.extend(
+            elf_file
+                .sections
+                .iter()
+                .enumerate()
+                .map(|(idx, sct)| StringReference {
+                    user: StringUser::SectionName { section: idx },
+                    offset: sct.header.sh_name as usize,
+                }),
+        );
+    }
+
+    for (sct_idx, sct) in elf_file.sections.iter().enumerate() {
+        if sct.header.sh_link as usize != strtab {
+            continue;
+        }
+
+        match &sct.contents {
+            Contents64::Symbols(symbols) => references.extend(symbols.iter().enumerate().map(
+                |(idx, sym)| StringReference {
+                    user: StringUser::Symbol {
+                        section: sct_idx,
+                        index: idx,
+                    },
+                    offset: sym.st_name as usize,
+                },
+            )),
+            Contents64::Dynamics(dynamics) => references.extend(
+                dynamics
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| is_string_tag(entry.d_tag))
+                    .map(|(idx, entry)| StringReference {
+                        user: StringUser::Dynamic {
+                            section: sct_idx,
+                            index: idx,
+                            tag: entry.d_tag,
+                        },
+                        offset: entry.d_un as usize,
+                    }),
+            ),
+            _ => {}
+        }
+
+        let user = StringUser::Version { section: sct_idx };
+        let offset = sct.header.sh_offset as usize;
+        let count = sct.header.sh_info as usize;
+        let names = match sct.header.sh_type {
+            versions::SHT_GNU_VERDEF => verdef_names(bytes, offset, count),
+            versions::SHT_GNU_VERNEED => verneed_names(bytes, offset, count),
+            _ => Vec::new(),
+        };
+        references.extend(
+            names
+                .into_iter()
+                .map(|offset| StringReference { user, offset }),
+        );
+    }
+
+    references
+}
+
+fn is_string_tag(tag: i64) -> bool {
+    matches!(
+        tag,
+        DT_NEEDED
+            | DT_SONAME
+            | DT_RPATH
+            | DT_RUNPATH
+            | DT_CONFIG
+            | DT_DEPAUDIT
+            | DT_AUDIT
+            | DT_AUXILIARY
+            | DT_FILTER
+    )
+}
+
+/// .gnu.version_d の全 verdaux の vda_name
+fn verdef_names(bytes: &[u8], offset: usize, count: usize) -> Vec<usize> {
+    let mut names = Vec::new();
+    let mut def = Some(offset);
+
+    for _ in 0..count {
+        let base = match def {
+            Some(base) => base,
+            None => break,
+        };
+        let aux_count = read_field(bytes, base, 6, read_u16).unwrap_or(0);
+        let mut aux = base.checked_add(read_field(bytes, base, 12, read_u32).unwrap_or(0) as usize);
+        for _ in 0..aux_count {
+            let aux_base = match aux {
+                Some(aux_base) => aux_base,
+                None => break,
+            };
+            names.extend(read_u32(bytes, aux_base).map(|name| name as usize));
+            match read_field(bytes, aux_base, 4, read_u32) {
+                Some(0) | None => break,
+                Some(next) => aux = aux_base.checked_add(next as usize),
+            }
+        }
+
+        match read_field(bytes, base, 16, read_u32) {
+            Some(0) | None => break,
+            Some(next) => def = base.checked_add(next as usize),
+        }
+    }
+
+    names
+}
+
+/// .gnu.version_r の vn_file と全 vernaux の vna_name
+fn verneed_names(bytes: &[u8], offset: usize, count: usize) -> Vec<usize> {
+    let mut names = Vec::new();
+    let mut need = Some(offset);
+
+    for _ in 0..count {
+        let base = match need {
+            Some(base) => base,
+            None => break,
+        };
+        names.extend(read_field(bytes, base, 4, read_u32).map(|file| file as usize));
+        let aux_count = read_field(bytes, base, 2, read_u16).unwrap_or(0);
+        let mut aux = base.checked_add(read_field(bytes, base, 8, read_u32).unwrap_or(0) as usize);
+        for _ in 0..aux_count {
+            let aux_base = match aux {
+                Some(aux_base) => aux_base,
+                None => break,
+            };
+            names.extend(read_field(bytes, aux_base, 8, read_u32).map(|name| name as usize));
+            match read_field(bytes, aux_base, 12, read_u32) {
+                Some(0) | None => break,
+                Some(next) => aux = aux_base.checked_add(next as usize),
+            }
+        }
+
+        match read_field(bytes, base, 12, read_u32) {
+            Some(0) | None => break,
+            Some(next) => need = base.checked_add(next as usize),
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(offset: usize) -> StringReference {
+        StringReference {
+            user: StringUser::SectionName { section: 0 },
+            offset,
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_keeps_byte_length() {
+        let mut table = StringTable {
+            section: 0,
+            name: String::new(),
+            entries: split_strings(b"\0ab\xff\xfecd\0\0\0\0xyz\0"),
+            dangling: Vec::new(),
+        };
+        assert_eq!(table.entries.len(), 3);
+        assert_eq!(table.entries[1].bytes.len(), 6);
+        assert_eq!(table.entries[2].offset, 11);
+
+        // 置換文字で長くなった表示用の文字列ではなく, 生のバイト列の長さで範囲を判定する
+        table.attach(reference(5));
+        table.attach(reference(7));
+        table.attach(reference(9));
+        assert_eq!(table.entries[1].references.len(), 2);
+        assert_eq!(table.dangling.len(), 1);
+        assert_eq!(table.dangling[0].offset, 9);
+    }
+}
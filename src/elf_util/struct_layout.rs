@@ -0,0 +1,280 @@
+//! DWARF の型情報から, pahole と同様に構造体のメンバ配置を求める.
+//! メンバ間の穴, 末尾のパディング, キャッシュラインの境界を並べる.
+
+use std::collections::HashSet;
+
+use crate::elf_util::dwarf::{
+    self, AttrValue, Die, Dwarf, Unit, DW_AT_DATA_MEMBER_LOCATION, DW_AT_NAME, DW_AT_TYPE,
+    DW_TAG_CLASS_TYPE, DW_TAG_STRUCTURE_TYPE, DW_TAG_UNION_TYPE,
+};
+
+pub const CACHELINE_SIZE: u64 = 64;
+
+const DW_TAG_MEMBER: u64 = 0x0d;
+const DW_TAG_INHERITANCE: u64 = 0x1c;
+const DW_AT_BIT_OFFSET: u64 = 0x0c;
+const DW_AT_BIT_SIZE: u64 = 0x0d;
+const DW_AT_DECLARATION: u64 = 0x3c;
+const DW_AT_DATA_BIT_OFFSET: u64 = 0x6b;
+const DW_OP_PLUS_UCONST: u8 = 0x23;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Struct,
+    Class,
+    Union,
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: Option<String>,
+    pub type_name: String,
+    /// 構造体先頭からのバイトオフセット
+    pub offset: u64,
+    /// 型のバイト数. 求められなければ None
+    pub size: Option<u64>,
+    /// ビットフィールドなら, (格納単位の先頭からのビット位置, ビット数)
+    pub bit_field: Option<(u64, u64)>,
+    /// C++ の基底クラス (DW_TAG_inheritance)
+    pub base_class: bool,
+}
+
+impl Member {
+    /// 構造体先頭からの, 占める範囲のビット位置. 大きさが分からなければ None
+    fn bit_range(&self) -> Option<(u64, u64)> {
+        match self.bit_field {
+            Some((bit_offset, bits)) => {
+                let begin = self.offset.checked_mul(8)?.checked_add(bit_offset)?;
+                Some((begin, begin.checked_add(bits)?))
+            }
+            None => Some((
+                self.offset.checked_mul(8)?,
+                self.offset.checked_add(self.size?)?.checked_mul(8)?,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructLayout {
+    pub kind: Kind,
+    pub name: String,
+    pub size: u64,
+    /// 定義している DIE の .debug_info 先頭からのオフセット
+    pub die_offset: u64,
+    pub members: Vec<Member>,
+}
+
+/// メンバと, その間に入れる注記
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutEntry {
+    Member(usize),
+    /// 穴のビット数
+    Hole(u64),
+    /// (キャッシュライン番号, 境界を過ぎてからのバイト数)
+    CachelineBoundary(u64, u64),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LayoutSummary {
+    pub sum_members: u64,
+    pub holes: usize,
+    /// 穴のビット数の合計
+    pub sum_holes: u64,
+    pub padding: u64,
+    pub cachelines: u64,
+    /// 最後のキャッシュラインが使うバイト数. ちょうど割り切れれば 0
+    pub last_cacheline: u64,
+}
+
+impl StructLayout {
+    /// メンバを配置順に並べ, 穴とキャッシュラインの境界を差し込む.
+    pub fn entries(&self) -> Vec<LayoutEntry> {
+        let mut entries = Vec::new();
+        let mut end_bit = 0;
+        let mut next_cacheline = 1;
+
+        for (idx, member) in self.members.iter().enumerate() {
+            let (begin, end) = member
+                .bit_range()
+                .unwrap_or((member.offset * 8, member.offset * 8));
+            if self.kind != Kind::Union {
+                if begin > end_bit {
+                    entries.push(LayoutEntry::Hole(begin - end_bit));
+                }
+                // 境界をまたぐメンバがあれば, 次のメンバの前に過ぎたバイト数を添える
+                let cacheline = member.offset / CACHELINE_SIZE;
+                if cacheline >= next_cacheline {
+                    entries.push(LayoutEntry::CachelineBoundary(
+                        cacheline,
+                        member.offset - cacheline * CACHELINE_SIZE,
+                    ));
+                    next_cacheline = cacheline + 1;
+                }
+            }
+            entries.push(LayoutEntry::Member(idx));
+            end_bit = end_bit.max(end);
+        }
+
+        entries
+    }
+
+    pub fn summary(&self) -> LayoutSummary {
+        let entries = self.entries();
+        let holes: Vec<u64> = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                LayoutEntry::Hole(bits) => Some(*bits),
+                _ => None,
+            })
+            .collect();
+        let end_bit = self
+            .members
+            .iter()
+            .filter_map(Member::bit_range)
+            .map(|(_, end)| end)
+            .max()
+            .unwrap_or(0);
+
+        LayoutSummary {
+            sum_members: self
+                .members
+                .iter()
+                .filter_map(|member| match member.bit_field {
+                    Some(_) => None,
+                    None => member.size,
+                })
+                .sum(),
+            holes: holes.len(),
+            sum_holes: holes.iter().sum(),
+            padding: self.size.saturating_sub(end_bit.div_ceil(8)),
+            cachelines: self.size.div_ceil(CACHELINE_SIZE),
+            last_cacheline: self.size % CACHELINE_SIZE,
+        }
+    }
+}
+
+/// 名前の付いた構造体/クラス/共用体を名前順に集める.
+/// 同じ型は複数のユニットに現れるので, 種類と名前と大きさが同じものは1つにまとめる.
+pub fn struct_layouts(dwarf: &Dwarf) -> Vec<StructLayout> {
+    let mut seen = HashSet::new();
+    let mut layouts = Vec::new();
+
+    for unit in dwarf.units.iter() {
+        for (die_idx, die) in unit.dies.iter().enumerate() {
+            let kind = match die.tag {
+                DW_TAG_STRUCTURE_TYPE => Kind::Struct,
+                DW_TAG_CLASS_TYPE => Kind::Class,
+                DW_TAG_UNION_TYPE => Kind::Union,
+                _ => continue,
+            };
+            if die.attribute(DW_AT_DECLARATION).is_some() {
+                continue;
+            }
+            let name = match die
+                .attribute(DW_AT_NAME)
+                .and_then(|name| dwarf.string(unit, name))
+            {
+                Some(name) => name,
+                None => continue,
+            };
+            let size = match die
+                .attribute(dwarf::DW_AT_BYTE_SIZE)
+                .and_then(AttrValue::constant)
+            {
+                Some(size) => size as u64,
+                None => continue,
+            };
+            if !seen.insert((kind, name.clone(), size)) {
+                continue;
+            }
+
+            let mut members: Vec<Member> = unit
+                .children(die_idx)
+                .map(|child| &unit.dies[child])
+                .filter(|child| child.tag == DW_TAG_MEMBER || child.tag == DW_TAG_INHERITANCE)
+                .filter_map(|child| member(dwarf, unit, child))
+                .collect();
+            members.sort_by_key(|member| member.bit_range().map(|(begin, _)| begin));
+
+            layouts.push(StructLayout {
+                kind,
+                name,
+                size,
+                die_offset: die.offset,
+                members,
+            });
+        }
+    }
+
+    layouts.sort_by(|a, b| a.name.cmp(&b.name));
+    layouts
+}
+
+/// ビット位置が u64 に収まらない壊れたメンバは None
+fn member(dwarf: &Dwarf, unit: &Unit, die: &Die) -> Option<Member> {
+    let type_offset = match die.attribute(DW_AT_TYPE) {
+        Some(AttrValue::Ref(offset)) => Some(*offset),
+        _ => None,
+    };
+    let size = type_offset.and_then(|offset| dwarf.type_size(offset));
+    let constant = |at| die.attribute(at).and_then(AttrValue::constant);
+
+    let mut offset = match die.attribute(DW_AT_DATA_MEMBER_LOCATION) {
+        Some(AttrValue::Udata(offset)) => *offset,
+        Some(AttrValue::Sdata(offset)) => *offset as u64,
+        // DWARF 2 では DW_OP_plus_uconst の式で表す
+        Some(AttrValue::Block(expr)) | Some(AttrValue::Exprloc(expr)) => {
+            match dwarf::operations(expr, unit).as_slice() {
+                [op] if op.opcode == DW_OP_PLUS_UCONST => match op.operands.as_slice() {
+                    [dwarf::Operand::Unsigned(offset)] => *offset,
+                    _ => 0,
+                },
+                _ => 0,
+            }
+        }
+        _ => 0,
+    };
+
+    let bit_field = constant(DW_AT_BIT_SIZE).map(|bits| {
+        let bits = bits as u64;
+        if let Some(data_bit_offset) = constant(DW_AT_DATA_BIT_OFFSET) {
+            // DWARF 4 以降は構造体先頭からのビット位置
+            let data_bit_offset = data_bit_offset as u64;
+            offset = data_bit_offset / 8;
+            (data_bit_offset % 8, bits)
+        } else {
+            // DWARF 2/3 の DW_AT_bit_offset は格納単位の最上位ビットから数える(リトルエンディアン前提)
+            let storage_bits = constant(dwarf::DW_AT_BYTE_SIZE)
+                .map(|size| size as u64)
+                .or(size)
+                .unwrap_or(0)
+                .saturating_mul(8);
+            let msb_offset = constant(DW_AT_BIT_OFFSET).unwrap_or(0) as u64;
+            let lsb = offset
+                .saturating_mul(8)
+                .saturating_add(storage_bits)
+                .saturating_sub(msb_offset.saturating_add(bits));
+            offset = lsb / 8;
+            (lsb % 8, bits)
+        }
+    });
+
+    let member = Member {
+        name: die
+            .attribute(DW_AT_NAME)
+            .and_then(|name| dwarf.string(unit, name)),
+        type_name: type_offset
+            .map(|offset| dwarf.type_name(offset))
+            .unwrap_or_else(|| "void".to_string()),
+        offset,
+        size,
+        bit_field,
+        base_class: die.tag == DW_TAG_INHERITANCE,
+    };
+    member.offset.checked_mul(8)?;
+    if (member.size.is_some() || member.bit_field.is_some()) && member.bit_range().is_none() {
+        return None;
+    }
+    Some(member)
+}
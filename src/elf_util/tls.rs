@@ -0,0 +1,261 @@
+//! スレッドローカルストレージ (PT_TLS, SHF_TLS のセクション, STT_TLS のシンボル) と,
+//! それを参照する TLS 用の再配置をまとめる.
+
+use crate::elf_util::ifunc;
+use crate::elf_util::relocations::{self, Relocation};
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+    segment, symbol,
+};
+
+pub const STT_TLS: u8 = 6;
+const SHF_TLS: u64 = 0x400;
+const DT_FLAGS: i64 = 30;
+const DF_STATIC_TLS: u64 = 0x10;
+
+const R_X86_64_DTPMOD64: u32 = 16;
+const R_X86_64_DTPOFF64: u32 = 17;
+const R_X86_64_TPOFF64: u32 = 18;
+const R_X86_64_TLSGD: u32 = 19;
+const R_X86_64_TLSLD: u32 = 20;
+const R_X86_64_DTPOFF32: u32 = 21;
+const R_X86_64_GOTTPOFF: u32 = 22;
+const R_X86_64_TPOFF32: u32 = 23;
+const R_X86_64_GOTPC32_TLSDESC: u32 = 34;
+const R_X86_64_TLSDESC_CALL: u32 = 35;
+const R_X86_64_TLSDESC: u32 = 36;
+
+/// 再配置から分かる TLS のアクセスモデル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    GeneralDynamic,
+    LocalDynamic,
+    InitialExec,
+    LocalExec,
+    Descriptor,
+    /// DTPMOD64/DTPOFF64 のように GD/LD の GOT を埋めるもの
+    DynamicSlot,
+}
+
+/// PT_TLS が示す TLS の初期化イメージ
+#[derive(Debug, Clone, Copy)]
+pub struct TlsTemplate {
+    pub segment: usize,
+    pub address: u64,
+    pub offset: u64,
+    /// .tdata の大きさ. 残り (memsz - filesz) は .tbss で 0 埋めされる
+    pub file_size: u64,
+    pub mem_size: u64,
+    pub align: u64,
+}
+
+impl TlsTemplate {
+    /// 整列に合わせて切り上げた, スレッドごとの TLS ブロックの大きさ. 桁あふれすれば None
+    pub fn block_size(&self) -> Option<u64> {
+        let align = self.align.max(1);
+        self.mem_size.div_ceil(align).checked_mul(align)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsVariable {
+    pub name: String,
+    /// TLS ブロック先頭からのオフセット (ET_REL ではセクション内のオフセット). 未定義なら None
+    pub offset: Option<u64>,
+    pub size: u64,
+    pub shndx: u16,
+    pub binding: u8,
+    pub references: Vec<Relocation>,
+}
+
+pub struct Tls {
+    pub template: Option<TlsTemplate>,
+    /// SHF_TLS のセクション
+    pub sections: Vec<usize>,
+    /// 初期化イメージ (.tdata) のバイト列
+    pub image: Vec<u8>,
+    pub variables: Vec<TlsVariable>,
+    /// どの変数にも結びつかないもの (LD のモジュール番号など)
+    pub module_relocations: Vec<Relocation>,
+    /// DT_FLAGS の DF_STATIC_TLS
+    pub static_tls: bool,
+    /// PIE でない ET_DYN. IE/LE のアクセスは dlopen で問題になる
+    pub shared_object: bool,
+}
+
+impl Tls {
+    pub fn is_empty(&self) -> bool {
+        self.template.is_none() && self.sections.is_empty() && self.variables.is_empty()
+    }
+
+    /// 共有ライブラリで静的 TLS を要求するアクセス
+    pub fn initial_exec_in_shared_object(&self, reloc: &Relocation) -> bool {
+        self.shared_object
+            && matches!(
+                model(reloc.r_type),
+                Some(Model::InitialExec | Model::LocalExec)
+            )
+    }
+
+    /// x86-64 (variant II) の実行ファイルでの, スレッドポインタからのオフセット.
+    /// 実行ファイルの TLS ブロックはスレッドポインタの直前に置かれる.
+    pub fn thread_pointer_offset(&self, offset: u64) -> Option<i64> {
+        let block = self.template?.block_size()?;
+        Some(offset as i64 - block as i64)
+    }
+}
+
+pub fn model(r_type: u32) -> Option<Model> {
+    let model = match r_type {
+        R_X86_64_TLSGD => Model::GeneralDynamic,
+        R_X86_64_TLSLD | R_X86_64_DTPOFF32 => Model::LocalDynamic,
+        R_X86_64_GOTTPOFF | R_X86_64_TPOFF64 => Model::InitialExec,
+        R_X86_64_TPOFF32 => Model::LocalExec,
+        R_X86_64_GOTPC32_TLSDESC | R_X86_64_TLSDESC_CALL | R_X86_64_TLSDESC => Model::Descriptor,
+        R_X86_64_DTPMOD64 | R_X86_64_DTPOFF64 => Model::DynamicSlot,
+        _ => return None,
+    };
+    Some(model)
+}
+
+/// symbol_table は変数を集める .symtab (無ければ .dynsym)
+pub fn tls(elf_file: &file::ELF64, bytes: &[u8], symbol_table: Option<&section::Section64>) -> Tls {
+    let template = elf_file
+        .segments
+        .iter()
+        .position(|seg| seg.header.get_type() == segment::Type::TLS)
+        .map(|idx| {
+            let seg = &elf_file.segments[idx].header;
+            TlsTemplate {
+                segment: idx,
+                address: seg.p_vaddr,
+                offset: seg.p_offset,
+                file_size: seg.p_filesz,
+                mem_size: seg.p_memsz,
+                align: seg.p_align,
+            }
+        });
+    let sections: Vec<usize> = elf_file
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, sct)| sct.header.sh_flags & SHF_TLS != 0)
+        .map(|(idx, _)| idx)
+        .collect();
+    // ET_REL には PT_TLS が無いので, 最初の SHF_TLS な PROGBITS を使う
+    let image_range = match template {
+        Some(template) => Some((template.offset, template.file_size)),
+        None => sections
+            .iter()
+            .map(|idx| &elf_file.sections[*idx].header)
+            .find(|shdr| shdr.get_type() != section::Type::NoBits)
+            .map(|shdr| (shdr.sh_offset, shdr.sh_size)),
+    };
+    let image = image_range
+        .and_then(|(offset, size)| bytes.get(offset as usize..offset.checked_add(size)? as usize))
+        .map(|image| image.to_vec())
+        .unwrap_or_default();
+
+    let mut variables: Vec<TlsVariable> = Vec::new();
+    if let Some(Contents64::Symbols(symbols)) = symbol_table.map(|sct| &sct.contents) {
+        for sym in symbols.iter().filter(|sym| is_tls(sym)) {
+            variables.push(variable(sym));
+        }
+    }
+    variables.sort_by_key(|var| (var.offset.is_none(), var.shndx, var.offset));
+
+    let mut module_relocations = Vec::new();
+    if elf_file.ehdr.e_machine == relocations::EM_X86_64 {
+        for reloc in relocations::relocations(elf_file)
+            .into_iter()
+            .filter(|reloc| model(reloc.r_type).is_some())
+        {
+            match reloc_variable(elf_file, &mut variables, &reloc) {
+                Some(idx) => variables[idx].references.push(reloc),
+                None => module_relocations.push(reloc),
+            }
+        }
+    }
+
+    Tls {
+        template,
+        sections,
+        image,
+        variables,
+        module_relocations,
+        static_tls: has_static_tls_flag(elf_file),
+        shared_object: elf_file.ehdr.get_type() == header::Type::Dyn && !ifunc::is_pie(elf_file),
+    }
+}
+
+fn is_tls(sym: &symbol::Symbol64) -> bool {
+    sym.st_info & 0x0f == STT_TLS && !sym.symbol_name.is_empty()
+}
+
+fn variable(sym: &symbol::Symbol64) -> TlsVariable {
+    TlsVariable {
+        name: sym.symbol_name.clone(),
+        offset: Some(sym.st_value).filter(|_| sym.st_shndx != section::SHN_UNDEF),
+        size: sym.st_size,
+        shndx: sym.st_shndx,
+        binding: sym.st_info >> 4,
+        references: Vec::new(),
+    }
+}
+
+/// 再配置が参照する変数. シンボルが無ければ加数を TLS ブロック内のオフセットとみなす.
+/// 変数一覧に無い (.dynsym にしか無い未定義の) シンボルは追加する.
+fn reloc_variable(
+    elf_file: &file::ELF64,
+    variables: &mut Vec<TlsVariable>,
+    reloc: &Relocation,
+) -> Option<usize> {
+    if reloc.r_sym == 0 {
+        let offset = reloc.r_addend? as u64;
+        // LD のモジュール番号 (DTPMOD64) は変数を指さない
+        if reloc.r_type == R_X86_64_DTPMOD64 {
+            return None;
+        }
+        return variables
+            .iter()
+            .position(|var| var.offset == Some(offset) && var.shndx != section::SHN_UNDEF);
+    }
+
+    let sym = match &elf_file
+        .sections
+        .get(elf_file.sections[reloc.section].header.sh_link as usize)?
+        .contents
+    {
+        Contents64::Symbols(symbols) => symbols.get(reloc.r_sym as usize)?,
+        _ => return None,
+    };
+    // 名前は static __thread の変数どうしで重なりうるので, 定義された場所で見分ける
+    let defined = sym.st_shndx != section::SHN_UNDEF;
+    if let Some(idx) = variables.iter().position(|var| {
+        if defined {
+            var.shndx == sym.st_shndx && var.offset == Some(sym.st_value)
+        } else {
+            var.shndx == section::SHN_UNDEF && var.name == sym.symbol_name
+        }
+    }) {
+        return Some(idx);
+    }
+    if !is_tls(sym) {
+        return None;
+    }
+    variables.push(variable(sym));
+    Some(variables.len() - 1)
+}
+
+fn has_static_tls_flag(elf_file: &file::ELF64) -> bool {
+    elf_file
+        .sections
+        .iter()
+        .filter_map(|sct| match &sct.contents {
+            Contents64::Dynamics(dynamics) => Some(dynamics),
+            _ => None,
+        })
+        .flatten()
+        .any(|dynamic| dynamic.d_tag == DT_FLAGS && dynamic.d_un & DF_STATIC_TLS != 0)
+}
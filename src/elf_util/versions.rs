@@ -0,0 +1,155 @@
+//! GNU シンボルバージョニング (.gnu.version, .gnu.version_d, .gnu.version_r) の解析.
+
+use std::collections::BTreeMap;
+
+use crate::elf_util::{read_cstr, read_u16, read_u32};
+
+pub const SHT_GNU_VERDEF: u32 = 0x6fff_fffd;
+pub const SHT_GNU_VERNEED: u32 = 0x6fff_fffe;
+pub const SHT_GNU_VERSYM: u32 = 0x6fff_ffff;
+
+/// VERSYM のうち, バージョンインデックス以外のビット
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+/// VER_NDX_LOCAL
+pub const VER_NDX_LOCAL: u16 = 0;
+/// VER_NDX_GLOBAL
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// VER_FLG_WEAK
+pub const VER_FLG_WEAK: u16 = 0x2;
+
+/// バージョン定義/要求の1エントリ
+#[derive(Debug, Clone)]
+pub struct VersionEntry {
+    pub name: String,
+    pub flags: u16,
+    /// 要求(verneed)の場合は要求先ファイル名
+    pub file: Option<String>,
+}
+
+/// .gnu.version の各エントリを読み出す.
+pub fn parse_versym(bytes: &[u8], offset: usize, count: usize) -> Vec<u16> {
+    (0..count)
+        .map_while(|idx| read_u16(bytes, offset.checked_add(idx.checked_mul(2)?)?))
+        .collect()
+}
+
+/// .gnu.version_d を読み出し, バージョンインデックスをキーとするマップを作る.
+pub fn parse_verdef(
+    bytes: &[u8],
+    offset: usize,
+    count: usize,
+    strtab_offset: usize,
+) -> BTreeMap<u16, VersionEntry> {
+    let mut entries = BTreeMap::new();
+    let mut def_offset = offset;
+
+    for _ in 0..count {
+        let (flags, ndx, aux_count, aux, next) = match (
+            read_field(bytes, def_offset, 2, read_u16),
+            read_field(bytes, def_offset, 4, read_u16),
+            read_field(bytes, def_offset, 6, read_u16),
+            read_field(bytes, def_offset, 12, read_u32),
+            read_field(bytes, def_offset, 16, read_u32),
+        ) {
+            (Some(f), Some(n), Some(c), Some(a), Some(nx)) => (f, n, c, a, nx),
+            _ => break,
+        };
+
+        // 最初の verdaux がバージョン名
+        let name = if aux_count != 0 {
+            read_field(bytes, def_offset, aux as usize, read_u32)
+                .and_then(|name| read_name(bytes, strtab_offset, name))
+        } else {
+            None
+        };
+        if let Some(name) = name {
+            entries.insert(
+                ndx,
+                VersionEntry {
+                    name,
+                    flags,
+                    file: None,
+                },
+            );
+        }
+
+        def_offset = match def_offset.checked_add(next as usize) {
+            Some(def_offset) if next != 0 => def_offset,
+            _ => break,
+        };
+    }
+
+    entries
+}
+
+/// .gnu.version_r を読み出し, バージョンインデックス(vna_other)をキーとするマップを作る.
+pub fn parse_verneed(
+    bytes: &[u8],
+    offset: usize,
+    count: usize,
+    strtab_offset: usize,
+) -> BTreeMap<u16, VersionEntry> {
+    let mut entries = BTreeMap::new();
+    let mut need_offset = offset;
+
+    for _ in 0..count {
+        let file = read_field(bytes, need_offset, 4, read_u32)
+            .and_then(|file| read_name(bytes, strtab_offset, file));
+        let (aux_count, aux, next) = match (
+            read_field(bytes, need_offset, 2, read_u16),
+            read_field(bytes, need_offset, 8, read_u32),
+            read_field(bytes, need_offset, 12, read_u32),
+        ) {
+            (Some(c), Some(a), Some(nx)) => (c, a, nx),
+            _ => break,
+        };
+
+        let mut aux_offset = need_offset.checked_add(aux as usize);
+        for _ in 0..aux_count {
+            let base = match aux_offset {
+                Some(base) => base,
+                None => break,
+            };
+            let flags = read_field(bytes, base, 4, read_u16);
+            let other = read_field(bytes, base, 6, read_u16);
+            let name = read_field(bytes, base, 8, read_u32)
+                .and_then(|name| read_name(bytes, strtab_offset, name));
+            if let (Some(flags), Some(other), Some(name)) = (flags, other, name) {
+                entries.insert(
+                    other,
+                    VersionEntry {
+                        name,
+                        flags,
+                        file: file.clone(),
+                    },
+                );
+            }
+            match read_field(bytes, base, 12, read_u32) {
+                Some(0) | None => break,
+                Some(aux_next) => aux_offset = base.checked_add(aux_next as usize),
+            }
+        }
+
+        need_offset = match need_offset.checked_add(next as usize) {
+            Some(need_offset) if next != 0 => need_offset,
+            _ => break,
+        };
+    }
+
+    entries
+}
+
+/// `base + offset` の位置から値を読み出す. オフセットの計算が溢れれば None を返す.
+pub fn read_field<T>(
+    bytes: &[u8],
+    base: usize,
+    offset: usize,
+    read: fn(&[u8], usize) -> Option<T>,
+) -> Option<T> {
+    read(bytes, base.checked_add(offset)?)
+}
+
+/// 文字列テーブル中の名前を読み出す.
+fn read_name(bytes: &[u8], strtab_offset: usize, name: u32) -> Option<String> {
+    read_cstr(bytes, strtab_offset.checked_add(name as usize)?)
+}
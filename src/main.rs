@@ -1,4 +1,7 @@
+use crate::elf_util::ld_cache::{self, LdCache};
+use crate::elf_util::{debug_file, printable_strings, reconstruct};
 use crate::tui_util::{App, AppState, Event, Events};
+use crate::widgets::hex_view;
 use std::error::Error;
 use std::io;
 use termion::event::Key;
@@ -8,19 +11,67 @@ use termion::screen::AlternateScreen;
 use tui::backend::TermionBackend;
 use tui::Terminal;
 
+mod elf_util;
 mod tui_util;
 mod widgets;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 2 {
-        eprintln!("usage: ./elfpeach <file-path>");
-        std::process::exit(1);
+    let mut file_path = None;
+    let mut ld_cache_path = None;
+    let mut debug_dirs = Vec::new();
+    let mut min_string_length = printable_strings::DEFAULT_MIN_LENGTH;
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--ld-cache" if ld_cache_path.is_none() => match rest.next() {
+                Some(path) => ld_cache_path = Some(path),
+                None => usage(),
+            },
+            "--debug-dir" => match rest.next() {
+                Some(dir) => debug_dirs.push(dir.clone()),
+                None => usage(),
+            },
+            "--min-string-length" => {
+                min_string_length = match rest.next().and_then(|n| n.parse().ok()) {
+                    Some(n) if n > 0 => n,
+                    _ => usage(),
+                }
+            }
+            _ if file_path.is_none() && !arg.starts_with("--") => file_path = Some(arg),
+            _ => usage(),
+        }
     }
+    let file_path = file_path.unwrap_or_else(|| usage());
 
-    let elf_file = elf_utilities::parser::parse_elf64(&args[1])?;
-    let events = Events::new();
+    // --ld-cache が指定されなければ, システムのキャッシュがあれば使う
+    let ld_cache = match ld_cache_path {
+        Some(path) => match LdCache::read(path) {
+            Some(cache) => Some(cache),
+            None => {
+                eprintln!("can't parse ld.so.cache `{}`", path);
+                std::process::exit(1);
+            }
+        },
+        None => LdCache::read(ld_cache::DEFAULT_LD_CACHE_PATH),
+    };
+
+    let file_bytes = std::fs::read(file_path)?;
+    // parse_elf64 はセクションヘッダの無いファイルでパニックするので自前で読み込む
+    let (elf_file, sections_reconstructed) = if reconstruct::has_section_headers(&file_bytes) {
+        (
+            elf_util::elf_from_section_headers(file_path, &file_bytes)?,
+            false,
+        )
+    } else {
+        reconstruct::elf_from_program_headers(&file_bytes)?
+    };
+    let debug_report = debug_file::locate(file_path, &elf_file, &file_bytes, &debug_dirs);
+    let debug_file = debug_report
+        .found()
+        .and_then(|found| debug_file::load(&found.path));
+    let mut events = Events::new();
 
     // Terminal initialization
     let stdout = io::stdout().into_raw_mode()?;
@@ -30,34 +81,142 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Application initialization
-    let mut app = App::new(&elf_file);
+    let mut app = App::new(
+        &elf_file,
+        &file_bytes,
+        sections_reconstructed,
+        ld_cache,
+        min_string_length,
+        debug_report,
+        debug_file
+            .as_ref()
+            .map(|(debug_elf, debug_bytes)| (debug_elf, debug_bytes.as_slice())),
+    );
 
     // Main loop
     loop {
-        terminal.draw(|f| app.draw(f, args[1].to_string(), &elf_file))?;
+        terminal.draw(|f| app.draw(f, file_path.to_string(), &elf_file))?;
 
         if let Event::Input(input) = events.next()? {
+            // 入力中は q や矢印キーも文字として扱う
+            if app.editing_query {
+                match input {
+                    Key::Char('\n') | Key::Esc => {
+                        app.editing_query = false;
+                        events.enable_exit_key();
+                    }
+                    Key::Backspace => {
+                        app.query_mut().pop();
+                    }
+                    Key::Char(c) => app.query_mut().push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
             match input {
                 Key::Char('q') | Key::Esc => {
                     break;
                 }
+                Key::Char('/') if matches!(app.state(), AppState::HashTable) => {
+                    app.editing_query = true;
+                    app.hash_query.clear();
+                    events.disable_exit_key();
+                }
+                Key::Char('g') if app.tabs.contains("Address") => {
+                    app.tabs.select("Address");
+                    app.editing_query = true;
+                    app.address_query.clear();
+                    events.disable_exit_key();
+                }
+                Key::Char('[') if matches!(app.state(), AppState::StringTable) => {
+                    app.switch_string_table(false)
+                }
+                Key::Char(']') if matches!(app.state(), AppState::StringTable) => {
+                    app.switch_string_table(true)
+                }
+                Key::Char('n') if matches!(app.state(), AppState::StringTable) => {
+                    app.next_string_user()
+                }
+                Key::Char('\n') if matches!(app.state(), AppState::StringTable) => {
+                    app.jump_to_string_user(&elf_file)
+                }
+                Key::Char('+') if matches!(app.state(), AppState::PrintableString) => {
+                    app.change_min_string_length(&elf_file, &file_bytes, true)
+                }
+                Key::Char('-') if matches!(app.state(), AppState::PrintableString) => {
+                    app.change_min_string_length(&elf_file, &file_bytes, false)
+                }
+                Key::Char('a') if matches!(app.state(), AppState::PrintableString) => {
+                    app.toggle_unloaded_strings(&elf_file, &file_bytes)
+                }
+                Key::Char('\n') if matches!(app.state(), AppState::Dwarf) => app.toggle_die(),
+                Key::Char('\n') if matches!(app.state(), AppState::RecoveredFunction) => {
+                    app.goto_recovered_function()
+                }
+                Key::Char('\n') if matches!(app.state(), AppState::FileLayout) => {
+                    app.goto_file_region()
+                }
+                Key::Char('\n') if matches!(app.state(), AppState::Header) => {
+                    app.goto_header_field()
+                }
+                Key::Char('h') if matches!(app.state(), AppState::Hex) => app.move_hex_cursor(-1),
+                Key::Char('l') if matches!(app.state(), AppState::Hex) => app.move_hex_cursor(1),
+                Key::PageUp if matches!(app.state(), AppState::Hex) => app.move_hex_page(false),
+                Key::PageDown if matches!(app.state(), AppState::Hex) => app.move_hex_page(true),
                 Key::Right => app.tabs.next(),
                 Key::Left => app.tabs.previous(),
                 Key::Up => match app.state() {
-                    AppState::Header => {}
+                    AppState::Header => app.header_fields.borrow_mut().previous(),
                     AppState::Section => app.sections.borrow_mut().previous(),
                     AppState::Segment => app.segments.borrow_mut().previous(),
                     AppState::Symbol => app.symbol_table.borrow_mut().previous(),
                     AppState::DynSym => app.dynamic_symbol_table.borrow_mut().previous(),
                     AppState::Dynamics => app.dynamic_table.borrow_mut().previous(),
+                    AppState::Binding => app.bindings.borrow_mut().previous(),
+                    AppState::LdCache => app.ld_cache_entries.borrow_mut().previous(),
+                    AppState::HashTable => app.hash_tables.borrow_mut().previous(),
+                    AppState::StringTable => app.previous_string(),
+                    AppState::PrintableString => app.previous_printable_string(),
+                    AppState::Dwarf => app.dies.borrow_mut().previous(),
+                    AppState::StructLayout => app.struct_layouts.borrow_mut().previous(),
+                    AppState::InitFini => app.init_fini.borrow_mut().previous(),
+                    AppState::EhFrame => app.eh_frames.borrow_mut().previous(),
+                    AppState::Relocation => app.relocations.borrow_mut().previous(),
+                    AppState::PltGot => app.plt_got.borrow_mut().previous(),
+                    AppState::AddressLayout => app.address_layout_rows.borrow_mut().previous(),
+                    AppState::FileLayout => app.file_layout_rows.borrow_mut().previous(),
+                    AppState::Hex => app.move_hex_cursor(-(hex_view::BYTES_PER_ROW as isize)),
+                    AppState::Ifunc => app.ifuncs.borrow_mut().previous(),
+                    AppState::Tls => app.tls_entries.borrow_mut().previous(),
+                    AppState::RecoveredFunction => app.recovered_functions.borrow_mut().previous(),
+                    AppState::Address | AppState::DebugFile => {}
                 },
                 Key::Down => match app.state() {
-                    AppState::Header => {}
+                    AppState::Header => app.header_fields.borrow_mut().next(),
                     AppState::Section => app.sections.borrow_mut().next(),
                     AppState::Segment => app.segments.borrow_mut().next(),
                     AppState::Symbol => app.symbol_table.borrow_mut().next(),
                     AppState::DynSym => app.dynamic_symbol_table.borrow_mut().next(),
                     AppState::Dynamics => app.dynamic_table.borrow_mut().next(),
+                    AppState::Binding => app.bindings.borrow_mut().next(),
+                    AppState::LdCache => app.ld_cache_entries.borrow_mut().next(),
+                    AppState::HashTable => app.hash_tables.borrow_mut().next(),
+                    AppState::StringTable => app.next_string(),
+                    AppState::PrintableString => app.next_printable_string(),
+                    AppState::Dwarf => app.dies.borrow_mut().next(),
+                    AppState::StructLayout => app.struct_layouts.borrow_mut().next(),
+                    AppState::InitFini => app.init_fini.borrow_mut().next(),
+                    AppState::EhFrame => app.eh_frames.borrow_mut().next(),
+                    AppState::Relocation => app.relocations.borrow_mut().next(),
+                    AppState::PltGot => app.plt_got.borrow_mut().next(),
+                    AppState::AddressLayout => app.address_layout_rows.borrow_mut().next(),
+                    AppState::FileLayout => app.file_layout_rows.borrow_mut().next(),
+                    AppState::Hex => app.move_hex_cursor(hex_view::BYTES_PER_ROW as isize),
+                    AppState::Ifunc => app.ifuncs.borrow_mut().next(),
+                    AppState::Tls => app.tls_entries.borrow_mut().next(),
+                    AppState::RecoveredFunction => app.recovered_functions.borrow_mut().next(),
+                    AppState::Address | AppState::DebugFile => {}
                 },
                 _ => {}
             }
@@ -65,3 +224,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
     Ok(())
 }
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: ./elfpeach [--ld-cache <path>] [--min-string-length <n>] [--debug-dir <dir>]... <file-path>"
+    );
+    std::process::exit(1);
+}
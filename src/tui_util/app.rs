@@ -1,8 +1,25 @@
 use std::cell::RefCell;
+use std::collections::HashSet;
 
+use crate::elf_util::{
+    address_layout::AddressLayout, binding, compression, compression::Compression,
+    debug_file::DebugFileReport, dwarf::eh_frame::EhFrame, dwarf::eh_frame::FrameEntry,
+    dwarf::Dwarf, file_fields::FileFields, file_layout::FileLayout, file_layout::FileRegionKind,
+    file_layout::Part, hash_table, ifunc, ifunc::Ifunc, init_fini::InitFiniEntry,
+    ld_cache::LdCache, plt_got::PltGot, printable_strings::PrintableString,
+    printable_strings::Xrefs, recovered_function, recovered_function::RecoveredFunction,
+    relocations, relocations::Relocation, string_table, string_table::StringUser, struct_layout,
+    struct_layout::StructLayout, tls::Tls,
+};
 use crate::tui_util::{StatefulList, TabsState};
 use crate::{
-    widgets::dynamics, widgets::elf_header, widgets::sections, widgets::segments, widgets::symbols,
+    widgets::address, widgets::address_layout as address_layout_widgets, widgets::bindings,
+    widgets::data_inspector, widgets::debug_file, widgets::dwarf, widgets::dynamics,
+    widgets::eh_frame, widgets::elf_header, widgets::file_layout as file_layout_widgets,
+    widgets::hash_tables, widgets::hex_view, widgets::ifuncs, widgets::init_fini,
+    widgets::ld_cache, widgets::plt_got, widgets::printable_strings, widgets::recovered_functions,
+    widgets::relocations as relocation_widgets, widgets::sections, widgets::segments,
+    widgets::string_tables, widgets::struct_layouts, widgets::symbols, widgets::tls as tls_widgets,
 };
 
 use elf_utilities::{file, section};
@@ -14,17 +31,100 @@ use tui::Frame;
 
 pub struct App<'a> {
     pub tabs: TabsState<'a>,
+    pub header_fields: RefCell<StatefulList<String>>,
     pub sections: RefCell<StatefulList<String>>,
     pub segments: RefCell<StatefulList<String>>,
+    pub address_layout_rows: RefCell<StatefulList<String>>,
+    pub file_layout_rows: RefCell<StatefulList<String>>,
+    /// Hex タブのカーソルのファイルオフセット
+    pub hex_cursor: usize,
+    /// Hex タブで先頭に表示している行
+    hex_top: usize,
+    /// 前回描画したときの Hex タブの行数. ページ送りに使う
+    hex_rows: usize,
     pub symbol_table: RefCell<StatefulList<String>>,
     pub dynamic_symbol_table: RefCell<StatefulList<String>>,
     pub dynamic_table: RefCell<StatefulList<String>>,
+    pub bindings: RefCell<StatefulList<String>>,
+    pub ld_cache_entries: RefCell<StatefulList<String>>,
+    pub hash_tables: RefCell<StatefulList<String>>,
+    /// ハッシュテーブルで探索するシンボル名
+    pub hash_query: String,
+    /// Address タブで引くアドレス
+    pub address_query: String,
+    /// hash_query/address_query を入力中かどうか
+    pub editing_query: bool,
+    pub strings: RefCell<StatefulList<String>>,
+    pub printable_strings: RefCell<StatefulList<String>>,
+    pub dies: RefCell<StatefulList<String>>,
+    pub struct_layouts: RefCell<StatefulList<String>>,
+    pub init_fini: RefCell<StatefulList<String>>,
+    pub eh_frames: RefCell<StatefulList<String>>,
+    pub relocations: RefCell<StatefulList<String>>,
+    pub plt_got: RefCell<StatefulList<String>>,
+    pub ifuncs: RefCell<StatefulList<String>>,
+    pub tls_entries: RefCell<StatefulList<String>>,
+    pub recovered_functions: RefCell<StatefulList<String>>,
 
     // 描画のたびにテーブルを探索すると無駄なので,
     // ファイル読み込み時に保持してしまう.
     symtab_sct: Option<&'a section::Section64>,
+    parsed_address_layout: AddressLayout,
+    parsed_file_layout: FileLayout,
+    parsed_file_fields: FileFields,
+    file_bytes: &'a [u8],
+    /// セクションをプログラムヘッダから組み立て直したかどうか
+    sections_reconstructed: bool,
+    /// セクションごとの圧縮形式
+    section_compressions: Vec<Option<Compression>>,
+    /// 圧縮されたセクションを展開した中身の先頭
+    decompressed_previews: Vec<Option<Vec<u8>>>,
+    /// symtab_sct を持つファイル. strip されていれば分離デバッグファイルになる
+    symtab_file: &'a file::ELF64,
     dynsym_sct: Option<&'a section::Section64>,
     dynamic_sct: Option<&'a section::Section64>,
+    /// 依存ライブラリを読むので, Bindings タブを初めて開いたときに解決する
+    binding_report: Option<binding::BindingReport>,
+    /// 未定義の動的シンボルがあり, Bindings タブを出す
+    has_imports: bool,
+    ld_cache: Option<LdCache>,
+    parsed_hash_tables: Vec<hash_table::HashTable>,
+    parsed_string_tables: Vec<string_table::StringTable>,
+    /// Strings タブで表示中の文字列テーブル
+    string_table_index: usize,
+    /// 選択中の文字列の, ジャンプ先として選んでいる参照元
+    string_user_index: usize,
+    parsed_printable_strings: Vec<PrintableString>,
+    /// 文字列のアドレスを指すシンボル/再配置
+    string_xrefs: Xrefs,
+    /// 抜き出す文字列の最小文字数
+    min_string_length: usize,
+    /// .comment や .debug_* 等のロードされないセクションからも文字列を抜き出すか
+    include_unloaded_strings: bool,
+    parsed_dwarf: Option<Dwarf>,
+    /// DWARF タブに表示している DIE の (ユニット, DIE) インデックス
+    visible_dies: Vec<(usize, usize)>,
+    /// 子を展開している DIE のオフセット
+    expanded_dies: HashSet<u64>,
+    parsed_struct_layouts: Vec<StructLayout>,
+    parsed_init_fini: Vec<InitFiniEntry>,
+    parsed_eh_frame: Option<EhFrame>,
+    /// EhFrame タブの一覧に並べた項目
+    eh_frame_entries: Vec<FrameEntry>,
+    parsed_relocations: Vec<Relocation>,
+    /// 再配置先に埋め込まれたものも含めた加数
+    relocation_addends: Vec<Option<i64>>,
+    parsed_plt_got: PltGot,
+    parsed_ifuncs: Vec<Ifunc>,
+    /// IFUNC に注意が要る文脈 (静的リンク, 非 PIE)
+    ifunc_contexts: Vec<ifunc::Context>,
+    parsed_tls: Tls,
+    /// .symtab が無いときに, 他の情報から見つけた関数
+    parsed_recovered_functions: Vec<RecoveredFunction>,
+    debug_report: DebugFileReport,
+    /// 分離デバッグファイルから取り込んだもの
+    symbols_from_debug_file: bool,
+    dwarf_from_debug_file: bool,
 }
 
 impl<'a> App<'a> {
@@ -50,7 +150,11 @@ impl<'a> App<'a> {
         frame.render_widget(block, outline);
 
         let tabs = Tabs::new(self.tabs.titles.clone())
-            .block(Block::default().borders(Borders::ALL).title(file_path))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(file_path.clone()),
+            )
             .select(self.tabs.index)
             .style(Style::default().fg(Color::Cyan))
             .highlight_style(
@@ -61,12 +165,33 @@ impl<'a> App<'a> {
         frame.render_widget(tabs, chunks[0]);
 
         match self.state() {
-            AppState::Header => self.draw_header_tab(frame, &elf_file, chunks[1]),
-            AppState::Section => self.draw_section_tab(frame, &elf_file, chunks[1]),
-            AppState::Segment => self.draw_segment_tab(frame, &elf_file, chunks[1]),
-            AppState::Symbol => self.draw_symbol_tab(frame, &elf_file, chunks[1], self.state()),
-            AppState::DynSym => self.draw_symbol_tab(frame, &elf_file, chunks[1], self.state()),
-            AppState::Dynamics => self.draw_dynamic_tab(frame, &elf_file, chunks[1]),
+            AppState::Header => self.draw_header_tab(frame, elf_file, chunks[1]),
+            AppState::Section => self.draw_section_tab(frame, elf_file, chunks[1]),
+            AppState::Segment => self.draw_segment_tab(frame, elf_file, chunks[1]),
+            AppState::Symbol => self.draw_symbol_tab(frame, elf_file, chunks[1], self.state()),
+            AppState::DynSym => self.draw_symbol_tab(frame, elf_file, chunks[1], self.state()),
+            AppState::Dynamics => self.draw_dynamic_tab(frame, elf_file, &file_path, chunks[1]),
+            AppState::Binding => self.draw_binding_tab(frame, &file_path, chunks[1]),
+            AppState::LdCache => self.draw_ld_cache_tab(frame, chunks[1]),
+            AppState::HashTable => self.draw_hash_table_tab(frame, chunks[1]),
+            AppState::StringTable => self.draw_string_table_tab(frame, elf_file, chunks[1]),
+            AppState::PrintableString => self.draw_printable_string_tab(frame, elf_file, chunks[1]),
+            AppState::Dwarf => self.draw_dwarf_tab(frame, chunks[1]),
+            AppState::StructLayout => self.draw_struct_layout_tab(frame, chunks[1]),
+            AppState::InitFini => self.draw_init_fini_tab(frame, elf_file, chunks[1]),
+            AppState::EhFrame => self.draw_eh_frame_tab(frame, elf_file, chunks[1]),
+            AppState::Relocation => self.draw_relocation_tab(frame, elf_file, chunks[1]),
+            AppState::PltGot => self.draw_plt_got_tab(frame, elf_file, chunks[1]),
+            AppState::AddressLayout => self.draw_address_layout_tab(frame, elf_file, chunks[1]),
+            AppState::FileLayout => self.draw_file_layout_tab(frame, elf_file, chunks[1]),
+            AppState::Hex => self.draw_hex_tab(frame, elf_file, chunks[1]),
+            AppState::Ifunc => self.draw_ifunc_tab(frame, elf_file, chunks[1]),
+            AppState::Tls => self.draw_tls_tab(frame, elf_file, chunks[1]),
+            AppState::RecoveredFunction => {
+                self.draw_recovered_function_tab(frame, elf_file, chunks[1])
+            }
+            AppState::Address => self.draw_address_tab(frame, elf_file, chunks[1]),
+            AppState::DebugFile => self.draw_debug_file_tab(frame, chunks[1]),
         }
     }
 
@@ -76,8 +201,8 @@ impl<'a> App<'a> {
         elf_file: &'a file::ELF64,
         area: Rect,
     ) {
-        let inner = elf_header::header_information(&elf_file);
-        frame.render_widget(inner, area);
+        let fields = elf_header::header_list(elf_file, self.sections_reconstructed);
+        frame.render_stateful_widget(fields, area, &mut self.header_fields.borrow_mut().state);
     }
     fn draw_section_tab<B: Backend>(
         &mut self,
@@ -87,13 +212,20 @@ impl<'a> App<'a> {
     ) {
         let chunks = self.split_list_and_detail(area);
 
-        let scts = sections::section_list(&elf_file);
+        let scts = sections::section_list(elf_file, self.sections_reconstructed);
         let selected_sct = self.sections.borrow().state.selected().unwrap();
 
         frame.render_stateful_widget(scts, chunks[0], &mut self.sections.borrow_mut().state);
 
-        let selected_sct = &elf_file.sections[selected_sct];
-        let sct_info = sections::section_information(elf_file, selected_sct);
+        let compression = self.section_compressions[selected_sct];
+        let decompressed = self.decompressed_previews[selected_sct].as_deref();
+        let sct_info = sections::section_information(
+            elf_file,
+            selected_sct,
+            compression,
+            decompressed,
+            self.sections_reconstructed,
+        );
         frame.render_widget(sct_info, chunks[1]);
     }
     fn draw_segment_tab<B: Backend>(
@@ -104,7 +236,7 @@ impl<'a> App<'a> {
     ) {
         let chunks = self.split_list_and_detail(area);
 
-        let segs = segments::segment_list(&elf_file);
+        let segs = segments::segment_list(elf_file);
         let selected_seg = self.segments.borrow().state.selected().unwrap();
 
         frame.render_stateful_widget(segs, chunks[0], &mut self.segments.borrow_mut().state);
@@ -124,7 +256,9 @@ impl<'a> App<'a> {
 
         match state {
             AppState::Symbol => {
-                let symbols = symbols::symbol_table_list(self.symtab_sct);
+                // 分離デバッグファイルのシンボルはセクションヘッダから読んでいる
+                let reconstructed = self.sections_reconstructed && !self.symbols_from_debug_file;
+                let symbols = symbols::symbol_table_list(self.symtab_sct, reconstructed);
                 frame.render_stateful_widget(
                     symbols,
                     chunks[0],
@@ -132,14 +266,17 @@ impl<'a> App<'a> {
                 );
 
                 let sym_info = symbols::symbol_information(
-                    elf_file,
+                    self.symtab_file,
                     self.symtab_sct.unwrap(),
                     self.symbol_table.borrow().state.selected().unwrap(),
+                    self.parsed_dwarf.as_ref(),
+                    reconstructed,
                 );
                 frame.render_widget(sym_info, chunks[1]);
             }
             AppState::DynSym => {
-                let symbols = symbols::symbol_table_list(self.dynsym_sct);
+                let symbols =
+                    symbols::symbol_table_list(self.dynsym_sct, self.sections_reconstructed);
                 frame.render_stateful_widget(
                     symbols,
                     chunks[0],
@@ -150,6 +287,8 @@ impl<'a> App<'a> {
                     elf_file,
                     self.dynsym_sct.unwrap(),
                     self.dynamic_symbol_table.borrow().state.selected().unwrap(),
+                    self.parsed_dwarf.as_ref(),
+                    self.sections_reconstructed,
                 );
                 frame.render_widget(sym_info, chunks[1]);
             }
@@ -160,11 +299,12 @@ impl<'a> App<'a> {
         &mut self,
         frame: &mut Frame<B>,
         elf_file: &'a file::ELF64,
+        file_path: &str,
         area: Rect,
     ) {
         let chunks = self.split_list_and_detail(area);
 
-        let dynamics = dynamics::dynamic_list(self.dynamic_sct);
+        let dynamics = dynamics::dynamic_list(self.dynamic_sct, self.sections_reconstructed);
         frame.render_stateful_widget(
             dynamics,
             chunks[0],
@@ -173,12 +313,441 @@ impl<'a> App<'a> {
 
         let dyn_info = dynamics::dynamic_information(
             elf_file,
+            file_path,
             self.dynamic_sct.unwrap(),
-            self.symtab_sct.unwrap(),
+            // strip されたファイルでは .dynsym で代用する
+            self.symtab_sct.or(self.dynsym_sct),
+            &self.parsed_init_fini,
             self.dynamic_table.borrow().state.selected().unwrap(),
+            self.sections_reconstructed,
         );
         frame.render_widget(dyn_info, chunks[1]);
     }
+    fn draw_binding_tab<B: Backend>(&mut self, frame: &mut Frame<B>, file_path: &str, area: Rect) {
+        if self.binding_report.is_none() {
+            let report = binding::resolve(file_path, self.file_bytes, self.ld_cache.as_ref());
+            let mut binding_list = StatefulList::with_items(bindings::binding_names(&report));
+            binding_list.next();
+            *self.bindings.borrow_mut() = binding_list;
+            self.binding_report = Some(report);
+        }
+        let report = self.binding_report.as_ref().unwrap();
+
+        let chunks = self.split_list_and_detail(area);
+        let detail_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[1]);
+
+        let binding_list = bindings::binding_list(report);
+        frame.render_stateful_widget(
+            binding_list,
+            chunks[0],
+            &mut self.bindings.borrow_mut().state,
+        );
+
+        let binding_info =
+            bindings::binding_information(report, self.bindings.borrow().state.selected().unwrap());
+        frame.render_widget(binding_info, detail_chunks[0]);
+
+        let unresolved = bindings::unresolved_information(report);
+        frame.render_widget(unresolved, detail_chunks[1]);
+    }
+    fn draw_ld_cache_tab<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let chunks = self.split_list_and_detail(area);
+        let cache = self.ld_cache.as_ref().unwrap();
+
+        let entries = ld_cache::cache_entry_list(cache);
+        frame.render_stateful_widget(
+            entries,
+            chunks[0],
+            &mut self.ld_cache_entries.borrow_mut().state,
+        );
+
+        let entry_info = ld_cache::cache_entry_information(
+            cache,
+            self.ld_cache_entries.borrow().state.selected().unwrap(),
+        );
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_hash_table_tab<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let chunks = self.split_list_and_detail(area);
+        let detail_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(chunks[1]);
+
+        let tables = hash_tables::hash_table_list(&self.parsed_hash_tables);
+        frame.render_stateful_widget(tables, chunks[0], &mut self.hash_tables.borrow_mut().state);
+
+        let table = &self.parsed_hash_tables[self.hash_tables.borrow().state.selected().unwrap()];
+        let table_info = hash_tables::hash_table_information(table);
+        frame.render_widget(table_info, detail_chunks[0]);
+
+        let lookup_info =
+            hash_tables::lookup_information(table, &self.hash_query, self.editing_query);
+        frame.render_widget(lookup_info, detail_chunks[1]);
+    }
+    fn draw_string_table_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let strings =
+            string_tables::string_list(&self.parsed_string_tables, self.string_table_index);
+        frame.render_stateful_widget(strings, chunks[0], &mut self.strings.borrow_mut().state);
+
+        let string_info = string_tables::string_information(
+            elf_file,
+            &self.parsed_string_tables[self.string_table_index],
+            self.strings.borrow().state.selected().unwrap(),
+            self.string_user_index,
+        );
+        frame.render_widget(string_info, chunks[1]);
+    }
+    fn draw_printable_string_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let strings = printable_strings::printable_string_list(
+            &self.parsed_printable_strings,
+            self.min_string_length,
+            self.include_unloaded_strings,
+        );
+        frame.render_stateful_widget(
+            strings,
+            chunks[0],
+            &mut self.printable_strings.borrow_mut().state,
+        );
+
+        let selected = self.printable_strings.borrow().state.selected();
+        match selected.and_then(|idx| self.parsed_printable_strings.get(idx)) {
+            Some(string) => {
+                let string_info = printable_strings::printable_string_information(
+                    elf_file,
+                    string,
+                    &self.string_xrefs,
+                );
+                frame.render_widget(string_info, chunks[1]);
+            }
+            None => frame.render_widget(
+                printable_strings::no_printable_string_information(
+                    self.min_string_length,
+                    self.include_unloaded_strings,
+                ),
+                chunks[1],
+            ),
+        }
+    }
+    fn draw_dwarf_tab<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let chunks = self.split_list_and_detail(area);
+        let parsed_dwarf = self.parsed_dwarf.as_ref().unwrap();
+
+        let dies = dwarf::die_tree_list(parsed_dwarf, &self.visible_dies, |offset| {
+            self.expanded_dies.contains(&offset)
+        });
+        frame.render_stateful_widget(dies, chunks[0], &mut self.dies.borrow_mut().state);
+
+        let (unit_idx, die_idx) = self.visible_dies[self.dies.borrow().state.selected().unwrap()];
+        let die_info = dwarf::die_information(parsed_dwarf, unit_idx, die_idx);
+        frame.render_widget(die_info, chunks[1]);
+    }
+    fn draw_struct_layout_tab<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let chunks = self.split_list_and_detail(area);
+
+        let layouts = struct_layouts::struct_layout_list(&self.parsed_struct_layouts);
+        frame.render_stateful_widget(
+            layouts,
+            chunks[0],
+            &mut self.struct_layouts.borrow_mut().state,
+        );
+
+        let layout =
+            &self.parsed_struct_layouts[self.struct_layouts.borrow().state.selected().unwrap()];
+        let layout_info = struct_layouts::struct_layout_information(layout);
+        frame.render_widget(layout_info, chunks[1]);
+    }
+    fn draw_init_fini_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let entries = init_fini::init_fini_list(elf_file, &self.parsed_init_fini);
+        frame.render_stateful_widget(entries, chunks[0], &mut self.init_fini.borrow_mut().state);
+
+        let entry = &self.parsed_init_fini[self.init_fini.borrow().state.selected().unwrap()];
+        let entry_info =
+            init_fini::init_fini_information(elf_file, entry, self.parsed_dwarf.as_ref());
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_eh_frame_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+        let eh_frame = self.parsed_eh_frame.as_ref().unwrap();
+        let symbol_table = self.symtab_sct.or(self.dynsym_sct);
+
+        let entries = eh_frame::eh_frame_list(elf_file, eh_frame, symbol_table);
+        frame.render_stateful_widget(entries, chunks[0], &mut self.eh_frames.borrow_mut().state);
+
+        let entry = self.eh_frame_entries[self.eh_frames.borrow().state.selected().unwrap()];
+        let entry_info = eh_frame::eh_frame_information(elf_file, eh_frame, entry, symbol_table);
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_relocation_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let entries = relocation_widgets::relocation_list(
+            elf_file,
+            &self.parsed_relocations,
+            &self.relocation_addends,
+        );
+        frame.render_stateful_widget(entries, chunks[0], &mut self.relocations.borrow_mut().state);
+
+        let entry_info = relocation_widgets::relocation_information(
+            elf_file,
+            &self.parsed_relocations,
+            &self.relocation_addends,
+            self.relocations.borrow().state.selected().unwrap(),
+            self.symtab_sct.or(self.dynsym_sct),
+        );
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_plt_got_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let entries = plt_got::plt_got_list(elf_file, &self.parsed_plt_got);
+        frame.render_stateful_widget(entries, chunks[0], &mut self.plt_got.borrow_mut().state);
+
+        let entry_info = plt_got::plt_got_information(
+            elf_file,
+            &self.parsed_plt_got,
+            self.plt_got.borrow().state.selected().unwrap(),
+            self.symtab_sct.or(self.dynsym_sct),
+        );
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_ifunc_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let ifuncs = ifuncs::ifunc_list(&self.parsed_ifuncs);
+        frame.render_stateful_widget(ifuncs, chunks[0], &mut self.ifuncs.borrow_mut().state);
+
+        let ifunc = &self.parsed_ifuncs[self.ifuncs.borrow().state.selected().unwrap()];
+        let ifunc_info = ifuncs::ifunc_information(elf_file, ifunc, &self.ifunc_contexts);
+        frame.render_widget(ifunc_info, chunks[1]);
+    }
+    fn draw_address_layout_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_map_and_detail(area);
+
+        let rows = address_layout_widgets::address_layout_list(
+            elf_file,
+            &self.parsed_address_layout,
+            chunks[0].width,
+        );
+        frame.render_stateful_widget(
+            rows,
+            chunks[0],
+            &mut self.address_layout_rows.borrow_mut().state,
+        );
+
+        let row_info = address_layout_widgets::address_layout_information(
+            elf_file,
+            &self.parsed_address_layout,
+            self.address_layout_rows.borrow().state.selected().unwrap(),
+        );
+        frame.render_widget(row_info, chunks[1]);
+    }
+    fn draw_file_layout_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_map_and_detail(area);
+
+        let rows = file_layout_widgets::file_layout_list(
+            elf_file,
+            &self.parsed_file_layout,
+            chunks[0].width,
+        );
+        frame.render_stateful_widget(
+            rows,
+            chunks[0],
+            &mut self.file_layout_rows.borrow_mut().state,
+        );
+
+        let row_info = file_layout_widgets::file_layout_information(
+            elf_file,
+            &self.parsed_file_layout,
+            self.file_layout_rows.borrow().state.selected().unwrap(),
+        );
+        frame.render_widget(row_info, chunks[1]);
+    }
+    fn draw_hex_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(area);
+        let view_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(48)].as_ref())
+            .split(chunks[0]);
+
+        // カーソルの行が見えるように表示範囲をずらす
+        self.hex_rows = view_chunks[0].height.saturating_sub(2).max(1) as usize;
+        let cursor_row = self.hex_cursor / hex_view::BYTES_PER_ROW;
+        if cursor_row < self.hex_top {
+            self.hex_top = cursor_row;
+        } else if self.hex_top + self.hex_rows <= cursor_row {
+            self.hex_top = cursor_row + 1 - self.hex_rows;
+        }
+
+        let hex = hex_view::hex_view(
+            elf_file,
+            self.file_bytes,
+            &self.parsed_file_fields,
+            self.hex_cursor,
+            self.hex_top,
+            self.hex_rows,
+        );
+        frame.render_widget(hex, view_chunks[0]);
+
+        let inspector = data_inspector::data_inspector_information(
+            elf_file,
+            self.file_bytes,
+            self.symtab_sct.or(self.dynsym_sct),
+            self.hex_cursor,
+        );
+        frame.render_widget(inspector, view_chunks[1]);
+
+        let status = hex_view::hex_status(
+            elf_file,
+            self.file_bytes,
+            &self.parsed_file_fields,
+            self.hex_cursor,
+        );
+        frame.render_widget(status, chunks[1]);
+    }
+    fn draw_tls_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let entries = tls_widgets::tls_list(elf_file, &self.parsed_tls);
+        frame.render_stateful_widget(entries, chunks[0], &mut self.tls_entries.borrow_mut().state);
+
+        let entry_info = tls_widgets::tls_information(
+            elf_file,
+            &self.parsed_tls,
+            self.tls_entries.borrow().state.selected().unwrap(),
+        );
+        frame.render_widget(entry_info, chunks[1]);
+    }
+    fn draw_recovered_function_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let chunks = self.split_list_and_detail(area);
+
+        let functions =
+            recovered_functions::recovered_function_list(&self.parsed_recovered_functions);
+        frame.render_stateful_widget(
+            functions,
+            chunks[0],
+            &mut self.recovered_functions.borrow_mut().state,
+        );
+
+        let function = &self.parsed_recovered_functions
+            [self.recovered_functions.borrow().state.selected().unwrap()];
+        let function_info = recovered_functions::recovered_function_information(
+            elf_file,
+            function,
+            self.parsed_dwarf.as_ref(),
+        );
+        frame.render_widget(function_info, chunks[1]);
+    }
+    fn draw_address_tab<B: Backend>(
+        &mut self,
+        frame: &mut Frame<B>,
+        elf_file: &'a file::ELF64,
+        area: Rect,
+    ) {
+        let inner = address::address_information(
+            elf_file,
+            self.symtab_sct.or(self.dynsym_sct),
+            self.parsed_dwarf.as_ref(),
+            &self.parsed_recovered_functions,
+            &self.address_query,
+            self.editing_query,
+        );
+        frame.render_widget(inner, area);
+    }
+    fn draw_debug_file_tab<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let mut merged = Vec::new();
+        if self.symbols_from_debug_file {
+            merged.push((
+                "Symbols",
+                format!(
+                    "{} entries in {}",
+                    symbols::symbol_names(self.symtab_sct).len(),
+                    self.symtab_sct.unwrap().name
+                ),
+            ));
+        }
+        if self.dwarf_from_debug_file {
+            merged.push((
+                "DWARF",
+                format!("{} units", self.parsed_dwarf.as_ref().unwrap().units.len()),
+            ));
+        }
+
+        let inner = debug_file::debug_file_information(&self.debug_report, merged);
+        frame.render_widget(inner, area);
+    }
     fn split_list_and_detail(&self, area: Rect) -> Vec<Rect> {
         Layout::default()
             .direction(Direction::Horizontal)
@@ -186,14 +755,46 @@ impl<'a> App<'a> {
             .split(area)
     }
 
-    pub fn new(elf_file: &'a file::ELF64) -> Self {
-        let symtab_sct =
-            elf_file.first_section_by(|sct| sct.header.get_type() == section::Type::SymTab);
+    /// 横幅を使う図は上に, 詳細はその下に置く
+    fn split_map_and_detail(&self, area: Rect) -> Vec<Rect> {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area)
+    }
+
+    pub fn new(
+        elf_file: &'a file::ELF64,
+        file_bytes: &'a [u8],
+        sections_reconstructed: bool,
+        ld_cache: Option<LdCache>,
+        min_string_length: usize,
+        debug_report: DebugFileReport,
+        debug_file: Option<(&'a file::ELF64, &[u8])>,
+    ) -> Self {
+        let symtab = |elf_file: &'a file::ELF64| {
+            elf_file.first_section_by(|sct| sct.header.get_type() == section::Type::SymTab)
+        };
+        // strip されていれば, 分離デバッグファイルのシンボルと DWARF で補う
+        let (symtab_sct, symtab_file, symbols_from_debug_file) =
+            match (symtab(elf_file), debug_file) {
+                (None, Some((debug_elf, _))) if symtab(debug_elf).is_some() => {
+                    (symtab(debug_elf), debug_elf, true)
+                }
+                (symtab_sct, _) => (symtab_sct, elf_file, false),
+            };
         let dynsym_sct =
             elf_file.first_section_by(|sct| sct.header.get_type() == section::Type::DynSym);
         let dynamic_sct =
             elf_file.first_section_by(|sct| sct.header.get_type() == section::Type::Dynamic);
 
+        let mut header_fields = StatefulList::with_items(
+            elf_header::header_fields()
+                .map(|field| field.to_string())
+                .collect(),
+        );
+        header_fields.next();
+
         let mut sections = StatefulList::with_items(sections::section_names(elf_file));
         sections.next();
 
@@ -201,6 +802,19 @@ impl<'a> App<'a> {
             StatefulList::with_items((0..elf_file.ehdr.e_phnum).map(|n| n.to_string()).collect());
         segments.next();
 
+        let parsed_address_layout = crate::elf_util::address_layout::address_layout(elf_file);
+        let mut address_layout_rows = StatefulList::with_items(
+            address_layout_widgets::address_layout_names(elf_file, &parsed_address_layout),
+        );
+        address_layout_rows.next();
+
+        let parsed_file_layout =
+            crate::elf_util::file_layout::file_layout(elf_file, file_bytes, sections_reconstructed);
+        let mut file_layout_rows = StatefulList::with_items(
+            file_layout_widgets::file_layout_names(elf_file, &parsed_file_layout),
+        );
+        file_layout_rows.next();
+
         let mut symbols = StatefulList::with_items(symbols::symbol_names(symtab_sct));
         symbols.next();
 
@@ -210,41 +824,548 @@ impl<'a> App<'a> {
         let mut dynamics = StatefulList::with_items(dynamics::dynamic_names(dynamic_sct));
         dynamics.next();
 
-        Self {
-            tabs: create_tabs_state(elf_file, symtab_sct, dynsym_sct, dynamic_sct),
+        let mut ld_cache_entries = StatefulList::with_items(
+            ld_cache
+                .as_ref()
+                .map(ld_cache::cache_entry_names)
+                .unwrap_or_default(),
+        );
+        ld_cache_entries.next();
+
+        let parsed_hash_tables = hash_table::hash_tables(elf_file);
+        let mut hash_table_list =
+            StatefulList::with_items(hash_tables::hash_table_names(&parsed_hash_tables));
+        hash_table_list.next();
+
+        let parsed_string_tables = string_table::string_tables(elf_file, file_bytes);
+        let mut string_list = StatefulList::with_items(
+            parsed_string_tables
+                .first()
+                .map(string_tables::string_names)
+                .unwrap_or_default(),
+        );
+        string_list.next();
+
+        let parsed_printable_strings = crate::elf_util::printable_strings::extract(
+            elf_file,
+            file_bytes,
+            min_string_length,
+            false,
+        );
+        let printable_string_list = printable_string_list(&parsed_printable_strings);
+
+        let mut parsed_dwarf = Dwarf::parse(elf_file, file_bytes).filter(|d| !d.units.is_empty());
+        let mut dwarf_from_debug_file = false;
+        if let (None, Some((debug_elf, debug_bytes))) = (&parsed_dwarf, debug_file) {
+            parsed_dwarf = Dwarf::parse(debug_elf, debug_bytes).filter(|d| !d.units.is_empty());
+            dwarf_from_debug_file = parsed_dwarf.is_some();
+        }
+        let parsed_struct_layouts = parsed_dwarf
+            .as_ref()
+            .map(struct_layout::struct_layouts)
+            .unwrap_or_default();
+        let mut struct_layout_list =
+            StatefulList::with_items(struct_layouts::struct_layout_names(&parsed_struct_layouts));
+        struct_layout_list.next();
+
+        let parsed_init_fini = crate::elf_util::init_fini::init_fini_entries(
+            elf_file,
+            file_bytes,
+            symtab_sct.or(dynsym_sct),
+        );
+        let mut init_fini_list =
+            StatefulList::with_items(init_fini::init_fini_names(elf_file, &parsed_init_fini));
+        init_fini_list.next();
+
+        let parsed_eh_frame = EhFrame::parse(elf_file, file_bytes);
+        let eh_frame_entries = parsed_eh_frame
+            .as_ref()
+            .map(EhFrame::entries)
+            .unwrap_or_default();
+        let mut eh_frame_list = StatefulList::with_items(
+            parsed_eh_frame
+                .as_ref()
+                .map(|eh_frame| {
+                    eh_frame::eh_frame_names(elf_file, eh_frame, symtab_sct.or(dynsym_sct))
+                })
+                .unwrap_or_default(),
+        );
+        eh_frame_list.next();
+
+        let parsed_relocations = relocations::relocations(elf_file);
+        let relocation_addends: Vec<Option<i64>> = parsed_relocations
+            .iter()
+            .map(|reloc| relocations::addend(elf_file, file_bytes, reloc))
+            .collect();
+        let mut relocation_list = StatefulList::with_items(relocation_widgets::relocation_names(
+            elf_file,
+            &parsed_relocations,
+            &relocation_addends,
+        ));
+        relocation_list.next();
+
+        let parsed_plt_got = crate::elf_util::plt_got::plt_got(elf_file, file_bytes);
+        let mut plt_got_list =
+            StatefulList::with_items(plt_got::plt_got_names(elf_file, &parsed_plt_got));
+        plt_got_list.next();
+
+        let parsed_ifuncs = ifunc::ifuncs(
+            elf_file,
+            file_bytes,
+            &symtab_sct.into_iter().chain(dynsym_sct).collect::<Vec<_>>(),
+        );
+        let mut ifunc_list = StatefulList::with_items(ifuncs::ifunc_names(&parsed_ifuncs));
+        ifunc_list.next();
+
+        let parsed_tls = crate::elf_util::tls::tls(elf_file, file_bytes, symtab_sct.or(dynsym_sct));
+        let mut tls_list = StatefulList::with_items(tls_widgets::tls_names(elf_file, &parsed_tls));
+        tls_list.next();
+
+        let parsed_recovered_functions = match symtab_sct {
+            Some(_) => Vec::new(),
+            None => recovered_function::recover(
+                elf_file,
+                parsed_eh_frame.as_ref(),
+                dynsym_sct,
+                &parsed_plt_got,
+                &parsed_init_fini,
+            ),
+        };
+        let mut recovered_function_list = StatefulList::with_items(
+            recovered_functions::recovered_function_names(&parsed_recovered_functions),
+        );
+        recovered_function_list.next();
+
+        let mut app = Self {
+            tabs: TabsState::new(vec!["Header"]),
+            header_fields: RefCell::new(header_fields),
             sections: RefCell::new(sections),
             segments: RefCell::new(segments),
+            address_layout_rows: RefCell::new(address_layout_rows),
+            file_layout_rows: RefCell::new(file_layout_rows),
+            hex_cursor: 0,
+            hex_top: 0,
+            hex_rows: 1,
             symbol_table: RefCell::new(symbols),
             dynamic_symbol_table: RefCell::new(dynamic_symbols),
             dynamic_table: RefCell::new(dynamics),
+            bindings: RefCell::new(StatefulList::with_items(Vec::new())),
+            ld_cache_entries: RefCell::new(ld_cache_entries),
+            hash_tables: RefCell::new(hash_table_list),
+            hash_query: String::new(),
+            address_query: String::new(),
+            editing_query: false,
+            strings: RefCell::new(string_list),
             symtab_sct,
+            parsed_address_layout,
+            parsed_file_layout,
+            parsed_file_fields: crate::elf_util::file_fields::file_fields(
+                elf_file,
+                sections_reconstructed,
+            ),
+            file_bytes,
+            sections_reconstructed,
+            section_compressions: elf_file
+                .sections
+                .iter()
+                .map(|sct| compression::compression(sct, file_bytes))
+                .collect(),
+            decompressed_previews: elf_file
+                .sections
+                .iter()
+                .map(|sct| {
+                    compression::compression(sct, file_bytes)?;
+                    let data = compression::section_data(sct, file_bytes)?;
+                    let len = data.len().min(sections::DECOMPRESSED_PREVIEW_SIZE);
+                    Some(data[..len].to_vec())
+                })
+                .collect(),
+            symtab_file,
             dynsym_sct,
             dynamic_sct,
+            binding_report: None,
+            has_imports: binding::has_imports(file_bytes),
+            ld_cache,
+            parsed_hash_tables,
+            parsed_string_tables,
+            string_table_index: 0,
+            string_user_index: 0,
+            printable_strings: RefCell::new(printable_string_list),
+            parsed_printable_strings,
+            string_xrefs: Xrefs::new(elf_file, file_bytes),
+            min_string_length,
+            include_unloaded_strings: false,
+            dies: RefCell::new(StatefulList::with_items(Vec::new())),
+            parsed_dwarf,
+            visible_dies: Vec::new(),
+            expanded_dies: HashSet::new(),
+            struct_layouts: RefCell::new(struct_layout_list),
+            parsed_struct_layouts,
+            init_fini: RefCell::new(init_fini_list),
+            parsed_init_fini,
+            eh_frames: RefCell::new(eh_frame_list),
+            parsed_eh_frame,
+            eh_frame_entries,
+            relocations: RefCell::new(relocation_list),
+            parsed_relocations,
+            relocation_addends,
+            plt_got: RefCell::new(plt_got_list),
+            parsed_plt_got,
+            ifuncs: RefCell::new(ifunc_list),
+            parsed_ifuncs,
+            ifunc_contexts: ifunc::contexts(elf_file),
+            tls_entries: RefCell::new(tls_list),
+            parsed_tls,
+            recovered_functions: RefCell::new(recovered_function_list),
+            parsed_recovered_functions,
+            debug_report,
+            symbols_from_debug_file,
+            dwarf_from_debug_file,
+        };
+        app.tabs = create_tabs_state(elf_file, &app);
+        app.update_visible_dies();
+        app.dies.borrow_mut().next();
+
+        app
+    }
+
+    /// 入力中の検索語. 表示中のタブによって対象が変わる.
+    pub fn query_mut(&mut self) -> &mut String {
+        match self.state() {
+            AppState::Address => &mut self.address_query,
+            _ => &mut self.hash_query,
+        }
+    }
+
+    /// Strings タブで表示する文字列テーブルを切り替える.
+    pub fn switch_string_table(&mut self, forward: bool) {
+        let count = self.parsed_string_tables.len();
+        self.string_table_index = if forward {
+            (self.string_table_index + 1) % count
+        } else {
+            (self.string_table_index + count - 1) % count
+        };
+
+        let mut strings = StatefulList::with_items(string_tables::string_names(
+            &self.parsed_string_tables[self.string_table_index],
+        ));
+        strings.next();
+        self.strings = RefCell::new(strings);
+        self.string_user_index = 0;
+    }
+
+    pub fn next_string(&mut self) {
+        self.strings.borrow_mut().next();
+        self.string_user_index = 0;
+    }
+
+    pub fn previous_string(&mut self) {
+        self.strings.borrow_mut().previous();
+        self.string_user_index = 0;
+    }
+
+    /// 選択中の文字列の参照元を順に選ぶ.
+    pub fn next_string_user(&mut self) {
+        let count = self.selected_string_users().len();
+        if count != 0 {
+            self.string_user_index = (self.string_user_index + 1) % count;
+        }
+    }
+
+    /// 選択中の参照元を表示するタブに移動する.
+    pub fn jump_to_string_user(&mut self, elf_file: &file::ELF64) {
+        let user = match self.selected_string_users().get(self.string_user_index) {
+            Some(user) => *user,
+            None => return,
+        };
+
+        let (title, list, index) = match user {
+            StringUser::SectionName { section } | StringUser::Version { section } => {
+                ("Sections", &self.sections, section)
+            }
+            StringUser::Symbol { section, index } => {
+                let is_symtab = elf_file
+                    .sections
+                    .get(section)
+                    .is_some_and(|sct| sct.header.get_type() == section::Type::SymTab);
+                if is_symtab {
+                    ("Symbols", &self.symbol_table, index)
+                } else {
+                    ("DynSyms", &self.dynamic_symbol_table, index)
+                }
+            }
+            StringUser::Dynamic { index, .. } => ("Dynamics", &self.dynamic_table, index),
+        };
+
+        if self.tabs.select(title) {
+            list.borrow_mut().state.select(Some(index));
         }
     }
+
+    /// 選択中の行のヘッダ, セクション, セグメントを表示するタブに移動する.
+    /// 重なりは後ろ側のものに移動する.
+    pub fn goto_file_region(&mut self) {
+        let selected = self.file_layout_rows.borrow().state.selected();
+        let kind = match selected.and_then(|idx| self.parsed_file_layout.regions.get(idx)) {
+            Some(region) => region.kind,
+            None => return,
+        };
+
+        let (title, list, index) = match kind {
+            FileRegionKind::Part(Part::ElfHeader) => {
+                self.tabs.select("Header");
+                return;
+            }
+            FileRegionKind::Part(Part::ProgramHeaders) => {
+                self.tabs.select("Segments");
+                return;
+            }
+            FileRegionKind::Segment(index) => ("Segments", &self.segments, index),
+            FileRegionKind::Part(Part::Section(index))
+            | FileRegionKind::Overlap(_, Part::Section(index)) => {
+                ("Sections", &self.sections, index)
+            }
+            FileRegionKind::Part(Part::SectionHeaders) => ("Sections", &self.sections, 0),
+            FileRegionKind::Overlap(_, part) => {
+                let title = match part {
+                    Part::ElfHeader => "Header",
+                    Part::ProgramHeaders => "Segments",
+                    _ => "Sections",
+                };
+                self.tabs.select(title);
+                return;
+            }
+            FileRegionKind::Gap => return,
+        };
+
+        if self.tabs.select(title) {
+            list.borrow_mut().state.select(Some(index));
+        }
+    }
+
+    /// Hex タブのカーソルを delta バイト動かす. ファイルの外には出ない.
+    pub fn move_hex_cursor(&mut self, delta: isize) {
+        let last = self.file_bytes.len().saturating_sub(1);
+        self.hex_cursor = self.hex_cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Hex タブのカーソルを 1 画面分動かす.
+    pub fn move_hex_page(&mut self, forward: bool) {
+        let delta = (self.hex_rows * hex_view::BYTES_PER_ROW) as isize;
+        self.move_hex_cursor(if forward { delta } else { -delta });
+    }
+
+    /// 選択中の ELF ヘッダのフィールドを Hex タブで表示する.
+    pub fn goto_header_field(&mut self) {
+        let selected = self.header_fields.borrow().state.selected();
+        let offset = selected
+            .and_then(elf_header::header_field)
+            .and_then(FileFields::header_field_offset);
+        if let Some(offset) = offset {
+            if self.tabs.select("Hex") {
+                self.hex_cursor = offset as usize;
+            }
+        }
+    }
+
+    /// 選択中の関数のアドレスを Address タブで引く.
+    pub fn goto_recovered_function(&mut self) {
+        let selected = self.recovered_functions.borrow().state.selected();
+        let function = match selected.and_then(|idx| self.parsed_recovered_functions.get(idx)) {
+            Some(function) => function,
+            None => return,
+        };
+
+        self.address_query = format!("0x{:x}", function.address);
+        self.tabs.select("Address");
+    }
+
+    /// 最小文字数を変えて文字列を抜き出し直す.
+    pub fn change_min_string_length(
+        &mut self,
+        elf_file: &file::ELF64,
+        file_bytes: &[u8],
+        longer: bool,
+    ) {
+        self.min_string_length = if longer {
+            self.min_string_length + 1
+        } else {
+            (self.min_string_length - 1).max(1)
+        };
+        self.reload_printable_strings(elf_file, file_bytes);
+    }
+
+    /// ロードされないセクションも対象にするかを切り替えて, 文字列を抜き出し直す.
+    pub fn toggle_unloaded_strings(&mut self, elf_file: &file::ELF64, file_bytes: &[u8]) {
+        self.include_unloaded_strings = !self.include_unloaded_strings;
+        self.reload_printable_strings(elf_file, file_bytes);
+    }
+
+    fn reload_printable_strings(&mut self, elf_file: &file::ELF64, file_bytes: &[u8]) {
+        self.parsed_printable_strings = crate::elf_util::printable_strings::extract(
+            elf_file,
+            file_bytes,
+            self.min_string_length,
+            self.include_unloaded_strings,
+        );
+        self.printable_strings =
+            RefCell::new(printable_string_list(&self.parsed_printable_strings));
+    }
+
+    pub fn next_printable_string(&mut self) {
+        if !self.parsed_printable_strings.is_empty() {
+            self.printable_strings.borrow_mut().next();
+        }
+    }
+
+    pub fn previous_printable_string(&mut self) {
+        if !self.parsed_printable_strings.is_empty() {
+            self.printable_strings.borrow_mut().previous();
+        }
+    }
+
+    /// 選択中の DIE の子を展開/折り畳む.
+    pub fn toggle_die(&mut self) {
+        let selected = self.dies.borrow().state.selected();
+        let (unit_idx, die_idx) = match selected.and_then(|idx| self.visible_dies.get(idx)) {
+            Some(found) => *found,
+            None => return,
+        };
+        let offset = self.parsed_dwarf.as_ref().unwrap().units[unit_idx].dies[die_idx].offset;
+        if !self.expanded_dies.remove(&offset) {
+            self.expanded_dies.insert(offset);
+        }
+
+        self.update_visible_dies();
+        self.dies.borrow_mut().state.select(selected);
+    }
+
+    /// 折り畳まれていない DIE だけを並べ直す.
+    fn update_visible_dies(&mut self) {
+        let parsed_dwarf = match &self.parsed_dwarf {
+            Some(parsed_dwarf) => parsed_dwarf,
+            None => return,
+        };
+
+        self.visible_dies.clear();
+        for (unit_idx, unit) in parsed_dwarf.units.iter().enumerate() {
+            // 折り畳まれた DIE の深さ. これより深い DIE は表示しない
+            let mut collapsed_depth = None;
+            for (die_idx, die) in unit.dies.iter().enumerate() {
+                match collapsed_depth {
+                    Some(depth) if die.depth > depth => continue,
+                    _ => collapsed_depth = None,
+                }
+                self.visible_dies.push((unit_idx, die_idx));
+                if !self.expanded_dies.contains(&die.offset) {
+                    collapsed_depth = Some(die.depth);
+                }
+            }
+        }
+
+        let names = dwarf::die_tree_names(parsed_dwarf, &self.visible_dies, |offset| {
+            self.expanded_dies.contains(&offset)
+        });
+        self.dies.borrow_mut().items = names;
+    }
+
+    fn selected_string_users(&self) -> Vec<StringUser> {
+        let selected = self.strings.borrow().state.selected().unwrap_or(0);
+        self.parsed_string_tables
+            .get(self.string_table_index)
+            .and_then(|table| table.entries.get(selected))
+            .map(|entry| entry.references.iter().map(|r| r.user).collect())
+            .unwrap_or_default()
+    }
 }
 
-fn create_tabs_state<'a>(
-    elf_file: &'a file::ELF64,
-    symtab_sct: Option<&'a section::Section64>,
-    dynsym_sct: Option<&'a section::Section64>,
-    dynamic_sct: Option<&'a section::Section64>,
-) -> TabsState<'a> {
-    let mut state = TabsState::new(vec!["Header", "Sections"]);
+fn printable_string_list(strings: &[PrintableString]) -> StatefulList<String> {
+    let mut list = StatefulList::with_items(printable_strings::printable_string_names(strings));
+    if !strings.is_empty() {
+        list.next();
+    }
+    list
+}
+
+/// 表示できる情報があるタブだけを並べる.
+fn create_tabs_state<'a>(elf_file: &'a file::ELF64, app: &App<'a>) -> TabsState<'a> {
+    let mut state = TabsState::new(vec!["Header"]);
+
+    if !elf_file.sections.is_empty() {
+        state.push("Sections");
+    }
 
     if elf_file.ehdr.e_phnum != 0 {
         state.push("Segments");
     }
+    if !app.parsed_address_layout.is_empty() {
+        state.push("AddrMap");
+    }
+    if !app.parsed_file_layout.is_empty() {
+        state.push("FileMap");
+    }
+    state.push("Hex");
 
-    if symtab_sct.is_some() {
+    if app.symtab_sct.is_some() {
         state.push("Symbols");
     }
-    if dynsym_sct.is_some() {
+    if !app.parsed_recovered_functions.is_empty() {
+        state.push("Recovered");
+    }
+    if app.dynsym_sct.is_some() {
         state.push("DynSyms");
     }
-    if dynamic_sct.is_some() {
+    if app.dynamic_sct.is_some() {
         state.push("Dynamics");
     }
+    if !app.parsed_relocations.is_empty() {
+        state.push("Relocations");
+    }
+    if !app.parsed_plt_got.is_empty() {
+        state.push("PLT/GOT");
+    }
+    if !app.parsed_ifuncs.is_empty() {
+        state.push("IFUNC");
+    }
+    if !app.parsed_tls.is_empty() {
+        state.push("TLS");
+    }
+    if !app.parsed_init_fini.is_empty() {
+        state.push("InitFini");
+    }
+    if !app.parsed_string_tables.is_empty() {
+        state.push("Strings");
+    }
+    // 最小文字数を変えれば見つかることもあるので, 空でもタブは出す
+    if !elf_file.sections.is_empty() {
+        state.push("Printable");
+    }
+    if app.parsed_dwarf.is_some() {
+        state.push("DWARF");
+    }
+    if !app.parsed_struct_layouts.is_empty() {
+        state.push("Structs");
+    }
+    if !app.eh_frame_entries.is_empty() {
+        state.push("EhFrame");
+    }
+    state.push("Address");
+    if app.debug_report.build_id.is_some() || app.debug_report.debuglink.is_some() {
+        state.push("DebugFile");
+    }
+    if !app.parsed_hash_tables.is_empty() {
+        state.push("HashTables");
+    }
+    if app.has_imports {
+        state.push("Bindings");
+    }
+    if app
+        .ld_cache
+        .as_ref()
+        .is_some_and(|cache| !cache.entries.is_empty())
+    {
+        state.push("LdCache");
+    }
 
     state
 }
@@ -253,9 +1374,28 @@ pub enum AppState {
     Header,
     Section,
     Segment,
+    AddressLayout,
+    FileLayout,
+    Hex,
     Symbol,
     DynSym,
     Dynamics,
+    Binding,
+    LdCache,
+    HashTable,
+    StringTable,
+    PrintableString,
+    Dwarf,
+    StructLayout,
+    InitFini,
+    EhFrame,
+    Relocation,
+    PltGot,
+    Ifunc,
+    Tls,
+    RecoveredFunction,
+    Address,
+    DebugFile,
 }
 
 impl<'a> From<&'a str> for AppState {
@@ -264,9 +1404,28 @@ impl<'a> From<&'a str> for AppState {
             "Header" => AppState::Header,
             "Sections" => AppState::Section,
             "Segments" => AppState::Segment,
+            "AddrMap" => AppState::AddressLayout,
+            "FileMap" => AppState::FileLayout,
+            "Hex" => AppState::Hex,
             "Symbols" => AppState::Symbol,
             "DynSyms" => AppState::DynSym,
             "Dynamics" => AppState::Dynamics,
+            "Bindings" => AppState::Binding,
+            "LdCache" => AppState::LdCache,
+            "HashTables" => AppState::HashTable,
+            "Strings" => AppState::StringTable,
+            "Printable" => AppState::PrintableString,
+            "DWARF" => AppState::Dwarf,
+            "Structs" => AppState::StructLayout,
+            "InitFini" => AppState::InitFini,
+            "EhFrame" => AppState::EhFrame,
+            "Relocations" => AppState::Relocation,
+            "PLT/GOT" => AppState::PltGot,
+            "IFUNC" => AppState::Ifunc,
+            "TLS" => AppState::Tls,
+            "Recovered" => AppState::RecoveredFunction,
+            "Address" => AppState::Address,
+            "DebugFile" => AppState::DebugFile,
             _ => panic!("not found such a mode"),
         }
     }
@@ -62,7 +62,7 @@ pub struct TabsState<'a> {
 
 #[allow(dead_code)]
 impl<'a> TabsState<'a> {
-    pub fn new(titles: Vec<&'a str>) -> TabsState {
+    pub fn new(titles: Vec<&'a str>) -> TabsState<'a> {
         TabsState {
             titles: titles
                 .iter()
@@ -79,6 +79,24 @@ impl<'a> TabsState<'a> {
             Self::styled_tab(title),
         )
     }
+    /// title のタブがあるかどうか.
+    pub fn contains(&self, title: &str) -> bool {
+        self.position(title).is_some()
+    }
+    /// title のタブに移動する. 存在しなければ false を返す.
+    pub fn select(&mut self, title: &str) -> bool {
+        match self.position(title) {
+            Some(index) => {
+                self.index = index;
+                self.current = title.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+    fn position(&self, title: &str) -> Option<usize> {
+        self.titles.iter().position(|t| t.0[0].content == title)
+    }
     pub fn next(&mut self) {
         self.index = (self.index + 1) % self.titles.len();
         self.current = self.titles[self.index].0[0].content.to_string();
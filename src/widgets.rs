@@ -4,5 +4,25 @@ pub mod segments;
 pub mod symbols;
 pub mod elf_header;
 pub mod dynamics;
+pub mod eh_frame;
+pub mod bindings;
+pub mod ld_cache;
+pub mod hash_tables;
+pub mod ifuncs;
+pub mod string_tables;
+pub mod printable_strings;
+pub mod dwarf;
+pub mod debug_file;
+pub mod address;
+pub mod address_layout;
+pub mod file_layout;
+pub mod hex_view;
+pub mod data_inspector;
+pub mod struct_layouts;
+pub mod init_fini;
+pub mod plt_got;
+pub mod recovered_functions;
+pub mod tls;
+pub mod relocations;
 
 pub use base::*;
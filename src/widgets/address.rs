@@ -0,0 +1,143 @@
+use crate::elf_util::address::{self, AddressInfo};
+use crate::elf_util::dwarf::{line::SourceFrame, Dwarf};
+use crate::elf_util::recovered_function::{self, RecoveredFunction};
+use elf_utilities::{file, section};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+
+pub fn address_information<'a>(
+    elf_file: &'a file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    dwarf: Option<&Dwarf>,
+    recovered_functions: &[RecoveredFunction],
+    query: &str,
+    editing: bool,
+) -> Paragraph<'a> {
+    let prompt = if editing {
+        Span::styled(
+            format!("{}_", query),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::raw(query.to_string())
+    };
+    let mut spans = vec![Spans::from(vec![Span::raw("Address: "), prompt])];
+
+    match address::parse_address(query) {
+        None if query.is_empty() => spans.push(Spans::from(vec![Span::raw(
+            "press 'g' to type an address in hex, Enter to finish",
+        )])),
+        None => spans.push(Spans::from(vec![Span::styled(
+            "not a hexadecimal address",
+            Style::default().fg(Color::Yellow),
+        )])),
+        Some(addr) => {
+            let info = address::lookup(elf_file, symbol_table, addr);
+            spans.append(&mut location_spans(elf_file, &info));
+            // シンボルが無ければ, 他の情報から見つけた関数で補う
+            if info.symbol.is_none() {
+                if let Some((name, delta)) =
+                    recovered_function::containing(recovered_functions, addr)
+                {
+                    spans.push(Spans::from(vec![
+                        Span::raw("Recovered Function: "),
+                        Span::raw(format!("{}+0x{:x}", name, delta)),
+                    ]));
+                }
+            }
+            spans.push(Spans::from(vec![Span::raw("")]));
+            spans.append(&mut source_spans(dwarf, addr));
+        }
+    }
+
+    Paragraph::new(spans)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Go to Address"),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+fn location_spans<'a>(elf_file: &file::ELF64, info: &AddressInfo) -> Vec<Spans<'a>> {
+    let not_found = || Span::styled("not found", Style::default().fg(Color::DarkGray));
+
+    vec![
+        Spans::from(vec![
+            Span::raw("Section: "),
+            match info.section {
+                Some((idx, delta)) => Span::raw(format!(
+                    "[{}] {} + 0x{:x}",
+                    idx, elf_file.sections[idx].name, delta
+                )),
+                None => not_found(),
+            },
+        ]),
+        Spans::from(vec![
+            Span::raw("Segment: "),
+            match info.segment {
+                Some((idx, Some(offset))) => {
+                    Span::raw(format!("[{}] LOAD, file offset 0x{:x}", idx, offset))
+                }
+                Some((idx, None)) => Span::raw(format!("[{}] LOAD, not in file (bss)", idx)),
+                None => not_found(),
+            },
+        ]),
+        Spans::from(vec![
+            Span::raw("Symbol: "),
+            match &info.symbol {
+                Some((name, 0)) => Span::raw(name.clone()),
+                Some((name, delta)) => Span::raw(format!("{}+0x{:x}", name, delta)),
+                None => not_found(),
+            },
+        ]),
+    ]
+}
+
+/// addr2line -f -i -p と同じ並びで, 内側の関数から順に表示する.
+fn source_spans<'a>(dwarf: Option<&Dwarf>, addr: u64) -> Vec<Spans<'a>> {
+    let dwarf = match dwarf {
+        Some(dwarf) => dwarf,
+        None => {
+            return vec![Spans::from(vec![Span::styled(
+                "Source: no DWARF line information",
+                Style::default().fg(Color::DarkGray),
+            )])]
+        }
+    };
+    let frames = dwarf.source_frames(addr);
+    if frames.is_empty() {
+        return vec![Spans::from(vec![Span::styled(
+            "Source: ??:0",
+            Style::default().fg(Color::DarkGray),
+        )])];
+    }
+
+    let mut spans = vec![Spans::from(vec![Span::raw("Source:")])];
+    spans.extend(frames.iter().enumerate().map(|(idx, frame)| {
+        let prefix = if idx == 0 { "  " } else { "  (inlined by) " };
+        Spans::from(vec![Span::raw(format!(
+            "{}{}",
+            prefix,
+            source_frame_string(frame)
+        ))])
+    }));
+    spans
+}
+
+fn source_frame_string(frame: &SourceFrame) -> String {
+    let location = match frame.column {
+        0 => format!("{}:{}", frame.file.as_deref().unwrap_or("??"), frame.line),
+        column => format!(
+            "{}:{}:{}",
+            frame.file.as_deref().unwrap_or("??"),
+            frame.line,
+            column
+        ),
+    };
+    match &frame.function {
+        Some(function) => format!("{} at {}", function, location),
+        None => location,
+    }
+}
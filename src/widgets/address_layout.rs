@@ -0,0 +1,345 @@
+use crate::elf_util::address_layout::{
+    AddressLayout, Region, RegionKind, PAGE_SIZE, PF_R, PF_W, PF_X,
+};
+use crate::widgets::list;
+use elf_utilities::file;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+/// 行の名前を置く幅
+const LABEL_WIDTH: usize = 24;
+
+/// width は枠を含む一覧の幅. 残りを棒グラフに使う.
+pub fn address_layout_list<'a>(
+    elf_file: &file::ELF64,
+    layout: &AddressLayout,
+    width: u16,
+) -> List<'a> {
+    let cells = (width as usize).saturating_sub(LABEL_WIDTH + 3).max(1);
+    let span = layout.end.saturating_sub(layout.start).max(1);
+    let items = layout
+        .regions
+        .iter()
+        .zip(address_layout_names(elf_file, layout))
+        .map(|(region, name)| {
+            let mut spans = vec![Span::raw(format!("{:<width$} ", name, width = LABEL_WIDTH))];
+            spans.append(&mut bar_spans(layout, region, cells));
+            ListItem::new(vec![Spans::from(spans)])
+        })
+        .collect();
+
+    list(
+        format!(
+            "Address Space 0x{:x}..0x{:x} (0x{:x} bytes per cell, █ file ░ zero-filled)",
+            layout.start,
+            layout.end,
+            span.div_ceil(cells as u64)
+        ),
+        items,
+    )
+}
+
+pub fn address_layout_names(elf_file: &file::ELF64, layout: &AddressLayout) -> Vec<String> {
+    layout
+        .regions
+        .iter()
+        .map(|region| match region.kind {
+            RegionKind::Segment(idx) => format!("LOAD[{}] {}", idx, flags_string(region.flags)),
+            RegionKind::Section(idx) => format!("  {}", elf_file.sections[idx].name),
+            RegionKind::Relro(_) => "  GNU_RELRO".to_string(),
+            RegionKind::Gap => "gap".to_string(),
+        })
+        .collect()
+}
+
+pub fn address_layout_information<'a>(
+    elf_file: &file::ELF64,
+    layout: &AddressLayout,
+    idx: usize,
+) -> Paragraph<'a> {
+    let region = &layout.regions[idx];
+    let mut spans = vec![Spans::from(vec![
+        Span::raw("Range: "),
+        Span::raw(format!(
+            "0x{:x}..0x{:x} (0x{:x} bytes)",
+            region.start,
+            region.end,
+            region.size()
+        )),
+    ])];
+
+    let title = match region.kind {
+        RegionKind::Segment(seg_idx) => {
+            segment_spans(elf_file, layout, region, seg_idx, &mut spans);
+            "Segment"
+        }
+        RegionKind::Section(sct_idx) => {
+            section_spans(elf_file, layout, region, sct_idx, &mut spans);
+            "Section"
+        }
+        RegionKind::Relro(_) => {
+            relro_spans(elf_file, layout, region, &mut spans);
+            "GNU_RELRO"
+        }
+        RegionKind::Gap => {
+            spans.push(Spans::from(vec![
+                Span::raw("Pages: "),
+                Span::raw(format!(
+                    "{} unmapped",
+                    page_down(region.end).saturating_sub(page_up(region.start)) / PAGE_SIZE
+                )),
+            ]));
+            spans.push(Spans::from(vec![Span::styled(
+                "not mapped by any PT_LOAD (usually padding so the next segment starts on its own page)",
+                Style::default().fg(Color::DarkGray),
+            )]));
+            "Gap"
+        }
+    };
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn segment_spans(
+    elf_file: &file::ELF64,
+    layout: &AddressLayout,
+    region: &Region,
+    seg_idx: usize,
+    spans: &mut Vec<Spans>,
+) {
+    let phdr = &elf_file.segments[seg_idx].header;
+    spans.push(Spans::from(vec![
+        Span::raw("Permissions: "),
+        Span::styled(
+            flags_string(region.flags),
+            Style::default().fg(flags_color(region.flags)),
+        ),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("Mapped Pages: "),
+        Span::raw(format!(
+            "0x{:x}..0x{:x} ({} pages)",
+            page_down(region.start),
+            page_up(region.end),
+            page_up(region.end).saturating_sub(page_down(region.start)) / PAGE_SIZE
+        )),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("File: "),
+        Span::raw(format!(
+            "offset 0x{:x}, 0x{:x} bytes",
+            phdr.p_offset, phdr.p_filesz
+        )),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("Zero-filled (memsz > filesz): "),
+        if region.file_end < region.end {
+            Span::raw(format!(
+                "0x{:x}..0x{:x} (0x{:x} bytes)",
+                region.file_end,
+                region.end,
+                region.end - region.file_end
+            ))
+        } else {
+            Span::raw("none")
+        },
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("Align: "),
+        Span::raw(format!("0x{:x}", phdr.p_align)),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("RELRO: "),
+        Span::raw(match layout.relro_overlap(region.start, region.end) {
+            Some((start, end)) => format!(
+                "0x{:x}..0x{:x} ({}% of the segment)",
+                start,
+                end,
+                u128::from(end - start) * 100 / u128::from(region.size().max(1))
+            ),
+            None => "none".to_string(),
+        }),
+    ]));
+
+    let sections = layout.sections_in(region.start, region.end);
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Sections ({}):",
+        sections.len()
+    ))]));
+    for sct in sections {
+        if let RegionKind::Section(sct_idx) = sct.kind {
+            spans.push(Spans::from(vec![Span::raw(format!(
+                "  0x{:x}..0x{:x} {}{}",
+                sct.start,
+                sct.end,
+                elf_file.sections[sct_idx].name,
+                if sct.file_end < sct.end {
+                    " (zero-filled)"
+                } else {
+                    ""
+                }
+            ))]));
+        }
+    }
+}
+
+fn section_spans(
+    elf_file: &file::ELF64,
+    layout: &AddressLayout,
+    region: &Region,
+    sct_idx: usize,
+    spans: &mut Vec<Spans>,
+) {
+    spans.push(Spans::from(vec![
+        Span::raw("Section: "),
+        Span::raw(format!("[{}] {}", sct_idx, elf_file.sections[sct_idx].name)),
+    ]));
+    if let Some(segment) = layout.segment_of(region.start) {
+        if let RegionKind::Segment(seg_idx) = segment.kind {
+            spans.push(Spans::from(vec![
+                Span::raw("Segment: "),
+                Span::raw(format!(
+                    "LOAD[{}] {} +0x{:x}",
+                    seg_idx,
+                    flags_string(segment.flags),
+                    region.start - segment.start
+                )),
+            ]));
+        }
+    }
+    spans.push(Spans::from(vec![
+        Span::raw("Contents: "),
+        Span::raw(if region.file_end < region.end {
+            "zero-filled, not in file"
+        } else {
+            "loaded from file"
+        }),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("Permissions: "),
+        match layout.relro_overlap(region.start, region.end) {
+            Some(_) => Span::styled(
+                format!(
+                    "{}, read-only after relocation (RELRO)",
+                    flags_string(region.flags)
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+            None => Span::raw(flags_string(region.flags)),
+        },
+    ]));
+}
+
+fn relro_spans(
+    elf_file: &file::ELF64,
+    layout: &AddressLayout,
+    region: &Region,
+    spans: &mut Vec<Spans>,
+) {
+    // ld.so は終わりをページ境界に切り下げて mprotect する
+    let (start, end) = (page_down(region.start), page_down(region.end));
+    spans.push(Spans::from(vec![
+        Span::raw("Made Read-only: "),
+        Span::raw(if start < end {
+            format!(
+                "0x{:x}..0x{:x} ({} pages)",
+                start,
+                end,
+                (end - start) / PAGE_SIZE
+            )
+        } else {
+            "nothing, the range does not span a whole page".to_string()
+        }),
+    ]));
+    if end < region.end {
+        spans.push(Spans::from(vec![Span::styled(
+            format!(
+                "0x{:x}..0x{:x} shares a page with writable data and stays writable",
+                end.max(region.start),
+                region.end
+            ),
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+
+    let sections = layout.sections_in(region.start, region.end);
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Covered Sections ({}):",
+        sections.len()
+    ))]));
+    for sct in sections {
+        if let RegionKind::Section(sct_idx) = sct.kind {
+            spans.push(Spans::from(vec![Span::raw(format!(
+                "  0x{:x}..0x{:x} {}",
+                sct.start, sct.end, elf_file.sections[sct_idx].name
+            ))]));
+        }
+    }
+}
+
+/// 行の範囲を, 全体に対する位置に置いた棒
+fn bar_spans<'a>(layout: &AddressLayout, region: &Region, cells: usize) -> Vec<Span<'a>> {
+    // 壊れたヘッダで end < start でも clamp が panic しないようにする
+    let layout_end = layout.end.max(layout.start);
+    let span = u128::from((layout_end - layout.start).max(1));
+    let column = |addr: u64, round_up: bool| {
+        let offset =
+            u128::from(addr.clamp(layout.start, layout_end) - layout.start) * cells as u128;
+        let column = if round_up {
+            offset.div_ceil(span)
+        } else {
+            offset / span
+        };
+        column as usize
+    };
+
+    let start = column(region.start, false).min(cells - 1);
+    let end = column(region.end, true).clamp(start + 1, cells);
+    let file_end = column(region.file_end, true).clamp(start, end);
+    let (fill, zero, color) = match region.kind {
+        RegionKind::Gap => ('·', '·', Color::DarkGray),
+        RegionKind::Relro(_) => ('▓', '▓', Color::Yellow),
+        _ => ('█', '░', flags_color(region.flags)),
+    };
+
+    vec![
+        Span::raw(" ".repeat(start)),
+        Span::styled(
+            fill.to_string().repeat(file_end - start),
+            Style::default().fg(color),
+        ),
+        Span::styled(
+            zero.to_string().repeat(end - file_end),
+            Style::default().fg(color),
+        ),
+    ]
+}
+
+fn flags_string(flags: u32) -> String {
+    [(PF_R, 'R'), (PF_W, 'W'), (PF_X, 'X')]
+        .iter()
+        .map(|(flag, c)| if flags & flag != 0 { *c } else { '-' })
+        .collect()
+}
+
+/// 実行可能は赤, 書き込み可能は緑, 読み込みのみは青
+fn flags_color(flags: u32) -> Color {
+    if flags & PF_X != 0 {
+        Color::Red
+    } else if flags & PF_W != 0 {
+        Color::Green
+    } else {
+        Color::Blue
+    }
+}
+
+fn page_down(addr: u64) -> u64 {
+    addr & !(PAGE_SIZE - 1)
+}
+
+fn page_up(addr: u64) -> u64 {
+    page_down(addr.saturating_add(PAGE_SIZE - 1))
+}
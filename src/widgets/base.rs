@@ -1,8 +1,12 @@
 use tui::layout::Corner;
 use tui::style::{Color, Modifier, Style};
+use tui::text::Spans;
 use tui::widgets::{Block, Borders, List, ListItem};
 
-pub fn list<'a>(title: &'a str, items: Vec<ListItem<'a>>) -> List<'a> {
+pub fn list<'a, T>(title: T, items: Vec<ListItem<'a>>) -> List<'a>
+where
+    T: Into<Spans<'a>>,
+{
     List::new(items)
         .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(
@@ -12,3 +16,12 @@ pub fn list<'a>(title: &'a str, items: Vec<ListItem<'a>>) -> List<'a> {
         )
         .start_corner(Corner::TopLeft)
 }
+
+/// セクションヘッダを持たないファイルでは, プログラムヘッダから組み立て直したデータである旨をタイトルに付ける.
+pub fn data_source_title(reconstructed: bool, title: &str) -> String {
+    if reconstructed {
+        format!("{} (from program headers)", title)
+    } else {
+        title.to_string()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::elf_util::binding::{Binding, BindingReport, BindingResult};
+use crate::elf_util::versions;
+use crate::widgets::list;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn binding_list(report: &BindingReport) -> List<'_> {
+    list(
+        "Bindings",
+        report
+            .bindings
+            .iter()
+            .map(|binding| {
+                ListItem::new(vec![Spans::from(vec![Span::styled(
+                    binding_name(binding),
+                    Style::default().fg(binding_color(report, binding)),
+                )])])
+            })
+            .collect(),
+    )
+}
+
+pub fn binding_names(report: &BindingReport) -> Vec<String> {
+    report.bindings.iter().map(binding_name).collect()
+}
+
+pub fn binding_information(report: &BindingReport, binding_idx: usize) -> Paragraph<'_> {
+    let binding = &report.bindings[binding_idx];
+
+    let (required_version, expected_library) = match &binding.required_version {
+        Some(version) if version.flags & versions::VER_FLG_WEAK != 0 => (
+            format!("{} (weak)", version.name),
+            version.file.clone().unwrap_or_else(|| "-".to_string()),
+        ),
+        Some(version) => (
+            version.name.clone(),
+            version.file.clone().unwrap_or_else(|| "-".to_string()),
+        ),
+        None => ("-".to_string(), "-".to_string()),
+    };
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Name: "),
+            Span::raw(binding.symbol.name.clone()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Required Version: "),
+            Span::raw(required_version),
+        ]),
+        Spans::from(vec![
+            Span::raw("Expected Library(from .gnu.version_r): "),
+            Span::raw(expected_library),
+        ]),
+    ];
+
+    match &binding.result {
+        BindingResult::Bound { library, version } => {
+            let dep = &report.dependencies[*library];
+            spans.append(&mut vec![
+                Spans::from(vec![
+                    Span::raw("Bound To: "),
+                    Span::raw(report.library_name(*library)),
+                ]),
+                Spans::from(vec![
+                    Span::raw("Library Path: "),
+                    Span::raw(
+                        dep.path
+                            .as_ref()
+                            .map(|path| path.display().to_string())
+                            .unwrap_or_default(),
+                    ),
+                ]),
+                Spans::from(vec![
+                    Span::raw("Provided Version: "),
+                    Span::raw(version.clone().unwrap_or_else(|| "-".to_string())),
+                ]),
+            ]);
+
+            if report.is_mismatched(binding) {
+                spans.push(Spans::from(vec![Span::styled(
+                    "Warning: provided by a different library than the one linked against",
+                    Style::default().fg(Color::Yellow),
+                )]));
+            }
+        }
+        BindingResult::Unresolved => {
+            let result = if binding.is_weak() {
+                "UNRESOLVED (weak, resolves to 0)"
+            } else {
+                "UNRESOLVED"
+            };
+            spans.push(Spans::from(vec![
+                Span::raw("Bound To: "),
+                Span::styled(result, Style::default().fg(Color::Red)),
+            ]));
+        }
+    }
+
+    if !binding.shadowed.is_empty() {
+        spans.push(Spans::from(vec![
+            Span::raw("Also Defined In: "),
+            Span::raw(
+                binding
+                    .shadowed
+                    .iter()
+                    .map(|idx| report.library_name(*idx))
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+            ),
+        ]));
+    }
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title("Bindings"))
+}
+
+/// 解決できなかったシンボルと, 見つからなかった依存ライブラリの一覧
+pub fn unresolved_information(report: &BindingReport) -> Paragraph<'_> {
+    let mut spans: Vec<Spans> = report
+        .missing_dependencies()
+        .map(|dep| {
+            Spans::from(vec![
+                Span::styled("library not found: ", Style::default().fg(Color::Red)),
+                Span::raw(format!("{} (needed by {})", dep.name, dep.needed_by)),
+            ])
+        })
+        .collect();
+
+    spans.extend(report.unresolved().map(|binding| {
+        Spans::from(vec![Span::styled(
+            binding_name(binding),
+            Style::default().fg(binding_color(report, binding)),
+        )])
+    }));
+
+    if spans.is_empty() {
+        spans.push(Spans::from(vec![Span::raw("all imports are resolved")]));
+    }
+
+    Paragraph::new(spans).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Unresolved Imports"),
+    )
+}
+
+fn binding_name(binding: &Binding) -> String {
+    match &binding.required_version {
+        Some(version) => format!("{}@{}", binding.symbol.name, version.name),
+        None => binding.symbol.name.clone(),
+    }
+}
+
+fn binding_color(report: &BindingReport, binding: &Binding) -> Color {
+    match binding.result {
+        BindingResult::Unresolved if binding.is_weak() => Color::Yellow,
+        BindingResult::Unresolved => Color::Red,
+        BindingResult::Bound { .. } if report.is_mismatched(binding) => Color::Yellow,
+        BindingResult::Bound { .. } => Color::White,
+    }
+}
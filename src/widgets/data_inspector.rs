@@ -0,0 +1,222 @@
+use crate::elf_util::{self, address};
+use elf_utilities::{file, section};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+
+/// 文字列として表示する最大の文字数
+const MAX_STRING_CHARS: usize = 32;
+
+/// cursor のバイト列を整数, 浮動小数点数, LEB128, 文字列として読み, アドレスらしければその場所を示す.
+pub fn data_inspector_information<'a>(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    symbol_table: Option<&section::Section64>,
+    cursor: usize,
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(format!("0x{:x}", cursor)),
+        ]),
+        Spans::from(vec![
+            Span::raw("i8: "),
+            Span::raw(value_string(read::<1>(bytes, cursor), |b| {
+                (b[0] as i8).to_string()
+            })),
+            Span::raw("  u8: "),
+            Span::raw(value_string(read::<1>(bytes, cursor), |b| {
+                format!("{} (0x{:02x})", b[0], b[0])
+            })),
+        ]),
+    ];
+
+    for big_endian in [false, true] {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::styled(
+            if big_endian {
+                "Big Endian"
+            } else {
+                "Little Endian"
+            },
+            Style::default().add_modifier(Modifier::BOLD),
+        )]));
+        spans.append(&mut endian_spans(bytes, cursor, big_endian));
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![
+        Span::raw("ULEB128: "),
+        Span::raw(match elf_util::read_uleb128(bytes, cursor) {
+            Some((value, len)) => format!("{} (0x{:x}, {} bytes)", value, value, len),
+            None => "-".to_string(),
+        }),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("SLEB128: "),
+        Span::raw(match elf_util::read_sleb128(bytes, cursor) {
+            Some((value, len)) => format!("{} ({} bytes)", value, len),
+            None => "-".to_string(),
+        }),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("String: "),
+        match bytes
+            .get(cursor..)
+            .and_then(|tail| Some(&tail[..tail.iter().position(|b| *b == 0)?]))
+        {
+            Some(string) => Span::raw(string_value(string)),
+            None => Span::styled(
+                "no NUL before the end of the file",
+                Style::default().fg(Color::DarkGray),
+            ),
+        },
+    ]));
+
+    // PT_LOAD の範囲に入る値だけをアドレスとみなす. 上位 32 ビットが 0 なら u32 は同じ値になる
+    let wide = elf_util::read_u64(bytes, cursor);
+    let candidates = [
+        ("u64 LE", wide),
+        (
+            "u32 LE",
+            elf_util::read_u32(bytes, cursor)
+                .map(u64::from)
+                .filter(|value| Some(*value) != wide),
+        ),
+    ];
+    for (width, value) in candidates {
+        let value = match value.filter(|value| *value != 0) {
+            Some(value) => value,
+            None => continue,
+        };
+        let info = address::lookup(elf_file, symbol_table, value);
+        if info.segment.is_none() {
+            continue;
+        }
+        let mut places = Vec::new();
+        if let Some((idx, delta)) = info.section {
+            places.push(format!("{}+0x{:x}", elf_file.sections[idx].name, delta));
+        }
+        if let Some((name, delta)) = info.symbol {
+            places.push(match delta {
+                0 => format!("<{}>", name),
+                delta => format!("<{}+0x{:x}>", name, delta),
+            });
+        }
+        spans.push(Spans::from(vec![
+            Span::raw(format!("Points To ({}): ", width)),
+            Span::styled(
+                format!("0x{:x} {}", value, places.join(" ")),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+    }
+
+    Paragraph::new(spans)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Data Inspector"),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+fn endian_spans<'a>(bytes: &[u8], cursor: usize, big_endian: bool) -> Vec<Spans<'a>> {
+    // 読み込んだバイト列をリトルエンディアンの並びにそろえる
+    let ordered = |mut b: [u8; 8], len: usize| {
+        if big_endian {
+            b[..len].reverse();
+        }
+        b
+    };
+    let widen = |b: Option<[u8; 8]>, len: usize| b.map(|b| ordered(b, len));
+    let b2 = widen(read::<2>(bytes, cursor).map(pad), 2);
+    let b4 = widen(read::<4>(bytes, cursor).map(pad), 4);
+    let b8 = widen(read::<8>(bytes, cursor), 8);
+
+    vec![
+        Spans::from(vec![
+            Span::raw("  i16: "),
+            Span::raw(value_string(b2, |b| {
+                i16::from_le_bytes([b[0], b[1]]).to_string()
+            })),
+            Span::raw("  u16: "),
+            Span::raw(value_string(b2, |b| {
+                u16::from_le_bytes([b[0], b[1]]).to_string()
+            })),
+        ]),
+        Spans::from(vec![
+            Span::raw("  i32: "),
+            Span::raw(value_string(b4, |b| {
+                i32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()
+            })),
+            Span::raw("  u32: "),
+            Span::raw(value_string(b4, |b| {
+                u32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()
+            })),
+        ]),
+        Spans::from(vec![
+            Span::raw("  i64: "),
+            Span::raw(value_string(b8, |b| i64::from_le_bytes(b).to_string())),
+        ]),
+        Spans::from(vec![
+            Span::raw("  u64: "),
+            Span::raw(value_string(b8, |b| {
+                let value = u64::from_le_bytes(b);
+                format!("{} (0x{:x})", value, value)
+            })),
+        ]),
+        Spans::from(vec![
+            Span::raw("  f32: "),
+            Span::raw(value_string(b4, |b| {
+                let value = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                float_string(value, f64::from(value.abs()))
+            })),
+        ]),
+        Spans::from(vec![
+            Span::raw("  f64: "),
+            Span::raw(value_string(b8, |b| {
+                let value = f64::from_le_bytes(b);
+                float_string(value, value.abs())
+            })),
+        ]),
+    ]
+}
+
+fn read<const N: usize>(bytes: &[u8], cursor: usize) -> Option<[u8; N]> {
+    let mut b = [0; N];
+    b.copy_from_slice(bytes.get(cursor..cursor.checked_add(N)?)?);
+    Some(b)
+}
+
+fn pad<const N: usize>(b: [u8; N]) -> [u8; 8] {
+    let mut padded = [0; 8];
+    padded[..N].copy_from_slice(&b);
+    padded
+}
+
+/// ファイルの終わりを越えて読めなければ "-"
+fn value_string<T>(b: Option<T>, f: impl Fn(T) -> String) -> String {
+    b.map_or("-".to_string(), f)
+}
+
+/// 極端に大きい/小さい値は指数表記にする
+fn float_string<T: std::fmt::Display + std::fmt::LowerExp>(value: T, abs: f64) -> String {
+    if abs == 0.0 || (1e-4..1e16).contains(&abs) || !abs.is_finite() {
+        format!("{}", value)
+    } else {
+        format!("{:e}", value)
+    }
+}
+
+fn string_value(raw: &[u8]) -> String {
+    let string = String::from_utf8_lossy(raw);
+    let chars = string.chars().count();
+    let shown: String = string.chars().take(MAX_STRING_CHARS).collect();
+    format!(
+        "\"{}{}\" ({} bytes)",
+        shown.escape_debug(),
+        if chars > MAX_STRING_CHARS { "…" } else { "" },
+        raw.len()
+    )
+}
@@ -0,0 +1,84 @@
+use crate::elf_util::debug_file::{DebugFileReport, Method, Status};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+
+/// 探した候補と, 見つけたデバッグファイルから取り込んだ情報を表示する.
+/// merged は (取り込んだもの, その説明) の組.
+pub fn debug_file_information<'a>(
+    report: &'a DebugFileReport,
+    merged: Vec<(&'a str, String)>,
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Build ID: "),
+            Span::raw(match &report.build_id {
+                Some(id) => id.iter().map(|b| format!("{:02x}", b)).collect(),
+                None => "none".to_string(),
+            }),
+        ]),
+        Spans::from(vec![
+            Span::raw("Debug Link: "),
+            Span::raw(match &report.debuglink {
+                Some((name, crc)) => format!("{} (CRC32 0x{:08x})", name, crc),
+                None => "none".to_string(),
+            }),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw(format!(
+            "Candidates ({})",
+            report.candidates.len()
+        ))]),
+    ];
+
+    spans.extend(report.candidates.iter().map(|candidate| {
+        let (status, color) = match candidate.status {
+            Status::Matched => ("matched".to_string(), Color::Green),
+            Status::NotFound => ("not found".to_string(), Color::DarkGray),
+            Status::BuildIdMismatch => ("build-id mismatch".to_string(), Color::Yellow),
+            Status::CrcMismatch(actual) => {
+                (format!("CRC mismatch (0x{:08x})", actual), Color::Yellow)
+            }
+            Status::Unreadable => ("not an ELF file".to_string(), Color::Yellow),
+        };
+        Spans::from(vec![
+            Span::raw(format!(
+                "  [{}] {} ",
+                method_string(candidate.method),
+                candidate.path.display()
+            )),
+            Span::styled(format!("({})", status), Style::default().fg(color)),
+        ])
+    }));
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    match report.found() {
+        Some(found) => {
+            spans.push(Spans::from(vec![
+                Span::raw("Debug File: "),
+                Span::styled(
+                    found.path.display().to_string(),
+                    Style::default().fg(Color::Green),
+                ),
+            ]));
+            spans.extend(merged.into_iter().map(|(what, description)| {
+                Spans::from(vec![Span::raw(format!("  {}: {}", what, description))])
+            }));
+        }
+        None => spans.push(Spans::from(vec![Span::styled(
+            "no matching debug file, use --debug-dir <dir> to add a search directory",
+            Style::default().fg(Color::Yellow),
+        )])),
+    }
+
+    Paragraph::new(spans)
+        .block(Block::default().borders(Borders::ALL).title("Debug File"))
+        .wrap(Wrap { trim: false })
+}
+
+fn method_string<'a>(method: Method) -> &'a str {
+    match method {
+        Method::BuildId => "build-id",
+        Method::DebugLink => "debuglink",
+    }
+}
@@ -0,0 +1,771 @@
+use crate::elf_util::dwarf::{self, line, AttrValue, Attribute, Die, Dwarf, Operand, Unit};
+use crate::widgets::list;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+
+// 値の解釈に使う属性
+const DW_AT_SIBLING: u64 = 0x01;
+const DW_AT_LANGUAGE: u64 = 0x13;
+const DW_AT_STRING_LENGTH: u64 = 0x19;
+const DW_AT_INLINE: u64 = 0x20;
+const DW_AT_RETURN_ADDR: u64 = 0x2a;
+const DW_AT_ACCESSIBILITY: u64 = 0x32;
+const DW_AT_ENCODING: u64 = 0x3e;
+const DW_AT_USE_LOCATION: u64 = 0x4a;
+const DW_AT_VTABLE_ELEM_LOCATION: u64 = 0x4d;
+const DW_AT_DATA_LOCATION: u64 = 0x50;
+const DW_AT_CALL_VALUE: u64 = 0x7e;
+const DW_AT_CALL_TARGET: u64 = 0x83;
+const DW_AT_GNU_CALL_SITE_VALUE: u64 = 0x2111;
+const DW_AT_GNU_CALL_SITE_TARGET: u64 = 0x2113;
+const DW_AT_GNU_DWO_ID: u64 = 0x2131;
+
+pub fn die_tree_list<'a>(
+    dwarf: &'a Dwarf,
+    visible: &[(usize, usize)],
+    is_expanded: impl Fn(u64) -> bool,
+) -> List<'a> {
+    list(
+        format!(
+            "DWARF ({} units{}, Enter to expand/collapse)",
+            dwarf.units.len(),
+            if dwarf.sections.unapplied_relocations {
+                ", relocations not applied"
+            } else {
+                ""
+            }
+        ),
+        die_tree_names(dwarf, visible, is_expanded)
+            .into_iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name)])]))
+            .collect(),
+    )
+}
+
+pub fn die_tree_names(
+    dwarf: &Dwarf,
+    visible: &[(usize, usize)],
+    is_expanded: impl Fn(u64) -> bool,
+) -> Vec<String> {
+    visible
+        .iter()
+        .map(|(unit_idx, die_idx)| {
+            let unit = &dwarf.units[*unit_idx];
+            let die = &unit.dies[*die_idx];
+            let marker = if unit.children(*die_idx).next().is_none() {
+                " "
+            } else if is_expanded(die.offset) {
+                "-"
+            } else {
+                "+"
+            };
+            format!(
+                "{}{} {} {}",
+                "  ".repeat(die.depth),
+                marker,
+                tag_string(die.tag),
+                dwarf.die_name(unit, die).unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+pub fn die_information(dwarf: &Dwarf, unit_idx: usize, die_idx: usize) -> Paragraph<'_> {
+    let unit = &dwarf.units[unit_idx];
+    let die = &unit.dies[die_idx];
+
+    let mut spans = Vec::new();
+    if die_idx == 0 {
+        spans.append(&mut unit_header_spans(unit));
+        spans.push(Spans::from(vec![Span::raw("")]));
+    }
+
+    spans.append(&mut vec![
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(format!("0x{:x}", die.offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Tag: "),
+            Span::styled(
+                tag_string(die.tag),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+    ]);
+
+    for attr in die.attributes.iter() {
+        let mut lines = attribute_value_lines(dwarf, unit, die, attr).into_iter();
+        spans.push(Spans::from(vec![
+            Span::styled(
+                format!("{}: ", attribute_string(attr.name)),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(lines.next().unwrap_or_default()),
+        ]));
+        spans.extend(lines.map(|line| Spans::from(vec![Span::raw(format!("    {}", line))])));
+    }
+
+    Paragraph::new(spans)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("DIE Information"),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+fn unit_header_spans<'a>(unit: &Unit) -> Vec<Spans<'a>> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Unit Offset: "),
+            Span::raw(format!("0x{:x}", unit.offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Version: "),
+            Span::raw(format!(
+                "{} ({}-bit DWARF)",
+                unit.version,
+                if unit.dwarf64 { 64 } else { 32 }
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Unit Type: "),
+            Span::raw(unit_type_string(unit.unit_type)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Address Size: "),
+            Span::raw(format!("{} (bytes)", unit.address_size)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Abbrev Offset: "),
+            Span::raw(format!("0x{:x}", unit.abbrev_offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("DIEs: "),
+            Span::raw(format!("{}", unit.dies.len())),
+        ]),
+    ];
+    if let Some(signature) = unit.signature {
+        spans.push(Spans::from(vec![
+            Span::raw("Signature/DWO ID: "),
+            Span::raw(format!("0x{:016x}", signature)),
+        ]));
+    }
+    spans
+}
+
+/// 属性値を人が読める形にする. 範囲リストや位置リストは複数行になる.
+fn attribute_value_lines(dwarf: &Dwarf, unit: &Unit, die: &Die, attr: &Attribute) -> Vec<String> {
+    let value = &attr.value;
+
+    match (attr.name, value) {
+        (dwarf::DW_AT_TYPE, AttrValue::Ref(offset)) => {
+            vec![format!("<0x{:x}> {}", offset, dwarf.type_name(*offset))]
+        }
+        (_, AttrValue::Ref(offset)) if attr.name != DW_AT_SIBLING => {
+            let name = dwarf
+                .die(*offset)
+                .and_then(|(unit, die)| dwarf.die_name(unit, die))
+                .unwrap_or_default();
+            vec![format!("<0x{:x}> {}", offset, name)]
+        }
+        (dwarf::DW_AT_HIGH_PC, AttrValue::Udata(len)) => {
+            let low = die
+                .attribute(dwarf::DW_AT_LOW_PC)
+                .and_then(|low| dwarf.address(unit, low));
+            match low {
+                Some(low) => vec![format!("0x{:x} (low_pc + 0x{:x})", low + len, len)],
+                None => vec![format!("low_pc + 0x{:x}", len)],
+            }
+        }
+        (dwarf::DW_AT_RANGES, _) => {
+            let ranges = dwarf.ranges(unit, value);
+            let mut lines = vec![format!(
+                "{} ({} ranges)",
+                raw_value_string(value),
+                ranges.len()
+            )];
+            lines.extend(
+                ranges
+                    .iter()
+                    .map(|(begin, end)| format!("[0x{:x}, 0x{:x})", begin, end)),
+            );
+            lines
+        }
+        (_, AttrValue::Exprloc(expr)) => vec![expression_string(expr, unit)],
+        (_, AttrValue::Block(expr)) if is_location_attribute(attr.name) => {
+            vec![expression_string(expr, unit)]
+        }
+        (_, AttrValue::SecOffset(_)) | (_, AttrValue::LocListIndex(_))
+            if is_location_attribute(attr.name) =>
+        {
+            let entries = dwarf.location_list(unit, value);
+            let mut lines = vec![format!(
+                "{} (location list, {} entries)",
+                raw_value_string(value),
+                entries.len()
+            )];
+            lines.extend(entries.iter().map(|entry| {
+                let range = match entry.range {
+                    Some((begin, end)) => format!("[0x{:x}, 0x{:x})", begin, end),
+                    None => "default".to_string(),
+                };
+                format!("{}: {}", range, expression_string(&entry.expression, unit))
+            }));
+            lines
+        }
+        (line::DW_AT_DECL_FILE | line::DW_AT_CALL_FILE, AttrValue::Udata(idx)) => {
+            match unit
+                .line_program
+                .as_ref()
+                .and_then(|program| program.file(*idx))
+            {
+                Some(file) => vec![format!("{} ({})", idx, file)],
+                None => vec![raw_value_string(value)],
+            }
+        }
+        (line::DW_AT_STMT_LIST, _) => match &unit.line_program {
+            Some(program) => vec![format!(
+                "{} ({} files, {} rows)",
+                raw_value_string(value),
+                program.files.len(),
+                program.rows.len()
+            )],
+            None => vec![raw_value_string(value)],
+        },
+        (DW_AT_LANGUAGE, AttrValue::Udata(lang)) => {
+            vec![format!("{} (0x{:x})", language_string(*lang), lang)]
+        }
+        (DW_AT_ENCODING, AttrValue::Udata(encoding)) => {
+            vec![format!("{} (0x{:x})", encoding_string(*encoding), encoding)]
+        }
+        (DW_AT_GNU_DWO_ID, AttrValue::Udata(id)) => vec![format!("0x{:016x}", id)],
+        (DW_AT_INLINE, AttrValue::Udata(inline)) => vec![inline_string(*inline).to_string()],
+        (DW_AT_ACCESSIBILITY, AttrValue::Udata(access)) => {
+            vec![accessibility_string(*access).to_string()]
+        }
+        _ => match dwarf
+            .string(unit, value)
+            .or_else(|| address_string(dwarf, unit, value))
+        {
+            Some(s) => vec![s],
+            None => vec![raw_value_string(value)],
+        },
+    }
+}
+
+fn address_string(dwarf: &Dwarf, unit: &Unit, value: &AttrValue) -> Option<String> {
+    match value {
+        AttrValue::Address(_) | AttrValue::AddressIndex(_) => dwarf
+            .address(unit, value)
+            .map(|addr| format!("0x{:x}", addr)),
+        _ => None,
+    }
+}
+
+fn raw_value_string(value: &AttrValue) -> String {
+    match value {
+        AttrValue::Address(addr) => format!("0x{:x}", addr),
+        AttrValue::AddressIndex(idx) => format!("(address index {})", idx),
+        AttrValue::Block(block) | AttrValue::Exprloc(block) => block
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<String>>()
+            .join(" "),
+        AttrValue::Udata(value) => format!("{}", value),
+        AttrValue::Sdata(value) => format!("{}", value),
+        AttrValue::Flag(flag) => format!("{}", flag),
+        AttrValue::String(s) => s.clone(),
+        AttrValue::StrOffset(offset) => format!("(.debug_str offset 0x{:x})", offset),
+        AttrValue::LineStrOffset(offset) => format!("(.debug_line_str offset 0x{:x})", offset),
+        AttrValue::StrIndex(idx) => format!("(string index {})", idx),
+        AttrValue::Ref(offset) => format!("<0x{:x}>", offset),
+        AttrValue::RefSig8(signature) => format!("signature 0x{:016x}", signature),
+        AttrValue::Supplementary(offset) => format!("<supplementary 0x{:x}>", offset),
+        AttrValue::SecOffset(offset) => format!("0x{:x}", offset),
+        AttrValue::LocListIndex(idx) => format!("(location list index {})", idx),
+        AttrValue::RngListIndex(idx) => format!("(range list index {})", idx),
+    }
+}
+
+fn is_location_attribute(name: u64) -> bool {
+    matches!(
+        name,
+        dwarf::DW_AT_LOCATION
+            | DW_AT_STRING_LENGTH
+            | DW_AT_RETURN_ADDR
+            | dwarf::DW_AT_DATA_MEMBER_LOCATION
+            | dwarf::DW_AT_FRAME_BASE
+            | DW_AT_USE_LOCATION
+            | DW_AT_VTABLE_ELEM_LOCATION
+            | DW_AT_DATA_LOCATION
+            | DW_AT_CALL_VALUE
+            | DW_AT_CALL_TARGET
+            | DW_AT_GNU_CALL_SITE_VALUE
+            | DW_AT_GNU_CALL_SITE_TARGET
+    )
+}
+
+/// DWARF 式を "DW_OP_fbreg -20; DW_OP_deref" のように並べる.
+pub fn expression_string(expression: &[u8], unit: &Unit) -> String {
+    dwarf::operations(expression, unit)
+        .iter()
+        .map(|op| {
+            let operands: Vec<String> = op
+                .operands
+                .iter()
+                .map(|operand| match operand {
+                    Operand::Unsigned(value) if op.opcode == 0x03 => format!("0x{:x}", value),
+                    Operand::Unsigned(value) => format!("{}", value),
+                    Operand::Signed(value) => format!("{}", value),
+                    // DW_OP_entry_value, GNU_entry_value のオペランドは式そのもの
+                    Operand::Block(block) if op.opcode == 0xa3 || op.opcode == 0xf3 => {
+                        format!("({})", expression_string(block, unit))
+                    }
+                    Operand::Block(block) => format!(
+                        "[{}]",
+                        block
+                            .iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    ),
+                })
+                .collect();
+            if operands.is_empty() {
+                op_string(op.opcode)
+            } else {
+                format!("{} {}", op_string(op.opcode), operands.join(", "))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+fn unit_type_string<'a>(unit_type: u8) -> &'a str {
+    match unit_type {
+        dwarf::DW_UT_COMPILE => "DW_UT_compile",
+        dwarf::DW_UT_TYPE => "DW_UT_type",
+        dwarf::DW_UT_PARTIAL => "DW_UT_partial",
+        dwarf::DW_UT_SKELETON => "DW_UT_skeleton",
+        dwarf::DW_UT_SPLIT_COMPILE => "DW_UT_split_compile",
+        dwarf::DW_UT_SPLIT_TYPE => "DW_UT_split_type",
+        _ => "unknown",
+    }
+}
+
+pub fn tag_string(tag: u64) -> String {
+    let name = match tag {
+        0x01 => "DW_TAG_array_type",
+        0x02 => "DW_TAG_class_type",
+        0x03 => "DW_TAG_entry_point",
+        0x04 => "DW_TAG_enumeration_type",
+        0x05 => "DW_TAG_formal_parameter",
+        0x08 => "DW_TAG_imported_declaration",
+        0x0a => "DW_TAG_label",
+        0x0b => "DW_TAG_lexical_block",
+        0x0d => "DW_TAG_member",
+        0x0f => "DW_TAG_pointer_type",
+        0x10 => "DW_TAG_reference_type",
+        0x11 => "DW_TAG_compile_unit",
+        0x12 => "DW_TAG_string_type",
+        0x13 => "DW_TAG_structure_type",
+        0x15 => "DW_TAG_subroutine_type",
+        0x16 => "DW_TAG_typedef",
+        0x17 => "DW_TAG_union_type",
+        0x18 => "DW_TAG_unspecified_parameters",
+        0x19 => "DW_TAG_variant",
+        0x1a => "DW_TAG_common_block",
+        0x1b => "DW_TAG_common_inclusion",
+        0x1c => "DW_TAG_inheritance",
+        0x1d => "DW_TAG_inlined_subroutine",
+        0x1e => "DW_TAG_module",
+        0x1f => "DW_TAG_ptr_to_member_type",
+        0x20 => "DW_TAG_set_type",
+        0x21 => "DW_TAG_subrange_type",
+        0x22 => "DW_TAG_with_stmt",
+        0x23 => "DW_TAG_access_declaration",
+        0x24 => "DW_TAG_base_type",
+        0x25 => "DW_TAG_catch_block",
+        0x26 => "DW_TAG_const_type",
+        0x27 => "DW_TAG_constant",
+        0x28 => "DW_TAG_enumerator",
+        0x29 => "DW_TAG_file_type",
+        0x2a => "DW_TAG_friend",
+        0x2b => "DW_TAG_namelist",
+        0x2c => "DW_TAG_namelist_item",
+        0x2d => "DW_TAG_packed_type",
+        0x2e => "DW_TAG_subprogram",
+        0x2f => "DW_TAG_template_type_parameter",
+        0x30 => "DW_TAG_template_value_parameter",
+        0x31 => "DW_TAG_thrown_type",
+        0x32 => "DW_TAG_try_block",
+        0x33 => "DW_TAG_variant_part",
+        0x34 => "DW_TAG_variable",
+        0x35 => "DW_TAG_volatile_type",
+        0x36 => "DW_TAG_dwarf_procedure",
+        0x37 => "DW_TAG_restrict_type",
+        0x38 => "DW_TAG_interface_type",
+        0x39 => "DW_TAG_namespace",
+        0x3a => "DW_TAG_imported_module",
+        0x3b => "DW_TAG_unspecified_type",
+        0x3c => "DW_TAG_partial_unit",
+        0x3d => "DW_TAG_imported_unit",
+        0x3f => "DW_TAG_condition",
+        0x40 => "DW_TAG_shared_type",
+        0x41 => "DW_TAG_type_unit",
+        0x42 => "DW_TAG_rvalue_reference_type",
+        0x43 => "DW_TAG_template_alias",
+        0x44 => "DW_TAG_coarray_type",
+        0x45 => "DW_TAG_generic_subrange",
+        0x46 => "DW_TAG_dynamic_type",
+        0x47 => "DW_TAG_atomic_type",
+        0x48 => "DW_TAG_call_site",
+        0x49 => "DW_TAG_call_site_parameter",
+        0x4a => "DW_TAG_skeleton_unit",
+        0x4b => "DW_TAG_immutable_type",
+        0x4101 => "DW_TAG_format_label",
+        0x4102 => "DW_TAG_function_template",
+        0x4103 => "DW_TAG_class_template",
+        0x4106 => "DW_TAG_GNU_template_template_param",
+        0x4107 => "DW_TAG_GNU_template_parameter_pack",
+        0x4108 => "DW_TAG_GNU_formal_parameter_pack",
+        0x4109 => "DW_TAG_GNU_call_site",
+        0x410a => "DW_TAG_GNU_call_site_parameter",
+        _ => return format!("DW_TAG_0x{:x}", tag),
+    };
+    name.to_string()
+}
+
+pub fn attribute_string(name: u64) -> String {
+    let s = match name {
+        0x01 => "DW_AT_sibling",
+        0x02 => "DW_AT_location",
+        0x03 => "DW_AT_name",
+        0x09 => "DW_AT_ordering",
+        0x0b => "DW_AT_byte_size",
+        0x0c => "DW_AT_bit_offset",
+        0x0d => "DW_AT_bit_size",
+        0x10 => "DW_AT_stmt_list",
+        0x11 => "DW_AT_low_pc",
+        0x12 => "DW_AT_high_pc",
+        0x13 => "DW_AT_language",
+        0x15 => "DW_AT_discr",
+        0x16 => "DW_AT_discr_value",
+        0x17 => "DW_AT_visibility",
+        0x18 => "DW_AT_import",
+        0x19 => "DW_AT_string_length",
+        0x1a => "DW_AT_common_reference",
+        0x1b => "DW_AT_comp_dir",
+        0x1c => "DW_AT_const_value",
+        0x1d => "DW_AT_containing_type",
+        0x1e => "DW_AT_default_value",
+        0x20 => "DW_AT_inline",
+        0x21 => "DW_AT_is_optional",
+        0x22 => "DW_AT_lower_bound",
+        0x25 => "DW_AT_producer",
+        0x27 => "DW_AT_prototyped",
+        0x2a => "DW_AT_return_addr",
+        0x2c => "DW_AT_start_scope",
+        0x2e => "DW_AT_bit_stride",
+        0x2f => "DW_AT_upper_bound",
+        0x31 => "DW_AT_abstract_origin",
+        0x32 => "DW_AT_accessibility",
+        0x33 => "DW_AT_address_class",
+        0x34 => "DW_AT_artificial",
+        0x35 => "DW_AT_base_types",
+        0x36 => "DW_AT_calling_convention",
+        0x37 => "DW_AT_count",
+        0x38 => "DW_AT_data_member_location",
+        0x39 => "DW_AT_decl_column",
+        0x3a => "DW_AT_decl_file",
+        0x3b => "DW_AT_decl_line",
+        0x3c => "DW_AT_declaration",
+        0x3d => "DW_AT_discr_list",
+        0x3e => "DW_AT_encoding",
+        0x3f => "DW_AT_external",
+        0x40 => "DW_AT_frame_base",
+        0x41 => "DW_AT_friend",
+        0x42 => "DW_AT_identifier_case",
+        0x43 => "DW_AT_macro_info",
+        0x44 => "DW_AT_namelist_item",
+        0x45 => "DW_AT_priority",
+        0x46 => "DW_AT_segment",
+        0x47 => "DW_AT_specification",
+        0x48 => "DW_AT_static_link",
+        0x49 => "DW_AT_type",
+        0x4a => "DW_AT_use_location",
+        0x4b => "DW_AT_variable_parameter",
+        0x4c => "DW_AT_virtuality",
+        0x4d => "DW_AT_vtable_elem_location",
+        0x4e => "DW_AT_allocated",
+        0x4f => "DW_AT_associated",
+        0x50 => "DW_AT_data_location",
+        0x51 => "DW_AT_byte_stride",
+        0x52 => "DW_AT_entry_pc",
+        0x53 => "DW_AT_use_UTF8",
+        0x54 => "DW_AT_extension",
+        0x55 => "DW_AT_ranges",
+        0x56 => "DW_AT_trampoline",
+        0x57 => "DW_AT_call_column",
+        0x58 => "DW_AT_call_file",
+        0x59 => "DW_AT_call_line",
+        0x5a => "DW_AT_description",
+        0x5b => "DW_AT_binary_scale",
+        0x5c => "DW_AT_decimal_scale",
+        0x5d => "DW_AT_small",
+        0x5e => "DW_AT_decimal_sign",
+        0x5f => "DW_AT_digit_count",
+        0x60 => "DW_AT_picture_string",
+        0x61 => "DW_AT_mutable",
+        0x62 => "DW_AT_threads_scaled",
+        0x63 => "DW_AT_explicit",
+        0x64 => "DW_AT_object_pointer",
+        0x65 => "DW_AT_endianity",
+        0x66 => "DW_AT_elemental",
+        0x67 => "DW_AT_pure",
+        0x68 => "DW_AT_recursive",
+        0x69 => "DW_AT_signature",
+        0x6a => "DW_AT_main_subprogram",
+        0x6b => "DW_AT_data_bit_offset",
+        0x6c => "DW_AT_const_expr",
+        0x6d => "DW_AT_enum_class",
+        0x6e => "DW_AT_linkage_name",
+        0x6f => "DW_AT_string_length_bit_size",
+        0x70 => "DW_AT_string_length_byte_size",
+        0x71 => "DW_AT_rank",
+        0x72 => "DW_AT_str_offsets_base",
+        0x73 => "DW_AT_addr_base",
+        0x74 => "DW_AT_rnglists_base",
+        0x76 => "DW_AT_dwo_name",
+        0x77 => "DW_AT_reference",
+        0x78 => "DW_AT_rvalue_reference",
+        0x79 => "DW_AT_macros",
+        0x7a => "DW_AT_call_all_calls",
+        0x7b => "DW_AT_call_all_source_calls",
+        0x7c => "DW_AT_call_all_tail_calls",
+        0x7d => "DW_AT_call_return_pc",
+        0x7e => "DW_AT_call_value",
+        0x7f => "DW_AT_call_origin",
+        0x80 => "DW_AT_call_parameter",
+        0x81 => "DW_AT_call_pc",
+        0x82 => "DW_AT_call_tail_call",
+        0x83 => "DW_AT_call_target",
+        0x84 => "DW_AT_call_target_clobbered",
+        0x85 => "DW_AT_call_data_location",
+        0x86 => "DW_AT_call_data_value",
+        0x87 => "DW_AT_noreturn",
+        0x88 => "DW_AT_alignment",
+        0x89 => "DW_AT_export_symbols",
+        0x8a => "DW_AT_deleted",
+        0x8b => "DW_AT_defaulted",
+        0x8c => "DW_AT_loclists_base",
+        0x2007 => "DW_AT_MIPS_linkage_name",
+        0x2107 => "DW_AT_GNU_vector",
+        0x210f => "DW_AT_GNU_odr_signature",
+        0x2110 => "DW_AT_GNU_template_name",
+        0x2111 => "DW_AT_GNU_call_site_value",
+        0x2112 => "DW_AT_GNU_call_site_data_value",
+        0x2113 => "DW_AT_GNU_call_site_target",
+        0x2114 => "DW_AT_GNU_call_site_target_clobbered",
+        0x2115 => "DW_AT_GNU_tail_call",
+        0x2116 => "DW_AT_GNU_all_tail_call_sites",
+        0x2117 => "DW_AT_GNU_all_call_sites",
+        0x2118 => "DW_AT_GNU_all_source_call_sites",
+        0x2119 => "DW_AT_GNU_macros",
+        0x211a => "DW_AT_GNU_deleted",
+        0x2130 => "DW_AT_GNU_dwo_name",
+        0x2131 => "DW_AT_GNU_dwo_id",
+        0x2132 => "DW_AT_GNU_ranges_base",
+        0x2133 => "DW_AT_GNU_addr_base",
+        0x2134 => "DW_AT_GNU_pubnames",
+        0x2135 => "DW_AT_GNU_pubtypes",
+        0x2136 => "DW_AT_GNU_discriminator",
+        0x2137 => "DW_AT_GNU_locviews",
+        0x2138 => "DW_AT_GNU_entry_view",
+        _ => return format!("DW_AT_0x{:x}", name),
+    };
+    s.to_string()
+}
+
+fn op_string(opcode: u8) -> String {
+    let name = match opcode {
+        0x03 => "DW_OP_addr",
+        0x06 => "DW_OP_deref",
+        0x08 => "DW_OP_const1u",
+        0x09 => "DW_OP_const1s",
+        0x0a => "DW_OP_const2u",
+        0x0b => "DW_OP_const2s",
+        0x0c => "DW_OP_const4u",
+        0x0d => "DW_OP_const4s",
+        0x0e => "DW_OP_const8u",
+        0x0f => "DW_OP_const8s",
+        0x10 => "DW_OP_constu",
+        0x11 => "DW_OP_consts",
+        0x12 => "DW_OP_dup",
+        0x13 => "DW_OP_drop",
+        0x14 => "DW_OP_over",
+        0x15 => "DW_OP_pick",
+        0x16 => "DW_OP_swap",
+        0x17 => "DW_OP_rot",
+        0x18 => "DW_OP_xderef",
+        0x19 => "DW_OP_abs",
+        0x1a => "DW_OP_and",
+        0x1b => "DW_OP_div",
+        0x1c => "DW_OP_minus",
+        0x1d => "DW_OP_mod",
+        0x1e => "DW_OP_mul",
+        0x1f => "DW_OP_neg",
+        0x20 => "DW_OP_not",
+        0x21 => "DW_OP_or",
+        0x22 => "DW_OP_plus",
+        0x23 => "DW_OP_plus_uconst",
+        0x24 => "DW_OP_shl",
+        0x25 => "DW_OP_shr",
+        0x26 => "DW_OP_shra",
+        0x27 => "DW_OP_xor",
+        0x28 => "DW_OP_bra",
+        0x29 => "DW_OP_eq",
+        0x2a => "DW_OP_ge",
+        0x2b => "DW_OP_gt",
+        0x2c => "DW_OP_le",
+        0x2d => "DW_OP_lt",
+        0x2e => "DW_OP_ne",
+        0x2f => "DW_OP_skip",
+        0x30..=0x4f => return format!("DW_OP_lit{}", opcode - 0x30),
+        0x50..=0x6f => return format!("DW_OP_reg{}", opcode - 0x50),
+        0x70..=0x8f => return format!("DW_OP_breg{}", opcode - 0x70),
+        0x90 => "DW_OP_regx",
+        0x91 => "DW_OP_fbreg",
+        0x92 => "DW_OP_bregx",
+        0x93 => "DW_OP_piece",
+        0x94 => "DW_OP_deref_size",
+        0x95 => "DW_OP_xderef_size",
+        0x96 => "DW_OP_nop",
+        0x97 => "DW_OP_push_object_address",
+        0x98 => "DW_OP_call2",
+        0x99 => "DW_OP_call4",
+        0x9a => "DW_OP_call_ref",
+        0x9b => "DW_OP_form_tls_address",
+        0x9c => "DW_OP_call_frame_cfa",
+        0x9d => "DW_OP_bit_piece",
+        0x9e => "DW_OP_implicit_value",
+        0x9f => "DW_OP_stack_value",
+        0xa0 => "DW_OP_implicit_pointer",
+        0xa1 => "DW_OP_addrx",
+        0xa2 => "DW_OP_constx",
+        0xa3 => "DW_OP_entry_value",
+        0xa4 => "DW_OP_const_type",
+        0xa5 => "DW_OP_regval_type",
+        0xa6 => "DW_OP_deref_type",
+        0xa7 => "DW_OP_xderef_type",
+        0xa8 => "DW_OP_convert",
+        0xa9 => "DW_OP_reinterpret",
+        0xe0 => "DW_OP_GNU_push_tls_address",
+        0xf0 => "DW_OP_GNU_uninit",
+        0xf1 => "DW_OP_GNU_encoded_addr",
+        0xf2 => "DW_OP_GNU_implicit_pointer",
+        0xf3 => "DW_OP_GNU_entry_value",
+        0xf4 => "DW_OP_GNU_const_type",
+        0xf5 => "DW_OP_GNU_regval_type",
+        0xf6 => "DW_OP_GNU_deref_type",
+        0xf7 => "DW_OP_GNU_convert",
+        0xf9 => "DW_OP_GNU_reinterpret",
+        0xfa => "DW_OP_GNU_parameter_ref",
+        0xfb => "DW_OP_GNU_addr_index",
+        0xfc => "DW_OP_GNU_const_index",
+        0xfd => "DW_OP_GNU_variable_value",
+        _ => return format!("DW_OP_0x{:x}", opcode),
+    };
+    name.to_string()
+}
+
+fn language_string<'a>(lang: u64) -> &'a str {
+    match lang {
+        0x01 => "DW_LANG_C89",
+        0x02 => "DW_LANG_C",
+        0x03 => "DW_LANG_Ada83",
+        0x04 => "DW_LANG_C_plus_plus",
+        0x05 => "DW_LANG_Cobol74",
+        0x06 => "DW_LANG_Cobol85",
+        0x07 => "DW_LANG_Fortran77",
+        0x08 => "DW_LANG_Fortran90",
+        0x09 => "DW_LANG_Pascal83",
+        0x0a => "DW_LANG_Modula2",
+        0x0b => "DW_LANG_Java",
+        0x0c => "DW_LANG_C99",
+        0x0d => "DW_LANG_Ada95",
+        0x0e => "DW_LANG_Fortran95",
+        0x0f => "DW_LANG_PLI",
+        0x10 => "DW_LANG_ObjC",
+        0x11 => "DW_LANG_ObjC_plus_plus",
+        0x12 => "DW_LANG_UPC",
+        0x13 => "DW_LANG_D",
+        0x14 => "DW_LANG_Python",
+        0x15 => "DW_LANG_OpenCL",
+        0x16 => "DW_LANG_Go",
+        0x17 => "DW_LANG_Modula3",
+        0x18 => "DW_LANG_Haskell",
+        0x19 => "DW_LANG_C_plus_plus_03",
+        0x1a => "DW_LANG_C_plus_plus_11",
+        0x1b => "DW_LANG_OCaml",
+        0x1c => "DW_LANG_Rust",
+        0x1d => "DW_LANG_C11",
+        0x1e => "DW_LANG_Swift",
+        0x1f => "DW_LANG_Julia",
+        0x20 => "DW_LANG_Dylan",
+        0x21 => "DW_LANG_C_plus_plus_14",
+        0x22 => "DW_LANG_Fortran03",
+        0x23 => "DW_LANG_Fortran08",
+        0x24 => "DW_LANG_RenderScript",
+        0x25 => "DW_LANG_BLISS",
+        0x8001 => "DW_LANG_Mips_Assembler",
+        _ => "unknown",
+    }
+}
+
+fn encoding_string<'a>(encoding: u64) -> &'a str {
+    match encoding {
+        0x01 => "DW_ATE_address",
+        0x02 => "DW_ATE_boolean",
+        0x03 => "DW_ATE_complex_float",
+        0x04 => "DW_ATE_float",
+        0x05 => "DW_ATE_signed",
+        0x06 => "DW_ATE_signed_char",
+        0x07 => "DW_ATE_unsigned",
+        0x08 => "DW_ATE_unsigned_char",
+        0x09 => "DW_ATE_imaginary_float",
+        0x0a => "DW_ATE_packed_decimal",
+        0x0b => "DW_ATE_numeric_string",
+        0x0c => "DW_ATE_edited",
+        0x0d => "DW_ATE_signed_fixed",
+        0x0e => "DW_ATE_unsigned_fixed",
+        0x0f => "DW_ATE_decimal_float",
+        0x10 => "DW_ATE_UTF",
+        0x11 => "DW_ATE_UCS",
+        0x12 => "DW_ATE_ASCII",
+        _ => "unknown",
+    }
+}
+
+fn inline_string<'a>(inline: u64) -> &'a str {
+    match inline {
+        0 => "DW_INL_not_inlined",
+        1 => "DW_INL_inlined",
+        2 => "DW_INL_declared_not_inlined",
+        3 => "DW_INL_declared_inlined",
+        _ => "unknown",
+    }
+}
+
+fn accessibility_string<'a>(access: u64) -> &'a str {
+    match access {
+        1 => "DW_ACCESS_public",
+        2 => "DW_ACCESS_protected",
+        3 => "DW_ACCESS_private",
+        _ => "unknown",
+    }
+}
@@ -1,15 +1,103 @@
-use crate::widgets::list;
+use std::path::Path;
+
+use crate::elf_util::init_fini::InitFiniEntry;
+use crate::elf_util::{binding, run_path};
+use crate::widgets::{data_source_title, init_fini, list};
 use elf_utilities::{
     dynamic, file,
     section::{self, Contents64},
     symbol,
 };
+use tui::style::{Color, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
-pub fn dynamic_list(dynamic_sct: Option<&section::Section64>) -> List {
+// elf_utilities の dynamic::EntryType が扱わない(あるいは誤って扱う)タグ
+const DT_HASH: i64 = 4;
+const DT_SONAME: i64 = 14;
+const DT_RPATH: i64 = 15;
+const DT_PLTREL: i64 = 20;
+const DT_RUNPATH: i64 = 29;
+const DT_RELRSZ: i64 = 35;
+const DT_RELR: i64 = 36;
+const DT_RELRENT: i64 = 37;
+const DT_LOOS: i64 = 0x6000_000d;
+const DT_ANDROID_REL: i64 = 0x6000_000f;
+const DT_ANDROID_RELSZ: i64 = 0x6000_0010;
+const DT_ANDROID_RELA: i64 = 0x6000_0011;
+const DT_ANDROID_RELASZ: i64 = 0x6000_0012;
+const DT_HIOS: i64 = 0x6fff_f000;
+const DT_GNU_PRELINKED: i64 = 0x6fff_fdf5;
+const DT_GNU_CONFLICTSZ: i64 = 0x6fff_fdf6;
+const DT_GNU_LIBLISTSZ: i64 = 0x6fff_fdf7;
+const DT_CHECKSUM: i64 = 0x6fff_fdf8;
+const DT_PLTPADSZ: i64 = 0x6fff_fdf9;
+const DT_MOVEENT: i64 = 0x6fff_fdfa;
+const DT_MOVESZ: i64 = 0x6fff_fdfb;
+const DT_FEATURE_1: i64 = 0x6fff_fdfc;
+const DT_POSFLAG_1: i64 = 0x6fff_fdfd;
+const DT_SYMINSZ: i64 = 0x6fff_fdfe;
+const DT_SYMINENT: i64 = 0x6fff_fdff;
+const DT_TLSDESC_PLT: i64 = 0x6fff_fef6;
+const DT_TLSDESC_GOT: i64 = 0x6fff_fef7;
+const DT_GNU_CONFLICT: i64 = 0x6fff_fef8;
+const DT_GNU_LIBLIST: i64 = 0x6fff_fef9;
+const DT_CONFIG: i64 = 0x6fff_fefa;
+const DT_DEPAUDIT: i64 = 0x6fff_fefb;
+const DT_AUDIT: i64 = 0x6fff_fefc;
+const DT_PLTPAD: i64 = 0x6fff_fefd;
+const DT_MOVETAB: i64 = 0x6fff_fefe;
+const DT_SYMINFO: i64 = 0x6fff_feff;
+const DT_VERDEF: i64 = 0x6fff_fffc;
+const DT_VERDEFNUM: i64 = 0x6fff_fffd;
+const DT_VERNEEDNUM: i64 = 0x6fff_ffff;
+const DT_LOPROC: i64 = 0x7000_0000;
+const DT_AUXILIARY: i64 = 0x7fff_fffd;
+const DT_USED: i64 = 0x7fff_fffe;
+const DT_FILTER: i64 = 0x7fff_ffff;
+const DT_HIPROC: i64 = 0x7fff_ffff;
+
+// プロセッサ固有のタグ. 同じ値がアーキテクチャごとに違う意味を持つので, e_machine と組で引く
+const EM_MIPS: u16 = 8;
+const EM_AARCH64: u16 = 183;
+const DT_AARCH64_BTI_PLT: i64 = 0x7000_0001;
+const DT_AARCH64_PAC_PLT: i64 = 0x7000_0003;
+const DT_AARCH64_VARIANT_PCS: i64 = 0x7000_0005;
+const DT_AARCH64_MEMTAG_MODE: i64 = 0x7000_0009;
+const DT_AARCH64_MEMTAG_HEAP: i64 = 0x7000_000b;
+const DT_AARCH64_MEMTAG_STACK: i64 = 0x7000_000c;
+const DT_AARCH64_MEMTAG_GLOBALS: i64 = 0x7000_000d;
+const DT_AARCH64_MEMTAG_GLOBALSSZ: i64 = 0x7000_000f;
+const DT_AARCH64_AUTH_RELRSZ: i64 = 0x7000_0011;
+const DT_AARCH64_AUTH_RELR: i64 = 0x7000_0012;
+const DT_AARCH64_AUTH_RELRENT: i64 = 0x7000_0013;
+const DT_MIPS_RLD_VERSION: i64 = 0x7000_0001;
+const DT_MIPS_TIME_STAMP: i64 = 0x7000_0002;
+const DT_MIPS_ICHECKSUM: i64 = 0x7000_0003;
+const DT_MIPS_IVERSION: i64 = 0x7000_0004;
+const DT_MIPS_FLAGS: i64 = 0x7000_0005;
+const DT_MIPS_BASE_ADDRESS: i64 = 0x7000_0006;
+const DT_MIPS_MSYM: i64 = 0x7000_0007;
+const DT_MIPS_CONFLICT: i64 = 0x7000_0008;
+const DT_MIPS_LIBLIST: i64 = 0x7000_0009;
+const DT_MIPS_LOCAL_GOTNO: i64 = 0x7000_000a;
+const DT_MIPS_CONFLICTNO: i64 = 0x7000_000b;
+const DT_MIPS_LIBLISTNO: i64 = 0x7000_0010;
+const DT_MIPS_SYMTABNO: i64 = 0x7000_0011;
+const DT_MIPS_UNREFEXTNO: i64 = 0x7000_0012;
+const DT_MIPS_GOTSYM: i64 = 0x7000_0013;
+const DT_MIPS_HIPAGENO: i64 = 0x7000_0014;
+const DT_MIPS_RLD_MAP: i64 = 0x7000_0016;
+const DT_MIPS_OPTIONS: i64 = 0x7000_0029;
+const DT_MIPS_GP_VALUE: i64 = 0x7000_0030;
+const DT_MIPS_PLTGOT: i64 = 0x7000_0032;
+const DT_MIPS_RWPLT: i64 = 0x7000_0034;
+const DT_MIPS_RLD_MAP_REL: i64 = 0x7000_0035;
+const DT_MIPS_XHASH: i64 = 0x7000_0036;
+
+pub fn dynamic_list(dynamic_sct: Option<&section::Section64>, reconstructed: bool) -> List<'_> {
     list(
-        "Dynamics",
+        data_source_title(reconstructed, "Dynamics"),
         dynamic_names(dynamic_sct)
             .iter()
             .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
@@ -19,31 +107,41 @@ pub fn dynamic_list(dynamic_sct: Option<&section::Section64>) -> List {
 
 pub fn dynamic_information<'a>(
     elf_file: &'a file::ELF64,
+    file_path: &str,
     dynamic_table: &'a section::Section64,
-    symbol_table: &'a section::Section64,
+    symbol_table: Option<&'a section::Section64>,
+    init_fini: &[InitFiniEntry],
     dyn_idx: usize,
+    reconstructed: bool,
 ) -> Paragraph<'a> {
     if let Contents64::Dynamics(dynamics) = &dynamic_table.contents {
         let dyn_entry = &dynamics[dyn_idx];
 
-        Paragraph::new(vec![
+        let mut spans = vec![
             Spans::from(vec![
                 Span::raw("Tag: "),
                 Span::raw(format!("0x{:x}", dyn_entry.d_tag)),
             ]),
             Spans::from(vec![
                 Span::raw("Type: "),
-                Span::raw(dyn_type_string(dyn_entry.get_type())),
+                Span::raw(dyn_tag_string(elf_file.ehdr.e_machine, dyn_entry.d_tag)),
             ]),
-            dyn_value_spans(
-                elf_file,
-                dynamic_table,
-                symbol_table,
-                dyn_entry.get_type(),
-                dyn_entry.d_un,
-            ),
-        ])
-        .block(Block::default().borders(Borders::ALL).title("Dynamics"))
+        ];
+        spans.append(&mut dyn_value_spans(
+            elf_file,
+            file_path,
+            dynamic_table,
+            symbol_table,
+            init_fini,
+            dyn_entry.d_tag,
+            dyn_entry.d_un,
+        ));
+
+        Paragraph::new(spans).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(data_source_title(reconstructed, "Dynamics")),
+        )
     } else {
         unreachable!()
     }
@@ -60,6 +158,144 @@ pub fn dynamic_names(dynamic_sct: Option<&section::Section64>) -> Vec<String> {
         unreachable!()
     }
 }
+/// elf_utilities が区別しないタグを先に判定してから, dyn_type_string() に委ねる.
+/// プロセッサ固有のタグは e_machine で解釈する
+pub fn dyn_tag_string<'a>(e_machine: u16, d_tag: i64) -> &'a str {
+    match d_tag {
+        DT_HASH => "HASH",
+        DT_RELRSZ => "RELRSZ",
+        DT_RELR => "RELR",
+        DT_RELRENT => "RELRENT",
+        DT_ANDROID_REL => "ANDROID_REL",
+        DT_ANDROID_RELSZ => "ANDROID_RELSZ",
+        DT_ANDROID_RELA => "ANDROID_RELA",
+        DT_ANDROID_RELASZ => "ANDROID_RELASZ",
+        DT_GNU_PRELINKED => "GNU_PRELINKED",
+        DT_GNU_CONFLICTSZ => "GNU_CONFLICTSZ",
+        DT_GNU_LIBLISTSZ => "GNU_LIBLISTSZ",
+        DT_CHECKSUM => "CHECKSUM",
+        DT_PLTPADSZ => "PLTPADSZ",
+        DT_MOVEENT => "MOVEENT",
+        DT_MOVESZ => "MOVESZ",
+        DT_FEATURE_1 => "FEATURE_1",
+        DT_POSFLAG_1 => "POSFLAG_1",
+        DT_SYMINSZ => "SYMINSZ",
+        DT_SYMINENT => "SYMINENT",
+        DT_TLSDESC_PLT => "TLSDESC_PLT",
+        DT_TLSDESC_GOT => "TLSDESC_GOT",
+        DT_GNU_CONFLICT => "GNU_CONFLICT",
+        DT_GNU_LIBLIST => "GNU_LIBLIST",
+        DT_CONFIG => "CONFIG",
+        DT_DEPAUDIT => "DEPAUDIT",
+        DT_AUDIT => "AUDIT",
+        DT_PLTPAD => "PLTPAD",
+        DT_MOVETAB => "MOVETAB",
+        DT_SYMINFO => "SYMINFO",
+        DT_VERDEF => "VERDEF",
+        DT_VERDEFNUM => "VERDEFNUM",
+        DT_AUXILIARY => "AUXILIARY",
+        DT_USED => "USED",
+        DT_FILTER => "FILTER",
+        _ => match dyn_type_string(dynamic::EntryType::from(d_tag)) {
+            "unknown" if (DT_LOOS..=DT_HIOS).contains(&d_tag) => "OS-specific",
+            "unknown" if (DT_LOPROC..=DT_HIPROC).contains(&d_tag) => {
+                processor_tag_string(e_machine, d_tag).unwrap_or("processor-specific")
+            }
+            name => name,
+        },
+    }
+}
+fn processor_tag_string<'a>(e_machine: u16, d_tag: i64) -> Option<&'a str> {
+    let name = match (e_machine, d_tag) {
+        (EM_AARCH64, DT_AARCH64_BTI_PLT) => "AARCH64_BTI_PLT",
+        (EM_AARCH64, DT_AARCH64_PAC_PLT) => "AARCH64_PAC_PLT",
+        (EM_AARCH64, DT_AARCH64_VARIANT_PCS) => "AARCH64_VARIANT_PCS",
+        (EM_AARCH64, DT_AARCH64_MEMTAG_MODE) => "AARCH64_MEMTAG_MODE",
+        (EM_AARCH64, DT_AARCH64_MEMTAG_HEAP) => "AARCH64_MEMTAG_HEAP",
+        (EM_AARCH64, DT_AARCH64_MEMTAG_STACK) => "AARCH64_MEMTAG_STACK",
+        (EM_AARCH64, DT_AARCH64_MEMTAG_GLOBALS) => "AARCH64_MEMTAG_GLOBALS",
+        (EM_AARCH64, DT_AARCH64_MEMTAG_GLOBALSSZ) => "AARCH64_MEMTAG_GLOBALSSZ",
+        (EM_AARCH64, DT_AARCH64_AUTH_RELRSZ) => "AARCH64_AUTH_RELRSZ",
+        (EM_AARCH64, DT_AARCH64_AUTH_RELR) => "AARCH64_AUTH_RELR",
+        (EM_AARCH64, DT_AARCH64_AUTH_RELRENT) => "AARCH64_AUTH_RELRENT",
+        (EM_MIPS, DT_MIPS_RLD_VERSION) => "MIPS_RLD_VERSION",
+        (EM_MIPS, DT_MIPS_TIME_STAMP) => "MIPS_TIME_STAMP",
+        (EM_MIPS, DT_MIPS_ICHECKSUM) => "MIPS_ICHECKSUM",
+        (EM_MIPS, DT_MIPS_IVERSION) => "MIPS_IVERSION",
+        (EM_MIPS, DT_MIPS_FLAGS) => "MIPS_FLAGS",
+        (EM_MIPS, DT_MIPS_BASE_ADDRESS) => "MIPS_BASE_ADDRESS",
+        (EM_MIPS, DT_MIPS_MSYM) => "MIPS_MSYM",
+        (EM_MIPS, DT_MIPS_CONFLICT) => "MIPS_CONFLICT",
+        (EM_MIPS, DT_MIPS_LIBLIST) => "MIPS_LIBLIST",
+        (EM_MIPS, DT_MIPS_LOCAL_GOTNO) => "MIPS_LOCAL_GOTNO",
+        (EM_MIPS, DT_MIPS_CONFLICTNO) => "MIPS_CONFLICTNO",
+        (EM_MIPS, DT_MIPS_LIBLISTNO) => "MIPS_LIBLISTNO",
+        (EM_MIPS, DT_MIPS_SYMTABNO) => "MIPS_SYMTABNO",
+        (EM_MIPS, DT_MIPS_UNREFEXTNO) => "MIPS_UNREFEXTNO",
+        (EM_MIPS, DT_MIPS_GOTSYM) => "MIPS_GOTSYM",
+        (EM_MIPS, DT_MIPS_HIPAGENO) => "MIPS_HIPAGENO",
+        (EM_MIPS, DT_MIPS_RLD_MAP) => "MIPS_RLD_MAP",
+        (EM_MIPS, DT_MIPS_OPTIONS) => "MIPS_OPTIONS",
+        (EM_MIPS, DT_MIPS_GP_VALUE) => "MIPS_GP_VALUE",
+        (EM_MIPS, DT_MIPS_PLTGOT) => "MIPS_PLTGOT",
+        (EM_MIPS, DT_MIPS_RWPLT) => "MIPS_RWPLT",
+        (EM_MIPS, DT_MIPS_RLD_MAP_REL) => "MIPS_RLD_MAP_REL",
+        (EM_MIPS, DT_MIPS_XHASH) => "MIPS_XHASH",
+        _ => return None,
+    };
+    Some(name)
+}
+/// 既知のプロセッサ固有タグの値. 知らないタグは None で, 生の値を表示する
+fn processor_value(
+    elf_file: &file::ELF64,
+    d_tag: i64,
+    value: u64,
+) -> Option<(&'static str, String)> {
+    let entry = match (elf_file.ehdr.e_machine, d_tag) {
+        (EM_AARCH64, DT_AARCH64_BTI_PLT | DT_AARCH64_PAC_PLT | DT_AARCH64_VARIANT_PCS) => {
+            ("Flag: ", "set".to_string())
+        }
+        (EM_AARCH64, DT_AARCH64_MEMTAG_MODE) => (
+            "Mode: ",
+            match value {
+                0 => "synchronous".to_string(),
+                1 => "asynchronous".to_string(),
+                _ => format!("unknown ({})", value),
+            },
+        ),
+        (EM_AARCH64, DT_AARCH64_MEMTAG_HEAP | DT_AARCH64_MEMTAG_STACK) => (
+            "Enabled: ",
+            if value != 0 { "yes" } else { "no" }.to_string(),
+        ),
+        (EM_AARCH64, DT_AARCH64_MEMTAG_GLOBALS | DT_AARCH64_AUTH_RELR)
+        | (
+            EM_MIPS,
+            DT_MIPS_MSYM | DT_MIPS_CONFLICT | DT_MIPS_LIBLIST | DT_MIPS_OPTIONS | DT_MIPS_PLTGOT
+            | DT_MIPS_RWPLT | DT_MIPS_XHASH,
+        ) => ("Related Section: ", find_section_by_value(elf_file, value)),
+        (
+            EM_AARCH64,
+            DT_AARCH64_MEMTAG_GLOBALSSZ | DT_AARCH64_AUTH_RELRSZ | DT_AARCH64_AUTH_RELRENT,
+        ) => ("Size: ", format!("{} (bytes)", value)),
+        (
+            EM_MIPS,
+            DT_MIPS_LOCAL_GOTNO | DT_MIPS_CONFLICTNO | DT_MIPS_LIBLISTNO | DT_MIPS_SYMTABNO
+            | DT_MIPS_UNREFEXTNO | DT_MIPS_HIPAGENO,
+        ) => ("Count: ", format!("{}", value)),
+        (EM_MIPS, DT_MIPS_GOTSYM) => ("First GOT Symbol Index: ", format!("{}", value)),
+        (EM_MIPS, DT_MIPS_RLD_VERSION | DT_MIPS_IVERSION) => ("Version: ", format!("{}", value)),
+        (EM_MIPS, DT_MIPS_TIME_STAMP) => ("Time Stamp: ", timestamp_string(value)),
+        (EM_MIPS, DT_MIPS_ICHECKSUM) => ("Checksum: ", format!("0x{:x}", value)),
+        (EM_MIPS, DT_MIPS_FLAGS) => ("Flag: ", format!("0x{:x}", value)),
+        (EM_MIPS, DT_MIPS_BASE_ADDRESS | DT_MIPS_RLD_MAP | DT_MIPS_GP_VALUE) => {
+            ("Address: ", format!("0x{:x}", value))
+        }
+        // DT_MIPS_RLD_MAP_REL はこのエントリ自身のアドレスからの相対値
+        (EM_MIPS, DT_MIPS_RLD_MAP_REL) => ("Offset: ", format!("0x{:x}", value)),
+        _ => return None,
+    };
+    Some(entry)
+}
 fn dyn_type_string<'a>(dyn_type: dynamic::EntryType) -> &'a str {
     match dyn_type {
         dynamic::EntryType::Null => "NULL",
@@ -108,16 +344,78 @@ fn dyn_type_string<'a>(dyn_type: dynamic::EntryType) -> &'a str {
 
 fn dyn_value_spans<'a>(
     elf_file: &'a file::ELF64,
+    file_path: &str,
     dynamic_table: &'a section::Section64,
-    symbol_table: &'a section::Section64,
-    dyn_type: dynamic::EntryType,
+    symbol_table: Option<&'a section::Section64>,
+    init_fini: &[InitFiniEntry],
+    d_tag: i64,
     value: u64,
-) -> Spans<'a> {
-    let (attribute, value_string) = match dyn_type {
-        dynamic::EntryType::Needed => (
-            "Needed: ",
-            dyn_library_string(elf_file, dynamic_table, value),
+) -> Vec<Spans<'a>> {
+    if let dynamic::EntryType::InitArray
+    | dynamic::EntryType::FiniArray
+    | dynamic::EntryType::PreInitArray = dynamic::EntryType::from(d_tag)
+    {
+        return array_spans(elf_file, init_fini, value);
+    }
+
+    let (attribute, value_string) = match d_tag {
+        DT_SONAME => ("SOName: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_AUXILIARY => ("Auxiliary: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_FILTER => ("Filter: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_CONFIG => ("Config: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_DEPAUDIT => (
+            "Dependency Audit: ",
+            dyn_string(elf_file, dynamic_table, value),
         ),
+        DT_AUDIT => ("Audit: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_USED => ("Used: ", dyn_string(elf_file, dynamic_table, value)),
+        DT_RPATH | DT_RUNPATH => {
+            let run_path = dyn_string(elf_file, dynamic_table, value);
+            return run_path_spans(elf_file, file_path, run_path);
+        }
+        DT_HASH | DT_RELR | DT_ANDROID_REL | DT_ANDROID_RELA | DT_VERDEF | DT_GNU_CONFLICT
+        | DT_GNU_LIBLIST | DT_MOVETAB | DT_SYMINFO | DT_PLTPAD | DT_TLSDESC_GOT
+        | DT_TLSDESC_PLT => ("Related Section: ", find_section_by_value(elf_file, value)),
+        DT_VERDEFNUM | DT_VERNEEDNUM => ("Count: ", format!("{}", value)),
+        DT_RELRSZ | DT_RELRENT | DT_ANDROID_RELSZ | DT_ANDROID_RELASZ | DT_GNU_CONFLICTSZ
+        | DT_GNU_LIBLISTSZ | DT_PLTPADSZ | DT_MOVEENT | DT_MOVESZ | DT_SYMINSZ | DT_SYMINENT => {
+            ("Size: ", format!("{} (bytes)", value))
+        }
+        DT_PLTREL => (
+            "Relocation Type: ",
+            dyn_tag_string(elf_file.ehdr.e_machine, value as i64).to_string(),
+        ),
+        DT_GNU_PRELINKED => ("Prelinked At: ", timestamp_string(value)),
+        DT_CHECKSUM => ("Checksum: ", format!("0x{:x}", value)),
+        DT_FEATURE_1 => ("Feature: ", dyn_feature1_string(value)),
+        DT_POSFLAG_1 => ("Flag: ", dyn_posflag1_string(value)),
+        DT_LOPROC..=DT_HIPROC if processor_tag_string(elf_file.ehdr.e_machine, d_tag).is_some() => {
+            processor_value(elf_file, d_tag, value)
+                .unwrap_or_else(|| ("Value: ", format!("0x{:x}", value)))
+        }
+        DT_LOOS..=DT_HIOS | DT_LOPROC..=DT_HIPROC
+            if dyn_type_string(dynamic::EntryType::from(d_tag)) == "unknown" =>
+        {
+            ("Value: ", format!("0x{:x}", value))
+        }
+        _ => dyn_entry_value(elf_file, dynamic_table, symbol_table, d_tag, value),
+    };
+
+    vec![Spans::from(vec![
+        Span::raw(attribute),
+        Span::raw(value_string),
+    ])]
+}
+
+fn dyn_entry_value<'a>(
+    elf_file: &'a file::ELF64,
+    dynamic_table: &'a section::Section64,
+    symbol_table: Option<&'a section::Section64>,
+    d_tag: i64,
+    value: u64,
+) -> (&'a str, String) {
+    match dynamic::EntryType::from(d_tag) {
+        dynamic::EntryType::Needed => ("Needed: ", dyn_string(elf_file, dynamic_table, value)),
         dynamic::EntryType::VerNeed
         | dynamic::EntryType::StrTab
         | dynamic::EntryType::SymTab
@@ -130,9 +428,6 @@ fn dyn_value_spans<'a>(
             ("Related Section: ", find_section_by_value(elf_file, value))
         }
 
-        dynamic::EntryType::InitArray | dynamic::EntryType::FiniArray => {
-            ("Related Symbol: ", find_array_by_value(symbol_table, value))
-        }
         dynamic::EntryType::Init | dynamic::EntryType::Fini => (
             "Related Symbol: ",
             find_symbol_by_value(symbol_table, value),
@@ -144,31 +439,140 @@ fn dyn_value_spans<'a>(
         dynamic::EntryType::Flags1 => ("Flag1:", dyn_flag1_string(value)),
         dynamic::EntryType::InitArraySz
         | dynamic::EntryType::FiniArraySz
+        | dynamic::EntryType::PreInitArraySz
         | dynamic::EntryType::PLTRelSz
         | dynamic::EntryType::RelaSz
+        | dynamic::EntryType::RelSz
         | dynamic::EntryType::StrSz
         | dynamic::EntryType::SymEnt
         | dynamic::EntryType::RelaEnt
         | dynamic::EntryType::RelEnt => ("Size: ", format!("{} (bytes)", value)),
+        dynamic::EntryType::RelaCount | dynamic::EntryType::RelCount => {
+            ("Count: ", format!("{}", value))
+        }
         _ => ("Address: ", format!("0x{:x}", value)),
-    };
+    }
+}
 
-    Spans::from(vec![Span::raw(attribute), Span::raw(value_string)])
+/// 配列の各スロットが指す関数を, 呼び出される順に並べる
+fn array_spans<'a>(
+    elf_file: &'a file::ELF64,
+    init_fini: &[InitFiniEntry],
+    value: u64,
+) -> Vec<Spans<'a>> {
+    let entries: Vec<&InitFiniEntry> = init_fini
+        .iter()
+        .filter(|entry| elf_file.sections[entry.section].header.sh_addr == value)
+        .collect();
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Related Section: "),
+            Span::raw(find_section_by_value(elf_file, value)),
+        ]),
+        Spans::from(vec![Span::raw(format!(
+            "Entries ({}, in execution order):",
+            entries.len()
+        ))]),
+    ];
+    spans.extend(entries.iter().map(|entry| {
+        Spans::from(vec![Span::raw(format!(
+            "  [{}] {}",
+            entry.index,
+            init_fini::target_string(elf_file, &entry.target)
+        ))])
+    }));
+
+    spans
 }
 
-fn find_symbol_by_value(symbol_table: &section::Section64, value: u64) -> String {
-    if let Contents64::Symbols(symbols) = &symbol_table.contents {
-        for sym in symbols.iter() {
-            if sym.st_value == value && sym.get_type() == symbol::Type::NoType {
-                return sym.symbol_name.clone();
-            }
+/// 検索パスを ':' で分割し, 各エントリのトークン展開結果と問題点を並べる
+fn run_path_spans<'a>(elf_file: &file::ELF64, file_path: &str, run_path: String) -> Vec<Spans<'a>> {
+    let file_path = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.into());
+    let origin = file_path.parent().unwrap_or_else(|| Path::new(""));
+    let triplet = binding::multiarch_triplet(elf_file.ehdr.e_machine, elf_file.ehdr.e_ident[5]);
+
+    let mut spans = vec![Spans::from(vec![
+        Span::raw("Search Path: "),
+        Span::raw(run_path.clone()),
+    ])];
+
+    for entry in run_path::expand_run_path(&run_path, origin, triplet) {
+        let mut line = vec![Span::raw(format!(
+            "  {} => {}",
+            entry.raw,
+            entry.expanded.unwrap_or_else(|| "-".to_string())
+        ))];
+        if let Some(problem) = entry.problem {
+            line.push(Span::styled(
+                format!(" ({})", problem),
+                Style::default().fg(Color::Yellow),
+            ));
         }
+        spans.push(Spans::from(line));
     }
 
-    String::from("unknown")
+    spans
+}
+
+/// DT_GNU_PRELINKED の time_t を UTC の日時に変換する
+fn timestamp_string(value: u64) -> String {
+    let days = (value / 86400) as i64;
+    let secs = value % 86400;
+
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC (0x{:x})",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        value
+    )
+}
+
+fn dyn_feature1_string(value: u64) -> String {
+    let mut s = String::new();
+    if value & 0x1 != 0 {
+        s += " PARINIT";
+    }
+    if value & 0x2 != 0 {
+        s += " CONFEXP";
+    }
+    if value & !0x3 != 0 {
+        s += &format!(" 0x{:x}", value & !0x3);
+    }
+    s
+}
+
+fn dyn_posflag1_string(value: u64) -> String {
+    let mut s = String::new();
+    if value & 0x1 != 0 {
+        s += " LAZYLOAD";
+    }
+    if value & 0x2 != 0 {
+        s += " GROUPPERM";
+    }
+    if value & !0x3 != 0 {
+        s += &format!(" 0x{:x}", value & !0x3);
+    }
+    s
 }
-fn find_array_by_value(symbol_table: &section::Section64, value: u64) -> String {
-    if let Contents64::Symbols(symbols) = &symbol_table.contents {
+
+fn find_symbol_by_value(symbol_table: Option<&section::Section64>, value: u64) -> String {
+    if let Some(Contents64::Symbols(symbols)) = symbol_table.map(|sct| &sct.contents) {
         for sym in symbols.iter() {
             if sym.st_value == value && sym.get_type() == symbol::Type::NoType {
                 return sym.symbol_name.clone();
@@ -233,29 +637,37 @@ fn dyn_flag1_string(value: u64) -> String {
         if f != 0 {
             s += &format!(" {}", dyn_flag_string(dynamic::Flag::from_1(f)));
         }
-        base = base << 1;
+        base <<= 1;
     }
 
     s
 }
-fn dyn_library_string<'a>(
-    elf_file: &'a file::ELF64,
-    dynamic_table: &'a section::Section64,
-    value: u64,
-) -> String {
+/// 動的文字列テーブル(.dynamic の sh_link)から value の位置の文字列を取り出す
+fn dyn_string(elf_file: &file::ELF64, dynamic_table: &section::Section64, value: u64) -> String {
     let table_index = dynamic_table.header.sh_link;
-    let strtab = &elf_file.sections[table_index as usize];
+    let value = value as usize;
 
-    if let Contents64::Raw(bytes) = &strtab.contents {
-        let library_name = bytes[value as usize..]
-            .to_vec()
+    let s = match elf_file
+        .sections
+        .get(table_index as usize)
+        .map(|sct| &sct.contents)
+    {
+        Some(Contents64::Raw(bytes)) => bytes.get(value..).map(|bytes| {
+            let s: Vec<u8> = bytes
+                .iter()
+                .take_while(|byte| **byte != 0x00)
+                .copied()
+                .collect();
+            String::from_utf8_lossy(&s).to_string()
+        }),
+        // 末尾を共有する文字列があるので, value を含むエントリから切り出す
+        Some(Contents64::StrTab(strs)) => strs
             .iter()
-            .take_while(|byte| **byte != 0x00)
-            .map(|byte| *byte)
-            .collect();
+            .find(|s| s.idx <= value && value <= s.idx + s.v.len())
+            .and_then(|s| s.v.get(value - s.idx..))
+            .map(|s| s.to_string()),
+        _ => None,
+    };
 
-        String::from_utf8(library_name).unwrap()
-    } else {
-        unreachable!()
-    }
+    s.unwrap_or_else(|| String::from("unknown"))
 }
@@ -0,0 +1,443 @@
+use crate::elf_util::address;
+use crate::elf_util::dwarf::eh_frame::{
+    self, CfaRow, CfaRule, Cie, EhFrame, EhFrameHdr, Fde, FrameEntry, RegisterRule,
+};
+use crate::elf_util::relocations;
+use crate::widgets::list;
+use elf_utilities::{file, section};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn eh_frame_list<'a>(
+    elf_file: &file::ELF64,
+    eh_frame: &EhFrame,
+    symbol_table: Option<&section::Section64>,
+) -> List<'a> {
+    list(
+        format!(
+            "{} ({} CIEs, {} FDEs)",
+            eh_frame
+                .section
+                .map_or(".eh_frame (via PT_GNU_EH_FRAME)", |idx| elf_file.sections
+                    [idx]
+                    .name
+                    .as_str()),
+            eh_frame.cies.len(),
+            eh_frame.fdes.len()
+        ),
+        eh_frame_names(elf_file, eh_frame, symbol_table)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn eh_frame_names(
+    elf_file: &file::ELF64,
+    eh_frame: &EhFrame,
+    symbol_table: Option<&section::Section64>,
+) -> Vec<String> {
+    eh_frame
+        .entries()
+        .iter()
+        .map(|entry| match entry {
+            FrameEntry::Header => format!(
+                ".eh_frame_hdr ({} entries)",
+                eh_frame.hdr.as_ref().map_or(0, |hdr| hdr.table.len())
+            ),
+            FrameEntry::Cie(idx) => {
+                let cie = &eh_frame.cies[*idx];
+                format!("CIE 0x{:04x} \"{}\"", cie.offset, cie.augmentation)
+            }
+            FrameEntry::Fde(idx) => {
+                let fde = &eh_frame.fdes[*idx];
+                let name = format!(
+                    "FDE 0x{:04x} {}",
+                    fde.offset,
+                    pc_range_string(elf_file, fde)
+                );
+                match fde_function(elf_file, fde, symbol_table) {
+                    Some(function) => format!("{} {}", name, function),
+                    None => name,
+                }
+            }
+        })
+        .collect()
+}
+
+pub fn eh_frame_information<'a>(
+    elf_file: &file::ELF64,
+    eh_frame: &EhFrame,
+    entry: FrameEntry,
+    symbol_table: Option<&section::Section64>,
+) -> Paragraph<'a> {
+    let (title, spans) = match entry {
+        FrameEntry::Header => match &eh_frame.hdr {
+            Some(hdr) => (".eh_frame_hdr", hdr_spans(eh_frame, hdr, symbol_table)),
+            None => (".eh_frame_hdr", Vec::new()),
+        },
+        FrameEntry::Cie(idx) => ("CIE", cie_spans(elf_file, eh_frame, &eh_frame.cies[idx])),
+        FrameEntry::Fde(idx) => (
+            "FDE",
+            fde_spans(elf_file, eh_frame, &eh_frame.fdes[idx], symbol_table),
+        ),
+    };
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn hdr_spans<'a>(
+    eh_frame: &EhFrame,
+    hdr: &EhFrameHdr,
+    symbol_table: Option<&section::Section64>,
+) -> Vec<Spans<'a>> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Address: "),
+            Span::raw(format!("0x{:x}", hdr.address)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Version: "),
+            Span::raw(hdr.version.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("eh_frame_ptr: "),
+            Span::raw(format!(
+                "{} ({})",
+                hdr.eh_frame_ptr
+                    .map_or("-".to_string(), |ptr| format!("0x{:x}", ptr)),
+                encoding_string(hdr.eh_frame_ptr_encoding)
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("fde_count Encoding: "),
+            Span::raw(encoding_string(hdr.fde_count_encoding)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Table Encoding: "),
+            Span::raw(encoding_string(hdr.table_encoding)),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::styled(
+            format!("Search Table ({}):", hdr.table.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+    ];
+
+    // 二分探索できるよう先頭アドレスの昇順に並んでいるはず
+    let sorted = hdr.table.windows(2).all(|pair| pair[0].0 <= pair[1].0);
+    if !sorted {
+        spans.push(Spans::from(vec![Span::styled(
+            "  not sorted by initial location",
+            Style::default().fg(Color::Red),
+        )]));
+    }
+    for (location, fde_address) in hdr.table.iter() {
+        let fde = eh_frame
+            .fdes
+            .iter()
+            .find(|fde| eh_frame.address + fde.offset == *fde_address);
+        let mut line = format!("  0x{:x} -> 0x{:x}", location, fde_address);
+        match fde {
+            Some(fde) => line.push_str(&format!(" (FDE 0x{:04x})", fde.offset)),
+            None => line.push_str(" (no FDE)"),
+        }
+        if let Some((name, 0)) =
+            symbol_table.and_then(|symbol_table| address::nearest_symbol(symbol_table, *location))
+        {
+            line.push_str(&format!(" <{}>", name));
+        }
+        spans.push(Spans::from(vec![Span::raw(line)]));
+    }
+
+    spans
+}
+
+fn cie_spans<'a>(elf_file: &file::ELF64, eh_frame: &EhFrame, cie: &Cie) -> Vec<Spans<'a>> {
+    let fde_count = eh_frame
+        .fdes
+        .iter()
+        .filter(|fde| eh_frame.cies[fde.cie].offset == cie.offset)
+        .count();
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(format!("0x{:x}", cie.offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Version: "),
+            Span::raw(cie.version.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Augmentation: "),
+            Span::raw(format!("\"{}\"", cie.augmentation)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Code Alignment Factor: "),
+            Span::raw(cie.code_align.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Data Alignment Factor: "),
+            Span::raw(cie.data_align.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Return Address Register: "),
+            Span::raw(format!(
+                "{} ({})",
+                cie.return_register,
+                register_string(elf_file, cie.return_register)
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("FDE Encoding: "),
+            Span::raw(encoding_string(cie.fde_encoding)),
+        ]),
+        Spans::from(vec![
+            Span::raw("LSDA Encoding: "),
+            Span::raw(encoding_string(cie.lsda_encoding)),
+        ]),
+    ];
+    if let Some((encoding, personality)) = cie.personality {
+        spans.push(Spans::from(vec![
+            Span::raw("Personality: "),
+            Span::raw(format!(
+                "0x{:x} ({})",
+                personality,
+                encoding_string(encoding)
+            )),
+        ]));
+    }
+    if cie.signal_frame {
+        spans.push(Spans::from(vec![Span::raw("Signal Frame: yes")]));
+    }
+    spans.push(Spans::from(vec![
+        Span::raw("FDEs: "),
+        Span::raw(fde_count.to_string()),
+    ]));
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        "Initial Rules:",
+        Style::default().add_modifier(Modifier::BOLD),
+    )]));
+    if let Some(row) = eh_frame.initial_row(cie) {
+        spans.extend(cfa_table_spans(elf_file, cie, &[row], false));
+    }
+
+    spans
+}
+
+fn fde_spans<'a>(
+    elf_file: &file::ELF64,
+    eh_frame: &EhFrame,
+    fde: &Fde,
+    symbol_table: Option<&section::Section64>,
+) -> Vec<Spans<'a>> {
+    let cie = &eh_frame.cies[fde.cie];
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(format!("0x{:x}", fde.offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("CIE: "),
+            Span::raw(format!("0x{:x} \"{}\"", cie.offset, cie.augmentation)),
+        ]),
+        Spans::from(vec![
+            Span::raw("PC Range: "),
+            Span::raw(format!(
+                "{} (0x{:x} bytes)",
+                pc_range_string(elf_file, fde),
+                fde.pc_range
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Function: "),
+            Span::raw(fde_function(elf_file, fde, symbol_table).unwrap_or_else(|| "-".to_string())),
+        ]),
+    ];
+    if let Some(lsda) = fde.lsda {
+        spans.push(Spans::from(vec![
+            Span::raw("LSDA: "),
+            Span::raw(format!("0x{:x}", lsda)),
+        ]));
+    }
+    if eh_frame.hdr.is_some() {
+        spans.push(Spans::from(vec![
+            Span::raw("Search Table: "),
+            match eh_frame.hdr_index(fde) {
+                Some(idx) => Span::raw(format!("[{}]", idx)),
+                None => Span::styled("not listed", Style::default().fg(Color::Red)),
+            },
+        ]));
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        "CFA Rules:",
+        Style::default().add_modifier(Modifier::BOLD),
+    )]));
+    spans.extend(cfa_table_spans(
+        elf_file,
+        cie,
+        &eh_frame.cfa_rows(fde),
+        true,
+    ));
+
+    spans
+}
+
+/// readelf --debug-dump=frames-interp のような表にする.
+fn cfa_table_spans<'a>(
+    elf_file: &file::ELF64,
+    cie: &Cie,
+    rows: &[CfaRow],
+    with_address: bool,
+) -> Vec<Spans<'a>> {
+    // 戻り番地のレジスタは ra として最後の列に置く
+    let mut registers: Vec<u64> = rows
+        .iter()
+        .flat_map(|row| row.registers.keys().copied())
+        .filter(|reg| *reg != cie.return_register)
+        .collect();
+    registers.sort_unstable();
+    registers.dedup();
+    registers.push(cie.return_register);
+
+    let column = |reg: u64| {
+        if reg == cie.return_register {
+            "ra".to_string()
+        } else {
+            register_string(elf_file, reg)
+        }
+    };
+    let line = |address: String, cfa: String, rules: Vec<String>| {
+        let mut line = if with_address {
+            format!("  {:<18} {:<10}", address, cfa)
+        } else {
+            format!("  {:<10}", cfa)
+        };
+        for rule in rules {
+            line.push_str(&format!(" {:<6}", rule));
+        }
+        line.trim_end().to_string()
+    };
+
+    let mut spans = vec![Spans::from(vec![Span::styled(
+        line(
+            "LOC".to_string(),
+            "CFA".to_string(),
+            registers.iter().map(|reg| column(*reg)).collect(),
+        ),
+        Style::default().fg(Color::DarkGray),
+    )])];
+    for row in rows.iter() {
+        spans.push(Spans::from(vec![Span::raw(line(
+            format!("0x{:x}", row.address),
+            cfa_string(elf_file, &row.cfa),
+            registers
+                .iter()
+                .map(|reg| {
+                    row.registers
+                        .get(reg)
+                        .map_or("u".to_string(), |rule| rule_string(elf_file, rule))
+                })
+                .collect(),
+        ))]));
+    }
+
+    spans
+}
+
+/// ET_REL では再配置先のセクションを添える
+fn pc_range_string(elf_file: &file::ELF64, fde: &Fde) -> String {
+    let end = fde.pc_begin.wrapping_add(fde.pc_range);
+    match fde.pc_section.and_then(|idx| elf_file.sections.get(idx)) {
+        Some(sct) => format!("{}+0x{:x}..0x{:x}", sct.name, fde.pc_begin, end),
+        None => format!("0x{:x}..0x{:x}", fde.pc_begin, end),
+    }
+}
+
+/// FDE が覆う関数のシンボル
+fn fde_function(
+    elf_file: &file::ELF64,
+    fde: &Fde,
+    symbol_table: Option<&section::Section64>,
+) -> Option<String> {
+    if let Some(sct_idx) = fde.pc_section {
+        return address::section_function(elf_file, symbol_table, sct_idx, fde.pc_begin);
+    }
+
+    match address::nearest_symbol(symbol_table?, fde.pc_begin)? {
+        (name, 0) => Some(name),
+        (name, delta) => Some(format!("{}+0x{:x}", name, delta)),
+    }
+}
+
+fn cfa_string(elf_file: &file::ELF64, cfa: &CfaRule) -> String {
+    match cfa {
+        CfaRule::RegisterOffset(reg, offset) => {
+            format!("{}{:+}", register_string(elf_file, *reg), offset)
+        }
+        CfaRule::Expression(_) => "exp".to_string(),
+    }
+}
+
+fn rule_string(elf_file: &file::ELF64, rule: &RegisterRule) -> String {
+    match rule {
+        RegisterRule::Undefined => "u".to_string(),
+        RegisterRule::SameValue => "s".to_string(),
+        RegisterRule::Offset(offset) => format!("c{:+}", offset),
+        RegisterRule::ValOffset(offset) => format!("v{:+}", offset),
+        RegisterRule::Register(reg) => register_string(elf_file, *reg),
+        RegisterRule::Expression(_) => "exp".to_string(),
+        RegisterRule::ValExpression(_) => "vexp".to_string(),
+    }
+}
+
+/// DWARF のレジスタ番号. x86-64 以外は番号のまま出す.
+fn register_string(elf_file: &file::ELF64, reg: u64) -> String {
+    const X86_64_REGISTERS: [&str; 17] = [
+        "rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12",
+        "r13", "r14", "r15", "rip",
+    ];
+    match X86_64_REGISTERS.get(reg as usize) {
+        Some(name) if elf_file.ehdr.e_machine == relocations::EM_X86_64 => name.to_string(),
+        _ => format!("r{}", reg),
+    }
+}
+
+/// "0x1b (pcrel sdata4)" のように並べる.
+fn encoding_string(encoding: u8) -> String {
+    if encoding == eh_frame::DW_EH_PE_OMIT {
+        return "0xff (omit)".to_string();
+    }
+
+    let format = match encoding & 0x0f {
+        0x00 => "absptr",
+        0x01 => "uleb128",
+        0x02 => "udata2",
+        0x03 => "udata4",
+        0x04 => "udata8",
+        0x09 => "sleb128",
+        0x0a => "sdata2",
+        0x0b => "sdata4",
+        0x0c => "sdata8",
+        _ => "unknown",
+    };
+    let mut names = match encoding & 0x70 {
+        0x10 => vec!["pcrel"],
+        0x20 => vec!["textrel"],
+        0x30 => vec!["datarel"],
+        0x40 => vec!["funcrel"],
+        0x50 => vec!["aligned"],
+        _ => Vec::new(),
+    };
+    names.push(format);
+    if encoding & eh_frame::DW_EH_PE_INDIRECT != 0 {
+        names.insert(0, "indirect");
+    }
+
+    format!("0x{:02x} ({})", encoding, names.join(" "))
+}
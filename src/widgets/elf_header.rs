@@ -1,70 +1,125 @@
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, Borders, Paragraph};
+use tui::widgets::{List, ListItem};
 
-use elf_utilities::{file, header, section::Contents64, symbol};
+use crate::widgets::list;
+use elf_utilities::{
+    file, header,
+    section::{self, Contents64},
+    symbol,
+};
 
-pub fn header_information(elf_file: &file::ELF64) -> Paragraph {
-    Paragraph::new(vec![
-        header_attribute_spans("Class", elf_class_string, elf_file.ehdr.get_class()),
-        header_attribute_spans("Data", elf_data_string, elf_file.ehdr.get_data()),
+/// header_list の 1 行: 表示しているフィールドと, 行を組み立てる関数.
+/// 関数にはセクションを組み立て直したかどうかも渡す.
+type HeaderLine = (&'static str, for<'a> fn(&'a file::ELF64, bool) -> Spans<'a>);
+
+const HEADER_LINES: [HeaderLine; 17] = [
+    ("e_ident[EI_CLASS]", |elf_file, _| {
+        header_attribute_spans("Class", elf_class_string, elf_file.ehdr.get_class())
+    }),
+    ("e_ident[EI_DATA]", |elf_file, _| {
+        header_attribute_spans("Data", elf_data_string, elf_file.ehdr.get_data())
+    }),
+    ("e_ident[EI_VERSION]", |elf_file, _| {
         header_attribute_spans(
             "ObjectVersion",
             elf_version_string,
             elf_file.ehdr.get_object_version(),
-        ),
-        header_attribute_spans("OS/ABI", elf_osabi_string, elf_file.ehdr.get_osabi()),
-        header_attribute_spans("Type", elf_type_string, elf_file.ehdr.get_type()),
-        header_attribute_spans("Machine", elf_machine_string, elf_file.ehdr.get_machine()),
+        )
+    }),
+    ("e_ident[EI_OSABI]", |elf_file, _| {
+        header_attribute_spans("OS/ABI", elf_osabi_string, elf_file.ehdr.get_osabi())
+    }),
+    ("e_type", |elf_file, _| {
+        header_attribute_spans("Type", elf_type_string, elf_file.ehdr.get_type())
+    }),
+    ("e_machine", |elf_file, _| {
+        header_attribute_spans("Machine", elf_machine_string, elf_file.ehdr.get_machine())
+    }),
+    ("e_version", |elf_file, _| {
         header_attribute_spans(
             "FileVersion",
             elf_version_string,
             elf_file.ehdr.get_file_version(),
-        ),
-        Spans::from(vec![
-            Span::raw("Entry point address: "),
-            Span::raw(elf_entry_string(elf_file)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Start of program headers: "),
-            Span::raw(format!("{} (bytes into file)", elf_file.ehdr.e_phoff)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Start of section headers: "),
-            Span::raw(format!("{} (bytes into file)", elf_file.ehdr.e_shoff)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Flags: "),
-            Span::raw(format!("0x{:x}", elf_file.ehdr.e_flags)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Size of this header: "),
-            Span::raw(format!("{} (bytes)", elf_file.ehdr.e_ehsize)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Size of program header: "),
-            Span::raw(format!("{} (bytes)", elf_file.ehdr.e_phentsize)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Number of program header: "),
-            Span::raw(format!("{}", elf_file.ehdr.e_phnum)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Size of section headers: "),
-            Span::raw(format!("{} (bytes)", elf_file.ehdr.e_shentsize)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Number of section headers: "),
-            Span::raw(format!("{}", elf_file.ehdr.e_shnum)),
-        ]),
-        Spans::from(vec![
-            Span::raw("Section header string table index: "),
-            Span::raw(format!(
-                "{} ({})",
-                elf_file.ehdr.e_shstrndx, elf_file.sections[elf_file.ehdr.e_shstrndx as usize].name
-            )),
-        ]),
-    ])
-    .block(Block::default().title("Header").borders(Borders::ALL))
+        )
+    }),
+    ("e_entry", |elf_file, _| {
+        value_spans("Entry point address: ", elf_entry_string(elf_file))
+    }),
+    ("e_phoff", |elf_file, _| {
+        value_spans(
+            "Start of program headers: ",
+            format!("{} (bytes into file)", elf_file.ehdr.e_phoff),
+        )
+    }),
+    ("e_shoff", |elf_file, _| {
+        value_spans(
+            "Start of section headers: ",
+            format!("{} (bytes into file)", elf_file.ehdr.e_shoff),
+        )
+    }),
+    ("e_flags", |elf_file, _| {
+        value_spans("Flags: ", format!("0x{:x}", elf_file.ehdr.e_flags))
+    }),
+    ("e_ehsize", |elf_file, _| {
+        value_spans(
+            "Size of this header: ",
+            format!("{} (bytes)", elf_file.ehdr.e_ehsize),
+        )
+    }),
+    ("e_phentsize", |elf_file, _| {
+        value_spans(
+            "Size of program header: ",
+            format!("{} (bytes)", elf_file.ehdr.e_phentsize),
+        )
+    }),
+    ("e_phnum", |elf_file, _| {
+        value_spans(
+            "Number of program header: ",
+            format!("{}", elf_file.ehdr.e_phnum),
+        )
+    }),
+    ("e_shentsize", |elf_file, _| {
+        value_spans(
+            "Size of section headers: ",
+            format!("{} (bytes)", elf_file.ehdr.e_shentsize),
+        )
+    }),
+    ("e_shnum", |elf_file, reconstructed| {
+        value_spans(
+            "Number of section headers: ",
+            shnum_string(elf_file, reconstructed),
+        )
+    }),
+    ("e_shstrndx", |elf_file, reconstructed| {
+        value_spans(
+            "Section header string table index: ",
+            shstrndx_string(elf_file, reconstructed),
+        )
+    }),
+];
+
+/// header_list の各行が表示しているフィールド
+pub fn header_fields() -> impl Iterator<Item = &'static str> {
+    HEADER_LINES.iter().map(|(field, _)| *field)
+}
+
+/// header_list の idx 行目が表示しているフィールド
+pub fn header_field(idx: usize) -> Option<&'static str> {
+    HEADER_LINES.get(idx).map(|(field, _)| *field)
+}
+
+pub fn header_list(elf_file: &file::ELF64, reconstructed: bool) -> List<'_> {
+    list(
+        "Header (Enter to show the field in the Hex tab)",
+        HEADER_LINES
+            .iter()
+            .map(|(_, line)| ListItem::new(vec![line(elf_file, reconstructed)]))
+            .collect(),
+    )
+}
+
+fn value_spans<'a>(attribute: &'a str, value: String) -> Spans<'a> {
+    Spans::from(vec![Span::raw(attribute), Span::raw(value)])
 }
 
 fn header_attribute_spans<'a, T>(
@@ -135,6 +190,41 @@ fn elf_machine_string<'a>(machine: header::Machine) -> &'a str {
         _ => "unknown",
     }
 }
+fn shnum_string(elf_file: &file::ELF64, reconstructed: bool) -> String {
+    let shnum = elf_file.ehdr.e_shnum;
+    if reconstructed {
+        format!(
+            "{} (dynamic sections reconstructed from program headers)",
+            shnum
+        )
+    } else if shnum == 0 && !elf_file.sections.is_empty() {
+        format!(
+            "{} ({} in sh_size of section 0)",
+            shnum,
+            elf_file.sections.len()
+        )
+    } else {
+        format!("{}", shnum)
+    }
+}
+fn shstrndx_string(elf_file: &file::ELF64, reconstructed: bool) -> String {
+    let shstrndx = elf_file.ehdr.e_shstrndx;
+    if reconstructed || elf_file.sections.is_empty() {
+        return format!("{}", shstrndx);
+    }
+
+    // 拡張形式では, 実際のインデックスは先頭エントリの sh_link に入っている
+    let (idx, via) = if shstrndx == section::SHN_XINDEX {
+        let idx = elf_file.sections[0].header.sh_link as usize;
+        (idx, format!("SHN_XINDEX, sh_link of section 0: {} ", idx))
+    } else {
+        (usize::from(shstrndx), String::new())
+    };
+    match elf_file.sections.get(idx) {
+        Some(sct) => format!("{} ({}{})", shstrndx, via, sct.name),
+        None => format!("{} ({}out of range)", shstrndx, via),
+    }
+}
 fn elf_entry_string(elf_file: &file::ELF64) -> String {
     let entry_point = format!("0x{:x}", elf_file.ehdr.e_entry);
     let symbol_table = elf_file.first_section_by(|sct| sct.name == ".symtab");
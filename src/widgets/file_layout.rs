@@ -0,0 +1,402 @@
+use crate::elf_util::file_layout::{FileLayout, FileRegion, FileRegionKind, Part};
+use crate::widgets::list;
+use elf_utilities::file;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+const PF_R: u32 = 0x4;
+
+/// 行の名前を置く幅
+const LABEL_WIDTH: usize = 24;
+
+/// width は枠を含む一覧の幅. 残りを棒グラフに使う.
+pub fn file_layout_list<'a>(elf_file: &file::ELF64, layout: &FileLayout, width: u16) -> List<'a> {
+    let cells = (width as usize).saturating_sub(LABEL_WIDTH + 3).max(1);
+    let span = scale_end(layout).max(1);
+    let items = layout
+        .regions
+        .iter()
+        .zip(file_layout_names(elf_file, layout))
+        .map(|(region, name)| {
+            let label = Span::raw(format!("{:<width$} ", name, width = LABEL_WIDTH));
+            let label = match region.kind {
+                FileRegionKind::Overlap(_, _) => {
+                    Span::styled(label.content, Style::default().fg(Color::LightRed))
+                }
+                _ => label,
+            };
+            let mut spans = vec![label];
+            spans.append(&mut bar_spans(elf_file, layout, region, cells));
+            ListItem::new(vec![Spans::from(spans)])
+        })
+        .collect();
+
+    list(
+        format!(
+            "File 0x0..0x{:x} (0x{:x} bytes per cell, {} gaps, {} overlaps)",
+            layout.file_size,
+            span.div_ceil(cells as u64),
+            layout.gap_count(),
+            layout.overlap_count()
+        ),
+        items,
+    )
+}
+
+pub fn file_layout_names(elf_file: &file::ELF64, layout: &FileLayout) -> Vec<String> {
+    layout
+        .regions
+        .iter()
+        .map(|region| match region.kind {
+            FileRegionKind::Part(Part::Section(idx)) => {
+                format!("  {}", part_name(elf_file, Part::Section(idx)))
+            }
+            FileRegionKind::Part(part) => part_name(elf_file, part),
+            FileRegionKind::Segment(idx) => {
+                let flags = elf_file.segments[idx].header.p_flags;
+                format!("LOAD[{}] {}", idx, flags_string(flags))
+            }
+            FileRegionKind::Gap => "gap".to_string(),
+            FileRegionKind::Overlap(_, _) => "  !overlap".to_string(),
+        })
+        .collect()
+}
+
+pub fn file_layout_information<'a>(
+    elf_file: &file::ELF64,
+    layout: &FileLayout,
+    idx: usize,
+) -> Paragraph<'a> {
+    let region = &layout.regions[idx];
+    let mut spans = vec![Spans::from(vec![
+        Span::raw("Range: "),
+        Span::raw(format!(
+            "0x{:x}..0x{:x} (0x{:x} bytes)",
+            region.start,
+            region.end,
+            region.size()
+        )),
+    ])];
+    if layout.file_size < region.end {
+        spans.push(Spans::from(vec![Span::styled(
+            format!(
+                "extends 0x{:x} bytes past the end of the file (0x{:x})",
+                region.end - layout.file_size.max(region.start),
+                layout.file_size
+            ),
+            Style::default().fg(Color::LightRed),
+        )]));
+    }
+
+    let title = match region.kind {
+        FileRegionKind::Part(part) => {
+            part_spans(elf_file, layout, region, part, &mut spans);
+            "Contents"
+        }
+        FileRegionKind::Segment(seg_idx) => {
+            segment_spans(elf_file, layout, region, seg_idx, &mut spans);
+            "Segment"
+        }
+        FileRegionKind::Gap => {
+            gap_spans(elf_file, layout, region, &mut spans);
+            "Gap"
+        }
+        FileRegionKind::Overlap(first, second) => {
+            spans.push(Spans::from(vec![
+                Span::raw("Between: "),
+                Span::styled(
+                    format!(
+                        "{} and {}",
+                        part_name(elf_file, first),
+                        part_name(elf_file, second)
+                    ),
+                    Style::default().fg(Color::LightRed),
+                ),
+            ]));
+            spans.push(Spans::from(vec![Span::styled(
+                "the same bytes are read as both; patching one changes the other",
+                Style::default().fg(Color::DarkGray),
+            )]));
+            spans.push(Spans::from(vec![Span::raw("")]));
+            spans.push(Spans::from(vec![Span::styled(
+                format!("Enter: go to {}", part_name(elf_file, second)),
+                Style::default().fg(Color::DarkGray),
+            )]));
+            "Overlap"
+        }
+    };
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn part_spans(
+    elf_file: &file::ELF64,
+    layout: &FileLayout,
+    region: &FileRegion,
+    part: Part,
+    spans: &mut Vec<Spans>,
+) {
+    let ehdr = &elf_file.ehdr;
+    let (entries, jump) = match part {
+        Part::ElfHeader => (format!("e_ehsize 0x{:x}", ehdr.e_ehsize), "the Header tab"),
+        Part::ProgramHeaders => (
+            format!(
+                "{} entries of 0x{:x} bytes (e_phoff 0x{:x})",
+                ehdr.e_phnum, ehdr.e_phentsize, ehdr.e_phoff
+            ),
+            "the Segments tab",
+        ),
+        Part::SectionHeaders => (
+            format!(
+                "{} entries of 0x{:x} bytes (e_shoff 0x{:x})",
+                elf_file.sections.len(),
+                ehdr.e_shentsize,
+                ehdr.e_shoff
+            ),
+            "the Sections tab",
+        ),
+        Part::Section(sct_idx) => {
+            let shdr = &elf_file.sections[sct_idx].header;
+            let align = shdr.sh_addralign.max(1);
+            spans.push(Spans::from(vec![
+                Span::raw("Alignment: "),
+                if shdr.sh_offset.is_multiple_of(align) {
+                    Span::raw(format!("0x{:x}", align))
+                } else {
+                    Span::styled(
+                        format!("0x{:x}, but sh_offset is not aligned", align),
+                        Style::default().fg(Color::Yellow),
+                    )
+                },
+            ]));
+            (
+                format!("[{}] {}", sct_idx, elf_file.sections[sct_idx].name),
+                "the Sections tab",
+            )
+        }
+    };
+    spans.insert(
+        1,
+        Spans::from(vec![
+            Span::raw(format!("{}: ", part_kind(part))),
+            Span::raw(entries),
+        ]),
+    );
+
+    spans.push(Spans::from(vec![
+        Span::raw("Loaded By: "),
+        Span::raw(match layout.segment_of(region.start) {
+            Some(FileRegion {
+                kind: FileRegionKind::Segment(seg_idx),
+                start,
+                ..
+            }) => format!(
+                "LOAD[{}] {} +0x{:x}",
+                seg_idx,
+                flags_string(elf_file.segments[*seg_idx].header.p_flags),
+                region.start - start
+            ),
+            _ => "none".to_string(),
+        }),
+    ]));
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        format!("Enter: go to {}", jump),
+        Style::default().fg(Color::DarkGray),
+    )]));
+}
+
+fn segment_spans(
+    elf_file: &file::ELF64,
+    layout: &FileLayout,
+    region: &FileRegion,
+    seg_idx: usize,
+    spans: &mut Vec<Spans>,
+) {
+    let phdr = &elf_file.segments[seg_idx].header;
+    spans.push(Spans::from(vec![
+        Span::raw("Virtual Address: "),
+        Span::raw(format!("0x{:x} (memsz 0x{:x})", phdr.p_vaddr, phdr.p_memsz)),
+    ]));
+    spans.push(Spans::from(vec![
+        Span::raw("Permissions: "),
+        Span::raw(flags_string(phdr.p_flags)),
+    ]));
+
+    let parts = layout.parts_in(region.start, region.end);
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Contents ({}):",
+        parts.len()
+    ))]));
+    for part in parts {
+        if let FileRegionKind::Part(part_kind) = part.kind {
+            spans.push(Spans::from(vec![Span::raw(format!(
+                "  0x{:x}..0x{:x} {}",
+                part.start,
+                part.end,
+                part_name(elf_file, part_kind)
+            ))]));
+        }
+    }
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        "Enter: go to the Segments tab",
+        Style::default().fg(Color::DarkGray),
+    )]));
+}
+
+fn gap_spans(
+    elf_file: &file::ELF64,
+    layout: &FileLayout,
+    region: &FileRegion,
+    spans: &mut Vec<Spans>,
+) {
+    spans.push(Spans::from(vec![
+        Span::raw("Non-zero Bytes: "),
+        if region.nonzero_bytes == 0 {
+            Span::raw("none")
+        } else {
+            Span::styled(
+                region.nonzero_bytes.to_string(),
+                Style::default().fg(Color::Yellow),
+            )
+        },
+    ]));
+
+    // 次のセクションの整列に合わせるための詰め物かどうか
+    let padding = match layout.part_at(region.end).map(|next| next.kind) {
+        Some(FileRegionKind::Part(Part::Section(sct_idx))) => {
+            let align = elf_file.sections[sct_idx].header.sh_addralign.max(1);
+            (region.end.is_multiple_of(align) && region.size() < align).then(|| {
+                format!(
+                    "alignment padding before {} (sh_addralign 0x{:x})",
+                    elf_file.sections[sct_idx].name, align
+                )
+            })
+        }
+        _ => None,
+    };
+    let note = match padding {
+        Some(padding) => padding,
+        None if region.end == layout.file_size => {
+            "trailing bytes after everything the headers describe".to_string()
+        }
+        None => "not described by any header (free space, or data hidden from tools)".to_string(),
+    };
+    spans.push(Spans::from(vec![Span::styled(
+        note,
+        Style::default().fg(Color::DarkGray),
+    )]));
+}
+
+/// 行の範囲を, ファイル全体に対する位置に置いた棒
+fn bar_spans<'a>(
+    elf_file: &file::ELF64,
+    layout: &FileLayout,
+    region: &FileRegion,
+    cells: usize,
+) -> Vec<Span<'a>> {
+    let end_of_scale = scale_end(layout);
+    let span = u128::from(end_of_scale.max(1));
+    let column = |offset: u64, round_up: bool| {
+        let offset = u128::from(offset.min(end_of_scale)) * cells as u128;
+        let column = if round_up {
+            offset.div_ceil(span)
+        } else {
+            offset / span
+        };
+        column as usize
+    };
+
+    let start = column(region.start, false).min(cells - 1);
+    let end = column(region.end, true).clamp(start + 1, cells);
+    let (fill, color) = match region.kind {
+        FileRegionKind::Part(Part::Section(sct_idx)) => (
+            '█',
+            section_color(elf_file.sections[sct_idx].header.sh_flags),
+        ),
+        FileRegionKind::Part(_) => ('█', Color::Magenta),
+        FileRegionKind::Segment(seg_idx) => (
+            '▒',
+            segment_color(elf_file.segments[seg_idx].header.p_flags),
+        ),
+        FileRegionKind::Gap if region.nonzero_bytes != 0 => ('·', Color::Yellow),
+        FileRegionKind::Gap => ('·', Color::DarkGray),
+        FileRegionKind::Overlap(_, _) => ('×', Color::LightRed),
+    };
+
+    vec![
+        Span::raw(" ".repeat(start)),
+        Span::styled(
+            fill.to_string().repeat(end - start),
+            Style::default().fg(color),
+        ),
+    ]
+}
+
+/// ヘッダがファイルの外を指していても棒に収まるようにする
+fn scale_end(layout: &FileLayout) -> u64 {
+    layout
+        .regions
+        .iter()
+        .map(|region| region.end)
+        .fold(layout.file_size, u64::max)
+}
+
+fn part_name(elf_file: &file::ELF64, part: Part) -> String {
+    match part {
+        Part::ElfHeader => "ELF header".to_string(),
+        Part::ProgramHeaders => "program headers".to_string(),
+        Part::Section(idx) => match elf_file.sections[idx].name.as_str() {
+            "" => format!("[{}]", idx),
+            name => name.to_string(),
+        },
+        Part::SectionHeaders => "section headers".to_string(),
+    }
+}
+
+fn part_kind<'a>(part: Part) -> &'a str {
+    match part {
+        Part::ElfHeader => "ELF Header",
+        Part::ProgramHeaders => "Program Header Table",
+        Part::Section(_) => "Section",
+        Part::SectionHeaders => "Section Header Table",
+    }
+}
+
+fn flags_string(flags: u32) -> String {
+    [(PF_R, 'R'), (PF_W, 'W'), (PF_X, 'X')]
+        .iter()
+        .map(|(flag, c)| if flags & flag != 0 { *c } else { '-' })
+        .collect()
+}
+
+/// 実行可能は赤, 書き込み可能は緑, 読み込みのみは青, 読み込まれないものは白
+fn section_color(flags: u64) -> Color {
+    if flags & SHF_ALLOC == 0 {
+        Color::White
+    } else if flags & SHF_EXECINSTR != 0 {
+        Color::Red
+    } else if flags & SHF_WRITE != 0 {
+        Color::Green
+    } else {
+        Color::Blue
+    }
+}
+
+fn segment_color(flags: u32) -> Color {
+    if flags & PF_X != 0 {
+        Color::Red
+    } else if flags & PF_W != 0 {
+        Color::Green
+    } else {
+        Color::Blue
+    }
+}
@@ -0,0 +1,236 @@
+use crate::elf_util::hash_table::{HashTable, Kind};
+use crate::widgets::list;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+
+/// ヒストグラムの棒の最大長
+const HISTOGRAM_WIDTH: usize = 40;
+
+pub fn hash_table_list(tables: &[HashTable]) -> List<'_> {
+    list(
+        "Hash Tables",
+        hash_table_names(tables)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn hash_table_names(tables: &[HashTable]) -> Vec<String> {
+    tables
+        .iter()
+        .map(|table| table.section_name.clone())
+        .collect()
+}
+
+pub fn hash_table_information(table: &HashTable) -> Paragraph<'_> {
+    let chain_lengths = table.chain_lengths();
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Kind: "),
+            Span::raw(hash_kind_string(table.kind)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Related Symbol Table(from sh_link): "),
+            Span::raw(table.symbol_table_name.clone()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Buckets: "),
+            Span::raw(format!("{}", table.buckets.len())),
+        ]),
+        Spans::from(vec![
+            Span::raw("Symbols: "),
+            Span::raw(format!("{}", table.symbols.len())),
+        ]),
+    ];
+
+    if table.kind == Kind::Gnu {
+        spans.append(&mut vec![
+            Spans::from(vec![
+                Span::raw("First Hashed Symbol(symoffset): "),
+                Span::raw(format!("{}", table.symoffset)),
+            ]),
+            Spans::from(vec![
+                Span::raw("Bloom Filter: "),
+                Span::raw(format!(
+                    "{} words, shift {}, {:.1}% bits set",
+                    table.bloom.len(),
+                    table.bloom_shift,
+                    table.bloom_fill_ratio() * 100.0
+                )),
+            ]),
+        ]);
+    }
+
+    spans.append(&mut vec![
+        Spans::from(vec![
+            Span::raw("Empty Buckets: "),
+            Span::raw(format!(
+                "{}",
+                chain_lengths.iter().filter(|len| **len == 0).count()
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Average Chain Length(non-empty buckets): "),
+            Span::raw(format!("{:.2}", table.average_chain_length())),
+        ]),
+        Spans::from(vec![
+            Span::raw("Longest Chain: "),
+            Span::raw(format!(
+                "{}",
+                chain_lengths.iter().max().copied().unwrap_or(0)
+            )),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw("Bucket Occupancy (chain length: buckets)")]),
+    ]);
+    spans.append(&mut histogram_spans(table));
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.append(&mut verification_spans(table));
+
+    Paragraph::new(spans)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(table.section_name.clone()),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+/// query の探索過程を表示する. editing の間は入力中である旨を示す.
+pub fn lookup_information<'a>(table: &'a HashTable, query: &str, editing: bool) -> Paragraph<'a> {
+    let prompt = if editing {
+        Span::styled(
+            format!("{}_", query),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::raw(query.to_string())
+    };
+    let mut spans = vec![Spans::from(vec![Span::raw("Name: "), prompt])];
+
+    if query.is_empty() {
+        spans.push(Spans::from(vec![Span::raw(
+            "press '/' to type a symbol name, Enter to finish",
+        )]));
+    } else {
+        let lookup = table.lookup(query);
+        spans.append(&mut vec![
+            Spans::from(vec![
+                Span::raw("Hash: "),
+                Span::raw(format!("0x{:08x}", lookup.hash)),
+            ]),
+            Spans::from(vec![
+                Span::raw("Bucket: "),
+                Span::raw(format!(
+                    "{} (0x{:x} % {})",
+                    lookup.bucket,
+                    lookup.hash,
+                    table.buckets.len()
+                )),
+            ]),
+        ]);
+
+        if let Some(passed) = lookup.bloom_passed {
+            spans.push(Spans::from(vec![
+                Span::raw("Bloom Filter: "),
+                if passed {
+                    Span::raw("maybe present")
+                } else {
+                    Span::styled("rejected", Style::default().fg(Color::Yellow))
+                },
+            ]));
+        }
+
+        spans.push(Spans::from(vec![Span::raw("Probe Sequence:")]));
+        spans.extend(lookup.probes.iter().map(|probe| {
+            let name = table
+                .symbols
+                .get(probe.index)
+                .map(|sym| sym.name.clone())
+                .unwrap_or_default();
+            let (mark, color) = if probe.name_matched {
+                ("match", Color::Green)
+            } else if probe.hash_matched && table.kind == Kind::Gnu {
+                ("hash collision", Color::Yellow)
+            } else {
+                ("skip", Color::White)
+            };
+            Spans::from(vec![
+                Span::raw(format!("  [{}] {} ", probe.index, name)),
+                Span::styled(format!("({})", mark), Style::default().fg(color)),
+            ])
+        }));
+
+        spans.push(Spans::from(vec![
+            Span::raw("Result: "),
+            match lookup.found {
+                Some(idx) => Span::styled(
+                    format!("found at symbol index {}", idx),
+                    Style::default().fg(Color::Green),
+                ),
+                None => Span::styled("not found", Style::default().fg(Color::Red)),
+            },
+        ]));
+    }
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title("Lookup"))
+}
+
+fn histogram_spans(table: &HashTable) -> Vec<Spans<'_>> {
+    let histogram = table.histogram();
+    let max = histogram.values().max().copied().unwrap_or(0);
+
+    histogram
+        .iter()
+        .map(|(len, count)| {
+            let width = if max == 0 {
+                0
+            } else {
+                // 1つでもあれば棒を見えるようにする
+                (count * HISTOGRAM_WIDTH).div_ceil(max)
+            };
+            Spans::from(vec![
+                Span::raw(format!("{:>4}: {:>6} ", len, count)),
+                Span::styled("#".repeat(width), Style::default().fg(Color::Cyan)),
+            ])
+        })
+        .collect()
+}
+
+fn verification_spans(table: &HashTable) -> Vec<Spans<'_>> {
+    let exported = table.symbols.iter().filter(|sym| sym.exported).count();
+
+    if table.unreachable.is_empty() {
+        return vec![Spans::from(vec![Span::styled(
+            format!("all {} exported symbols are reachable", exported),
+            Style::default().fg(Color::Green),
+        )])];
+    }
+
+    let mut spans = vec![Spans::from(vec![Span::styled(
+        format!(
+            "{} of {} exported symbols are unreachable:",
+            table.unreachable.len(),
+            exported
+        ),
+        Style::default().fg(Color::Red),
+    )])];
+    spans.extend(table.unreachable.iter().map(|idx| {
+        Spans::from(vec![Span::raw(format!(
+            "  [{}] {}",
+            idx, table.symbols[*idx].name
+        ))])
+    }));
+    spans
+}
+
+fn hash_kind_string<'a>(kind: Kind) -> &'a str {
+    match kind {
+        Kind::SysV => "SysV (DT_HASH)",
+        Kind::Gnu => "GNU (DT_GNU_HASH)",
+    }
+}
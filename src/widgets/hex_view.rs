@@ -0,0 +1,205 @@
+use crate::elf_util::file_fields::{Field, FieldKind, FileFields};
+use elf_utilities::{file, section::Contents64};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+pub const BYTES_PER_ROW: usize = 16;
+
+/// top_row 行目から rows 行分を表示する. カーソルのバイトは反転, 同じフィールドのバイトには下線を引く.
+pub fn hex_view<'a>(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    fields: &FileFields,
+    cursor: usize,
+    top_row: usize,
+    rows: usize,
+) -> Paragraph<'a> {
+    let cursor_field = fields.field_at(cursor as u64);
+    let offset_width = format!("{:x}", bytes.len()).len().max(8);
+
+    let lines = (top_row..)
+        .map(|row| row * BYTES_PER_ROW)
+        .take_while(|start| *start < bytes.len())
+        .take(rows)
+        .map(|start| {
+            let end = (start + BYTES_PER_ROW).min(bytes.len());
+            let mut hex = vec![Span::raw(format!(
+                "{:0width$x}  ",
+                start,
+                width = offset_width
+            ))];
+            let mut ascii = vec![Span::raw(" |")];
+            for (offset, byte) in (start..end).zip(bytes[start..end].iter().copied()) {
+                let style = byte_style(elf_file, fields, offset, cursor, cursor_field);
+                hex.push(Span::styled(format!("{:02x}", byte), style));
+                hex.push(Span::raw(if offset % BYTES_PER_ROW == 7 {
+                    "  "
+                } else {
+                    " "
+                }));
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                ascii.push(Span::styled(c.to_string(), style));
+            }
+            // 最後の行が短くても ASCII の列を揃える
+            let missing = start + BYTES_PER_ROW - end;
+            if missing != 0 {
+                hex.push(Span::raw(
+                    " ".repeat(missing * 3 + usize::from(end - start < 8)),
+                ));
+            }
+            ascii.push(Span::raw("|"));
+            hex.append(&mut ascii);
+            Spans::from(hex)
+        })
+        .collect::<Vec<Spans>>();
+
+    Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+        "Hex 0x{:x}/0x{:x} (↑↓ row, h/l byte, PgUp/PgDn page)",
+        cursor,
+        bytes.len()
+    )))
+}
+
+/// カーソル位置のフィールド (無ければセクション) の名前と値
+pub fn hex_status<'a>(
+    elf_file: &file::ELF64,
+    bytes: &[u8],
+    fields: &FileFields,
+    cursor: usize,
+) -> Paragraph<'a> {
+    let offset = cursor as u64;
+    let mut spans = vec![Span::raw(format!("0x{:x}  ", offset))];
+    match fields.field_at(offset) {
+        Some(field) => {
+            spans.push(Span::styled(
+                field.name(elf_file),
+                Style::default()
+                    .fg(field_color(field.kind))
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(format!(
+                " (+{} of {} bytes) = {}",
+                offset - field.start,
+                field.size,
+                field_value(bytes, &field)
+            )));
+            if let Some(name) = symbol_name(elf_file, &field) {
+                spans.push(Span::raw(format!(" <{}>", name)));
+            }
+        }
+        None => match fields.section_at(offset) {
+            Some((sct_idx, delta)) => {
+                let flags = elf_file.sections[sct_idx].header.sh_flags;
+                spans.push(Span::styled(
+                    format!(
+                        "[{}] {}+0x{:x}",
+                        sct_idx, elf_file.sections[sct_idx].name, delta
+                    ),
+                    Style::default().fg(section_color(flags)),
+                ));
+            }
+            None => spans.push(Span::styled(
+                "not described by any header",
+                Style::default().fg(Color::DarkGray),
+            )),
+        },
+    }
+
+    Paragraph::new(vec![Spans::from(spans)])
+        .block(Block::default().borders(Borders::ALL).title("Field"))
+}
+
+fn byte_style(
+    elf_file: &file::ELF64,
+    fields: &FileFields,
+    offset: usize,
+    cursor: usize,
+    cursor_field: Option<Field>,
+) -> Style {
+    let field = fields.field_at(offset as u64);
+    let mut style = match field {
+        // 隣り合うフィールドを見分けられるよう, 交互に太字にする
+        Some(field) if field.index % 2 == 1 => Style::default()
+            .fg(field_color(field.kind))
+            .add_modifier(Modifier::BOLD),
+        Some(field) => Style::default().fg(field_color(field.kind)),
+        None => match fields.section_at(offset as u64) {
+            Some((sct_idx, _)) => {
+                Style::default().fg(section_color(elf_file.sections[sct_idx].header.sh_flags))
+            }
+            None => Style::default().fg(Color::DarkGray),
+        },
+    };
+    if field.is_some() && field == cursor_field {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if offset == cursor {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+}
+
+/// リトルエンディアンの整数として読む. e_ident のようなバイト列はそのまま並べる
+fn field_value(bytes: &[u8], field: &Field) -> String {
+    let raw = match bytes.get(field.start as usize..field.end() as usize) {
+        Some(raw) => raw,
+        None => return "(past the end of the file)".to_string(),
+    };
+    if field.size > 8 || (field.kind == FieldKind::Ident && field.size != 1) {
+        return raw
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+    let value = raw
+        .iter()
+        .rev()
+        .fold(0u64, |value, b| (value << 8) | u64::from(*b));
+    format!("0x{:x} ({})", value, value)
+}
+
+fn symbol_name(elf_file: &file::ELF64, field: &Field) -> Option<String> {
+    match field.kind {
+        FieldKind::Symbol(sct_idx, idx) => match &elf_file.sections[sct_idx].contents {
+            Contents64::Symbols(symbols) => symbols
+                .get(idx)
+                .map(|sym| sym.symbol_name.clone())
+                .filter(|name| !name.is_empty()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn field_color(kind: FieldKind) -> Color {
+    match kind {
+        FieldKind::Ident => Color::LightMagenta,
+        FieldKind::ElfHeader => Color::Magenta,
+        FieldKind::ProgramHeader(_) => Color::Cyan,
+        FieldKind::SectionHeader(_) => Color::LightCyan,
+        FieldKind::Symbol(_, _) => Color::Yellow,
+    }
+}
+
+/// 実行可能は赤, 書き込み可能は緑, 読み込みのみは青, 読み込まれないものは白
+fn section_color(flags: u64) -> Color {
+    if flags & SHF_ALLOC == 0 {
+        Color::White
+    } else if flags & SHF_EXECINSTR != 0 {
+        Color::Red
+    } else if flags & SHF_WRITE != 0 {
+        Color::Green
+    } else {
+        Color::Blue
+    }
+}
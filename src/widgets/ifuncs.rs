@@ -0,0 +1,132 @@
+use crate::elf_util::address;
+use crate::elf_util::ifunc::{Context, Ifunc};
+use crate::elf_util::relocations;
+use crate::widgets::list;
+use elf_utilities::file;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+
+pub fn ifunc_list<'a>(ifuncs: &[Ifunc]) -> List<'a> {
+    list(
+        format!("IFUNCs ({})", ifuncs.len()),
+        ifunc_names(ifuncs)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn ifunc_names(ifuncs: &[Ifunc]) -> Vec<String> {
+    ifuncs
+        .iter()
+        .map(|ifunc| {
+            let name = format!("0x{:x} {}", ifunc.resolver, ifunc.display_name());
+            match ifunc.candidates.len() {
+                0 => name,
+                n => format!("{} ({} candidates)", name, n),
+            }
+        })
+        .collect()
+}
+
+pub fn ifunc_information<'a>(
+    elf_file: &file::ELF64,
+    ifunc: &Ifunc,
+    contexts: &[Context],
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Name: "),
+            Span::raw(if ifunc.names.is_empty() {
+                "- (no STT_GNU_IFUNC symbol)".to_string()
+            } else {
+                ifunc.names.join(", ")
+            }),
+        ]),
+        Spans::from(vec![
+            Span::raw("Resolver: "),
+            Span::raw(match &ifunc.resolver_name {
+                Some(name) => format!("0x{:x} <{}>", ifunc.resolver, name),
+                None => format!("0x{:x}", ifunc.resolver),
+            }),
+        ]),
+    ];
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "IRELATIVE Relocations ({}):",
+        ifunc.irelatives.len()
+    ))]));
+    if ifunc.irelatives.is_empty() {
+        spans.push(Spans::from(vec![Span::styled(
+            "  none, resolved when another object binds to the exported symbol",
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+    for reloc in ifunc.irelatives.iter() {
+        let slot = match address::lookup(elf_file, None, reloc.r_offset).section {
+            Some((idx, delta)) => format!(
+                "0x{:x} ({}+0x{:x})",
+                reloc.r_offset, elf_file.sections[idx].name, delta
+            ),
+            None => format!("0x{:x}", reloc.r_offset),
+        };
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "  {} <- {} ({}[{}])",
+            slot,
+            relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+            elf_file.sections[reloc.section].name,
+            reloc.index
+        ))]));
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Selects Between ({}):",
+        ifunc.candidates.len()
+    ))]));
+    if ifunc.candidates.is_empty() {
+        spans.push(Spans::from(vec![Span::styled(
+            if elf_file.ehdr.e_machine == relocations::EM_X86_64 {
+                "  no function addresses found in the resolver's code"
+            } else {
+                "  unknown, the resolver's code is only decoded on x86-64"
+            },
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+    for (name, addr) in ifunc.candidates.iter() {
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "  0x{:x} {}",
+            addr, name
+        ))]));
+    }
+
+    if !contexts.is_empty() {
+        spans.push(Spans::from(vec![Span::raw("")]));
+    }
+    for context in contexts.iter() {
+        spans.push(Spans::from(vec![Span::styled(
+            context_string(*context),
+            Style::default().fg(Color::Yellow),
+        )]));
+    }
+
+    Paragraph::new(spans)
+        .block(Block::default().borders(Borders::ALL).title("IFUNC"))
+        .wrap(Wrap { trim: false })
+}
+
+fn context_string<'a>(context: Context) -> &'a str {
+    match context {
+        Context::Static => {
+            "statically linked: libc startup applies IRELATIVE before constructors run, \
+             so the resolver must not depend on other relocations or initialised libc state"
+        }
+        Context::NonPie => {
+            "non-PIE executable: a function pointer to an IFUNC is its PLT entry \
+             (canonical PLT), not the function the resolver selects"
+        }
+    }
+}
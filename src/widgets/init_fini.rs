@@ -0,0 +1,147 @@
+use crate::elf_util::dwarf::Dwarf;
+use crate::elf_util::init_fini::{ArrayKind, InitFiniEntry, Target};
+use crate::elf_util::relocations;
+use crate::widgets::list;
+use elf_utilities::file;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn init_fini_list<'a>(elf_file: &'a file::ELF64, entries: &[InitFiniEntry]) -> List<'a> {
+    list(
+        format!("Init/Fini ({}, in execution order)", entries.len()),
+        init_fini_names(elf_file, entries)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn init_fini_names(elf_file: &file::ELF64, entries: &[InitFiniEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(order, entry)| {
+            format!(
+                "{:>3} {} {}[{}] {}",
+                order + 1,
+                if entry.kind.is_startup() {
+                    "startup"
+                } else {
+                    "exit   "
+                },
+                elf_file.sections[entry.section].name,
+                entry.index,
+                target_string(elf_file, &entry.target)
+            )
+        })
+        .collect()
+}
+
+pub fn init_fini_information<'a>(
+    elf_file: &'a file::ELF64,
+    entry: &InitFiniEntry,
+    dwarf: Option<&Dwarf>,
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Kind: "),
+            Span::raw(array_kind_string(entry.kind)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!(
+                "[{}] {}",
+                entry.section, elf_file.sections[entry.section].name
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Index: "),
+            Span::raw(entry.index.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Slot: "),
+            Span::raw(format!("0x{:x}", entry.slot)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Target: "),
+            Span::raw(target_string(elf_file, &entry.target)),
+        ]),
+    ];
+
+    if let (Target::Address(addr, _), Some(dwarf)) = (&entry.target, dwarf) {
+        if let Some(frame) = dwarf.source_frames(*addr).last() {
+            spans.push(Spans::from(vec![
+                Span::raw("Source: "),
+                Span::raw(format!(
+                    "{}:{}",
+                    frame.file.as_deref().unwrap_or("??"),
+                    frame.line
+                )),
+            ]));
+        }
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        order_note(entry.kind),
+        Style::default().fg(Color::DarkGray),
+    )]));
+
+    Paragraph::new(spans).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Init/Fini Entry"),
+    )
+}
+
+pub fn target_string(elf_file: &file::ELF64, target: &Target) -> String {
+    match target {
+        Target::Address(addr, Some((name, 0))) => format!("0x{:x} <{}>", addr, name),
+        Target::Address(addr, Some((name, delta))) => {
+            format!("0x{:x} <{}+0x{:x}>", addr, name, delta)
+        }
+        Target::Address(addr, None) => format!("0x{:x}", addr),
+        Target::SectionOffset(sct_idx, offset, function) => {
+            let location = format!(
+                "{}+0x{:x}",
+                elf_file
+                    .sections
+                    .get(*sct_idx)
+                    .map(|sct| sct.name.as_str())
+                    .unwrap_or("?"),
+                offset
+            );
+            match function {
+                Some(function) => format!("{} <{}>", location, function),
+                None => location,
+            }
+        }
+        Target::External(name) => format!("{} (undefined)", name),
+        Target::Unresolved(value, r_type) => format!(
+            "0x{:x} (unresolved {})",
+            value,
+            relocations::type_string(elf_file.ehdr.e_machine, *r_type)
+        ),
+    }
+}
+
+fn array_kind_string<'a>(kind: ArrayKind) -> &'a str {
+    match kind {
+        ArrayKind::PreInit => "PREINIT_ARRAY",
+        ArrayKind::Init => "INIT_ARRAY",
+        ArrayKind::Fini => "FINI_ARRAY",
+        ArrayKind::Ctors => ".ctors (legacy)",
+        ArrayKind::Dtors => ".dtors (legacy)",
+    }
+}
+
+fn order_note<'a>(kind: ArrayKind) -> &'a str {
+    match kind {
+        ArrayKind::PreInit => "called first at startup, executables only",
+        ArrayKind::Ctors => "called from _init by crtstuff, last entry first",
+        ArrayKind::Init => "called at startup after DT_INIT, first entry first",
+        ArrayKind::Fini => "called at exit before DT_FINI, last entry first",
+        ArrayKind::Dtors => "called from _fini by crtstuff, first entry first",
+    }
+}
@@ -0,0 +1,94 @@
+use crate::elf_util::ld_cache::{Format, LdCache};
+use crate::widgets::list;
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn cache_entry_list(ld_cache: &LdCache) -> List<'_> {
+    list(
+        "ld.so.cache",
+        cache_entry_names(ld_cache)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn cache_entry_names(ld_cache: &LdCache) -> Vec<String> {
+    ld_cache
+        .entries
+        .iter()
+        .map(|entry| match &entry.hwcaps_subdir {
+            Some(subdir) => format!("{} ({})", entry.name, subdir),
+            None => entry.name.clone(),
+        })
+        .collect()
+}
+
+pub fn cache_entry_information(ld_cache: &LdCache, entry_idx: usize) -> Paragraph<'_> {
+    let entry = &ld_cache.entries[entry_idx];
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Cache: "),
+            Span::raw(format!(
+                "{} ({})",
+                ld_cache.path,
+                cache_format_string(ld_cache.format)
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Generator: "),
+            Span::raw(
+                ld_cache
+                    .generator
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw("Name: "), Span::raw(entry.name.clone())]),
+        Spans::from(vec![
+            Span::raw("Flags: "),
+            Span::raw(format!(
+                "0x{:x} ({}{}{})",
+                entry.flags,
+                entry.type_string(),
+                if entry.arch_string().is_empty() {
+                    ""
+                } else {
+                    ", "
+                },
+                entry.arch_string()
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Hwcap: "),
+            Span::raw(format!("0x{:x}", entry.hwcap)),
+        ]),
+    ];
+
+    if let Some(subdir) = &entry.hwcaps_subdir {
+        spans.push(Spans::from(vec![
+            Span::raw("glibc-hwcaps Subdirectory: "),
+            Span::raw(subdir.clone()),
+        ]));
+    }
+
+    spans.append(&mut vec![
+        Spans::from(vec![
+            Span::raw("OS Version: "),
+            Span::raw(format!("0x{:x}", entry.osversion)),
+        ]),
+        Spans::from(vec![Span::raw("Path: "), Span::raw(entry.path.clone())]),
+    ]);
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title("ld.so.cache"))
+}
+
+fn cache_format_string<'a>(format: Format) -> &'a str {
+    match format {
+        Format::Old => "old format (ld.so-1.7.0)",
+        Format::New => "new format (glibc-ld.so.cache1.1)",
+        Format::Compat => "old + new format",
+    }
+}
@@ -0,0 +1,317 @@
+use crate::elf_util::address;
+use crate::elf_util::plt_got::{GotSlot, PltGot, PltStub};
+use crate::elf_util::relocations::{self, Relocation};
+use crate::widgets::list;
+use elf_utilities::{file, section};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+const R_X86_64_GLOB_DAT: u32 = 6;
+const R_X86_64_JUMP_SLOT: u32 = 7;
+
+/// スタブのあとにスロットを並べる
+pub fn plt_got_list<'a>(elf_file: &file::ELF64, plt_got: &PltGot) -> List<'a> {
+    list(
+        format!(
+            "PLT/GOT ({} stubs, {} slots)",
+            plt_got.stubs.len(),
+            plt_got.slots.len()
+        ),
+        plt_got_names(elf_file, plt_got)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn plt_got_names(elf_file: &file::ELF64, plt_got: &PltGot) -> Vec<String> {
+    let stubs = plt_got.stubs.iter().map(|stub| {
+        format!(
+            "{}[{}] 0x{:x} {}",
+            elf_file.sections[stub.section].name,
+            stub.index,
+            stub.address,
+            stub_name(elf_file, stub)
+        )
+    });
+    let slots = plt_got.slots.iter().map(|slot| {
+        let name = format!(
+            "{}[{}] 0x{:x} {}",
+            elf_file.sections[slot.section].name,
+            slot.index,
+            slot.address,
+            slot.symbol.as_deref().unwrap_or("")
+        );
+        match slot.value {
+            Some(value) => format!("{} = 0x{:x}", name.trim_end(), value),
+            None => name,
+        }
+    });
+
+    stubs.chain(slots).collect()
+}
+
+pub fn plt_got_information<'a>(
+    elf_file: &file::ELF64,
+    plt_got: &PltGot,
+    idx: usize,
+    symbol_table: Option<&section::Section64>,
+) -> Paragraph<'a> {
+    let (title, spans) = match plt_got.stubs.get(idx) {
+        Some(stub) => (
+            "PLT Stub",
+            stub_spans(elf_file, plt_got, stub, symbol_table),
+        ),
+        None => (
+            "GOT Slot",
+            slot_spans(
+                elf_file,
+                plt_got,
+                &plt_got.slots[idx - plt_got.stubs.len()],
+                symbol_table,
+            ),
+        ),
+    };
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn stub_spans<'a>(
+    elf_file: &file::ELF64,
+    plt_got: &PltGot,
+    stub: &PltStub,
+    symbol_table: Option<&section::Section64>,
+) -> Vec<Spans<'a>> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!(
+                "[{}] {}",
+                stub.section, elf_file.sections[stub.section].name
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Index: "),
+            Span::raw(stub.index.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Address: "),
+            Span::raw(format!(
+                "0x{:x}..0x{:x}",
+                stub.address,
+                stub.address + stub.size
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Kind: "),
+            Span::raw(stub_kind(elf_file, stub)),
+        ]),
+    ];
+    if let Some(slot) = stub.slot {
+        spans.push(Spans::from(vec![
+            Span::raw("GOT Slot: "),
+            Span::raw(match plt_got.slot(slot) {
+                Some(got) => format!(
+                    "0x{:x} ({}[{}])",
+                    slot, elf_file.sections[got.section].name, got.index
+                ),
+                None => format!("0x{:x}", slot),
+            }),
+        ]));
+    }
+    if let Some(push_index) = stub.push_index {
+        spans.push(Spans::from(vec![
+            Span::raw("Pushed Index: "),
+            Span::raw(push_index.to_string()),
+        ]));
+    }
+    spans.append(&mut relocation_spans(elf_file, stub.reloc.as_ref()));
+    spans.push(Spans::from(vec![
+        Span::raw("Symbol: "),
+        Span::raw(stub.symbol.clone().unwrap_or_else(|| "-".to_string())),
+    ]));
+
+    // 遅延束縛ならスロットの初期値がスタブの後半 (push の位置) を指している
+    let slot = stub
+        .slot
+        .or_else(|| stub.reloc.map(|reloc| reloc.r_offset))
+        .and_then(|slot| plt_got.slot(slot));
+    if let Some(value) = slot.and_then(|slot| slot.value) {
+        spans.push(Spans::from(vec![
+            Span::raw("Slot Initial Value: "),
+            Span::raw(pointee_string(elf_file, symbol_table, value)),
+        ]));
+    }
+
+    spans
+}
+
+fn slot_spans<'a>(
+    elf_file: &file::ELF64,
+    plt_got: &PltGot,
+    slot: &GotSlot,
+    symbol_table: Option<&section::Section64>,
+) -> Vec<Spans<'a>> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!(
+                "[{}] {}",
+                slot.section, elf_file.sections[slot.section].name
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Index: "),
+            Span::raw(slot.index.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Address: "),
+            Span::raw(format!("0x{:x}", slot.address)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Initial Value: "),
+            match slot.value {
+                Some(value) => Span::raw(pointee_string(elf_file, symbol_table, value)),
+                None => Span::styled("not in file", Style::default().fg(Color::DarkGray)),
+            },
+        ]),
+    ];
+    if let Some(reserved) = slot.reserved(elf_file) {
+        spans.push(Spans::from(vec![
+            Span::raw("Reserved: "),
+            Span::raw(reserved),
+        ]));
+    }
+    spans.append(&mut relocation_spans(elf_file, slot.reloc.as_ref()));
+    spans.push(Spans::from(vec![
+        Span::raw("Symbol: "),
+        Span::raw(slot.symbol.clone().unwrap_or_else(|| "-".to_string())),
+    ]));
+
+    let stubs = plt_got.stubs_of(slot);
+    if !stubs.is_empty() {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::raw("Used by:")]));
+        for stub in stubs {
+            spans.push(Spans::from(vec![Span::raw(format!(
+                "  {}[{}] 0x{:x} ({})",
+                elf_file.sections[stub.section].name,
+                stub.index,
+                stub.address,
+                stub_kind(elf_file, stub)
+            ))]));
+        }
+    }
+    if let Some(note) = binding_note(elf_file, slot) {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::styled(
+            note,
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+
+    spans
+}
+
+fn relocation_spans<'a>(elf_file: &file::ELF64, reloc: Option<&Relocation>) -> Vec<Spans<'a>> {
+    let reloc = match reloc {
+        Some(reloc) => reloc,
+        None => {
+            return vec![Spans::from(vec![
+                Span::raw("Relocation: "),
+                Span::styled("none", Style::default().fg(Color::DarkGray)),
+            ])]
+        }
+    };
+
+    let mut text = format!(
+        "{} ({}[{}])",
+        relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+        elf_file.sections[reloc.section].name,
+        reloc.index
+    );
+    if let Some(addend) = reloc.r_addend.filter(|addend| *addend != 0) {
+        text.push_str(&format!(", addend 0x{:x}", addend));
+    }
+    vec![Spans::from(vec![
+        Span::raw("Relocation: "),
+        Span::raw(text),
+    ])]
+}
+
+fn stub_name(elf_file: &file::ELF64, stub: &PltStub) -> String {
+    if stub.is_resolver(elf_file) {
+        return "PLT0".to_string();
+    }
+    match (&stub.symbol, stub.slot) {
+        (Some(symbol), Some(_)) => format!("{}@plt", symbol),
+        (Some(symbol), None) => format!("{} (lazy)", symbol),
+        (None, _) => String::new(),
+    }
+}
+
+fn stub_kind<'a>(elf_file: &file::ELF64, stub: &PltStub) -> &'a str {
+    if stub.is_resolver(elf_file) {
+        return "PLT0, pushes GOT[1] and jumps to GOT[2]";
+    }
+    match (stub.slot, stub.push_index) {
+        (Some(_), Some(_)) => "jumps through its GOT slot, lazy binding stub follows",
+        (Some(_), None) => "jumps through its GOT slot",
+        (None, Some(_)) => "lazy binding stub, pushes the index and jumps to PLT0",
+        (None, None) => "unknown",
+    }
+}
+
+/// "0x1036 (.plt+0x16)" のように, 値が指す場所を添える.
+fn pointee_string(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    value: u64,
+) -> String {
+    if value == 0 {
+        return "0x0".to_string();
+    }
+    let info = address::lookup(elf_file, symbol_table, value);
+    let mut places = Vec::new();
+    if let Some((idx, delta)) = info.section {
+        places.push(format!("{}+0x{:x}", elf_file.sections[idx].name, delta));
+    }
+    match info.symbol {
+        Some((name, 0)) => places.push(format!("<{}>", name)),
+        Some((name, delta)) => places.push(format!("<{}+0x{:x}>", name, delta)),
+        None => {}
+    }
+
+    if places.is_empty() {
+        format!("0x{:x}", value)
+    } else {
+        format!("0x{:x} ({})", value, places.join(" "))
+    }
+}
+
+/// JUMP_SLOT の初期値が PLT を指していれば, 最初の呼び出しで解決される
+fn binding_note<'a>(elf_file: &file::ELF64, slot: &GotSlot) -> Option<&'a str> {
+    let reloc = slot.reloc?;
+    if elf_file.ehdr.e_machine != relocations::EM_X86_64 {
+        return None;
+    }
+    match reloc.r_type {
+        R_X86_64_JUMP_SLOT => {}
+        R_X86_64_GLOB_DAT => return Some("bound by ld.so at load time"),
+        _ => return None,
+    }
+
+    let points_to_plt = slot.value.is_some_and(|value| {
+        elf_file.sections.iter().any(|sct| {
+            sct.name == ".plt"
+                && sct.header.sh_addr <= value
+                && value < sct.header.sh_addr + sct.header.sh_size
+        })
+    });
+    if points_to_plt {
+        Some("lazy: points back into .plt until the first call resolves it (unless BIND_NOW)")
+    } else {
+        Some("bound by ld.so at load time")
+    }
+}
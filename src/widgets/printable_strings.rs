@@ -0,0 +1,198 @@
+use crate::elf_util::printable_strings::{Encoding, PrintableString, Xref, Xrefs};
+use crate::elf_util::relocations;
+use crate::widgets::list;
+use elf_utilities::{file, header};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+
+pub fn printable_string_list(
+    strings: &[PrintableString],
+    min_length: usize,
+    include_unloaded: bool,
+) -> List<'_> {
+    list(
+        format!(
+            "Printable Strings (min {} [+/-], {} [a])",
+            min_length,
+            if include_unloaded { "all" } else { "alloc" }
+        ),
+        printable_string_names(strings)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn printable_string_names(strings: &[PrintableString]) -> Vec<String> {
+    strings
+        .iter()
+        .map(|string| format!("0x{:x} {}", string.addr, escape(&string.value)))
+        .collect()
+}
+
+pub fn printable_string_information<'a>(
+    elf_file: &'a file::ELF64,
+    string: &'a PrintableString,
+    xrefs: &Xrefs,
+) -> Paragraph<'a> {
+    let section_name = elf_file
+        .sections
+        .get(string.section)
+        .map(|sct| sct.name.clone())
+        .unwrap_or_default();
+    let references = xrefs.find(string);
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!("[{}] {}", string.section, section_name)),
+        ]),
+        Spans::from(vec![
+            Span::raw("File Offset: "),
+            Span::raw(match string.offset {
+                Some(offset) => format!("0x{:x}", offset),
+                None => "none (in decompressed contents)".to_string(),
+            }),
+        ]),
+        Spans::from(vec![
+            Span::raw(if is_relocatable(elf_file) || !string.loaded {
+                "Section Offset: "
+            } else {
+                "Virtual Address: "
+            }),
+            Span::raw(format!("0x{:x}", string.addr)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Encoding: "),
+            Span::raw(encoding_string(string.encoding)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Length: "),
+            Span::raw(format!(
+                "{} (characters), {} (bytes)",
+                string.value.chars().count(),
+                string.size
+            )),
+        ]),
+        Spans::from(vec![Span::raw("Value: "), Span::raw(string.value.clone())]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw(format!(
+            "References ({})",
+            references.len()
+        ))]),
+    ];
+
+    if references.is_empty() {
+        spans.push(Spans::from(vec![Span::styled(
+            "  no symbols or relocations point into this string",
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+    spans.extend(references.iter().map(|(delta, xref)| {
+        let delta = if *delta == 0 {
+            String::new()
+        } else {
+            format!(" (+0x{:x})", delta)
+        };
+        Spans::from(vec![Span::raw(format!(
+            "  {}{}",
+            xref_string(elf_file, xref),
+            delta
+        ))])
+    }));
+
+    Paragraph::new(spans)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("String Information"),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+pub fn no_printable_string_information<'a>(
+    min_length: usize,
+    include_unloaded: bool,
+) -> Paragraph<'a> {
+    Paragraph::new(vec![Spans::from(vec![Span::raw(format!(
+        "no strings of {} or more characters, press '-' to lower the minimum length{}",
+        min_length,
+        if include_unloaded {
+            ""
+        } else {
+            " or 'a' to include non-allocated sections"
+        }
+    ))])])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("String Information"),
+    )
+    .wrap(Wrap { trim: false })
+}
+
+fn xref_string(elf_file: &file::ELF64, xref: &Xref) -> String {
+    match xref {
+        Xref::Symbol {
+            symbol_table,
+            index,
+            name,
+        } => format!(
+            "symbol {} ({}[{}])",
+            name,
+            section_name(elf_file, *symbol_table),
+            index
+        ),
+        Xref::Relocation(reloc) => {
+            let target = if is_relocatable(elf_file) {
+                // 再配置可能ファイルの r_offset は sh_info が示すセクション内のオフセット
+                elf_file
+                    .sections
+                    .get(reloc.section)
+                    .map(|sct| sct.header.sh_info as usize)
+            } else {
+                elf_file.sections.iter().position(|sct| {
+                    sct.header.sh_addr != 0
+                        && sct.header.sh_addr <= reloc.r_offset
+                        && reloc.r_offset < sct.header.sh_addr + sct.header.sh_size
+                })
+            };
+            format!(
+                "{} at 0x{:x}{} ({}[{}])",
+                relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+                reloc.r_offset,
+                target
+                    .map(|idx| format!(" in {}", section_name(elf_file, idx)))
+                    .unwrap_or_default(),
+                section_name(elf_file, reloc.section),
+                reloc.index
+            )
+        }
+    }
+}
+
+fn section_name(elf_file: &file::ELF64, idx: usize) -> String {
+    elf_file
+        .sections
+        .get(idx)
+        .map(|sct| sct.name.clone())
+        .unwrap_or_else(|| format!("section {}", idx))
+}
+
+fn is_relocatable(elf_file: &file::ELF64) -> bool {
+    elf_file.ehdr.get_type() == header::Type::Rel
+}
+
+/// リストが崩れないよう, 制御文字はエスケープして表示する.
+fn escape(value: &str) -> String {
+    value.replace('\t', "\\t")
+}
+
+fn encoding_string<'a>(encoding: Encoding) -> &'a str {
+    match encoding {
+        Encoding::Ascii => "ASCII",
+        Encoding::Utf8 => "UTF-8",
+        Encoding::Utf16Le => "UTF-16LE",
+    }
+}
@@ -0,0 +1,110 @@
+use crate::elf_util::address;
+use crate::elf_util::dwarf::Dwarf;
+use crate::elf_util::init_fini::ArrayKind;
+use crate::elf_util::recovered_function::{RecoveredFunction, Source};
+use crate::widgets::list;
+use elf_utilities::file;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn recovered_function_list<'a>(functions: &[RecoveredFunction]) -> List<'a> {
+    list(
+        format!("Recovered Functions ({})", functions.len()),
+        recovered_function_names(functions)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn recovered_function_names(functions: &[RecoveredFunction]) -> Vec<String> {
+    functions
+        .iter()
+        .map(|function| format!("0x{:x} {}", function.address, function.display_name()))
+        .collect()
+}
+
+pub fn recovered_function_information<'a>(
+    elf_file: &file::ELF64,
+    function: &RecoveredFunction,
+    dwarf: Option<&Dwarf>,
+) -> Paragraph<'a> {
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Name: "),
+            Span::raw(function.display_name()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Address: "),
+            Span::raw(format!("0x{:x}", function.address)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Size: "),
+            match function.size {
+                Some(size) => Span::raw(format!(
+                    "0x{:x} (ends at 0x{:x})",
+                    size,
+                    function.address + size
+                )),
+                None => Span::styled("unknown", Style::default().fg(Color::DarkGray)),
+            },
+        ]),
+    ];
+
+    let info = address::lookup(elf_file, None, function.address);
+    if let Some((idx, delta)) = info.section {
+        spans.push(Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!(
+                "[{}] {} + 0x{:x}",
+                idx, elf_file.sections[idx].name, delta
+            )),
+        ]));
+    }
+    if let Some(frame) = dwarf.and_then(|dwarf| dwarf.source_frames(function.address).pop()) {
+        spans.push(Spans::from(vec![
+            Span::raw("Source: "),
+            Span::raw(format!(
+                "{}:{}",
+                frame.file.as_deref().unwrap_or("??"),
+                frame.line
+            )),
+        ]));
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw("Found in:")]));
+    for source in function.sources.iter() {
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "  {}",
+            source_string(*source)
+        ))]));
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        "press Enter to look up this address in the Address tab",
+        Style::default().fg(Color::DarkGray),
+    )]));
+
+    Paragraph::new(spans).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Recovered Function"),
+    )
+}
+
+fn source_string<'a>(source: Source) -> &'a str {
+    match source {
+        Source::Fde => ".eh_frame FDE",
+        Source::DynamicSymbol => ".dynsym export",
+        Source::PltStub => "PLT stub",
+        Source::InitFini(ArrayKind::PreInit) => ".preinit_array",
+        Source::InitFini(ArrayKind::Init) => ".init_array",
+        Source::InitFini(ArrayKind::Fini) => ".fini_array",
+        Source::InitFini(ArrayKind::Ctors) => ".ctors",
+        Source::InitFini(ArrayKind::Dtors) => ".dtors",
+        Source::EntryPoint => "entry point (e_entry)",
+    }
+}
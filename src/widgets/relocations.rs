@@ -0,0 +1,220 @@
+use crate::elf_util::address;
+use crate::elf_util::relocations::{self, Format, Relocation};
+use crate::widgets::list;
+use elf_utilities::{file, header, section};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+const SHF_INFO_LINK: u64 = 0x40;
+const RELA_SIZE: u64 = 0x18;
+const REL_SIZE: u64 = 0x10;
+
+/// addends は再配置先に埋め込まれたものも含めた加数
+pub fn relocation_list<'a>(
+    elf_file: &file::ELF64,
+    relocations: &[Relocation],
+    addends: &[Option<i64>],
+) -> List<'a> {
+    let packed = relocations
+        .iter()
+        .filter(|reloc| reloc.format.is_packed())
+        .count();
+    let title = match packed {
+        0 => format!("Relocations ({})", relocations.len()),
+        n => format!("Relocations ({}, {} unpacked)", relocations.len(), n),
+    };
+    list(
+        title,
+        relocation_names(elf_file, relocations, addends)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn relocation_names(
+    elf_file: &file::ELF64,
+    relocations: &[Relocation],
+    addends: &[Option<i64>],
+) -> Vec<String> {
+    relocations
+        .iter()
+        .zip(addends.iter())
+        .map(|(reloc, addend)| {
+            let name = format!(
+                "{}[{}] 0x{:x} {} {}",
+                elf_file.sections[reloc.section].name,
+                reloc.index,
+                reloc.r_offset,
+                relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+                target_string(relocations::symbol_name(elf_file, reloc), *addend)
+            );
+            name.trim_end().to_string()
+        })
+        .collect()
+}
+
+pub fn relocation_information<'a>(
+    elf_file: &file::ELF64,
+    relocations: &[Relocation],
+    addends: &[Option<i64>],
+    idx: usize,
+    symbol_table: Option<&section::Section64>,
+) -> Paragraph<'a> {
+    let reloc = &relocations[idx];
+    let sct = &elf_file.sections[reloc.section];
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(format!(
+                "[{}] {} ({})",
+                reloc.section,
+                sct.name,
+                format_string(reloc.format)
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Index: "),
+            Span::raw(reloc.index.to_string()),
+        ]),
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(location_string(elf_file, symbol_table, reloc)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Type: "),
+            Span::raw(format!(
+                "{} ({})",
+                relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+                reloc.r_type
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Symbol: "),
+            Span::raw(match relocations::symbol_name(elf_file, reloc) {
+                Some(name) => format!("{} (index {})", name, reloc.r_sym),
+                None => "-".to_string(),
+            }),
+        ]),
+        Spans::from(vec![
+            Span::raw("Addend: "),
+            match (reloc.r_addend, addends[idx]) {
+                (Some(addend), _) => Span::raw(signed_hex(addend)),
+                (None, Some(addend)) => Span::raw(format!("{} (in place)", signed_hex(addend))),
+                (None, None) => Span::styled(
+                    "in place, width depends on the type",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            },
+        ]),
+    ];
+
+    // シンボルを持たない RELATIVE などは加数がそのまま指す先になる
+    if let Some(addend) = addends[idx].filter(|_| reloc.r_sym == 0) {
+        spans.push(Spans::from(vec![
+            Span::raw("Points To: "),
+            Span::raw(pointee_string(elf_file, symbol_table, addend as u64)),
+        ]));
+    }
+
+    if reloc.format.is_packed() {
+        let count = relocations
+            .iter()
+            .filter(|other| other.section == reloc.section)
+            .count() as u64;
+        let unpacked_size = match reloc.format {
+            Format::AndroidRel => REL_SIZE,
+            _ => RELA_SIZE,
+        };
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::styled(
+            format!(
+                "{} relocations packed into 0x{:x} bytes (0x{:x} bytes as {})",
+                count,
+                sct.header.sh_size,
+                count * unpacked_size,
+                if unpacked_size == REL_SIZE {
+                    "SHT_REL"
+                } else {
+                    "SHT_RELA"
+                }
+            ),
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title("Relocation"))
+}
+
+fn format_string<'a>(format: Format) -> &'a str {
+    match format {
+        Format::Rel => "SHT_REL",
+        Format::Rela => "SHT_RELA",
+        Format::Relr => "SHT_RELR, expanded from address/bitmap words",
+        Format::AndroidRel => "SHT_ANDROID_REL, expanded from APS2 groups",
+        Format::AndroidRela => "SHT_ANDROID_RELA, expanded from APS2 groups",
+    }
+}
+
+/// "printf+0x8" のようにシンボルと加数を並べる. シンボルが無ければ加数だけ.
+fn target_string(symbol: Option<String>, addend: Option<i64>) -> String {
+    match (symbol, addend) {
+        (Some(name), Some(addend)) if addend < 0 => format!("{}{}", name, signed_hex(addend)),
+        (Some(name), Some(addend)) if addend > 0 => format!("{}+{}", name, signed_hex(addend)),
+        (Some(name), _) => name,
+        (None, Some(addend)) => signed_hex(addend),
+        (None, None) => String::new(),
+    }
+}
+
+fn signed_hex(value: i64) -> String {
+    if value < 0 {
+        format!("-0x{:x}", value.unsigned_abs())
+    } else {
+        format!("0x{:x}", value)
+    }
+}
+
+/// ET_REL の r_offset は sh_info が指すセクション内のオフセット
+fn location_string(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    reloc: &Relocation,
+) -> String {
+    let sct = &elf_file.sections[reloc.section];
+    if sct.header.sh_flags & SHF_INFO_LINK != 0 && elf_file.ehdr.get_type() == header::Type::Rel {
+        return match elf_file.sections.get(sct.header.sh_info as usize) {
+            Some(target) => format!(
+                "0x{:x} ({}+0x{:x})",
+                reloc.r_offset, target.name, reloc.r_offset
+            ),
+            None => format!("0x{:x}", reloc.r_offset),
+        };
+    }
+    pointee_string(elf_file, symbol_table, reloc.r_offset)
+}
+
+/// "0x3da0 (.init_array+0x0 <__frame_dummy_init_array_entry>)" のように, アドレスの場所を添える.
+fn pointee_string(
+    elf_file: &file::ELF64,
+    symbol_table: Option<&section::Section64>,
+    value: u64,
+) -> String {
+    let info = address::lookup(elf_file, symbol_table, value);
+    let mut places = Vec::new();
+    if let Some((idx, delta)) = info.section {
+        places.push(format!("{}+0x{:x}", elf_file.sections[idx].name, delta));
+    }
+    match info.symbol {
+        Some((name, 0)) => places.push(format!("<{}>", name)),
+        Some((name, delta)) => places.push(format!("<{}+0x{:x}>", name, delta)),
+        None => {}
+    }
+
+    if places.is_empty() {
+        format!("0x{:x}", value)
+    } else {
+        format!("0x{:x} ({})", value, places.join(" "))
+    }
+}
@@ -1,15 +1,19 @@
-use std::collections::HashSet;
-
-use crate::widgets::list;
+use crate::elf_util::compression::{self, Compression, Format};
+use crate::elf_util::{relocations, section_group};
+use crate::widgets::{data_source_title, list};
 use elf_utilities::{
     file,
     section::{self, Contents64},
 };
+use tui::style::{Color, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
-pub fn section_list(elf_file: &file::ELF64) -> List {
-    list("Sections", section_items(&elf_file))
+pub fn section_list(elf_file: &file::ELF64, reconstructed: bool) -> List<'_> {
+    list(
+        data_source_title(reconstructed, "Sections"),
+        section_items(elf_file),
+    )
 }
 
 pub fn section_names(elf_file: &file::ELF64) -> Vec<String> {
@@ -29,23 +33,109 @@ pub fn section_names(elf_file: &file::ELF64) -> Vec<String> {
     names
 }
 
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_MERGE: u64 = 0x10;
+const SHF_STRINGS: u64 = 0x20;
+const SHF_INFO_LINK: u64 = 0x40;
+const SHF_LINK_ORDER: u64 = 0x80;
+const SHF_OS_NONCONFORMING: u64 = 0x100;
+const SHF_GROUP: u64 = 0x200;
+const SHF_TLS: u64 = 0x400;
+const SHF_GNU_RETAIN: u64 = 0x20_0000;
+const SHF_GNU_MBIND: u64 = 0x100_0000;
+const SHF_X86_64_LARGE: u64 = 0x1000_0000;
+const SHF_EXCLUDE: u64 = 0x8000_0000;
+const SHF_MASKOS: u64 = 0x0ff0_0000;
+const SHF_MASKPROC: u64 = 0xf000_0000;
+
+/// readelf と同じ, フラグの文字と名前
+const SECTION_FLAGS: [(u64, char, &str); 14] = [
+    (SHF_WRITE, 'W', "WRITE"),
+    (SHF_ALLOC, 'A', "ALLOC"),
+    (SHF_EXECINSTR, 'X', "EXECINSTR"),
+    (SHF_MERGE, 'M', "MERGE"),
+    (SHF_STRINGS, 'S', "STRINGS"),
+    (SHF_INFO_LINK, 'I', "INFO_LINK"),
+    (SHF_LINK_ORDER, 'L', "LINK_ORDER"),
+    (SHF_OS_NONCONFORMING, 'O', "OS_NONCONFORMING"),
+    (SHF_GROUP, 'G', "GROUP"),
+    (SHF_TLS, 'T', "TLS"),
+    (compression::SHF_COMPRESSED, 'C', "COMPRESSED"),
+    (SHF_GNU_RETAIN, 'R', "GNU_RETAIN"),
+    (SHF_GNU_MBIND, 'D', "GNU_MBIND"),
+    (SHF_EXCLUDE, 'E', "EXCLUDE"),
+];
+
+const FLAG_LEGEND: [&str; 4] = [
+    "  W (write), A (alloc), X (execute), M (merge), S (strings), I (info),",
+    "  L (link order), O (extra OS processing required), G (group), T (TLS),",
+    "  C (compressed), x (unknown), o (OS specific), E (exclude),",
+    "  R (retain), D (mbind), l (large), p (processor specific)",
+];
+
+/// 圧縮されたセクションの詳細に表示する, 展開後の先頭のバイト数
+pub const DECOMPRESSED_PREVIEW_SIZE: usize = 0x100;
+
+/// decompressed は圧縮されたセクションを展開した中身の先頭 (展開できなければ None)
 pub fn section_information<'a>(
     elf_file: &'a file::ELF64,
-    sct: &'a section::Section64,
+    sct_idx: usize,
+    compression: Option<Compression>,
+    decompressed: Option<&[u8]>,
+    reconstructed: bool,
 ) -> Paragraph<'a> {
-    let sct_info = match sct.header.get_type() {
+    let sct = &elf_file.sections[sct_idx];
+    let mut sct_info = match sct.header.get_type() {
         section::Type::Dynamic => dynamic_info(elf_file, sct),
         section::Type::Hash | section::Type::SymTabShNdx => hash_info(elf_file, sct),
         section::Type::SymTab | section::Type::DynSym => symtab_info(elf_file, sct),
-        section::Type::Group => group_info(elf_file, sct),
+        section::Type::Group => group_info(elf_file, sct_idx),
         section::Type::Rel | section::Type::Rela => relocation_info(elf_file, sct),
-        _ => common_section_info(sct),
+        _ => common_section_info(elf_file, sct),
     };
+    if let Some(compression) = compression {
+        sct_info.append(&mut compression_info(compression));
+        sct_info.append(&mut decompressed_info(compression, decompressed));
+    }
+    if let Some(group) = section_group::group_of(elf_file, sct_idx) {
+        sct_info.push(Spans::from(vec![
+            Span::raw("Section Group: "),
+            Span::raw(format!(
+                "[{}] {} [{}]{}",
+                group.section,
+                elf_file.sections[group.section].name,
+                group.signature(elf_file).unwrap_or_default(),
+                if group.is_comdat() { " COMDAT" } else { "" }
+            )),
+        ]));
+    }
 
-    Paragraph::new(sct_info).block(Block::default().borders(Borders::ALL).title("Sections"))
+    sct_info.push(Spans::from(vec![Span::raw("")]));
+    sct_info.extend(
+        std::iter::once("Key to Flags:")
+            .chain(FLAG_LEGEND.iter().copied())
+            .map(|line| {
+                Spans::from(vec![Span::styled(
+                    line,
+                    Style::default().fg(Color::DarkGray),
+                )])
+            }),
+    );
+
+    Paragraph::new(sct_info).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(data_source_title(reconstructed, "Sections")),
+    )
 }
 
-fn common_section_info(sct: &section::Section64) -> Vec<Spans> {
+fn common_section_info<'a>(elf_file: &file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
+    let flags = sct_flags(sct.header.sh_flags, elf_file.ehdr.e_machine);
+    let letters: String = flags.iter().map(|(c, _)| *c).collect();
+    let names: Vec<String> = flags.into_iter().map(|(_, name)| name).collect();
+
     vec![
         Spans::from(vec![Span::raw("Name: "), Span::raw(&sct.name)]),
         section_attribute_spans("Type", sct_type_string, sct.header.get_type()),
@@ -67,7 +157,12 @@ fn common_section_info(sct: &section::Section64) -> Vec<Spans> {
         ]),
         Spans::from(vec![
             Span::raw("Flags: "),
-            Span::raw(sct_flag_string(sct.header.get_flags())),
+            Span::raw(format!(
+                "0x{:x} {} ({})",
+                sct.header.sh_flags,
+                letters,
+                names.join(", ")
+            )),
         ]),
         Spans::from(vec![
             Span::raw("Link: "),
@@ -85,11 +180,9 @@ fn common_section_info(sct: &section::Section64) -> Vec<Spans> {
 }
 
 fn symtab_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
-    let mut base_info = common_section_info(sct);
+    let mut base_info = common_section_info(elf_file, sct);
     let first_sym_name = get_first_globsym_name_from_sh_info(sct);
 
-    let strtab_sct = &elf_file.sections[sct.header.sh_link as usize];
-
     base_info.append(&mut vec![
         Spans::from(vec![
             Span::raw("First Global Symbol(from sh_info): "),
@@ -97,86 +190,185 @@ fn symtab_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Ve
         ]),
         Spans::from(vec![
             Span::raw("Related String Table(from sh_link): "),
-            Span::raw(&strtab_sct.name),
+            Span::raw(linked_section_name(elf_file, sct.header.sh_link)),
         ]),
     ]);
 
     base_info
 }
 fn dynamic_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
-    let mut base_info = common_section_info(sct);
-    let strtab_sct = &elf_file.sections[sct.header.sh_link as usize];
-
+    let mut base_info = common_section_info(elf_file, sct);
     base_info.push(Spans::from(vec![
         Span::raw("Related String Table(from sh_link): "),
-        Span::raw(&strtab_sct.name),
+        Span::raw(linked_section_name(elf_file, sct.header.sh_link)),
     ]));
 
     base_info
 }
 fn hash_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
-    let mut base_info = common_section_info(sct);
-    let symtab_sct = &elf_file.sections[sct.header.sh_link as usize];
-
+    let mut base_info = common_section_info(elf_file, sct);
     base_info.push(Spans::from(vec![
         Span::raw("Related Symbol Table(from sh_link): "),
-        Span::raw(&symtab_sct.name),
+        Span::raw(linked_section_name(elf_file, sct.header.sh_link)),
     ]));
 
     base_info
 }
 fn relocation_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
-    let mut base_info = common_section_info(sct);
-    let symtab_sct = &elf_file.sections[sct.header.sh_link as usize];
-    let reloc_sct = &elf_file.sections[sct.header.sh_info as usize];
-
+    let mut base_info = common_section_info(elf_file, sct);
     base_info.push(Spans::from(vec![
         Span::raw("Related Symbol Table(from sh_link): "),
-        Span::raw(&symtab_sct.name),
+        Span::raw(linked_section_name(elf_file, sct.header.sh_link)),
     ]));
 
-    if sct.header.get_flags().contains(&section::Flag::InfoLink) {
+    if sct.header.sh_flags & SHF_INFO_LINK != 0 {
         base_info.push(Spans::from(vec![
             Span::raw("Relocation Target Section (from sh_info): "),
-            Span::raw(&reloc_sct.name),
+            Span::raw(linked_section_name(elf_file, sct.header.sh_info)),
         ]));
     }
     base_info
 }
-fn group_info<'a>(elf_file: &'a file::ELF64, sct: &'a section::Section64) -> Vec<Spans<'a>> {
-    let mut base_info = common_section_info(sct);
-    let symtab_sct = &elf_file.sections[sct.header.sh_link as usize];
-    if let Contents64::Symbols(symbols) = &symtab_sct.contents {
-        let signature_sym = &symbols[sct.header.sh_info as usize];
-        let signature_sym_name = signature_sym.symbol_name.clone();
+fn group_info(elf_file: &file::ELF64, sct_idx: usize) -> Vec<Spans<'_>> {
+    let sct = &elf_file.sections[sct_idx];
+    let mut base_info = common_section_info(elf_file, sct);
+    let group = match section_group::section_group(elf_file, sct_idx) {
+        Some(group) => group,
+        None => return base_info,
+    };
 
-        base_info.push(Spans::from(vec![
-            Span::raw("Related Symbol Table(from sh_link): "),
-            Span::raw(&symtab_sct.name),
-        ]));
+    base_info.push(Spans::from(vec![
+        Span::raw("Related Symbol Table(from sh_link): "),
+        Span::raw(linked_section_name(elf_file, sct.header.sh_link)),
+    ]));
+    base_info.push(Spans::from(vec![
+        Span::raw("Section Group Signature (from sh_info): "),
+        Span::raw(group.signature(elf_file).unwrap_or_default()),
+    ]));
+    base_info.push(Spans::from(vec![
+        Span::raw("Group Flags: "),
+        Span::raw(if group.is_comdat() {
+            format!("0x{:x} (GRP_COMDAT)", group.flags)
+        } else {
+            format!("0x{:x}", group.flags)
+        }),
+    ]));
+    base_info.push(Spans::from(vec![Span::raw(format!(
+        "Members ({}):",
+        group.members.len()
+    ))]));
+    base_info.extend(group.members.iter().map(|member| {
+        Spans::from(vec![Span::raw(format!(
+            "  [{}] {}",
+            member,
+            elf_file
+                .sections
+                .get(*member)
+                .map(|sct| sct.name.as_str())
+                .unwrap_or("(invalid section index)")
+        ))])
+    }));
 
-        if sct.header.get_flags().contains(&section::Flag::InfoLink) {
-            base_info.push(Spans::from(vec![
-                Span::raw("Section Group Signature (from sh_info): "),
-                Span::raw(signature_sym_name),
-            ]));
-        }
+    base_info
+}
+
+fn compression_info<'a>(compression: Compression) -> Vec<Spans<'a>> {
+    let header = if compression.legacy {
+        ".zdebug"
     } else {
-        unreachable!()
+        "Elf64_Chdr"
+    };
+    let mut info = vec![
+        Spans::from(vec![
+            Span::raw("Compression: "),
+            Span::raw(format!(
+                "{} ({})",
+                compression_format_string(compression.format),
+                header
+            )),
+        ]),
+        Spans::from(vec![
+            Span::raw("Uncompressed Size: "),
+            Span::raw(format!("0x{:x}", compression.uncompressed_size)),
+        ]),
+    ];
+    if let Some(align) = compression.uncompressed_align {
+        info.push(Spans::from(vec![
+            Span::raw("Uncompressed Align: "),
+            Span::raw(format!("{}", align)),
+        ]));
     }
 
-    base_info
+    info
+}
+
+/// 展開後の中身を hexdump -C のように表示する.
+fn decompressed_info<'a>(compression: Compression, decompressed: Option<&[u8]>) -> Vec<Spans<'a>> {
+    let data = match decompressed {
+        Some(data) => data,
+        None => {
+            return vec![Spans::from(vec![
+                Span::raw("Decompressed Contents: "),
+                Span::styled("(failed to decompress)", Style::default().fg(Color::Red)),
+            ])]
+        }
+    };
+
+    let mut info = vec![Spans::from(vec![Span::raw(
+        if compression.uncompressed_size > data.len() as u64 {
+            format!("Decompressed Contents (first 0x{:x} bytes):", data.len())
+        } else {
+            "Decompressed Contents:".to_string()
+        },
+    )])];
+    info.extend(data.chunks(16).enumerate().map(|(row, chunk)| {
+        let hex = chunk
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        Spans::from(vec![
+            Span::styled(
+                format!("  {:08x}  ", row * 16),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!("{:<47}  |{}|", hex, ascii)),
+        ])
+    }));
+
+    info
 }
 
-fn get_first_globsym_name_from_sh_info<'a>(symtab_sct: &'a section::Section64) -> &'a str {
+fn get_first_globsym_name_from_sh_info(symtab_sct: &section::Section64) -> &str {
     if let Contents64::Symbols(symbols) = &symtab_sct.contents {
-        let first_sym = &symbols[symtab_sct.header.sh_info as usize];
-        &first_sym.symbol_name
+        symbols
+            .get(symtab_sct.header.sh_info as usize)
+            .map(|sym| sym.symbol_name.as_str())
+            .unwrap_or("(invalid)")
     } else {
         unreachable!()
     }
 }
 
+/// sh_link/sh_info が指すセクションの名前. 壊れたファイルでは範囲外を指すことがある
+fn linked_section_name(elf_file: &file::ELF64, idx: u32) -> &str {
+    elf_file
+        .sections
+        .get(idx as usize)
+        .map(|sct| sct.name.as_str())
+        .unwrap_or("(invalid)")
+}
+
 fn section_attribute_spans<'a, T>(
     attribute: &'a str,
     to_str_f: fn(T) -> &'a str,
@@ -211,22 +403,47 @@ fn sct_type_string<'a>(sct_type: section::Type) -> &'a str {
         _ => "unknown",
     }
 }
-fn sct_flag_string(flags: HashSet<section::Flag>) -> String {
-    let write_str_with = |s: &mut String, c: char, const_flag: section::Flag| {
-        if flags.contains(&const_flag) {
-            s.push(c);
-        }
-    };
-    let mut s = String::new();
+fn compression_format_string(format: Format) -> String {
+    match format {
+        Format::Zlib => "ZLIB".to_string(),
+        Format::Zstd => "ZSTD".to_string(),
+        Format::Unknown(ch_type) => format!("unknown (0x{:x})", ch_type),
+    }
+}
+/// sh_flags をビットごとに (readelf の文字, 名前) にする.
+/// Section64Header::get_flags は未知のビットで panic するので, sh_flags を直接見る.
+fn sct_flags(flags: u64, e_machine: u16) -> Vec<(char, String)> {
+    let mut decoded: Vec<(char, String)> = SECTION_FLAGS
+        .iter()
+        .filter(|(flag, _, _)| flags & flag != 0)
+        .map(|(_, c, name)| (*c, name.to_string()))
+        .collect();
+    let mut rest = SECTION_FLAGS
+        .iter()
+        .fold(flags, |rest, (flag, _, _)| rest & !flag);
 
-    write_str_with(&mut s, 'A', section::Flag::Alloc);
-    write_str_with(&mut s, 'X', section::Flag::ExecInstr);
-    write_str_with(&mut s, 'I', section::Flag::InfoLink);
+    if e_machine == relocations::EM_X86_64 && rest & SHF_X86_64_LARGE != 0 {
+        decoded.push(('l', "X86_64_LARGE".to_string()));
+        rest &= !SHF_X86_64_LARGE;
+    }
+    if rest & SHF_MASKOS != 0 {
+        decoded.push(('o', format!("OS specific (0x{:x})", rest & SHF_MASKOS)));
+    }
+    if rest & SHF_MASKPROC != 0 {
+        decoded.push((
+            'p',
+            format!("processor specific (0x{:x})", rest & SHF_MASKPROC),
+        ));
+    }
+    let unknown = rest & !(SHF_MASKOS | SHF_MASKPROC);
+    if unknown != 0 {
+        decoded.push(('x', format!("unknown (0x{:x})", unknown)));
+    }
 
-    s
+    decoded
 }
 
-fn section_items(elf_file: &file::ELF64) -> Vec<ListItem> {
+fn section_items(elf_file: &file::ELF64) -> Vec<ListItem<'_>> {
     section_names(elf_file)
         .iter()
         .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
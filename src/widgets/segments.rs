@@ -5,7 +5,7 @@ use elf_utilities::{file, segment};
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
-pub fn segment_list(elf_file: &file::ELF64) -> List {
+pub fn segment_list(elf_file: &file::ELF64) -> List<'_> {
     list("Segments", segment_items(elf_file))
 }
 
@@ -77,7 +77,7 @@ fn seg_flag_string(flags: HashSet<segment::Flag>) -> String {
     s
 }
 
-fn segment_items(elf_file: &file::ELF64) -> Vec<ListItem> {
+fn segment_items(elf_file: &file::ELF64) -> Vec<ListItem<'_>> {
     (0..elf_file.ehdr.e_phnum)
         .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
         .collect()
@@ -0,0 +1,182 @@
+use crate::elf_util::string_table::{StringEntry, StringTable, StringUser};
+use crate::widgets::{dynamics, list};
+use elf_utilities::{file, section::Contents64};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+
+pub fn string_list(tables: &[StringTable], table_idx: usize) -> List<'_> {
+    let table = &tables[table_idx];
+    list(
+        format!(
+            "{} ({}/{}, [ ] to switch)",
+            table.name,
+            table_idx + 1,
+            tables.len()
+        ),
+        table
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(vec![Spans::from(vec![Span::styled(
+                    string_name(entry),
+                    Style::default().fg(string_color(entry)),
+                )])])
+            })
+            .collect(),
+    )
+}
+
+pub fn string_names(table: &StringTable) -> Vec<String> {
+    table.entries.iter().map(string_name).collect()
+}
+
+/// user_idx 番目の参照元を強調して表示する.
+pub fn string_information<'a>(
+    elf_file: &'a file::ELF64,
+    table: &'a StringTable,
+    entry_idx: usize,
+    user_idx: usize,
+) -> Paragraph<'a> {
+    let entry = &table.entries[entry_idx];
+
+    let status = if entry.is_unreferenced() {
+        Span::styled("unreferenced", Style::default().fg(Color::DarkGray))
+    } else if !entry.is_referenced_directly() {
+        Span::styled(
+            "referenced only through tail-merged suffixes",
+            Style::default().fg(Color::Yellow),
+        )
+    } else if entry.suffix_references().next().is_some() {
+        Span::raw("referenced, tail-merged")
+    } else {
+        Span::raw("referenced")
+    };
+
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw("String Table: "),
+            Span::raw(format!("[{}] {}", table.section, table.name)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::raw(format!("0x{:x}", entry.offset)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Length: "),
+            Span::raw(format!("{} (bytes)", entry.bytes.len())),
+        ]),
+        Spans::from(vec![Span::raw("Value: "), Span::raw(entry.value.clone())]),
+        Spans::from(vec![Span::raw("Status: "), status]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw(format!(
+            "Users ({}, n: next user, Enter: jump)",
+            entry.references.len()
+        ))]),
+    ];
+
+    spans.extend(entry.references.iter().enumerate().map(|(idx, reference)| {
+        let suffix = if reference.offset == entry.offset {
+            String::new()
+        } else {
+            format!(
+                " -> \"{}\" (offset 0x{:x})",
+                String::from_utf8_lossy(
+                    entry
+                        .bytes
+                        .get(reference.offset - entry.offset..)
+                        .unwrap_or_default()
+                ),
+                reference.offset
+            )
+        };
+        let text = format!("{}{}", user_string(elf_file, &reference.user), suffix);
+
+        if idx == user_idx {
+            Spans::from(vec![Span::styled(
+                format!("> {}", text),
+                Style::default().add_modifier(Modifier::BOLD),
+            )])
+        } else {
+            Spans::from(vec![Span::raw(format!("  {}", text))])
+        }
+    }));
+
+    if !table.dangling.is_empty() {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::styled(
+            format!(
+                "{} references point outside of any string in this table",
+                table.dangling.len()
+            ),
+            Style::default().fg(Color::Red),
+        )]));
+    }
+
+    Paragraph::new(spans)
+        .block(Block::default().borders(Borders::ALL).title("Strings"))
+        .wrap(Wrap { trim: false })
+}
+
+/// 参照元の説明
+pub fn user_string(elf_file: &file::ELF64, user: &StringUser) -> String {
+    let section_name = |idx: usize| {
+        elf_file
+            .sections
+            .get(idx)
+            .map(|sct| sct.name.clone())
+            .unwrap_or_default()
+    };
+
+    match user {
+        StringUser::SectionName { section } => {
+            format!("name of section [{}] {}", section, section_name(*section))
+        }
+        StringUser::Symbol { section, index } => {
+            let symbol_name = match elf_file.sections.get(*section).map(|sct| &sct.contents) {
+                Some(Contents64::Symbols(symbols)) => symbols
+                    .get(*index)
+                    .map(|sym| sym.symbol_name.clone())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+            format!(
+                "symbol [{}] {} in {}",
+                index,
+                symbol_name,
+                section_name(*section)
+            )
+        }
+        StringUser::Dynamic {
+            section,
+            index,
+            tag,
+        } => format!(
+            "{} entry [{}] in {}",
+            dynamics::dyn_tag_string(elf_file.ehdr.e_machine, *tag),
+            index,
+            section_name(*section)
+        ),
+        StringUser::Version { section } => {
+            format!("version name in {}", section_name(*section))
+        }
+    }
+}
+
+fn string_name(entry: &StringEntry) -> String {
+    if entry.value.is_empty() {
+        format!("0x{:06x} (empty)", entry.offset)
+    } else {
+        format!("0x{:06x} {}", entry.offset, entry.value)
+    }
+}
+
+fn string_color(entry: &StringEntry) -> Color {
+    if entry.is_unreferenced() {
+        Color::DarkGray
+    } else if !entry.is_referenced_directly() {
+        Color::Yellow
+    } else {
+        Color::White
+    }
+}
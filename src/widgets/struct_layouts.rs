@@ -0,0 +1,157 @@
+use crate::elf_util::struct_layout::{Kind, LayoutEntry, Member, StructLayout, CACHELINE_SIZE};
+use crate::widgets::list;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+pub fn struct_layout_list(layouts: &[StructLayout]) -> List<'_> {
+    list(
+        format!("Structs ({})", layouts.len()),
+        struct_layout_names(layouts)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn struct_layout_names(layouts: &[StructLayout]) -> Vec<String> {
+    layouts
+        .iter()
+        .map(|layout| {
+            format!(
+                "{} {} ({})",
+                kind_string(layout.kind),
+                layout.name,
+                layout.size
+            )
+        })
+        .collect()
+}
+
+/// pahole と同じく, メンバごとに /* オフセット サイズ */ を添えて表示する.
+pub fn struct_layout_information(layout: &StructLayout) -> Paragraph<'_> {
+    let comment = Style::default().fg(Color::DarkGray);
+    let warning = Style::default().fg(Color::Yellow);
+
+    let mut spans = vec![Spans::from(vec![Span::raw(format!(
+        "{} {} {{",
+        kind_string(layout.kind),
+        layout.name
+    ))])];
+
+    for entry in layout.entries() {
+        match entry {
+            LayoutEntry::Member(idx) => spans.push(Spans::from(vec![Span::raw(member_string(
+                &layout.members[idx],
+            ))])),
+            LayoutEntry::Hole(bits) => {
+                spans.push(Spans::from(vec![Span::raw("")]));
+                spans.push(Spans::from(vec![Span::styled(
+                    format!("    /* XXX {} hole, try to pack */", bits_string(bits)),
+                    warning,
+                )]));
+                spans.push(Spans::from(vec![Span::raw("")]));
+            }
+            LayoutEntry::CachelineBoundary(cacheline, 0) => {
+                spans.push(Spans::from(vec![Span::styled(
+                    format!(
+                        "    /* --- cacheline {} boundary ({} bytes) --- */",
+                        cacheline,
+                        cacheline * CACHELINE_SIZE
+                    ),
+                    comment,
+                )]))
+            }
+            LayoutEntry::CachelineBoundary(cacheline, passed) => {
+                spans.push(Spans::from(vec![Span::styled(
+                    format!(
+                        "    /* --- cacheline {} boundary ({} bytes) was {} bytes ago --- */",
+                        cacheline,
+                        cacheline * CACHELINE_SIZE,
+                        passed
+                    ),
+                    comment,
+                )]))
+            }
+        }
+    }
+
+    let summary = layout.summary();
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::styled(
+        format!(
+            "    /* size: {}, cachelines: {}, members: {} */",
+            layout.size,
+            summary.cachelines,
+            layout.members.len()
+        ),
+        comment,
+    )]));
+    if summary.holes != 0 {
+        spans.push(Spans::from(vec![Span::styled(
+            format!(
+                "    /* sum members: {}, holes: {}, sum holes: {} */",
+                summary.sum_members,
+                summary.holes,
+                bits_string(summary.sum_holes)
+            ),
+            comment,
+        )]));
+    }
+    if summary.padding != 0 {
+        spans.push(Spans::from(vec![Span::styled(
+            format!("    /* padding: {} */", summary.padding),
+            warning,
+        )]));
+    }
+    if summary.last_cacheline != 0 && summary.cachelines > 1 {
+        spans.push(Spans::from(vec![Span::styled(
+            format!("    /* last cacheline: {} bytes */", summary.last_cacheline),
+            comment,
+        )]));
+    }
+    spans.push(Spans::from(vec![Span::raw("};")]));
+
+    Paragraph::new(spans).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Struct Layout <0x{:x}>", layout.die_offset)),
+    )
+}
+
+fn member_string(member: &Member) -> String {
+    let name = match (&member.name, member.bit_field) {
+        _ if member.base_class => "/* base class */".to_string(),
+        (name, Some((_, bits))) => format!("{}:{};", name.as_deref().unwrap_or(""), bits),
+        (name, None) => format!("{};", name.as_deref().unwrap_or("")),
+    };
+    let offset = match member.bit_field {
+        Some((bit_offset, _)) => format!("{}:{}", member.offset, bit_offset),
+        None => member.offset.to_string(),
+    };
+    let size = member
+        .size
+        .map(|size| size.to_string())
+        .unwrap_or_else(|| "?".to_string());
+
+    format!(
+        "    {:<28} {:<24} /* {:>7} {:>5} */",
+        member.type_name, name, offset, size
+    )
+}
+
+fn bits_string(bits: u64) -> String {
+    if bits.is_multiple_of(8) {
+        format!("{} bytes", bits / 8)
+    } else {
+        format!("{} bits", bits)
+    }
+}
+
+fn kind_string<'a>(kind: Kind) -> &'a str {
+    match kind {
+        Kind::Struct => "struct",
+        Kind::Class => "class",
+        Kind::Union => "union",
+    }
+}
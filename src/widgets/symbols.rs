@@ -1,4 +1,6 @@
-use crate::widgets::list;
+use crate::elf_util::dwarf::Dwarf;
+use crate::elf_util::ifunc;
+use crate::widgets::{data_source_title, list};
 use elf_utilities::{
     file,
     section::{self, Contents64},
@@ -7,9 +9,12 @@ use elf_utilities::{
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
-pub fn symbol_table_list(symbol_table: Option<&section::Section64>) -> List {
+pub fn symbol_table_list(
+    symbol_table: Option<&section::Section64>,
+    reconstructed: bool,
+) -> List<'_> {
     list(
-        "Symbols",
+        data_source_title(reconstructed, "Symbols"),
         symbol_names(symbol_table)
             .iter()
             .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
@@ -21,11 +26,13 @@ pub fn symbol_information<'a>(
     elf_file: &'a file::ELF64,
     symbol_table: &'a section::Section64,
     sym_idx: usize,
+    dwarf: Option<&Dwarf>,
+    reconstructed: bool,
 ) -> Paragraph<'a> {
     if let Contents64::Symbols(symbols) = &symbol_table.contents {
         let sym = &symbols[sym_idx];
 
-        Paragraph::new(vec![
+        let mut spans = vec![
             Spans::from(vec![
                 Span::raw("Name: "),
                 Span::raw(sym.symbol_name.clone()),
@@ -40,7 +47,7 @@ pub fn symbol_information<'a>(
             ]),
             Spans::from(vec![
                 Span::raw("Type: "),
-                Span::raw(sym_type_string(sym.get_type())),
+                Span::raw(sym_type_string(sym.st_info & 0x0f)),
             ]),
             Spans::from(vec![
                 Span::raw("Bind: "),
@@ -52,10 +59,32 @@ pub fn symbol_information<'a>(
             ]),
             Spans::from(vec![
                 Span::raw("Section: "),
-                Span::raw(sym_ndx_string(elf_file, sym.st_shndx)),
+                Span::raw(sym_ndx_string(elf_file, sym.st_shndx, reconstructed)),
             ]),
-        ])
-        .block(Block::default().borders(Borders::ALL).title("Symbols"))
+        ];
+
+        // 関数なら, 行番号表から定義されているソースの範囲を引く
+        let is_defined_func =
+            sym.get_type() == symbol::Type::Func && sym.st_shndx != section::SHN_UNDEF;
+        if let Some((file, first, last)) = dwarf.filter(|_| is_defined_func).and_then(|dwarf| {
+            dwarf.source_range(sym.st_value, sym.st_value.wrapping_add(sym.st_size))
+        }) {
+            let lines = if first == last {
+                first.to_string()
+            } else {
+                format!("{}-{}", first, last)
+            };
+            spans.push(Spans::from(vec![
+                Span::raw("Source: "),
+                Span::raw(format!("{}:{}", file, lines)),
+            ]));
+        }
+
+        Paragraph::new(spans).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(data_source_title(reconstructed, "Symbols")),
+        )
     } else {
         unreachable!()
     }
@@ -84,16 +113,20 @@ pub fn symbol_names(symbol_table: Option<&section::Section64>) -> Vec<String> {
     }
 }
 
-fn sym_type_string<'a>(sym_type: symbol::Type) -> &'a str {
-    match sym_type {
-        symbol::Type::NoType => "NOTYPE",
-        symbol::Type::Object => "OBJECT",
-        symbol::Type::Func => "FUNC",
-        symbol::Type::Section => "SECTION",
-        symbol::Type::Num => "NUM",
-        symbol::Type::Common => "COMMON",
-        symbol::Type::File => "FILE",
-        symbol::Type::TLS => "TLS",
+/// symbol::Type::from は STT_GNU_IFUNC (10) を LoProc にしてしまうので, st_info の下位4ビットを直接見る.
+fn sym_type_string<'a>(st_type: u8) -> &'a str {
+    match st_type {
+        0 => "NOTYPE",
+        1 => "OBJECT",
+        2 => "FUNC",
+        3 => "SECTION",
+        4 => "FILE",
+        5 => "COMMON",
+        6 => "TLS",
+        7 => "NUM",
+        ifunc::STT_GNU_IFUNC => "IFUNC",
+        11..=12 => "OS specific",
+        13..=15 => "processor specific",
         _ => "unknown",
     }
 }
@@ -117,12 +150,17 @@ fn sym_vis_string<'a>(sym_vis: symbol::Visibility) -> &'a str {
     }
 }
 
-fn sym_ndx_string(elf_file: &file::ELF64, ndx: u16) -> String {
+fn sym_ndx_string(elf_file: &file::ELF64, ndx: u16, reconstructed: bool) -> String {
     match ndx {
         section::SHN_UNDEF => "UND".to_string(),
         section::SHN_ABS => "ABS".to_string(),
         section::SHN_COMMON => "COMMON".to_string(),
         section::SHN_XINDEX => "XINDEX".to_string(),
-        _ => elf_file.sections[ndx as usize].name.to_string(),
+        // 組み立て直したセクションは元のインデックスと対応しない
+        _ if reconstructed => format!("{} (no section headers)", ndx),
+        _ => elf_file.sections.get(ndx as usize).map_or_else(
+            || format!("{} (out of range)", ndx),
+            |sct| sct.name.to_string(),
+        ),
     }
 }
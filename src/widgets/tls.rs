@@ -0,0 +1,427 @@
+use crate::elf_util::relocations::{self, Relocation};
+use crate::elf_util::tls::{self, Model, Tls, TlsVariable};
+use crate::widgets::list;
+use elf_utilities::{file, header, section};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+/// 初期化イメージを何行まで表示するか
+const IMAGE_LINES: usize = 32;
+const IMAGE_LINE_BYTES: usize = 16;
+
+/// 先頭にテンプレート, そのあとに変数を並べる
+pub fn tls_list<'a>(elf_file: &file::ELF64, tls: &Tls) -> List<'a> {
+    list(
+        format!("TLS ({} variables)", tls.variables.len()),
+        tls_names(elf_file, tls)
+            .iter()
+            .map(|name| ListItem::new(vec![Spans::from(vec![Span::raw(name.to_string())])]))
+            .collect(),
+    )
+}
+
+pub fn tls_names(elf_file: &file::ELF64, tls: &Tls) -> Vec<String> {
+    let template = match tls.template {
+        Some(template) => format!(
+            "template 0x{:x} bytes (0x{:x} .tdata), align 0x{:x}",
+            template.mem_size, template.file_size, template.align
+        ),
+        None => "template (no PT_TLS)".to_string(),
+    };
+    let variables = tls.variables.iter().map(|var| {
+        let offset = match var.offset {
+            Some(offset) => format!("0x{:x}", offset),
+            None => "UND".to_string(),
+        };
+        let mut name = format!(
+            "{:>8} {:>6} {} ({})",
+            offset,
+            format!("0x{:x}", var.size),
+            var.name,
+            shndx_string(elf_file, var.shndx)
+        );
+        let models = models_string(&var.references);
+        if !models.is_empty() {
+            name.push_str(&format!(" [{}]", models));
+        }
+        if var
+            .references
+            .iter()
+            .any(|reloc| tls.initial_exec_in_shared_object(reloc))
+        {
+            name.push_str(" !");
+        }
+        name
+    });
+
+    std::iter::once(template).chain(variables).collect()
+}
+
+pub fn tls_information<'a>(elf_file: &file::ELF64, tls: &Tls, idx: usize) -> Paragraph<'a> {
+    let (title, spans) = match idx.checked_sub(1) {
+        None => ("TLS Template", template_spans(elf_file, tls)),
+        Some(var_idx) => (
+            "TLS Variable",
+            variable_spans(elf_file, tls, &tls.variables[var_idx]),
+        ),
+    };
+
+    Paragraph::new(spans).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn template_spans<'a>(elf_file: &file::ELF64, tls: &Tls) -> Vec<Spans<'a>> {
+    let mut spans = Vec::new();
+    match tls.template {
+        Some(template) => {
+            spans.push(Spans::from(vec![
+                Span::raw("Segment: "),
+                Span::raw(format!("[{}] PT_TLS", template.segment)),
+            ]));
+            spans.push(Spans::from(vec![
+                Span::raw("Address: "),
+                Span::raw(format!(
+                    "0x{:x} (file offset 0x{:x})",
+                    template.address, template.offset
+                )),
+            ]));
+            spans.push(Spans::from(vec![
+                Span::raw("Initialized (.tdata): "),
+                Span::raw(format!("0x{:x} bytes", template.file_size)),
+            ]));
+            spans.push(Spans::from(vec![
+                Span::raw("Zero-filled (.tbss): "),
+                Span::raw(format!(
+                    "0x{:x} bytes",
+                    template.mem_size.saturating_sub(template.file_size)
+                )),
+            ]));
+            spans.push(Spans::from(vec![
+                Span::raw("Size: "),
+                Span::raw(match template.block_size() {
+                    Some(block_size) => format!(
+                        "0x{:x} ({} bytes per thread, 0x{:x} after alignment)",
+                        template.mem_size, template.mem_size, block_size
+                    ),
+                    None => format!(
+                        "0x{:x} ({} bytes per thread, alignment overflows)",
+                        template.mem_size, template.mem_size
+                    ),
+                }),
+            ]));
+            spans.push(Spans::from(vec![
+                Span::raw("Alignment: "),
+                Span::raw(format!("0x{:x}", template.align)),
+            ]));
+        }
+        None => spans.push(Spans::from(vec![Span::styled(
+            "no PT_TLS segment, offsets are relative to each section",
+            Style::default().fg(Color::DarkGray),
+        )])),
+    }
+    spans.push(Spans::from(vec![
+        Span::raw("DF_STATIC_TLS: "),
+        if tls.static_tls {
+            Span::styled(
+                "set, ld.so must place this object in the static TLS block",
+                Style::default().fg(Color::Yellow),
+            )
+        } else {
+            Span::raw("not set")
+        },
+    ]));
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Sections ({}):",
+        tls.sections.len()
+    ))]));
+    for sct_idx in tls.sections.iter() {
+        let shdr = &elf_file.sections[*sct_idx].header;
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "  [{}] {} 0x{:x}..0x{:x} (0x{:x} bytes{})",
+            sct_idx,
+            elf_file.sections[*sct_idx].name,
+            shdr.sh_addr,
+            shdr.sh_addr + shdr.sh_size,
+            shdr.sh_size,
+            if shdr.get_type() == section::Type::NoBits {
+                ", not in file"
+            } else {
+                ""
+            }
+        ))]));
+    }
+
+    if !tls.module_relocations.is_empty() {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::raw(format!(
+            "Module Relocations ({}):",
+            tls.module_relocations.len()
+        ))]));
+        for reloc in tls.module_relocations.iter() {
+            spans.push(reference_spans(elf_file, tls, reloc));
+        }
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Image (0x{:x} bytes):",
+        tls.image.len()
+    ))]));
+    for (line, chunk) in tls
+        .image
+        .chunks(IMAGE_LINE_BYTES)
+        .enumerate()
+        .take(IMAGE_LINES)
+    {
+        let start = (line * IMAGE_LINE_BYTES) as u64;
+        // この行から始まる変数の名前を添える
+        let names: Vec<&str> = tls
+            .variables
+            .iter()
+            .filter(|var| {
+                var.offset
+                    .is_some_and(|offset| start <= offset && offset < start + chunk.len() as u64)
+                    && is_initialized(elf_file, tls, var)
+            })
+            .map(|var| var.name.as_str())
+            .collect();
+        spans.push(Spans::from(vec![
+            Span::raw(format!(
+                "  {:04x}: {:<48} |{:<16}|",
+                start,
+                chunk
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                chunk
+                    .iter()
+                    .map(|b| if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    })
+                    .collect::<String>()
+            )),
+            Span::styled(
+                format!(" {}", names.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+    let shown = IMAGE_LINES * IMAGE_LINE_BYTES;
+    if tls.image.len() > shown {
+        spans.push(Spans::from(vec![Span::styled(
+            format!("  ... 0x{:x} more bytes", tls.image.len() - shown),
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+
+    spans
+}
+
+fn variable_spans<'a>(elf_file: &file::ELF64, tls: &Tls, var: &TlsVariable) -> Vec<Spans<'a>> {
+    let relocatable = elf_file.ehdr.get_type() == header::Type::Rel;
+    let mut spans = vec![
+        Spans::from(vec![Span::raw("Name: "), Span::raw(var.name.clone())]),
+        Spans::from(vec![
+            Span::raw("Binding: "),
+            Span::raw(bind_string(var.binding)),
+        ]),
+        Spans::from(vec![
+            Span::raw("Section: "),
+            Span::raw(shndx_string(elf_file, var.shndx)),
+        ]),
+    ];
+
+    match var.offset {
+        Some(offset) => {
+            spans.push(Spans::from(vec![
+                Span::raw(if relocatable {
+                    "Offset (in section): "
+                } else {
+                    "Offset (in TLS block): "
+                }),
+                Span::raw(format!(
+                    "0x{:x}..0x{:x} (0x{:x} bytes)",
+                    offset,
+                    offset + var.size,
+                    var.size
+                )),
+            ]));
+            // 共有ライブラリのブロックの位置は読み込まれるまで決まらない
+            if !relocatable && !tls.shared_object {
+                if let Some(tp_offset) = tls.thread_pointer_offset(offset) {
+                    spans.push(Spans::from(vec![
+                        Span::raw("Thread Pointer Offset: "),
+                        Span::raw(format!("%fs:-0x{:x}", tp_offset.unsigned_abs())),
+                    ]));
+                }
+            }
+            spans.push(Spans::from(vec![
+                Span::raw("Initial Value: "),
+                initial_value_span(elf_file, tls, var, offset),
+            ]));
+        }
+        None => spans.push(Spans::from(vec![
+            Span::raw("Offset: "),
+            Span::styled(
+                "defined in another module",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])),
+    }
+
+    spans.push(Spans::from(vec![Span::raw("")]));
+    spans.push(Spans::from(vec![Span::raw(format!(
+        "Relocations ({}):",
+        var.references.len()
+    ))]));
+    if var.references.is_empty() {
+        spans.push(Spans::from(vec![Span::styled(
+            "  none, accessed with local-exec or resolved at link time",
+            Style::default().fg(Color::DarkGray),
+        )]));
+    }
+    for reloc in var.references.iter() {
+        spans.push(reference_spans(elf_file, tls, reloc));
+    }
+
+    if var
+        .references
+        .iter()
+        .any(|reloc| tls.initial_exec_in_shared_object(reloc))
+    {
+        spans.push(Spans::from(vec![Span::raw("")]));
+        spans.push(Spans::from(vec![Span::styled(
+            "initial-exec access in a shared object: it needs static TLS, \
+             so dlopen can fail with \"cannot allocate memory in static TLS block\"",
+            Style::default().fg(Color::Yellow),
+        )]));
+    }
+
+    spans
+}
+
+fn reference_spans<'a>(elf_file: &file::ELF64, tls: &Tls, reloc: &Relocation) -> Spans<'a> {
+    let text = format!(
+        "  {}[{}] 0x{:x} {} ({})",
+        elf_file.sections[reloc.section].name,
+        reloc.index,
+        reloc.r_offset,
+        relocations::type_string(elf_file.ehdr.e_machine, reloc.r_type),
+        tls::model(reloc.r_type).map(model_string).unwrap_or("-")
+    );
+    if tls.initial_exec_in_shared_object(reloc) {
+        Spans::from(vec![Span::styled(text, Style::default().fg(Color::Yellow))])
+    } else {
+        Spans::from(vec![Span::raw(text)])
+    }
+}
+
+fn initial_value_span<'a>(
+    elf_file: &file::ELF64,
+    tls: &Tls,
+    var: &TlsVariable,
+    offset: u64,
+) -> Span<'a> {
+    if !is_initialized(elf_file, tls, var) {
+        return Span::raw("zero (.tbss)");
+    }
+    let start = offset as usize;
+    let end = (offset + var.size.min(IMAGE_LINE_BYTES as u64)) as usize;
+    match tls.image.get(start..end) {
+        Some(bytes) => Span::raw(format!(
+            "{}{}",
+            bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+            if var.size > IMAGE_LINE_BYTES as u64 {
+                " ..."
+            } else {
+                ""
+            }
+        )),
+        None => Span::styled("not in file", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// .tdata (ファイル上にあるセクション) の変数か
+fn is_initialized(elf_file: &file::ELF64, tls: &Tls, var: &TlsVariable) -> bool {
+    if var.offset.is_none() {
+        return false;
+    }
+    match elf_file.sections.get(var.shndx as usize) {
+        // ET_REL のイメージは最初の .tdata だけ
+        Some(sct) if elf_file.ehdr.get_type() == header::Type::Rel => {
+            sct.header.get_type() != section::Type::NoBits
+                && tls
+                    .sections
+                    .iter()
+                    .find(|idx| elf_file.sections[**idx].header.get_type() != section::Type::NoBits)
+                    == Some(&(var.shndx as usize))
+        }
+        Some(sct) => sct.header.get_type() != section::Type::NoBits,
+        None => false,
+    }
+}
+
+fn models_string(references: &[Relocation]) -> String {
+    let mut models: Vec<&str> = Vec::new();
+    for model in references
+        .iter()
+        .filter_map(|reloc| tls::model(reloc.r_type))
+    {
+        let name = model_abbreviation(model);
+        if !models.contains(&name) {
+            models.push(name);
+        }
+    }
+    models.join(", ")
+}
+
+fn model_abbreviation<'a>(model: Model) -> &'a str {
+    match model {
+        Model::GeneralDynamic => "GD",
+        Model::LocalDynamic => "LD",
+        Model::InitialExec => "IE",
+        Model::LocalExec => "LE",
+        Model::Descriptor => "DESC",
+        Model::DynamicSlot => "GOT",
+    }
+}
+
+fn model_string<'a>(model: Model) -> &'a str {
+    match model {
+        Model::GeneralDynamic => "general-dynamic",
+        Model::LocalDynamic => "local-dynamic",
+        Model::InitialExec => "initial-exec",
+        Model::LocalExec => "local-exec",
+        Model::Descriptor => "TLS descriptor",
+        Model::DynamicSlot => "module/offset GOT slot",
+    }
+}
+
+fn bind_string<'a>(binding: u8) -> &'a str {
+    match binding {
+        0 => "LOCAL",
+        1 => "GLOBAL",
+        2 => "WEAK",
+        10 => "UNIQUE",
+        _ => "unknown",
+    }
+}
+
+fn shndx_string(elf_file: &file::ELF64, shndx: u16) -> String {
+    match shndx {
+        section::SHN_UNDEF => "UND".to_string(),
+        _ => elf_file
+            .sections
+            .get(shndx as usize)
+            .map_or_else(|| shndx.to_string(), |sct| sct.name.to_string()),
+    }
+}